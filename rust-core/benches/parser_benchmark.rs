@@ -1,5 +1,6 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
 use autodoc_core::analyzer::RustAnalyzer;
+use autodoc_core::parser::PythonParser;
 use std::path::Path;
 
 fn benchmark_analyze_file(c: &mut Criterion) {
@@ -24,5 +25,33 @@ fn benchmark_analyze_directory(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, benchmark_analyze_file, benchmark_analyze_directory);
+fn benchmark_parse_source(c: &mut Criterion) {
+    // Decorated, annotated methods exercise the per-entity string building
+    let class = r#"
+class Service{n}:
+    """Service number {n}."""
+
+    @app.route("/items/{n}", methods=["GET", "POST"])
+    def handle(self, request: Request, limit: int) -> Response:
+        """Handle a request."""
+        return Response(limit)
+
+    async def fetch(self, key: str) -> Optional[bytes]:
+        return await self.store.get(key)
+"#;
+    let source: String = (0..200).map(|n| class.replace("{n}", &n.to_string())).collect();
+    let parser = PythonParser::new();
+    let entities = parser.parse_source(&source, Path::new("corpus.py")).unwrap().len();
+    
+    let mut group = c.benchmark_group("parse_source");
+    group.throughput(Throughput::Elements(entities as u64));
+    group.bench_function("entities", |b| {
+        b.iter(|| {
+            parser.parse_source(black_box(&source), Path::new("corpus.py"))
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_analyze_file, benchmark_analyze_directory, benchmark_parse_source);
 criterion_main!(benches);
\ No newline at end of file
@@ -0,0 +1,56 @@
+/* C interface to the autodoc analyzer; build with `--features capi`. */
+
+#ifndef AUTODOC_H
+#define AUTODOC_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* One entity. Strings are NUL-terminated UTF-8 owned by the enclosing
+ * AutodocResult; optional ones (docstring, return_type) are NULL when absent. */
+typedef struct AutodocEntity {
+    char *entity_id;
+    char *entity_type;
+    char *name;
+    char *qualified_name;
+    char *file_path;
+    size_t line_number;
+    size_t end_line;
+    char *docstring;
+    char *return_type;
+    char *code;
+    bool is_async;
+    bool is_public;
+    bool is_deprecated;
+} AutodocEntity;
+
+/* On success error is NULL, json holds the entities as a JSON array and
+ * entities/len the same entities as structs. On failure only error is set. */
+typedef struct AutodocResult {
+    char *json;
+    AutodocEntity *entities;
+    size_t len;
+    char *error;
+} AutodocResult;
+
+/* config_json is an AnalyzerConfig as JSON, or NULL for the defaults. */
+AutodocResult *autodoc_analyze_file(const char *path, const char *config_json);
+
+/* path picks the language backend and may be NULL for Python. */
+AutodocResult *autodoc_analyze_source(const char *source, const char *path, const char *config_json);
+
+/* Releases a result and every string it owns; NULL is ignored. */
+void autodoc_result_free(AutodocResult *result);
+
+/* Static string; do not free. */
+const char *autodoc_version(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* AUTODOC_H */
@@ -0,0 +1,78 @@
+// Node.js bindings for the Rust core; napi-rs exposes the snake_case
+// functions below in camelCase
+
+use std::path::{Path, PathBuf};
+
+use autodoc_core::analyzer::RustAnalyzer;
+use autodoc_core::config::AnalyzerConfig;
+use autodoc_core::entity::CodeEntity;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use serde_json::Value;
+
+/// Analyze in-memory source as if it lived at `path` (default `<string>`,
+/// parsed as Python). `config` takes `AnalyzerConfig` fields.
+#[napi(ts_return_type = "Array<Record<string, any>>")]
+pub fn analyze_source(source: String, path: Option<String>, config: Option<Value>) -> Result<Value> {
+    let path = path.unwrap_or_else(|| "<string>".to_string());
+    let entities = analyzer(config)?.analyze_source(&source, Path::new(&path)).map_err(reason)?;
+    to_json(&entities)
+}
+
+/// Analyze one file
+#[napi(ts_return_type = "Array<Record<string, any>>")]
+pub fn analyze_file(path: String, config: Option<Value>) -> Result<Value> {
+    let entities = analyzer(config)?.analyze_file(Path::new(&path)).map_err(reason)?;
+    to_json(&entities)
+}
+
+/// Analyze a directory on the libuv thread pool, resolving to its entities
+#[napi(ts_return_type = "Promise<Array<Record<string, any>>>")]
+pub fn analyze_directory(path: String, config: Option<Value>) -> Result<AsyncTask<AnalyzeDirectory>> {
+    Ok(AsyncTask::new(AnalyzeDirectory {
+        path: PathBuf::from(path),
+        analyzer: Some(analyzer(config)?),
+    }))
+}
+
+/// Version of the core the addon was built from
+#[napi]
+pub fn version() -> String {
+    autodoc_core::VERSION.to_string()
+}
+
+pub struct AnalyzeDirectory {
+    path: PathBuf,
+    analyzer: Option<RustAnalyzer>,
+}
+
+impl Task for AnalyzeDirectory {
+    type Output = Vec<CodeEntity>;
+    type JsValue = Value;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        let analyzer = self.analyzer.take().ok_or_else(|| Error::from_reason("Task already ran"))?;
+        analyzer.analyze_directory(&self.path).map_err(reason)
+    }
+
+    fn resolve(&mut self, _env: Env, entities: Self::Output) -> Result<Self::JsValue> {
+        to_json(&entities)
+    }
+}
+
+fn analyzer(config: Option<Value>) -> Result<RustAnalyzer> {
+    let config = match config {
+        Some(value) => serde_json::from_value::<AnalyzerConfig>(value)
+            .map_err(|e| Error::new(Status::InvalidArg, format!("Invalid config: {}", e)))?,
+        None => AnalyzerConfig::default(),
+    };
+    Ok(RustAnalyzer::with_config(config))
+}
+
+fn to_json(entities: &[CodeEntity]) -> Result<Value> {
+    serde_json::to_value(entities).map_err(|e| Error::from_reason(format!("Failed to serialize entities: {}", e)))
+}
+
+fn reason(error: anyhow::Error) -> Error {
+    Error::from_reason(format!("{:#}", error))
+}
@@ -1,16 +1,52 @@
 use anyhow::{Result, Context};
 use rayon::prelude::*;
+use rayon::ThreadPool;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 use walkdir::WalkDir;
 use glob::Pattern;
 
+use crate::backends::{builtin_backends, LanguageBackend};
+#[cfg(feature = "tree-sitter")]
+use crate::backends::QueryBackend;
+use crate::cache::DiskCache;
+use crate::config::AnalyzerConfig;
+use crate::diagnostic::Diagnostic;
+use crate::endpoints;
 use crate::entity::CodeEntity;
+use crate::generated;
+use crate::git;
+use crate::hierarchy;
+use crate::hooks::{apply_hooks, EntityHook};
+use crate::identity;
+use crate::inheritance;
+use crate::lazy;
+use crate::licenses::FileLicense;
+use crate::location;
+use crate::memory;
+use crate::modules;
 use crate::parser::PythonParser;
+use crate::paths;
+use crate::redact;
+use crate::schema;
+use crate::source::read_source_lenient;
+use crate::stats::{FileTiming, RunStats};
+use crate::testmap;
+use crate::visibility::{self, VisibilityRules};
 
 /// High-performance Rust analyzer for Python codebases
 pub struct RustAnalyzer {
-    parser: PythonParser,
+    backends: Vec<Box<dyn LanguageBackend>>,
+    custom_backends: Vec<Box<dyn LanguageBackend>>,
     exclude_patterns: Vec<Pattern>,
+    include_patterns: Vec<Pattern>,
+    hooks: Vec<Box<dyn EntityHook>>,
+    config: AnalyzerConfig,
+    pool: Option<Arc<ThreadPool>>,
+    cache: Option<DiskCache>,
+    last_stats: Mutex<Option<RunStats>>,
 }
 
 impl RustAnalyzer {
@@ -26,79 +62,384 @@ impl RustAnalyzer {
             .collect();
         
         RustAnalyzer {
-            parser: PythonParser::new(),
+            backends: builtin_backends(),
+            custom_backends: Vec::new(),
             exclude_patterns,
+            include_patterns: Vec::new(),
+            hooks: Vec::new(),
+            config: AnalyzerConfig::default(),
+            pool: None,
+            cache: None,
+            last_stats: Mutex::new(None),
         }
     }
 
-    /// Analyze a single Python file
+    /// Create an analyzer from a shared configuration. Query languages that
+    /// can't be built are logged and left out; see `try_with_config`.
+    pub fn with_config(config: AnalyzerConfig) -> Self {
+        let backends = query_backends(&config).unwrap_or_else(|e| {
+            log::warn!("Query languages disabled: {:#}", e);
+            Vec::new()
+        });
+        RustAnalyzer::configured(config, backends)
+    }
+
+    /// `with_config`, failing when a query language can't be built
+    pub fn try_with_config(config: AnalyzerConfig) -> Result<Self> {
+        let backends = query_backends(&config)?;
+        Ok(RustAnalyzer::configured(config, backends))
+    }
+
+    fn configured(config: AnalyzerConfig, custom_backends: Vec<Box<dyn LanguageBackend>>) -> Self {
+        let mut analyzer = RustAnalyzer::new()
+            .with_excludes(config.exclude.iter().map(|s| s.as_str()).collect());
+        
+        analyzer.include_patterns = config.include
+            .iter()
+            .filter_map(|p| Pattern::new(p).ok())
+            .collect();
+        
+        let parser = PythonParser::with_visibility(VisibilityRules::from_config(&config));
+        for backend in analyzer.backends.iter_mut().filter(|b| b.language() == "python") {
+            *backend = Box::new(parser.clone());
+        }
+        
+        analyzer.custom_backends = custom_backends;
+        analyzer.pool = config.threads.and_then(|threads| thread_pool(threads, config.shared_pool));
+        
+        analyzer.cache = config.cache_dir.as_deref().and_then(|dir| {
+            match DiskCache::open(dir, &config) {
+                Ok(cache) => Some(cache),
+                Err(e) => {
+                    log::warn!("Analysis cache disabled: {:#}", e);
+                    None
+                }
+            }
+        });
+        
+        analyzer.config = config;
+        analyzer
+    }
+
+    /// The configuration this analyzer was built with
+    pub fn config(&self) -> &AnalyzerConfig {
+        &self.config
+    }
+
+    /// The pool built for `AnalyzerConfig::threads`, if any, for parallel
+    /// work on results after the analyzer is done with them
+    pub fn thread_pool(&self) -> Option<Arc<ThreadPool>> {
+        self.pool.clone()
+    }
+
+    /// Run `op` on the configured pool, so Rayon work inside it keeps to
+    /// `threads`; on Rayon's global pool when none is configured
+    pub fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        install(self.pool.as_deref(), op)
+    }
+
+    /// The enabled backend responsible for `path`, if any
+    pub fn backend_for(&self, path: &Path) -> Option<&dyn LanguageBackend> {
+        self.custom_backends
+            .iter()
+            .find(|b| b.handles(path))
+            .or_else(|| {
+                self.backends
+                    .iter()
+                    .find(|b| self.config.language_enabled(b.language()) && b.handles(path))
+            })
+            .map(|b| b.as_ref())
+    }
+
+    /// The registered Python backend, enabled or not; in-memory sources
+    /// without a recognized extension are parsed with it
+    fn python_backend(&self) -> Option<&dyn LanguageBackend> {
+        self.custom_backends
+            .iter()
+            .chain(&self.backends)
+            .find(|b| b.language() == "python")
+            .map(|b| b.as_ref())
+    }
+
+    /// Whether `path` is a source file one of the enabled backends can analyze
+    pub fn is_source_file(&self, path: &Path) -> bool {
+        self.backend_for(path).is_some()
+    }
+
+    /// Analyze a single source file with the backend matching its extension
     pub fn analyze_file(&self, file_path: &Path) -> Result<Vec<CodeEntity>> {
+        let (result, timing) = self.analyze_file_timed(file_path, None);
+        let mut stats = RunStats::from_files(vec![timing]);
+        stats.total = stats.file_timings[0].total;
+        if let Ok(entities) = &result {
+            stats.memory = memory::estimate(entities);
+        }
+        self.record_stats(stats);
+        result
+    }
+
+    /// Analyze a file that is part of the tree at `root`, so entity ids are
+    /// derived from the path relative to `root`
+    pub fn analyze_file_in(&self, file_path: &Path, root: Option<&Path>) -> Result<Vec<CodeEntity>> {
+        self.analyze_file_timed(file_path, root).0
+    }
+
+    /// `analyze_file_in`, also reporting where the time went
+    fn analyze_file_timed(&self, file_path: &Path, root: Option<&Path>) -> (Result<Vec<CodeEntity>>, FileTiming) {
+        let mut timing = FileTiming::new(file_path);
+        let start = Instant::now();
+        let result = self.analyze_file_with(file_path, root, &mut timing);
+        timing.total = start.elapsed();
+        timing.entities = result.as_ref().map_or(0, Vec::len);
+        timing.failed = result.is_err();
+        timing.diagnostic = result.as_ref().err().and_then(|e| e.downcast_ref::<Diagnostic>()).cloned();
+        (result, timing)
+    }
+
+    fn analyze_file_with(
+        &self,
+        file_path: &Path,
+        root: Option<&Path>,
+        timing: &mut FileTiming,
+    ) -> Result<Vec<CodeEntity>> {
         if !file_path.exists() {
             return Err(anyhow::anyhow!("File does not exist: {:?}", file_path));
         }
         
-        if !file_path.extension().map_or(false, |ext| ext == "py") {
-            return Err(anyhow::anyhow!("Not a Python file: {:?}", file_path));
+        let backend = self.backend_for(file_path)
+            .ok_or_else(|| anyhow::anyhow!("Unsupported file type: {:?}", file_path))?;
+        
+        let started = Instant::now();
+        let (source, issue) = read_source_lenient(file_path, self.config.transcode_latin1)
+            .with_context(|| format!("Failed to analyze file: {:?}", file_path))?;
+        timing.read = started.elapsed();
+        timing.issue = issue;
+        let Some(source) = source else {
+            log::info!("Skipping {:?}: {}", file_path, issue.map_or("unreadable", |i| i.as_str()));
+            return Ok(Vec::new());
+        };
+        if issue.is_some() {
+            log::info!("{:?} is not valid UTF-8, decoded it as Latin-1", file_path);
+        }
+        timing.generated = generated::is_generated(file_path, &source);
+        if timing.generated && self.config.skip_generated {
+            log::debug!("Skipping generated file {:?}", file_path);
+            return Ok(Vec::new());
         }
         
-        self.parser.parse_file(file_path)
-            .with_context(|| format!("Failed to analyze file: {:?}", file_path))
+        let cached = self.cache.as_ref().map(|cache| (cache, cache.key(file_path, root, source.as_bytes())));
+        let mut entities = match cached.and_then(|(cache, key)| cache.get(key)) {
+            Some(entities) => {
+                log::trace!("Cache hit for {:?}", file_path);
+                timing.cached = true;
+                entities
+            }
+            None => {
+                let started = Instant::now();
+                let mut entities = backend.parse_source(&source, file_path)
+                    .with_context(|| format!("Failed to analyze file: {:?}", file_path))?;
+                timing.parse = started.elapsed();
+                
+                let started = Instant::now();
+                if timing.generated {
+                    entities.iter_mut().for_each(|e| e.is_generated = true);
+                }
+                self.post_process(&mut entities, &source);
+                if let Some((cache, key)) = cached {
+                    if let Err(e) = cache.put(key, &entities) {
+                        log::debug!("Not caching {:?}: {:#}", file_path, e);
+                    }
+                }
+                timing.extract = started.elapsed();
+                entities
+            }
+        };
+        
+        let started = Instant::now();
+        self.resolve_names(&mut entities, root);
+        if self.config.relative_paths {
+            paths::normalize_entity_paths(&mut entities, root);
+        }
+        apply_hooks(&self.hooks, &mut entities);
+        visibility::retain_visible(&mut entities, &self.config);
+        // Deferred code is reloaded as strict UTF-8, so transcoded files keep
+        // theirs. Mapped files always defer, slicing code from the map on use
+        if (self.config.lazy_code || source.is_mapped()) && issue.is_none() {
+            lazy::defer_code(&mut entities, &source);
+        }
+        timing.extract += started.elapsed();
+        Ok(entities)
     }
 
-    /// Analyze all Python files in a directory (parallel processing)
-    pub fn analyze_directory(&self, dir_path: &Path) -> Result<Vec<CodeEntity>> {
-        let python_files = self.collect_python_files(dir_path)?;
-        
+    /// Per-file post-processing that depends only on the file's contents;
+    /// cached results are stored after this step
+    fn post_process(&self, entities: &mut [CodeEntity], source: &str) {
+        entities.iter_mut().for_each(CodeEntity::detect_deprecation);
+        identity::hash_sources(entities, source);
+        location::locate(entities, source);
+        if self.config.redact_secrets {
+            entities.iter_mut().for_each(redact::redact_entity);
+        }
+        FileLicense::detect(source).apply(entities);
+    }
+
+    /// Module path, qualified names, ids and the links built on them. The
+    /// module path follows the `__init__.py` files around the file, which
+    /// the cache key doesn't cover, so this runs on cached entities too.
+    fn resolve_names(&self, entities: &mut [CodeEntity], root: Option<&Path>) {
+        let module = entities.first().map(|first| modules::module_path(&first.file_path, root));
+        self.resolve_names_in(entities, root, &module.unwrap_or_default());
+    }
+
+    /// `resolve_names` for entities of the module `module`
+    fn resolve_names_in(&self, entities: &mut [CodeEntity], root: Option<&Path>, module: &str) {
+        entities.iter_mut().for_each(|e| e.module_path = module.to_string());
+        hierarchy::qualify(entities, module);
+        identity::assign_ids(entities, root);
+        hierarchy::link(entities);
+        testmap::clear_non_tests(entities, root);
+        inheritance::resolve(entities, &self.config);
+        endpoints::link_models(entities);
+    }
+
+    /// Analyze in-memory source as if it lived at `virtual_path`.
+    ///
+    /// The backend is chosen from the path's extension, falling back to
+    /// Python for paths such as `<string>` that have none.
+    pub fn analyze_source(&self, source: &str, virtual_path: &Path) -> Result<Vec<CodeEntity>> {
+        self.analyze_source_with(source, virtual_path, None)
+    }
+
+    /// `analyze_source` for a file of the module `module`, for trees that
+    /// aren't on disk where the module path can't be worked out from the
+    /// surrounding packages
+    pub fn analyze_source_as(&self, source: &str, virtual_path: &Path, module: &str) -> Result<Vec<CodeEntity>> {
+        self.analyze_source_with(source, virtual_path, Some(module))
+    }
+
+    fn analyze_source_with(&self, source: &str, virtual_path: &Path, module: Option<&str>) -> Result<Vec<CodeEntity>> {
+        let backend = self
+            .backend_for(virtual_path)
+            .or_else(|| self.python_backend())
+            .ok_or_else(|| anyhow::anyhow!("Unsupported file type: {:?}", virtual_path))?;
+        let generated = generated::is_generated(virtual_path, source);
+        if generated && self.config.skip_generated {
+            return Ok(Vec::new());
+        }
+        let mut entities = backend.parse_source(source, virtual_path)
+            .with_context(|| format!("Failed to analyze source: {:?}", virtual_path))?;
         
+        if generated {
+            entities.iter_mut().for_each(|e| e.is_generated = true);
+        }
+        self.post_process(&mut entities, source);
+        match module {
+            Some(module) => self.resolve_names_in(&mut entities, None, module),
+            None => self.resolve_names(&mut entities, None),
+        }
+        apply_hooks(&self.hooks, &mut entities);
+        visibility::retain_visible(&mut entities, &self.config);
+        Ok(entities)
+    }
+
+    /// Analyze all source files in a directory (parallel processing)
+    pub fn analyze_directory(&self, dir_path: &Path) -> Result<Vec<CodeEntity>> {
+        let start = Instant::now();
+        let source_files = self.collect_source_files(dir_path)?;
+        let walk = start.elapsed();
+        log::debug!("Analyzing {} source files under {:?}", source_files.len(), dir_path);
         
-        // Process files in parallel using Rayon
-        let results: Vec<Result<Vec<CodeEntity>>> = python_files
-            .par_iter()
-            .map(|file_path| self.analyze_file(file_path))
-            .collect();
+        // Process files in parallel using Rayon, on the configured pool if any
+        let analyze_all = || -> Vec<(Result<Vec<CodeEntity>>, FileTiming)> {
+            source_files
+                .par_iter()
+                .map(|file_path| self.analyze_file_timed(file_path, Some(dir_path)))
+                .collect()
+        };
+        let results = self.install(analyze_all);
         
         // Collect all entities, skipping failed files
         let mut all_entities = Vec::new();
-        let mut errors = Vec::new();
+        let mut timings = Vec::with_capacity(results.len());
         
-        for (path, result) in python_files.iter().zip(results.into_iter()) {
+        for (result, timing) in results {
             match result {
                 Ok(entities) => all_entities.extend(entities),
-                Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+                Err(e) => log::warn!("Skipping {}: {:#}", timing.path.display(), e),
             }
+            timings.push(timing);
         }
         
+        let linking = Instant::now();
+        schema::link_models(&mut all_entities);
+        inheritance::resolve(&mut all_entities, &self.config);
+        endpoints::link_models(&mut all_entities);
+        endpoints::link_routers(&mut all_entities);
+        testmap::link(&mut all_entities, Some(dir_path));
+        if self.config.git_blame {
+            if let Err(e) = self.install(|| git::blame_entities(dir_path, &mut all_entities)) {
+                log::warn!("Skipping git blame for {:?}: {:#}", dir_path, e);
+            }
+        }
         
-        
+        let mut stats = RunStats::from_files(timings);
+        stats.entities = all_entities.len();
+        stats.walk = walk;
+        stats.extract += linking.elapsed();
+        stats.total = start.elapsed();
+        stats.memory = memory::estimate(&all_entities);
+        log::debug!("Extracted {} entities from {:?}", all_entities.len(), dir_path);
+        self.record_stats(stats);
         Ok(all_entities)
     }
 
-    /// Collect all Python files in a directory, respecting exclude patterns
-    fn collect_python_files(&self, dir_path: &Path) -> Result<Vec<PathBuf>> {
-        let mut python_files = Vec::new();
+    /// Timings of the last `analyze_file` or `analyze_directory` call
+    pub fn last_run_stats(&self) -> Option<RunStats> {
+        self.last_stats.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Add time spent converting the last run's results, e.g. for Python
+    pub fn record_convert_time(&self, duration: Duration) {
+        if let Some(stats) = self.last_stats.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            stats.convert += duration;
+            stats.total += duration;
+        }
+    }
+
+    fn record_stats(&self, stats: RunStats) {
+        *self.last_stats.lock().unwrap_or_else(|e| e.into_inner()) = Some(stats);
+    }
+
+    /// Collect all files handled by an enabled backend, respecting exclude patterns
+    pub fn collect_source_files(&self, dir_path: &Path) -> Result<Vec<PathBuf>> {
+        let mut source_files = Vec::new();
         
         for entry in WalkDir::new(dir_path)
-            .follow_links(false)
+            .follow_links(self.config.follow_symlinks)
             .into_iter()
             .filter_entry(|e| !self.should_exclude(e.path())) 
         {
             let entry = entry?;
             let path = entry.path();
             
-            if path.is_file() && path.extension().map_or(false, |ext| ext == "py") {
-                python_files.push(path.to_path_buf());
+            if path.is_file()
+                && self.is_source_file(path)
+                && self.should_include(path, dir_path)
+            {
+                source_files.push(path.to_path_buf());
             }
         }
         
-        Ok(python_files)
+        Ok(source_files)
     }
 
     /// Check if a path should be excluded
-    fn should_exclude(&self, path: &Path) -> bool {
+    pub fn should_exclude(&self, path: &Path) -> bool {
         for component in path.components() {
             if let Some(name) = component.as_os_str().to_str() {
                 for pattern in &self.exclude_patterns {
                     if pattern.matches(name) {
+                        log::trace!("Excluding {:?} (matched {})", path, pattern);
                         return true;
                     }
                 }
@@ -107,6 +448,22 @@ impl RustAnalyzer {
         false
     }
 
+    /// Whether `analyze_directory` would analyze the file at `relative`
+    /// (relative to the analysis root), e.g. for files listed by git
+    pub fn selects(&self, relative: &Path) -> bool {
+        self.is_source_file(relative) && !self.should_exclude(relative) && self.should_include(relative, Path::new(""))
+    }
+
+    /// Check a file against the include globs, matched relative to the analysis root
+    fn should_include(&self, path: &Path, root: &Path) -> bool {
+        if self.include_patterns.is_empty() {
+            return true;
+        }
+        
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        self.include_patterns.iter().any(|p| p.matches_path(relative))
+    }
+
     /// Analyze with custom exclude patterns
     pub fn with_excludes(mut self, patterns: Vec<&str>) -> Self {
         for pattern_str in patterns {
@@ -116,6 +473,95 @@ impl RustAnalyzer {
         }
         self
     }
+
+    /// Register an additional language backend, e.g. a `QueryBackend`.
+    ///
+    /// Registered backends take precedence over the built-in ones and are
+    /// active regardless of `AnalyzerConfig::languages`.
+    pub fn with_backend<B: LanguageBackend + 'static>(mut self, backend: B) -> Self {
+        self.custom_backends.push(Box::new(backend));
+        self
+    }
+
+    /// Analyze directories on `threads` worker threads, on a pool shared with
+    /// other analyzers when `AnalyzerConfig::shared_pool` is set
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.pool = thread_pool(threads, self.config.shared_pool);
+        self.config.threads = self.pool.as_ref().map(|_| threads);
+        self
+    }
+
+    /// Share the worker pool with other analyzers using the same thread count
+    pub fn with_shared_pool(mut self) -> Self {
+        self.config.shared_pool = true;
+        self.pool = self.config.threads.and_then(|threads| thread_pool(threads, true));
+        self
+    }
+
+    /// Number of threads directory analysis runs on
+    pub fn num_threads(&self) -> usize {
+        match &self.pool {
+            Some(pool) => pool.current_num_threads(),
+            None => rayon::current_num_threads(),
+        }
+    }
+
+    /// Register a post-processing hook run on every extracted entity
+    pub fn with_hook<H: EntityHook + 'static>(mut self, hook: H) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+}
+
+/// Run `op` on `pool`, or on Rayon's global pool without one
+pub(crate) fn install<R: Send>(pool: Option<&ThreadPool>, op: impl FnOnce() -> R + Send) -> R {
+    match pool {
+        Some(pool) => pool.install(op),
+        None => op(),
+    }
+}
+
+/// Backends for `AnalyzerConfig::query_languages`
+#[cfg(feature = "tree-sitter")]
+fn query_backends(config: &AnalyzerConfig) -> Result<Vec<Box<dyn LanguageBackend>>> {
+    config
+        .query_languages
+        .iter()
+        .map(|language| Ok(Box::new(QueryBackend::from_config(language)?) as Box<dyn LanguageBackend>))
+        .collect()
+}
+
+#[cfg(not(feature = "tree-sitter"))]
+fn query_backends(config: &AnalyzerConfig) -> Result<Vec<Box<dyn LanguageBackend>>> {
+    match config.query_languages.first() {
+        Some(language) => Err(anyhow::anyhow!("Query language {} needs the tree-sitter feature", language.name)),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// A `threads`-thread pool, or `None` (meaning Rayon's global pool) if one
+/// can't be built. Shared pools are created once per thread count and live
+/// for the rest of the process.
+fn thread_pool(threads: usize, shared: bool) -> Option<Arc<ThreadPool>> {
+    static SHARED: OnceLock<Mutex<HashMap<usize, Arc<ThreadPool>>>> = OnceLock::new();
+    let build = || match rayon::ThreadPoolBuilder::new().num_threads(threads).build() {
+        Ok(pool) => Some(Arc::new(pool)),
+        Err(e) => {
+            log::warn!("Failed to build {}-thread pool, using the global pool: {}", threads, e);
+            None
+        }
+    };
+    if !shared {
+        return build();
+    }
+    
+    let mut pools = SHARED.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(pool) = pools.get(&threads) {
+        return Some(Arc::clone(pool));
+    }
+    let pool = build()?;
+    pools.insert(threads, Arc::clone(&pool));
+    Some(pool)
 }
 
 /// Performance benchmarking utilities
@@ -177,6 +623,88 @@ mod tests {
         assert_eq!(entities[0].name, "hello");
     }
 
+    #[test]
+    fn test_last_run_stats() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.py"), "def a(): pass\ndef b(): pass").unwrap();
+        fs::write(temp_dir.path().join("broken.py"), "def broken(:").unwrap();
+        
+        let analyzer = RustAnalyzer::new();
+        assert!(analyzer.last_run_stats().is_none());
+        analyzer.analyze_directory(temp_dir.path()).unwrap();
+        
+        let stats = analyzer.last_run_stats().unwrap();
+        assert_eq!((stats.files, stats.entities, stats.failed), (2, 2, 1));
+        assert_eq!(stats.file_timings.len(), 2);
+        assert!(stats.total >= stats.walk);
+        assert_eq!(stats.memory.entities, 2);
+
+        let diagnostic = stats.diagnostics().next().unwrap();
+        assert!(diagnostic.file.ends_with("broken.py"));
+        assert_eq!((diagnostic.line, diagnostic.source_line.as_str()), (1, "def broken(:"));
+    }
+
+    #[test]
+    fn test_non_utf8_files_are_transcoded_or_skipped() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("latin1.py"), b"# Fran\xe7ois wrote this\ndef ok(): pass\n").unwrap();
+        fs::write(temp_dir.path().join("blob.py"), b"\0\0\x7fELF").unwrap();
+        
+        let analyzer = RustAnalyzer::new();
+        let entities = analyzer.analyze_directory(temp_dir.path()).unwrap();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].name, "ok");
+        let stats = analyzer.last_run_stats().unwrap();
+        assert_eq!((stats.transcoded, stats.skipped, stats.failed), (1, 1, 0));
+    }
+
+    #[test]
+    fn test_generated_files_are_tagged_or_skipped() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("users_pb2.py"), "class User: pass").unwrap();
+        fs::write(temp_dir.path().join("app.py"), "def handler(): pass").unwrap();
+        
+        let mut entities = RustAnalyzer::new().analyze_directory(temp_dir.path()).unwrap();
+        entities.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(entities.len(), 2);
+        assert!(entities[0].is_generated && entities[0].name == "User");
+        assert!(!entities[1].is_generated);
+        
+        let config = AnalyzerConfig {
+            skip_generated: true,
+            ..AnalyzerConfig::default()
+        };
+        let analyzer = RustAnalyzer::with_config(config);
+        let entities = analyzer.analyze_directory(temp_dir.path()).unwrap();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(analyzer.last_run_stats().unwrap().generated, 1);
+    }
+
+    #[test]
+    fn test_paths_are_relative_to_the_root() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("pkg")).unwrap();
+        fs::write(temp_dir.path().join("pkg").join("mod.py"), "def f(): pass").unwrap();
+        
+        let entities = RustAnalyzer::new().analyze_directory(temp_dir.path()).unwrap();
+        assert_eq!(entities[0].file_path, Path::new("pkg/mod.py"));
+        let absolute = entities[0].absolute_path.as_ref().unwrap();
+        assert!(absolute.is_absolute() && absolute.ends_with("pkg/mod.py"));
+        assert_eq!(entities[0].source_path(), absolute.as_path());
+    }
+
+    #[test]
+    fn test_analyze_source() {
+        let analyzer = RustAnalyzer::new();
+        let entities = analyzer
+            .analyze_source("class Buffer:\n    def flush(self): pass", Path::new("<editor>/buffer.py"))
+            .unwrap();
+        
+        assert_eq!(entities.len(), 2);
+        assert_eq!(entities[1].name, "flush");
+        assert_eq!(entities[1].file_path, PathBuf::from("<editor>/buffer.py"));
+    }
+
     #[test]
     fn test_analyze_directory() {
         let temp_dir = TempDir::new().unwrap();
@@ -199,6 +727,76 @@ mod tests {
         assert!(!entities.iter().any(|e| e.name == "excluded"));
     }
 
+    #[test]
+    fn test_hook_filters_entities() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.py");
+        
+        fs::write(&file_path, "def public(): pass\ndef _private(): pass").unwrap();
+        
+        let analyzer = RustAnalyzer::new()
+            .with_hook(|e: &mut CodeEntity| !e.name.starts_with('_'));
+        let entities = analyzer.analyze_file(&file_path).unwrap();
+        
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].name, "public");
+    }
+
+    #[test]
+    fn test_config_include_and_threads() {
+        let temp_dir = TempDir::new().unwrap();
+        let src = temp_dir.path().join("src");
+        fs::create_dir(&src).unwrap();
+        fs::write(src.join("lib.py"), "def kept(): pass").unwrap();
+        fs::write(temp_dir.path().join("setup.py"), "def skipped(): pass").unwrap();
+        
+        let config = AnalyzerConfig {
+            include: vec!["src/*".to_string()],
+            threads: Some(2),
+            ..AnalyzerConfig::default()
+        };
+        let entities = RustAnalyzer::with_config(config)
+            .analyze_directory(temp_dir.path())
+            .unwrap();
+        
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].name, "kept");
+    }
+
+    #[test]
+    fn test_shared_pools_are_reused() {
+        let first = RustAnalyzer::new().with_shared_pool().with_threads(3);
+        let second = RustAnalyzer::new().with_shared_pool().with_threads(3);
+        let private = RustAnalyzer::new().with_threads(3);
+        
+        assert_eq!(first.num_threads(), 3);
+        assert_eq!(first.config().threads, Some(3));
+        assert!(Arc::ptr_eq(first.pool.as_ref().unwrap(), second.pool.as_ref().unwrap()));
+        assert!(!Arc::ptr_eq(first.pool.as_ref().unwrap(), private.pool.as_ref().unwrap()));
+    }
+
+    #[test]
+    fn test_rust_backend_is_opt_in() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("app.py"), "def handler(): pass").unwrap();
+        fs::write(temp_dir.path().join("lib.rs"), "/// Adds one.\npub fn incr(x: u32) -> u32 { x + 1 }").unwrap();
+        
+        let python_only = RustAnalyzer::new().analyze_directory(temp_dir.path()).unwrap();
+        assert_eq!(python_only.len(), 1);
+        
+        let config = AnalyzerConfig {
+            languages: vec!["python".to_string(), "rust".to_string()],
+            ..AnalyzerConfig::default()
+        };
+        let entities = RustAnalyzer::with_config(config)
+            .analyze_directory(temp_dir.path())
+            .unwrap();
+        
+        assert_eq!(entities.len(), 2);
+        let incr = entities.iter().find(|e| e.name == "incr").unwrap();
+        assert_eq!(incr.docstring.as_deref(), Some("Adds one."));
+    }
+
     #[test]
     fn test_parallel_performance() {
         // This test would create many files and verify parallel processing
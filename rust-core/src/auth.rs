@@ -0,0 +1,256 @@
+use rustpython_parser::ast;
+
+use crate::endpoints::{self, ParamLocation};
+use crate::entity::CodeEntity;
+use crate::walk::{argument, dotted_name, keyword, string_literal};
+
+/// Decorators requiring a signed-in user: Flask-Login's and Django's
+/// `login_required`, Flask-JWT-Extended's `jwt_required`, Django admin's
+/// `staff_member_required`
+const LOGIN_DECORATORS: &[&str] = &[
+    "login_required",
+    "jwt_required",
+    "fresh_jwt_required",
+    "staff_member_required",
+    "user_passes_test",
+    "auth_required",
+    "requires_auth",
+];
+
+/// Decorators naming the permissions, roles or scopes required, in their
+/// positional arguments: Django's `permission_required`, Flask-Security's
+/// `roles_required`/`roles_accepted` and Starlette's `requires`
+const PERMISSION_DECORATORS: &[&str] = &["permission_required", "roles_required", "roles_accepted", "requires"];
+
+/// Django mixins protecting class-based views
+const AUTH_MIXINS: &[&str] = &["LoginRequiredMixin", "PermissionRequiredMixin", "UserPassesTestMixin"];
+
+/// Words marking a FastAPI dependency as an auth check, e.g. `get_current_user`
+const AUTH_DEPENDENCY_WORDS: &[&str] = &["current_user", "auth", "login", "token", "jwt", "api_key", "apikey", "permission", "require"];
+
+/// DRF permission classes that let anyone through
+const PUBLIC_PERMISSIONS: &[&str] = &["AllowAny"];
+
+/// Authentication and permission requirements of an endpoint or view
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Auth {
+    pub required: bool,
+    /// What enforces it: the dependency, decorator, mixin or DRF
+    /// authentication classes, e.g. `get_current_user` or `login_required`
+    pub scheme: Option<String>,
+    /// Permissions, roles, scopes or DRF permission classes required
+    pub permissions: Vec<String>,
+}
+
+impl Auth {
+    /// Requirements of a function from its decorators and, for FastAPI,
+    /// its `Depends(...)`/`Security(...)` parameters
+    pub fn of_function(decorators: &[ast::Expr], args: &ast::Arguments) -> Option<Self> {
+        let mut auth = Auth::default();
+        auth.decorators(decorators);
+        for arg in args.posonlyargs.iter().chain(&args.args).chain(&args.kwonlyargs) {
+            let marker = arg
+                .default
+                .as_deref()
+                .and_then(endpoints::marker_call)
+                .or_else(|| endpoints::annotated_marker(arg.def.annotation.as_deref()));
+            if let Some((ParamLocation::Dependency, call)) = marker {
+                auth.dependency(call);
+            }
+        }
+        auth.found()
+    }
+
+    /// Requirements of a class-based view: decorators, Django auth mixins
+    /// and DRF `permission_classes`/`authentication_classes` or Django
+    /// `permission_required` attributes
+    pub fn of_class(decorators: &[ast::Expr], bases: &[String], body: &[ast::Stmt]) -> Option<Self> {
+        let mut auth = Auth::default();
+        auth.decorators(decorators);
+        for base in bases {
+            let last = base.rsplit('.').next().unwrap_or(base);
+            if AUTH_MIXINS.contains(&last) {
+                auth.require(last);
+            }
+        }
+        for stmt in body {
+            let ast::Stmt::Assign(assign) = stmt else { continue };
+            for target in &assign.targets {
+                let ast::Expr::Name(name) = target else { continue };
+                match name.id.as_str() {
+                    "permission_classes" => auth.permission_classes(&assign.value),
+                    "authentication_classes" => auth.authentication_classes(&assign.value),
+                    "permission_required" => auth.permissions.extend(strings(&assign.value)),
+                    _ => {}
+                }
+            }
+        }
+        auth.found()
+    }
+
+    /// The requirements recorded on `entity`, if any
+    pub fn of_entity(entity: &CodeEntity) -> Option<Self> {
+        Auth {
+            required: entity.auth_required,
+            scheme: entity.auth_scheme.clone(),
+            permissions: entity.permissions.clone(),
+        }
+        .found()
+    }
+
+    pub fn apply(&self, entity: &mut CodeEntity) {
+        entity.auth_required = self.required;
+        entity.auth_scheme = self.scheme.clone();
+        entity.permissions = self.permissions.clone();
+    }
+
+    fn found(self) -> Option<Self> {
+        (self.required || self.scheme.is_some() || !self.permissions.is_empty()).then_some(self)
+    }
+
+    fn require(&mut self, scheme: &str) {
+        self.required = true;
+        self.scheme.get_or_insert_with(|| scheme.to_string());
+    }
+
+    fn decorators(&mut self, decorators: &[ast::Expr]) {
+        for decorator in decorators {
+            self.decorator(decorator);
+        }
+    }
+
+    fn decorator(&mut self, decorator: &ast::Expr) {
+        let (name, call) = match decorator {
+            ast::Expr::Call(call) => (dotted_name(&call.func), Some(call)),
+            other => (dotted_name(other), None),
+        };
+        let Some(name) = name else { return };
+        let last = name.rsplit('.').next().unwrap_or(&name);
+        let first = call.and_then(|call| call.args.first());
+        match last {
+            _ if LOGIN_DECORATORS.contains(&last) => self.require(last),
+            _ if PERMISSION_DECORATORS.contains(&last) => {
+                self.require(last);
+                for arg in call.map_or(&[][..], |call| &call.args[..]) {
+                    self.permissions.extend(strings(arg));
+                }
+            }
+            // Django's `@method_decorator(login_required, name="dispatch")`
+            "method_decorator" => {
+                if let Some(inner) = first {
+                    self.decorator(inner);
+                }
+            }
+            "permission_classes" => {
+                if let Some(classes) = first {
+                    self.permission_classes(classes);
+                }
+            }
+            "authentication_classes" => {
+                if let Some(classes) = first {
+                    self.authentication_classes(classes);
+                }
+            }
+            _ => {}
+        }
+        // FastAPI route-level `dependencies=[Depends(verify_token)]`
+        if let Some(ast::Expr::List(dependencies)) = call.and_then(|call| keyword(call, "dependencies")) {
+            for dependency in &dependencies.elts {
+                if let Some((ParamLocation::Dependency, call)) = endpoints::marker_call(dependency) {
+                    self.dependency(call);
+                }
+            }
+        }
+    }
+
+    /// `Security(...)` always guards a route; `Depends(...)` does when the
+    /// dependency's name says so
+    fn dependency(&mut self, call: &ast::ExprCall) {
+        let Some(name) = argument(call, 0, "dependency").and_then(dotted_name) else { return };
+        let last = name.rsplit('.').next().unwrap_or(&name).to_ascii_lowercase();
+        let is_security = dotted_name(&call.func).map_or(false, |func| func.ends_with("Security"));
+        if is_security || AUTH_DEPENDENCY_WORDS.iter().any(|word| last.contains(word)) {
+            self.require(&name);
+            if let Some(scopes) = keyword(call, "scopes") {
+                self.permissions.extend(strings(scopes));
+            }
+        }
+    }
+
+    fn permission_classes(&mut self, classes: &ast::Expr) {
+        let mut names = Vec::new();
+        class_names(classes, &mut names);
+        if names.iter().any(|name| !PUBLIC_PERMISSIONS.contains(&name.as_str())) {
+            self.required = true;
+        }
+        self.permissions.extend(names);
+    }
+
+    fn authentication_classes(&mut self, classes: &ast::Expr) {
+        let mut names = Vec::new();
+        class_names(classes, &mut names);
+        if !names.is_empty() {
+            self.scheme = Some(names.join(", "));
+        }
+    }
+}
+
+/// A string literal, or the string literals of a list, tuple or set
+fn strings(expr: &ast::Expr) -> Vec<String> {
+    let items = match expr {
+        ast::Expr::List(list) => &list.elts[..],
+        ast::Expr::Tuple(tuple) => &tuple.elts[..],
+        ast::Expr::Set(set) => &set.elts[..],
+        other => std::slice::from_ref(other),
+    };
+    items.iter().filter_map(string_literal).map(str::to_string).collect()
+}
+
+/// Class names in `[IsAuthenticated, perms.IsOwner]` or DRF's composed
+/// `IsAuthenticated & (IsAdminUser | IsOwner)`
+fn class_names(expr: &ast::Expr, out: &mut Vec<String>) {
+    match expr {
+        ast::Expr::List(list) => list.elts.iter().for_each(|elt| class_names(elt, out)),
+        ast::Expr::Tuple(tuple) => tuple.elts.iter().for_each(|elt| class_names(elt, out)),
+        ast::Expr::BinOp(op) => {
+            class_names(&op.left, out);
+            class_names(&op.right, out);
+        }
+        ast::Expr::UnaryOp(op) => class_names(&op.operand, out),
+        ast::Expr::Call(call) => class_names(&call.func, out),
+        other => {
+            if let Some(name) = dotted_name(other) {
+                let last = name.rsplit('.').next().unwrap_or(&name).to_string();
+                if !out.contains(&last) {
+                    out.push(last);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::PythonParser;
+    use std::path::Path;
+
+    #[test]
+    fn test_auth_detection() {
+        let source = "@app.get(\"/me\")\nasync def me(user: User = Security(get_current_user, scopes=[\"me\"])):\n    pass\n\n@app.get(\"/health\")\nasync def health(db = Depends(get_db)):\n    pass\n\n@permission_required(\"polls.change_poll\")\ndef edit(request, pk):\n    pass\n\nclass Orders(APIView):\n    permission_classes = [IsAuthenticated & IsOwner]\n    authentication_classes = (TokenAuthentication,)\n\n    def get(self, request):\n        pass\n";
+        let entities = PythonParser::new().parse_source(source, Path::new("views.py")).unwrap();
+        let find = |name: &str| entities.iter().find(|e| e.name == name).unwrap();
+
+        let me = find("me");
+        assert!(me.auth_required);
+        assert_eq!(me.auth_scheme.as_deref(), Some("get_current_user"));
+        assert_eq!(me.permissions, vec!["me"]);
+        assert!(!find("health").auth_required);
+        assert_eq!(find("edit").permissions, vec!["polls.change_poll"]);
+
+        let orders = find("Orders");
+        assert!(orders.auth_required);
+        assert_eq!(orders.auth_scheme.as_deref(), Some("TokenAuthentication"));
+        assert_eq!(orders.permissions, vec!["IsAuthenticated", "IsOwner"]);
+        assert_eq!(find("get").permissions, orders.permissions);
+    }
+}
@@ -0,0 +1,361 @@
+use anyhow::Result;
+use std::path::Path;
+use tree_sitter::{Language, Node};
+
+use crate::backends::syntax::{self, field, line, named_children, text};
+use crate::backends::{has_extension, LanguageBackend};
+use crate::entity::CodeEntity;
+
+/// Backend for C and C++ headers built on tree-sitter.
+///
+/// Extracts function prototypes and inline definitions, structs, unions,
+/// enums, typedefs and C++ classes, documented by the Doxygen comment
+/// (`/** */`, `/*! */`, `///` or `//!`) directly above each declaration.
+pub struct CHeaderBackend {
+    language: &'static str,
+    extensions: &'static [&'static str],
+}
+
+impl CHeaderBackend {
+    /// Backend for `.h` headers, enabled as `c`
+    pub fn new() -> Self {
+        CHeaderBackend {
+            language: "c",
+            extensions: &["h"],
+        }
+    }
+
+    /// Backend for `.hpp`-style headers, enabled as `cpp`
+    pub fn cpp() -> Self {
+        CHeaderBackend {
+            language: "cpp",
+            extensions: &["hpp", "hh", "hxx", "h++"],
+        }
+    }
+
+    fn grammar(&self) -> Language {
+        if self.language == "cpp" {
+            tree_sitter_cpp::language()
+        } else {
+            tree_sitter_c::language()
+        }
+    }
+}
+
+impl LanguageBackend for CHeaderBackend {
+    fn language(&self) -> &str {
+        self.language
+    }
+
+    fn handles(&self, path: &Path) -> bool {
+        has_extension(path, self.extensions)
+    }
+
+    fn parse_source(&self, source: &str, file_path: &Path) -> Result<Vec<CodeEntity>> {
+        let tree = syntax::parse(&self.grammar(), source, file_path)?;
+        let mut visitor = HeaderVisitor {
+            source,
+            file_path,
+            entities: Vec::new(),
+        };
+        visitor.visit_children(tree.root_node(), false);
+        Ok(visitor.entities)
+    }
+}
+
+struct HeaderVisitor<'a> {
+    source: &'a str,
+    file_path: &'a Path,
+    entities: Vec<CodeEntity>,
+}
+
+impl HeaderVisitor<'_> {
+    fn visit_children(&mut self, node: Node, hidden: bool) {
+        for child in named_children(node) {
+            self.visit(child, hidden);
+        }
+    }
+
+    /// Visit a declaration; `hidden` marks members outside a public section
+    fn visit(&mut self, node: Node, hidden: bool) {
+        match node.kind() {
+            // Include guards, `extern "C"` blocks and namespaces only wrap declarations
+            "preproc_ifdef" | "preproc_if" | "preproc_else" | "preproc_elif" | "linkage_specification"
+            | "declaration_list" => self.visit_children(node, hidden),
+            "namespace_definition" => {
+                if let Some(body) = node.child_by_field_name("body") {
+                    self.visit_children(body, hidden);
+                }
+            }
+            "declaration" | "field_declaration" | "function_definition" => self.visit_function(node, hidden),
+            "struct_specifier" | "union_specifier" | "enum_specifier" | "class_specifier" => {
+                self.visit_record(node, node, None, hidden);
+            }
+            "type_definition" => self.visit_typedef(node, hidden),
+            "template_declaration" => {
+                if let Some(inner) = named_children(node).last() {
+                    self.visit(inner, hidden);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_function(&mut self, node: Node, hidden: bool) {
+        let Some(declarator) = function_declarator(node) else {
+            // `struct point { ... } origin;` still defines the struct
+            if let Some(ty) = node.child_by_field_name("type") {
+                self.visit_record(ty, node, None, hidden);
+            }
+            return;
+        };
+        let Some(name_node) = declarator.child_by_field_name("declarator") else { return };
+        let name = text(name_node, self.source);
+
+        let entity_type = if node.kind() == "field_declaration" || name.contains("::") { "method" } else { "function" };
+        let mut entity = self.entity(entity_type, name, node);
+        let is_static = named_children(node)
+            .any(|c| c.kind() == "storage_class_specifier" && text(c, self.source) == "static");
+        entity.is_internal = hidden || is_static || name.starts_with('_');
+
+        entity.parameters = declarator
+            .child_by_field_name("parameters")
+            .map(|params| {
+                named_children(params)
+                    .filter_map(|p| p.child_by_field_name("declarator").and_then(|d| identifier(d, self.source)))
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // Everything before the name minus storage classes: `static inline const char *` → `const char *`
+        let prefix = &self.source[node.start_byte()..name_node.start_byte()];
+        let return_type = prefix
+            .split_whitespace()
+            .filter(|w| !matches!(*w, "static" | "inline" | "extern" | "virtual" | "explicit"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        entity.return_type = (!return_type.is_empty() && return_type != "void").then_some(return_type);
+
+        entity.code = syntax::header(node, self.source).trim_end_matches(';').trim().to_string();
+        entity.calculate_complexity();
+        self.entities.push(entity);
+    }
+
+    /// Record a struct/union/enum/class with a body; `typedef_name` names anonymous ones
+    fn visit_record(&mut self, node: Node, doc_node: Node, typedef_name: Option<&str>, hidden: bool) {
+        let Some(body) = node.child_by_field_name("body") else { return };
+        let Some(name) = field(node, "name", self.source).or(typedef_name) else { return };
+
+        let entity_type = node.kind().trim_end_matches("_specifier");
+        let mut entity = self.entity(entity_type, name, doc_node);
+        entity.is_internal = hidden || name.starts_with('_');
+        entity.code = match typedef_name {
+            Some(alias) => format!("typedef {} {}", entity_type, alias),
+            None => format!("{} {}", entity_type, name),
+        };
+        entity.parameters = named_children(body)
+            .filter_map(|member| match member.kind() {
+                "enumerator" => field(member, "name", self.source),
+                "field_declaration" if function_declarator(member).is_none() => {
+                    member.child_by_field_name("declarator").and_then(|d| identifier(d, self.source))
+                }
+                _ => None,
+            })
+            .map(str::to_string)
+            .collect();
+        let internal = entity.is_internal;
+        self.entities.push(entity);
+
+        if entity_type == "class" || entity_type == "struct" {
+            self.visit_members(body, entity_type == "class", internal);
+        }
+    }
+
+    /// Visit a member function, which reads as a method rather than a function
+    fn visit_method(&mut self, member: Node, hidden: bool) {
+        let before = self.entities.len();
+        self.visit(member, hidden);
+        for method in &mut self.entities[before..] {
+            if method.entity_type == "function" {
+                method.entity_type = "method".into();
+            }
+        }
+    }
+
+    /// Visit C++ member functions, tracking `public:`/`private:` sections
+    fn visit_members(&mut self, body: Node, private_by_default: bool, hidden: bool) {
+        let mut private = private_by_default;
+        for member in named_children(body) {
+            match member.kind() {
+                "access_specifier" => private = text(member, self.source).trim_end_matches(':').trim() != "public",
+                "template_declaration" => self.visit_method(member, hidden || private),
+                "field_declaration" | "function_definition" | "declaration" if function_declarator(member).is_some() => {
+                    self.visit_method(member, hidden || private)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn visit_typedef(&mut self, node: Node, hidden: bool) {
+        let Some(alias) = node.child_by_field_name("declarator").and_then(|d| identifier(d, self.source)) else {
+            return;
+        };
+        match node.child_by_field_name("type") {
+            Some(ty) if ty.child_by_field_name("body").is_some() => self.visit_record(ty, node, Some(alias), hidden),
+            _ => {
+                let mut entity = self.entity("typedef", alias, node);
+                entity.is_internal = hidden || alias.starts_with('_');
+                entity.code = text(node, self.source).split_whitespace().collect::<Vec<_>>().join(" ");
+                entity.code = entity.code.trim_end_matches(';').to_string();
+                self.entities.push(entity);
+            }
+        }
+    }
+
+    fn entity(&self, entity_type: &str, name: &str, node: Node) -> CodeEntity {
+        let mut entity = CodeEntity::new(
+            entity_type.to_string(),
+            name.to_string(),
+            self.file_path.to_path_buf(),
+            line(node),
+        );
+        entity.end_line = syntax::end_line(node);
+        entity.docstring = doxygen_comment(node, self.source);
+        entity
+    }
+}
+
+/// The function declarator of a prototype or definition; function pointers don't count
+fn function_declarator(node: Node) -> Option<Node> {
+    find_declarator(node, "function_declarator")
+        .filter(|f| f.child_by_field_name("declarator").map_or(false, |d| d.kind() != "parenthesized_declarator"))
+}
+
+/// Follow `declarator` fields (through pointers, references, arrays) to a node of `kind`
+fn find_declarator<'t>(node: Node<'t>, kind: &str) -> Option<Node<'t>> {
+    let mut current = node.child_by_field_name("declarator")?;
+    loop {
+        if current.kind() == kind {
+            return Some(current);
+        }
+        current = current
+            .child_by_field_name("declarator")
+            .or_else(|| named_children(current).find(|c| c.kind().ends_with("declarator")))?;
+    }
+}
+
+/// The declared identifier inside a (possibly pointer or array) declarator
+fn identifier<'a>(declarator: Node, source: &'a str) -> Option<&'a str> {
+    match declarator.kind() {
+        "identifier" | "field_identifier" | "type_identifier" | "qualified_identifier" | "destructor_name"
+        | "operator_name" => Some(text(declarator, source)),
+        _ => declarator
+            .child_by_field_name("declarator")
+            .or_else(|| named_children(declarator).find(|c| c.kind().ends_with("declarator")))
+            .and_then(|inner| identifier(inner, source)),
+    }
+}
+
+/// The Doxygen comment block directly above `node`, if any
+fn doxygen_comment(node: Node, source: &str) -> Option<String> {
+    let comments = syntax::leading_comments(node, source);
+    let last = comments.last()?;
+
+    let doc = if last.starts_with("/**") || last.starts_with("/*!") {
+        syntax::clean_block_comment(&last.replacen("/*!", "/**", 1))
+    } else {
+        let lines: Vec<&str> = comments
+            .iter()
+            .copied()
+            .filter(|c| c.starts_with("///") || c.starts_with("//!"))
+            .map(|c| c[3..].strip_prefix(' ').unwrap_or(&c[3..]))
+            .collect();
+        lines.join("\n")
+    };
+
+    let doc = doc
+        .lines()
+        .map(|l| l.trim_start_matches("@brief ").trim_start_matches("\\brief "))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let doc = doc.trim();
+    (!doc.is_empty()).then(|| doc.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_c_header() {
+        let source = r#"#ifndef MYLIB_H
+#define MYLIB_H
+
+/**
+ * @brief Open a connection.
+ * @param host server name
+ */
+int mylib_open(const char *host, int port);
+
+/// A 2D point.
+typedef struct {
+    double x;
+    double y;
+} point_t;
+
+enum color { RED, GREEN };
+
+static inline const char *_version(void) { return "1.0"; }
+
+#endif
+"#;
+        let entities = CHeaderBackend::new().parse_source(source, Path::new("mylib.h")).unwrap();
+        let find = |name: &str| entities.iter().find(|e| e.name == name).unwrap();
+
+        let open = find("mylib_open");
+        assert_eq!(open.entity_type, "function");
+        assert_eq!(open.parameters, vec!["host", "port"]);
+        assert_eq!(open.return_type.as_deref(), Some("int"));
+        assert_eq!(open.docstring.as_deref(), Some("Open a connection.\n@param host server name"));
+        assert_eq!(open.code, "int mylib_open(const char *host, int port)");
+
+        let point = find("point_t");
+        assert_eq!(point.entity_type, "struct");
+        assert_eq!(point.parameters, vec!["x", "y"]);
+        assert_eq!(point.docstring.as_deref(), Some("A 2D point."));
+
+        assert_eq!(find("color").parameters, vec!["RED", "GREEN"]);
+
+        let version = find("_version");
+        assert!(version.is_internal);
+        assert_eq!(version.return_type.as_deref(), Some("const char *"));
+    }
+
+    #[test]
+    fn test_cpp_class_members() {
+        let source = r#"namespace geo {
+/// A shape.
+class Shape {
+public:
+    /// Area in square units.
+    virtual double area() const;
+private:
+    void recompute();
+};
+}
+"#;
+        let entities = CHeaderBackend::cpp().parse_source(source, Path::new("shape.hpp")).unwrap();
+        let names: Vec<(&str, &str, bool)> = entities
+            .iter()
+            .map(|e| (e.entity_type.as_str(), e.name.as_str(), e.is_internal))
+            .collect();
+
+        assert_eq!(
+            names,
+            vec![("class", "Shape", false), ("method", "area", false), ("method", "recompute", true)]
+        );
+        assert_eq!(entities[1].docstring.as_deref(), Some("Area in square units."));
+    }
+}
@@ -0,0 +1,302 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::Path;
+use tree_sitter::Node;
+
+use crate::backends::syntax::{
+    self, clean_block_comment, clean_line_comments, field, header, join_route, leading_comments, line, named_children, text, unquote,
+};
+use crate::backends::{has_extension, LanguageBackend};
+use crate::entity::CodeEntity;
+
+/// Router methods registering a handler for one HTTP verb (gin, echo, chi)
+const ROUTER_VERBS: [&str; 7] = ["GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS"];
+
+/// Backend for Go sources built on tree-sitter.
+///
+/// Extracts functions, methods, structs, interfaces and other type
+/// declarations with their doc comments, plus `net/http` and gin-style
+/// route registrations. Unexported identifiers are marked internal.
+pub struct GoBackend;
+
+impl GoBackend {
+    pub fn new() -> Self {
+        GoBackend
+    }
+}
+
+impl LanguageBackend for GoBackend {
+    fn language(&self) -> &str {
+        "go"
+    }
+
+    fn handles(&self, path: &Path) -> bool {
+        has_extension(path, &["go"])
+    }
+
+    fn parse_source(&self, source: &str, file_path: &Path) -> Result<Vec<CodeEntity>> {
+        let tree = syntax::parse(&tree_sitter_go::language(), source, file_path)?;
+        let mut visitor = GoVisitor {
+            source,
+            file_path,
+            groups: HashMap::new(),
+            entities: Vec::new(),
+        };
+
+        for declaration in named_children(tree.root_node()) {
+            visitor.visit_declaration(declaration);
+        }
+        visitor.visit_routes(tree.root_node());
+        Ok(visitor.entities)
+    }
+}
+
+struct GoVisitor<'a> {
+    source: &'a str,
+    file_path: &'a Path,
+    /// Route prefixes of `v1 := r.Group("/v1")` style router groups
+    groups: HashMap<String, String>,
+    entities: Vec<CodeEntity>,
+}
+
+impl GoVisitor<'_> {
+    fn visit_declaration(&mut self, node: Node) {
+        match node.kind() {
+            "function_declaration" | "method_declaration" => {
+                let Some(name) = field(node, "name", self.source) else { return };
+                let entity_type = if node.kind() == "method_declaration" { "method" } else { "function" };
+                let mut entity = self.entity(entity_type, name, node, node);
+                entity.parameters = node
+                    .child_by_field_name("parameters")
+                    .map(|list| parameter_names(list, self.source))
+                    .unwrap_or_default();
+                entity.return_type = field(node, "result", self.source).map(str::to_string);
+                entity.code = header(node, self.source);
+                entity.calculate_complexity();
+                self.entities.push(entity);
+            }
+            "type_declaration" => {
+                let specs: Vec<Node> = named_children(node).filter(|n| n.kind() == "type_spec").collect();
+                for spec in &specs {
+                    // A lone spec is documented on the `type` keyword, grouped ones individually
+                    let doc_node = if specs.len() == 1 { node } else { *spec };
+                    self.visit_type(*spec, doc_node);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_type(&mut self, spec: Node, doc_node: Node) {
+        let Some(name) = field(spec, "name", self.source) else { return };
+        let Some(ty) = spec.child_by_field_name("type") else { return };
+
+        let (entity_type, keyword) = match ty.kind() {
+            "struct_type" => ("struct", "struct"),
+            "interface_type" => ("interface", "interface"),
+            _ => ("type", text(ty, self.source).lines().next().unwrap_or_default()),
+        };
+        let mut entity = self.entity(entity_type, name, spec, doc_node);
+        entity.code = format!("type {} {}", name, keyword);
+        entity.parameters = match ty.kind() {
+            "struct_type" => named_children(ty)
+                .filter(|n| n.kind() == "field_declaration_list")
+                .flat_map(|list| parameter_names(list, self.source))
+                .collect(),
+            "interface_type" => named_children(ty)
+                .filter_map(|member| field(member, "name", self.source))
+                .map(str::to_string)
+                .collect(),
+            _ => Vec::new(),
+        };
+        self.entities.push(entity);
+    }
+
+    /// Walk every node looking for router groups and route registrations
+    fn visit_routes(&mut self, node: Node) {
+        match node.kind() {
+            "short_var_declaration" | "assignment_statement" => self.record_group(node),
+            "call_expression" => self.record_route(node),
+            _ => {}
+        }
+        for child in named_children(node) {
+            self.visit_routes(child);
+        }
+    }
+
+    fn record_group(&mut self, node: Node) {
+        let (Some(left), Some(right)) = (node.child_by_field_name("left"), node.child_by_field_name("right")) else {
+            return;
+        };
+        let (Some(var), Some(call)) = (left.named_child(0), right.named_child(0)) else { return };
+        if call.kind() != "call_expression" {
+            return;
+        }
+        let Some((receiver, method, path)) = routing_call(call, self.source) else { return };
+        if method == "Group" {
+            let prefix = join_route(self.groups.get(receiver).map_or("", String::as_str), path);
+            self.groups.insert(text(var, self.source).to_string(), prefix);
+        }
+    }
+
+    fn record_route(&mut self, call: Node) {
+        let Some((receiver, method, pattern)) = routing_call(call, self.source) else { return };
+
+        let (verb, path) = match method {
+            // Go 1.22 patterns may carry the method: "GET /items/{id}"
+            "HandleFunc" | "Handle" => match pattern.split_once(' ') {
+                Some((verb, path)) => (verb.to_string(), path.trim()),
+                None => ("ANY".to_string(), pattern),
+            },
+            "Any" => ("ANY".to_string(), pattern),
+            verb if ROUTER_VERBS.contains(&verb) => (verb.to_string(), pattern),
+            _ => return,
+        };
+        if !path.starts_with('/') {
+            return;
+        }
+        let path = join_route(self.groups.get(receiver).map_or("", String::as_str), path);
+
+        let statement = call.parent().unwrap_or(call);
+        let mut entity = self.entity("route", &format!("{} {}", verb, path), call, statement);
+        entity.is_internal = false;
+        entity.is_api_endpoint = true;
+        entity.endpoint_path = Some(path);
+        entity.http_methods = vec![verb];
+        entity.code = text(call, self.source).lines().next().unwrap_or_default().trim().to_string();
+        if let Some(handler) = call
+            .child_by_field_name("arguments")
+            .and_then(|args| named_children(args).last())
+            .filter(|h| matches!(h.kind(), "identifier" | "selector_expression"))
+        {
+            entity.parameters = vec![text(handler, self.source).to_string()];
+        }
+        self.entities.push(entity);
+    }
+
+    fn entity(&self, entity_type: &str, name: &str, node: Node, doc_node: Node) -> CodeEntity {
+        let mut entity = CodeEntity::new(
+            entity_type.to_string(),
+            name.to_string(),
+            self.file_path.to_path_buf(),
+            line(node),
+        );
+        entity.end_line = syntax::end_line(node);
+        entity.docstring = doc_comment(doc_node, self.source);
+        entity.is_internal = !name.starts_with(|c: char| c.is_uppercase());
+        entity
+    }
+}
+
+/// Split `recv.Method("/path", ...)` into its receiver, method and path
+fn routing_call<'a>(call: Node, source: &'a str) -> Option<(&'a str, &'a str, &'a str)> {
+    let callee = call.child_by_field_name("function").filter(|f| f.kind() == "selector_expression")?;
+    let receiver = field(callee, "operand", source)?;
+    let method = field(callee, "field", source)?;
+    let first = call.child_by_field_name("arguments")?.named_child(0)?;
+    if !matches!(first.kind(), "interpreted_string_literal" | "raw_string_literal") {
+        return None;
+    }
+    Some((receiver, method, unquote(text(first, source))))
+}
+
+/// Declared names of a parameter or field list; unnamed entries use their type
+fn parameter_names(list: Node, source: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for declaration in named_children(list).filter(|n| n.kind() != "comment") {
+        let mut cursor = declaration.walk();
+        let declared: Vec<String> = declaration
+            .children_by_field_name("name", &mut cursor)
+            .map(|n| text(n, source).to_string())
+            .collect();
+        if declared.is_empty() {
+            names.push(field(declaration, "type", source).unwrap_or_else(|| text(declaration, source)).to_string());
+        } else {
+            names.extend(declared);
+        }
+    }
+    names
+}
+
+fn doc_comment(node: Node, source: &str) -> Option<String> {
+    let comments = leading_comments(node, source);
+    let doc = match comments.last() {
+        None => return None,
+        Some(block) if block.starts_with("/*") => clean_block_comment(block),
+        Some(_) => clean_line_comments(&comments, "//"),
+    };
+    (!doc.is_empty()).then_some(doc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_go_declarations() {
+        let source = r#"package users
+
+// User is a registered account.
+type User struct {
+	ID   int
+	Name string
+	internal.Base
+}
+
+// Store persists users.
+type Store interface {
+	Save(u *User) error
+}
+
+// Serve starts the HTTP server.
+// It blocks until the listener fails.
+func (s *Server) Serve(addr string, opts ...Option) error {
+	return nil
+}
+
+func helper(a, b int) int { return a + b }
+"#;
+        let entities = GoBackend::new().parse_source(source, Path::new("users.go")).unwrap();
+        let find = |name: &str| entities.iter().find(|e| e.name == name).unwrap();
+
+        let user = find("User");
+        assert_eq!(user.entity_type, "struct");
+        assert_eq!(user.parameters, vec!["ID", "Name", "internal.Base"]);
+        assert_eq!(user.docstring.as_deref(), Some("User is a registered account."));
+
+        assert_eq!(find("Store").parameters, vec!["Save"]);
+
+        let serve = find("Serve");
+        assert_eq!(serve.entity_type, "method");
+        assert_eq!(serve.parameters, vec!["addr", "opts"]);
+        assert_eq!(serve.return_type.as_deref(), Some("error"));
+        assert_eq!(serve.docstring.as_deref(), Some("Serve starts the HTTP server.\nIt blocks until the listener fails."));
+        assert_eq!(serve.code, "func (s *Server) Serve(addr string, opts ...Option) error");
+        assert!(!serve.is_internal);
+
+        let helper = find("helper");
+        assert!(helper.is_internal);
+        assert_eq!(helper.parameters, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_go_routes() {
+        let source = r#"package main
+
+func main() {
+	http.HandleFunc("/health", health)
+	http.HandleFunc("POST /items", createItem)
+	r := gin.Default()
+	v1 := r.Group("/api/v1")
+	v1.GET("/users/:id", handlers.GetUser)
+}
+"#;
+        let entities = GoBackend::new().parse_source(source, Path::new("main.go")).unwrap();
+        let routes: Vec<&CodeEntity> = entities.iter().filter(|e| e.entity_type == "route").collect();
+
+        let names: Vec<&str> = routes.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["ANY /health", "POST /items", "GET /api/v1/users/:id"]);
+        assert_eq!(routes[2].parameters, vec!["handlers.GetUser"]);
+        assert!(routes.iter().all(|r| r.is_api_endpoint));
+    }
+}
@@ -0,0 +1,100 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::entity::CodeEntity;
+use crate::parser::PythonParser;
+use crate::source::read_source;
+
+#[cfg(feature = "c")]
+pub mod c;
+#[cfg(feature = "go")]
+pub mod go;
+#[cfg(feature = "tree-sitter")]
+pub mod query;
+pub mod rust;
+pub mod shell;
+pub mod sql;
+#[cfg(feature = "tree-sitter")]
+pub mod syntax;
+#[cfg(feature = "typescript")]
+pub mod typescript;
+
+#[cfg(feature = "c")]
+pub use self::c::CHeaderBackend;
+#[cfg(feature = "go")]
+pub use self::go::GoBackend;
+#[cfg(feature = "tree-sitter")]
+pub use self::query::QueryBackend;
+pub use self::rust::RustBackend;
+pub use self::shell::{MakefileBackend, ShellBackend};
+pub use self::sql::SqlBackend;
+#[cfg(feature = "typescript")]
+pub use self::typescript::TypeScriptBackend;
+
+/// A language-specific parser turning source files into `CodeEntity` values
+pub trait LanguageBackend: Send + Sync {
+    /// Name used to enable the backend in `AnalyzerConfig::languages`
+    fn language(&self) -> &str;
+
+    /// Whether this backend handles the file at `path`
+    fn handles(&self, path: &Path) -> bool;
+
+    /// Parse in-memory source as if it lived at `file_path`
+    fn parse_source(&self, source: &str, file_path: &Path) -> Result<Vec<CodeEntity>>;
+
+    /// Read and parse a file from disk; large files are memory-mapped
+    fn parse_file(&self, file_path: &Path) -> Result<Vec<CodeEntity>> {
+        let source = read_source(file_path)?;
+        self.parse_source(&source, file_path)
+    }
+}
+
+/// Check `path` against a list of extensions given without the leading dot
+pub fn has_extension<S: AsRef<str>>(path: &Path, extensions: &[S]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map_or(false, |ext| extensions.iter().any(|e| e.as_ref() == ext))
+}
+
+impl LanguageBackend for PythonParser {
+    fn language(&self) -> &str {
+        "python"
+    }
+
+    fn handles(&self, path: &Path) -> bool {
+        has_extension(path, &["py"])
+    }
+
+    fn parse_source(&self, source: &str, file_path: &Path) -> Result<Vec<CodeEntity>> {
+        PythonParser::parse_source(self, source, file_path)
+    }
+}
+
+/// Every backend shipped with the crate, in dispatch order
+pub fn builtin_backends() -> Vec<Box<dyn LanguageBackend>> {
+    #[allow(unused_mut)]
+    let mut backends: Vec<Box<dyn LanguageBackend>> = vec![
+        Box::new(PythonParser::new()),
+        Box::new(RustBackend::new()),
+        Box::new(SqlBackend::new()),
+        Box::new(ShellBackend::new()),
+        Box::new(MakefileBackend::new()),
+    ];
+
+    #[cfg(feature = "typescript")]
+    {
+        backends.push(Box::new(TypeScriptBackend::new()));
+        backends.push(Box::new(TypeScriptBackend::javascript()));
+    }
+
+    #[cfg(feature = "go")]
+    backends.push(Box::new(GoBackend::new()));
+
+    #[cfg(feature = "c")]
+    {
+        backends.push(Box::new(CHeaderBackend::new()));
+        backends.push(Box::new(CHeaderBackend::cpp()));
+    }
+
+    backends
+}
@@ -0,0 +1,245 @@
+//! Languages defined at runtime by a tree-sitter grammar plus a query.
+//!
+//! Each query pattern describes one kind of entity through its capture names:
+//!
+//! - `@entity.<type>`: the definition node; `<type>` becomes `entity_type`
+//! - `@name`: the entity name (required)
+//! - `@doc`: doc comment nodes, cleaned and joined in order
+//! - `@parameter`: one capture per parameter; repeat a group with the
+//!   separator, as in `((parameter) ","?)*`, since anonymous nodes between
+//!   siblings otherwise end the repetition after the first one
+//! - `@return_type`, `@decorator`: copied as text
+//! - `@async`, `@internal`: flags set when the capture is present
+//!
+//! Other captures are ignored, so they can be used freely in predicates.
+//!
+//! ```scheme
+//! ((comment)* @doc
+//!  .
+//!  (function_declaration
+//!     name: (identifier) @name
+//!     parameters: (parameter_list ((parameter_declaration name: (identifier) @parameter) ","?)*)) @entity.function)
+//! ```
+
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use tree_sitter::{Language, Query, QueryCursor};
+
+use crate::backends::syntax::{self, clean_block_comment, end_line, header, line, text};
+use crate::backends::{has_extension, LanguageBackend};
+use crate::config::QueryLanguage;
+use crate::entity::CodeEntity;
+
+/// Capture-name prefix marking the definition node of an entity
+const ENTITY_CAPTURE: &str = "entity.";
+
+/// A backend whose extraction rules live in a tree-sitter query
+pub struct QueryBackend {
+    name: String,
+    extensions: Vec<String>,
+    language: Language,
+    query: Query,
+}
+
+impl QueryBackend {
+    /// Define a language from a grammar and query source
+    pub fn new(name: &str, language: Language, query_source: &str, extensions: &[&str]) -> Result<Self> {
+        let query = Query::new(&language, query_source)
+            .map_err(|e| anyhow::anyhow!("Invalid query for {}: {}", name, e))?;
+
+        if !query.capture_names().iter().any(|c| c.starts_with(ENTITY_CAPTURE)) {
+            return Err(anyhow::anyhow!("Query for {} has no @{}<type> capture", name, ENTITY_CAPTURE));
+        }
+        if !query.capture_names().contains(&"name") {
+            return Err(anyhow::anyhow!("Query for {} has no @name capture", name));
+        }
+
+        Ok(QueryBackend {
+            name: name.to_string(),
+            extensions: extensions.iter().map(|e| e.trim_start_matches('.').to_string()).collect(),
+            language,
+            query,
+        })
+    }
+
+    /// Define a language from a grammar and a `.scm` query file
+    pub fn from_file(name: &str, language: Language, query_path: &Path, extensions: &[&str]) -> Result<Self> {
+        let source = fs::read_to_string(query_path)
+            .with_context(|| format!("Failed to read query file: {:?}", query_path))?;
+        QueryBackend::new(name, language, &source, extensions)
+    }
+
+    /// Define a language from an `AnalyzerConfig::query_languages` entry
+    pub fn from_config(config: &QueryLanguage) -> Result<Self> {
+        let language = grammar(&config.grammar).ok_or_else(|| {
+            anyhow::anyhow!("Unknown grammar for {}: {} (not built into this binary?)", config.name, config.grammar)
+        })?;
+        let extensions: Vec<&str> = config.extensions.iter().map(String::as_str).collect();
+        match (&config.query, &config.query_file) {
+            (Some(query), _) => QueryBackend::new(&config.name, language, query, &extensions),
+            (None, Some(path)) => QueryBackend::from_file(&config.name, language, path, &extensions),
+            (None, None) => Err(anyhow::anyhow!("Query language {} has neither query nor query_file", config.name)),
+        }
+    }
+}
+
+/// The bundled tree-sitter grammar called `name`, if its feature is enabled
+pub fn grammar(name: &str) -> Option<Language> {
+    type Grammar = fn() -> Language;
+    let grammars: &[(&str, Grammar)] = &[
+        #[cfg(feature = "go")]
+        ("go", tree_sitter_go::language),
+        #[cfg(feature = "typescript")]
+        ("typescript", tree_sitter_typescript::language_typescript),
+        #[cfg(feature = "typescript")]
+        ("tsx", tree_sitter_typescript::language_tsx),
+        #[cfg(feature = "typescript")]
+        ("javascript", tree_sitter_javascript::language),
+        #[cfg(feature = "c")]
+        ("c", tree_sitter_c::language),
+        #[cfg(feature = "c")]
+        ("cpp", tree_sitter_cpp::language),
+    ];
+    grammars
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(name))
+        .map(|(_, language)| language())
+}
+
+impl LanguageBackend for QueryBackend {
+    fn language(&self) -> &str {
+        &self.name
+    }
+
+    fn handles(&self, path: &Path) -> bool {
+        has_extension(path, &self.extensions)
+    }
+
+    fn parse_source(&self, source: &str, file_path: &Path) -> Result<Vec<CodeEntity>> {
+        let tree = syntax::parse(&self.language, source, file_path)?;
+        let capture_names = self.query.capture_names();
+        let mut cursor = QueryCursor::new();
+        let mut seen = HashSet::new();
+        let mut entities = Vec::new();
+
+        for query_match in cursor.matches(&self.query, tree.root_node(), source.as_bytes()) {
+            let mut definition = None;
+            let mut name = None;
+            let mut docs = Vec::new();
+            let mut entity = CodeEntity::new(String::new(), String::new(), file_path.to_path_buf(), 0);
+
+            for capture in query_match.captures {
+                let node = capture.node;
+                match capture_names[capture.index as usize] {
+                    capture_name if capture_name.starts_with(ENTITY_CAPTURE) => {
+                        entity.entity_type = capture_name[ENTITY_CAPTURE.len()..].into();
+                        definition = Some(node);
+                    }
+                    "name" => name = Some(text(node, source)),
+                    "doc" => docs.push(text(node, source)),
+                    "parameter" => entity.parameters.push(text(node, source).to_string()),
+                    "return_type" => entity.return_type = Some(text(node, source).to_string()),
+                    "decorator" => entity.decorators.push(text(node, source).into()),
+                    "async" => entity.is_async = true,
+                    "internal" => entity.is_internal = true,
+                    _ => {}
+                }
+            }
+
+            let (Some(definition), Some(name)) = (definition, name) else { continue };
+            // Overlapping patterns may match the same definition more than once
+            if !seen.insert((definition.id(), entity.entity_type.clone())) {
+                continue;
+            }
+
+            entity.name = name.to_string();
+            entity.line_number = line(definition);
+            entity.end_line = end_line(definition);
+            entity.code = header(definition, source);
+            entity.docstring = clean_doc(&docs);
+            entity.calculate_complexity();
+            entities.push(entity);
+        }
+
+        entities.sort_by_key(|e| e.line_number);
+        Ok(entities)
+    }
+}
+
+/// Turn captured comment nodes into a docstring, whatever the comment syntax
+fn clean_doc(comments: &[&str]) -> Option<String> {
+    let doc = comments
+        .iter()
+        .map(|comment| {
+            if comment.starts_with("/*") {
+                clean_block_comment(comment)
+            } else {
+                let stripped = comment.trim().trim_start_matches(['/', '#', '-', ';', '!']);
+                stripped.strip_prefix(' ').unwrap_or(stripped).to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let doc = doc.trim();
+    (!doc.is_empty()).then(|| doc.to_string())
+}
+
+#[cfg(all(test, feature = "go"))]
+mod tests {
+    use super::*;
+
+    const QUERY: &str = r#"
+((comment)* @doc
+ .
+ (function_declaration
+    name: (identifier) @name
+    parameters: (parameter_list ((parameter_declaration name: (identifier) @parameter) ","?)*)
+    result: (_)? @return_type) @entity.function)
+
+(type_spec name: (type_identifier) @name type: (struct_type)) @entity.struct
+"#;
+
+    #[test]
+    fn test_query_backend_extracts_captures() {
+        let backend = QueryBackend::new("golang", tree_sitter_go::language(), QUERY, &[".go"]).unwrap();
+        assert!(backend.handles(Path::new("main.go")));
+
+        let source = "package main\n\n// Add sums two ints.\nfunc Add(a int, b int) int { return a + b }\n\ntype Point struct{ X int }\n";
+        let entities = backend.parse_source(source, Path::new("main.go")).unwrap();
+
+        assert_eq!(entities.len(), 2);
+        assert_eq!(entities[0].entity_type, "function");
+        assert_eq!(entities[0].name, "Add");
+        assert_eq!(entities[0].parameters, vec!["a", "b"]);
+        assert_eq!(entities[0].return_type.as_deref(), Some("int"));
+        assert_eq!(entities[0].docstring.as_deref(), Some("Add sums two ints."));
+        assert_eq!(entities[1].entity_type, "struct");
+        assert_eq!(entities[1].name, "Point");
+    }
+
+    #[test]
+    fn test_query_backend_from_config() {
+        let config = QueryLanguage {
+            name: "golang".to_string(),
+            grammar: "go".to_string(),
+            extensions: vec!["go".to_string()],
+            query: Some(QUERY.to_string()),
+            query_file: None,
+        };
+        let backend = QueryBackend::from_config(&config).unwrap();
+        assert_eq!(backend.language(), "golang");
+        assert!(backend.handles(Path::new("main.go")));
+
+        let unknown = QueryLanguage { grammar: "cobol".to_string(), ..config };
+        assert!(QueryBackend::from_config(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_query_without_entity_capture_is_rejected() {
+        let result = QueryBackend::new("golang", tree_sitter_go::language(), "(identifier) @name", &["go"]);
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,339 @@
+use anyhow::Result;
+use proc_macro2::Span;
+use quote::ToTokens;
+use std::path::Path;
+use syn::spanned::Spanned;
+use syn::{Attribute, Expr, FnArg, ImplItem, Item, Lit, Meta, ReturnType, Signature, TraitItem, Visibility};
+
+use crate::backends::{has_extension, LanguageBackend};
+use crate::diagnostic::Diagnostic;
+use crate::entity::CodeEntity;
+use crate::intern::Symbol;
+
+/// Backend for Rust sources built on `syn`.
+///
+/// Extracts functions, structs, enums, traits, impl blocks and their
+/// methods. Doc comments become docstrings and other attributes are
+/// recorded as decorators.
+pub struct RustBackend;
+
+impl RustBackend {
+    pub fn new() -> Self {
+        RustBackend
+    }
+}
+
+impl LanguageBackend for RustBackend {
+    fn language(&self) -> &str {
+        "rust"
+    }
+
+    fn handles(&self, path: &Path) -> bool {
+        has_extension(path, &["rs"])
+    }
+
+    fn parse_source(&self, source: &str, file_path: &Path) -> Result<Vec<CodeEntity>> {
+        let file = syn::parse_file(source).map_err(|e| {
+            let start = e.span().start();
+            Diagnostic::new(file_path, source, start.line, start.column + 1, e.to_string())
+        })?;
+        log::trace!("Parsed {:?} ({} top-level items)", file_path, file.items.len());
+
+        let mut visitor = ItemVisitor {
+            file_path,
+            entities: Vec::new(),
+        };
+        visitor.visit_items(&file.items, false);
+        Ok(visitor.entities)
+    }
+}
+
+/// Walks items, treating everything inside a non-`pub` module as internal
+struct ItemVisitor<'a> {
+    file_path: &'a Path,
+    entities: Vec<CodeEntity>,
+}
+
+impl ItemVisitor<'_> {
+    fn visit_items(&mut self, items: &[Item], hidden: bool) {
+        for item in items {
+            self.visit_item(item, hidden);
+        }
+    }
+
+    fn visit_item(&mut self, item: &Item, hidden: bool) {
+        match item {
+            Item::Fn(func) => {
+                let mut entity = self.entity("function", func.sig.ident.to_string(), func.sig.ident.span(), func.span(), &func.attrs);
+                entity.is_internal = hidden || !is_pub(&func.vis);
+                fill_signature(&mut entity, &func.sig, &func.vis);
+                self.entities.push(entity);
+            }
+            Item::Struct(item) => {
+                let mut entity = self.entity("struct", item.ident.to_string(), item.ident.span(), item.span(), &item.attrs);
+                entity.is_internal = hidden || !is_pub(&item.vis);
+                entity.code = format!("{}struct {}{}", vis_prefix(&item.vis), item.ident, tokens(&item.generics));
+                entity.parameters = item
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, field)| field.ident.as_ref().map_or_else(|| i.to_string(), |id| id.to_string()))
+                    .collect();
+                self.entities.push(entity);
+            }
+            Item::Enum(item) => {
+                let mut entity = self.entity("enum", item.ident.to_string(), item.ident.span(), item.span(), &item.attrs);
+                entity.is_internal = hidden || !is_pub(&item.vis);
+                entity.code = format!("{}enum {}{}", vis_prefix(&item.vis), item.ident, tokens(&item.generics));
+                entity.parameters = item.variants.iter().map(|v| v.ident.to_string()).collect();
+                self.entities.push(entity);
+            }
+            Item::Trait(item) => {
+                let internal = hidden || !is_pub(&item.vis);
+                let mut entity = self.entity("trait", item.ident.to_string(), item.ident.span(), item.span(), &item.attrs);
+                entity.is_internal = internal;
+                entity.code = format!("{}trait {}{}", vis_prefix(&item.vis), item.ident, tokens(&item.generics));
+                self.entities.push(entity);
+
+                for member in &item.items {
+                    if let TraitItem::Fn(method) = member {
+                        let mut entity = self.entity("method", method.sig.ident.to_string(), method.sig.ident.span(), method.span(), &method.attrs);
+                        entity.is_internal = internal;
+                        fill_signature(&mut entity, &method.sig, &Visibility::Inherited);
+                        self.entities.push(entity);
+                    }
+                }
+            }
+            Item::Impl(item) => {
+                let self_ty = tokens(&item.self_ty);
+                let name = match &item.trait_ {
+                    Some((negated, path, _)) => {
+                        format!("{}{} for {}", if negated.is_some() { "!" } else { "" }, tokens(path), self_ty)
+                    }
+                    None => self_ty,
+                };
+                let mut entity = self.entity("impl", name.clone(), item.impl_token.span, item.span(), &item.attrs);
+                entity.is_internal = hidden;
+                entity.code = format!("impl{} {}", tokens(&item.generics), name);
+                self.entities.push(entity);
+
+                for member in &item.items {
+                    if let ImplItem::Fn(method) = member {
+                        let mut entity = self.entity("method", method.sig.ident.to_string(), method.sig.ident.span(), method.span(), &method.attrs);
+                        // Trait impl methods are as visible as the trait itself
+                        entity.is_internal = hidden || (item.trait_.is_none() && !is_pub(&method.vis));
+                        fill_signature(&mut entity, &method.sig, &method.vis);
+                        self.entities.push(entity);
+                    }
+                }
+            }
+            Item::Mod(module) => {
+                if let Some((_, items)) = &module.content {
+                    self.visit_items(items, hidden || !is_pub(&module.vis));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Entity starting at the line of `span` and ending where `item` ends
+    fn entity(&self, entity_type: &str, name: String, span: Span, item: Span, attrs: &[Attribute]) -> CodeEntity {
+        let mut entity = CodeEntity::new(
+            entity_type.to_string(),
+            name,
+            self.file_path.to_path_buf(),
+            span.start().line,
+        );
+        entity.end_line = item.end().line;
+        entity.docstring = doc_comment(attrs);
+        entity.decorators = attrs
+            .iter()
+            .filter(|a| !a.path().is_ident("doc"))
+            .map(|a| Symbol::from(tokens(&a.meta)))
+            .collect();
+        entity
+    }
+}
+
+/// Fill in parameters, return type and signature of a function or method
+fn fill_signature(entity: &mut CodeEntity, sig: &Signature, vis: &Visibility) {
+    entity.is_async = sig.asyncness.is_some();
+    entity.parameters = sig
+        .inputs
+        .iter()
+        .map(|arg| match arg {
+            FnArg::Receiver(receiver) => tokens(receiver),
+            FnArg::Typed(typed) => tokens(&typed.pat),
+        })
+        .collect();
+    entity.return_type = match &sig.output {
+        ReturnType::Default => None,
+        ReturnType::Type(_, ty) => Some(tokens(ty)),
+    };
+    entity.code = format!("{}{}", vis_prefix(vis), tokens(sig));
+    entity.calculate_complexity();
+}
+
+/// Join `///` and `#[doc = "..."]` attributes into a single docstring
+fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(nv) if nv.path.is_ident("doc") => match &nv.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(s) => Some(s.value()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+
+    if lines.is_empty() {
+        return None;
+    }
+
+    let doc = lines
+        .iter()
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n");
+    Some(doc.trim().to_string())
+}
+
+fn is_pub(vis: &Visibility) -> bool {
+    matches!(vis, Visibility::Public(_))
+}
+
+fn vis_prefix(vis: &Visibility) -> String {
+    match vis {
+        Visibility::Inherited => String::new(),
+        _ => format!("{} ", tokens(vis)),
+    }
+}
+
+/// Render a syntax node as source text
+fn tokens<T: ToTokens>(node: &T) -> String {
+    tidy(&node.to_token_stream().to_string())
+}
+
+/// Remove the spaces `TokenStream::to_string` puts between every token,
+/// e.g. `Option < & str >` becomes `Option<&str>`
+fn tidy(rendered: &str) -> String {
+    let chars: Vec<char> = rendered.chars().collect();
+    let mut out = String::with_capacity(rendered.len());
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c == ' ' {
+            let prev = out.chars().last();
+            let next = chars.get(i + 1).copied();
+            let before_closing = matches!(next, Some(',' | ')' | ']' | '>' | ';' | ':' | '?'));
+            let after_opening = matches!(prev, Some('(' | '[' | '<' | '&' | '#')) || out.ends_with("::");
+            let opens_group = matches!(next, Some('(' | '<' | '['))
+                && prev.map_or(false, |p| p.is_alphanumeric() || p == '_' || p == '>')
+                && !out.ends_with("->");
+            if before_closing || after_opening || opens_group {
+                continue;
+            }
+        }
+        out.push(c);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+/// A user account.
+#[derive(Debug, Clone)]
+pub struct User {
+    pub id: u32,
+    name: String,
+}
+
+/// Account state.
+pub enum State { Active, Locked }
+
+/// Something that can be saved.
+pub trait Store {
+    /// Persist the value.
+    fn save(&self, user: &User) -> std::io::Result<()>;
+}
+
+impl User {
+    /// Create a user.
+    pub fn new(id: u32, name: &str) -> Self {
+        User { id, name: name.to_string() }
+    }
+
+    fn secret(&self) {}
+}
+
+pub async fn fetch(ids: Vec<u32>) -> Option<&'static str> {
+    None
+}
+
+mod private {
+    pub fn hidden() {}
+}
+"#;
+
+    fn parse() -> Vec<CodeEntity> {
+        RustBackend::new().parse_source(SOURCE, Path::new("src/lib.rs")).unwrap()
+    }
+
+    #[test]
+    fn test_extracts_items() {
+        let entities = parse();
+        let kinds: Vec<(&str, &str)> = entities
+            .iter()
+            .map(|e| (e.entity_type.as_str(), e.name.as_str()))
+            .collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                ("struct", "User"),
+                ("enum", "State"),
+                ("trait", "Store"),
+                ("method", "save"),
+                ("impl", "User"),
+                ("method", "new"),
+                ("method", "secret"),
+                ("function", "fetch"),
+                ("function", "hidden"),
+            ]
+        );
+
+        let user = &entities[0];
+        assert_eq!(user.docstring.as_deref(), Some("A user account."));
+        assert_eq!(user.decorators, vec!["derive(Debug, Clone)"]);
+        assert_eq!(user.parameters, vec!["id", "name"]);
+        assert_eq!(user.line_number, 4);
+        assert_eq!(entities[1].parameters, vec!["Active", "Locked"]);
+    }
+
+    #[test]
+    fn test_signatures_and_visibility() {
+        let entities = parse();
+        let find = |name: &str| entities.iter().find(|e| e.name == name).unwrap();
+
+        let new = find("new");
+        assert_eq!(new.code, "pub fn new(id: u32, name: &str) -> Self");
+        assert_eq!(new.parameters, vec!["id", "name"]);
+        assert_eq!(new.return_type.as_deref(), Some("Self"));
+        assert!(!new.is_internal);
+
+        let fetch = find("fetch");
+        assert!(fetch.is_async);
+        assert_eq!(fetch.return_type.as_deref(), Some("Option<&'static str>"));
+
+        assert_eq!(find("save").parameters, vec!["&self", "user"]);
+        assert!(find("secret").is_internal);
+        assert!(find("hidden").is_internal);
+    }
+}
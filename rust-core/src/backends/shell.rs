@@ -0,0 +1,268 @@
+use anyhow::Result;
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::backends::{has_extension, LanguageBackend};
+use crate::entity::CodeEntity;
+
+/// Backend for shell scripts.
+///
+/// The header comment block becomes a `script` entity for the file; each
+/// function becomes a `function` entity documented by the comments above it.
+pub struct ShellBackend;
+
+impl ShellBackend {
+    pub fn new() -> Self {
+        ShellBackend
+    }
+}
+
+impl LanguageBackend for ShellBackend {
+    fn language(&self) -> &str {
+        "shell"
+    }
+
+    fn handles(&self, path: &Path) -> bool {
+        has_extension(path, &["sh", "bash", "zsh"])
+    }
+
+    fn parse_source(&self, source: &str, file_path: &Path) -> Result<Vec<CodeEntity>> {
+        let lines: Vec<&str> = source.lines().collect();
+        let mut entities = Vec::new();
+
+        let file_name = file_path.file_name().map_or_else(|| file_path.to_string_lossy(), |n| n.to_string_lossy());
+        let mut script = CodeEntity::new("script".to_string(), file_name.into_owned(), file_path.to_path_buf(), 1);
+        script.code = lines.first().filter(|l| l.starts_with("#!")).map(|l| l.to_string()).unwrap_or_default();
+        script.docstring = header_comment(&lines);
+        entities.push(script);
+
+        for (i, line) in lines.iter().enumerate() {
+            let Some(caps) = shell_function().captures(line) else { continue };
+            let name = caps.get(1).or_else(|| caps.get(2)).map_or("", |m| m.as_str());
+
+            let mut entity = CodeEntity::new("function".to_string(), name.to_string(), file_path.to_path_buf(), i + 1);
+            entity.code = line.trim().trim_end_matches('{').trim().to_string();
+            entity.docstring = comments_above(&lines, i, "#");
+            entity.parameters = positional_parameters(function_body(&lines, i));
+            entity.is_internal = name.starts_with('_');
+            entity.calculate_complexity();
+            entities.push(entity);
+        }
+
+        Ok(entities)
+    }
+}
+
+/// Backend for Makefiles: targets documented with `##` (the `make help` convention)
+pub struct MakefileBackend;
+
+impl MakefileBackend {
+    pub fn new() -> Self {
+        MakefileBackend
+    }
+}
+
+impl LanguageBackend for MakefileBackend {
+    fn language(&self) -> &str {
+        "make"
+    }
+
+    fn handles(&self, path: &Path) -> bool {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        matches!(name, "Makefile" | "makefile" | "GNUmakefile") || has_extension(path, &["mk"])
+    }
+
+    fn parse_source(&self, source: &str, file_path: &Path) -> Result<Vec<CodeEntity>> {
+        let lines: Vec<&str> = source.lines().collect();
+        let mut entities = Vec::new();
+
+        for (i, line) in lines.iter().enumerate() {
+            let Some((targets, prerequisites, inline_doc)) = parse_rule(line) else { continue };
+            let doc = inline_doc
+                .map(|d| d.trim().to_string())
+                .filter(|d| !d.is_empty())
+                .or_else(|| comments_above(&lines, i, "##"));
+            let Some(doc) = doc else { continue };
+
+            let prerequisites: Vec<String> = prerequisites.split_whitespace().map(str::to_string).collect();
+            for target in targets.split_whitespace() {
+                let mut entity = CodeEntity::new("target".to_string(), target.to_string(), file_path.to_path_buf(), i + 1);
+                entity.docstring = Some(doc.clone());
+                entity.parameters = prerequisites.clone();
+                entity.code = line.split("##").next().unwrap_or(line).trim().to_string();
+                entities.push(entity);
+            }
+        }
+
+        Ok(entities)
+    }
+}
+
+/// Split a rule line into targets, prerequisites and an inline `##` comment.
+///
+/// Recipes, special targets such as `.PHONY` and variable assignments are skipped.
+fn parse_rule(line: &str) -> Option<(&str, &str, Option<&str>)> {
+    if line.starts_with(['\t', ' ', '.', '#']) {
+        return None;
+    }
+    let (targets, rest) = line.split_once(':')?;
+    // `::` double-colon rules; `:=` and `::=` assignments
+    let rest = rest.strip_prefix(':').unwrap_or(rest);
+    if rest.starts_with('=') || targets.contains(['=', '$']) {
+        return None;
+    }
+
+    let (prerequisites, doc) = match rest.split_once("##") {
+        Some((prerequisites, doc)) => (prerequisites, Some(doc)),
+        None => (rest, None),
+    };
+    let prerequisites = prerequisites.split('#').next().unwrap_or_default();
+    // Target-specific variables: `build: CFLAGS = -O2`
+    if prerequisites.contains('=') {
+        return None;
+    }
+    Some((targets.trim(), prerequisites, doc))
+}
+
+/// The comment block at the top of a script, after the shebang
+fn header_comment(lines: &[&str]) -> Option<String> {
+    let body: Vec<&str> = lines
+        .iter()
+        .skip_while(|l| l.starts_with("#!"))
+        .map(|l| l.trim())
+        .take_while(|l| l.starts_with('#'))
+        .filter(|l| !l.starts_with("# shellcheck"))
+        .collect();
+    clean_comments(&body, "#")
+}
+
+/// Comment lines directly above line index `index`, without their `marker`
+fn comments_above(lines: &[&str], index: usize, marker: &str) -> Option<String> {
+    let mut block: Vec<&str> = lines[..index]
+        .iter()
+        .rev()
+        .map(|l| l.trim())
+        .take_while(|l| l.starts_with(marker))
+        .collect();
+    block.reverse();
+    clean_comments(&block, marker)
+}
+
+fn clean_comments(block: &[&str], marker: &str) -> Option<String> {
+    let doc = block
+        .iter()
+        .map(|l| {
+            let l = l.trim_start_matches(marker);
+            l.strip_prefix(' ').unwrap_or(l)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let doc = doc.trim();
+    (!doc.is_empty()).then(|| doc.to_string())
+}
+
+/// Lines of the function starting at `start`, up to its closing brace
+fn function_body<'a>(lines: &'a [&'a str], start: usize) -> &'a [&'a str] {
+    let indent = lines[start].len() - lines[start].trim_start().len();
+    let end = lines[start + 1..]
+        .iter()
+        .position(|l| l.trim() == "}" && l.len() - l.trim_start().len() <= indent)
+        .map_or(lines.len(), |offset| start + 1 + offset + 1);
+    &lines[start..end]
+}
+
+/// Positional parameters a function reads, named after `local x="$1"` style assignments
+fn positional_parameters(body: &[&str]) -> Vec<String> {
+    let mut positions = BTreeSet::new();
+    let mut names = std::collections::BTreeMap::new();
+
+    for line in body {
+        for caps in positional().captures_iter(line) {
+            let position: usize = caps[1].parse().unwrap_or(0);
+            if position > 0 {
+                positions.insert(position);
+            }
+        }
+        if let Some(caps) = named_positional().captures(line) {
+            if let Ok(position) = caps[2].parse::<usize>() {
+                names.entry(position).or_insert_with(|| caps[1].to_string());
+            }
+        }
+    }
+
+    positions
+        .into_iter()
+        .map(|p| names.get(&p).cloned().unwrap_or_else(|| format!("${}", p)))
+        .collect()
+}
+
+fn shell_function() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"^\s*(?:function\s+([A-Za-z_][\w:.-]*)\s*(?:\(\s*\))?|([A-Za-z_][\w:.-]*)\s*\(\s*\))\s*\{?\s*$").unwrap()
+    })
+}
+
+fn positional() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\$\{?([1-9])").unwrap())
+}
+
+fn named_positional() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r#"(?:local|readonly|declare)?\s*([A-Za-z_]\w*)=["']?\$\{?([1-9])\b"#).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_functions_and_header() {
+        let source = r#"#!/usr/bin/env bash
+# Deploy the service to a target environment.
+# Usage: deploy.sh <env>
+
+set -euo pipefail
+
+# Push the image and roll the deployment.
+deploy() {
+    local env="$1"
+    kubectl rollout restart "deployment/$2" --context "$env"
+}
+
+function _log {
+    echo "$*"
+}
+"#;
+        let entities = ShellBackend::new().parse_source(source, Path::new("scripts/deploy.sh")).unwrap();
+        assert_eq!(entities.len(), 3);
+
+        assert_eq!(entities[0].entity_type, "script");
+        assert_eq!(entities[0].name, "deploy.sh");
+        assert_eq!(entities[0].docstring.as_deref(), Some("Deploy the service to a target environment.\nUsage: deploy.sh <env>"));
+
+        let deploy = &entities[1];
+        assert_eq!(deploy.name, "deploy");
+        assert_eq!(deploy.line_number, 8);
+        assert_eq!(deploy.docstring.as_deref(), Some("Push the image and roll the deployment."));
+        assert_eq!(deploy.parameters, vec!["env", "$2"]);
+
+        assert_eq!(entities[2].name, "_log");
+        assert!(entities[2].is_internal);
+    }
+
+    #[test]
+    fn test_makefile_documented_targets() {
+        let source = "VERSION := 1.0\n\n.PHONY: build test\n\nbuild: deps ## Build the release binary\n\tcargo build --release\n\n## Run the test suite\ntest:\n\tcargo test\n\nclean:\n\trm -rf target\n";
+        let entities = MakefileBackend::new().parse_source(source, Path::new("Makefile")).unwrap();
+
+        let names: Vec<&str> = entities.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["build", "test"]);
+        assert_eq!(entities[0].docstring.as_deref(), Some("Build the release binary"));
+        assert_eq!(entities[0].parameters, vec!["deps"]);
+        assert_eq!(entities[1].docstring.as_deref(), Some("Run the test suite"));
+    }
+}
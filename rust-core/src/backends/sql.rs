@@ -0,0 +1,315 @@
+use anyhow::Result;
+use regex::Regex;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::backends::{has_extension, LanguageBackend};
+use crate::entity::CodeEntity;
+use crate::schema::table_entity;
+
+/// Constraint clauses that may appear among column definitions
+const TABLE_CONSTRAINTS: [&str; 7] = ["PRIMARY", "FOREIGN", "UNIQUE", "CHECK", "CONSTRAINT", "INDEX", "KEY"];
+
+/// Backend for SQL schema and migration files.
+///
+/// Every `CREATE TABLE` and `ALTER TABLE ... ADD COLUMN` becomes a `schema`
+/// entity; `--` comments directly above a statement become its docstring,
+/// and `COMMENT ON TABLE` that of the `CREATE TABLE`.
+pub struct SqlBackend;
+
+impl SqlBackend {
+    pub fn new() -> Self {
+        SqlBackend
+    }
+}
+
+impl LanguageBackend for SqlBackend {
+    fn language(&self) -> &str {
+        "sql"
+    }
+
+    fn handles(&self, path: &Path) -> bool {
+        has_extension(path, &["sql"])
+    }
+
+    fn parse_source(&self, source: &str, file_path: &Path) -> Result<Vec<CodeEntity>> {
+        let mut entities: Vec<CodeEntity> = Vec::new();
+
+        for statement in split_statements(source) {
+            if let Some(caps) = create_table().captures(&statement.text) {
+                let columns = split_top_level(&caps[2])
+                    .into_iter()
+                    .filter_map(|definition| column(&definition))
+                    .collect::<Vec<_>>();
+                let code = format!("CREATE TABLE {}", &caps[1]);
+                let mut entity = table_entity(&caps[1], &columns, code, file_path, statement.line);
+                entity.docstring = statement.doc;
+                entities.push(entity);
+            } else if let Some(caps) = alter_table().captures(&statement.text) {
+                let columns: Vec<(String, String)> = add_column()
+                    .captures_iter(&caps[2])
+                    .filter(|c| !c[1].eq_ignore_ascii_case("CONSTRAINT"))
+                    .map(|c| (unquote(&c[1]), c[2].to_string()))
+                    .collect();
+                if columns.is_empty() {
+                    continue;
+                }
+                let code = format!("ALTER TABLE {}", &caps[1]);
+                let mut entity = table_entity(&caps[1], &columns, code, file_path, statement.line);
+                entity.docstring = statement.doc;
+                entity.metadata.insert("operation".to_string(), "alter".to_string());
+                entities.push(entity);
+            } else if let Some(caps) = comment_on_table().captures(&statement.text) {
+                // The comment describes the table, so it goes on its CREATE
+                // TABLE; only files that merely alter it fall back to that
+                let table = crate::schema::normalize_table(&caps[1]);
+                let of_table = |e: &CodeEntity| e.metadata.get("table") == Some(&table);
+                let target = match entities.iter().position(|e| of_table(e) && !e.metadata.contains_key("operation")) {
+                    Some(created) => Some(created),
+                    None => entities.iter().rposition(of_table),
+                };
+                if let Some(index) = target {
+                    entities[index].docstring = Some(caps[2].replace("''", "'"));
+                }
+            }
+        }
+
+        Ok(entities)
+    }
+}
+
+/// A statement with the line it starts on and the comments directly above it
+struct Statement {
+    text: String,
+    line: usize,
+    doc: Option<String>,
+}
+
+/// Split SQL into statements on `;`, ignoring semicolons in strings and comments
+fn split_statements(source: &str) -> Vec<Statement> {
+    let code = mask_comments(source);
+    let lines: Vec<&str> = source.lines().collect();
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut line = 1;
+    let mut start_line = None;
+
+    let mut flush = |current: &mut String, start_line: &mut Option<usize>| {
+        if let Some(start) = start_line.take() {
+            statements.push(Statement {
+                text: std::mem::take(current),
+                line: start,
+                doc: comments_above(&lines, start),
+            });
+        }
+        current.clear();
+    };
+
+    for c in code.chars() {
+        if c == '\n' {
+            line += 1;
+        }
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if matches!(c, '\'' | '"' | '`') => quote = Some(c),
+            None if c == ';' => {
+                flush(&mut current, &mut start_line);
+                continue;
+            }
+            None => {}
+        }
+        if !c.is_whitespace() {
+            start_line.get_or_insert(line);
+        }
+        current.push(c);
+    }
+    flush(&mut current, &mut start_line);
+
+    statements
+}
+
+/// Replace `--` and `/* */` comments with spaces, keeping line breaks
+fn mask_comments(source: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    let mut quote: Option<char> = None;
+
+    while let Some(c) = chars.next() {
+        if let Some(q) = quote {
+            if c == q {
+                quote = None;
+            }
+            out.push(c);
+            continue;
+        }
+
+        match c {
+            '\'' | '"' | '`' => {
+                quote = Some(c);
+                out.push(c);
+            }
+            '-' if chars.peek() == Some(&'-') => {
+                while chars.next_if(|&n| n != '\n').is_some() {}
+                out.push(' ');
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        out.push('\n');
+                    }
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                out.push(' ');
+            }
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// `--` comment lines directly above the 1-based `line`
+fn comments_above(lines: &[&str], line: usize) -> Option<String> {
+    let mut doc: Vec<&str> = lines[..line.saturating_sub(1).min(lines.len())]
+        .iter()
+        .rev()
+        .map(|l| l.trim())
+        .take_while(|l| l.starts_with("--"))
+        .map(|l| l.trim_start_matches('-').trim())
+        .collect();
+    doc.reverse();
+
+    let doc = doc.join("\n").trim().to_string();
+    (!doc.is_empty()).then_some(doc)
+}
+
+/// Split a column list on commas outside parentheses
+fn split_top_level(body: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut current = String::new();
+
+    for c in body.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    parts.push(current);
+
+    parts.into_iter().map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect()
+}
+
+/// Parse `name TYPE ...` into the column name and its type, skipping table constraints
+fn column(definition: &str) -> Option<(String, String)> {
+    let (name, rest) = definition.split_once(char::is_whitespace).unwrap_or((definition, ""));
+    if TABLE_CONSTRAINTS.iter().any(|k| name.eq_ignore_ascii_case(k)) {
+        return None;
+    }
+
+    // The type runs to the first space outside parentheses: NUMERIC(10, 2)
+    let mut depth = 0usize;
+    let rest = rest.trim_start();
+    let end = rest
+        .char_indices()
+        .find(|&(_, c)| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            c.is_whitespace() && depth == 0
+        })
+        .map_or(rest.len(), |(i, _)| i);
+    Some((unquote(name), rest[..end].to_string()))
+}
+
+fn unquote(name: &str) -> String {
+    name.trim_matches(|c| matches!(c, '"' | '`' | '[' | ']')).to_string()
+}
+
+fn create_table() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r#"(?is)^\s*CREATE\s+(?:OR\s+REPLACE\s+)?(?:(?:GLOBAL|LOCAL)\s+)?(?:(?:TEMP|TEMPORARY|UNLOGGED)\s+)?TABLE\s+(?:IF\s+NOT\s+EXISTS\s+)?([\w."`\[\]]+)\s*\((.*)\)"#,
+        )
+        .unwrap()
+    })
+}
+
+fn alter_table() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r#"(?is)^\s*ALTER\s+TABLE\s+(?:IF\s+EXISTS\s+)?(?:ONLY\s+)?([\w."`\[\]]+)\s+(.*)$"#).unwrap()
+    })
+}
+
+fn add_column() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r#"(?i)\bADD\s+(?:COLUMN\s+)?(?:IF\s+NOT\s+EXISTS\s+)?([\w"`\[\]]+)\s+([\w()]+(?:\s*\(\s*\d+(?:\s*,\s*\d+)?\s*\))?)"#)
+            .unwrap()
+    })
+}
+
+fn comment_on_table() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r#"(?is)^\s*COMMENT\s+ON\s+TABLE\s+([\w."`\[\]]+)\s+IS\s+'((?:[^']|'')*)'"#).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+-- Registered accounts.
+CREATE TABLE IF NOT EXISTS public.users (
+    id SERIAL PRIMARY KEY,
+    email VARCHAR(255) NOT NULL UNIQUE, -- login; never shown
+    balance NUMERIC(10, 2) DEFAULT 0,
+    CONSTRAINT email_lower CHECK (email = lower(email))
+);
+
+-- unrelated note
+
+ALTER TABLE users ADD COLUMN created_at TIMESTAMP, ADD CONSTRAINT x UNIQUE (email);
+COMMENT ON TABLE users IS 'People who can log in; it''s the core table';
+"#;
+
+    #[test]
+    fn test_create_and_alter_table() {
+        let entities = SqlBackend::new().parse_source(SOURCE, Path::new("schema.sql")).unwrap();
+        assert_eq!(entities.len(), 2);
+
+        let users = &entities[0];
+        assert_eq!(users.entity_type, "schema");
+        assert_eq!(users.name, "public.users");
+        assert_eq!(users.line_number, 3);
+        assert_eq!(users.parameters, vec!["id", "email", "balance"]);
+        assert_eq!(users.metadata["columns"], "id SERIAL, email VARCHAR(255), balance NUMERIC(10, 2)");
+        assert_eq!(users.docstring.as_deref(), Some("People who can log in; it's the core table"));
+
+        let altered = &entities[1];
+        assert_eq!(altered.parameters, vec!["created_at"]);
+        assert_eq!(altered.metadata["operation"], "alter");
+        assert!(altered.docstring.is_none());
+
+        let migration = "ALTER TABLE users ADD COLUMN age INT;\nCOMMENT ON TABLE users IS 'Accounts';\n";
+        let entities = SqlBackend::new().parse_source(migration, Path::new("002.sql")).unwrap();
+        assert_eq!(entities[0].docstring.as_deref(), Some("Accounts"));
+    }
+}
@@ -0,0 +1,139 @@
+//! Helpers shared by the tree-sitter based backends
+
+use anyhow::Result;
+use std::path::Path;
+use tree_sitter::{Language, Node, Parser, Tree};
+
+/// Parse `source` with `language`; tree-sitter recovers from syntax errors,
+/// so a tree is returned even for partially broken files
+pub fn parse(language: &Language, source: &str, file_path: &Path) -> Result<Tree> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(language)
+        .map_err(|e| anyhow::anyhow!("Incompatible grammar: {}", e))?;
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| anyhow::anyhow!("Parse error: parser produced no tree"))?;
+
+    if tree.root_node().has_error() {
+        log::debug!("{:?} contains syntax errors; extracting what parsed", file_path);
+    }
+    Ok(tree)
+}
+
+/// Source text covered by `node`
+pub fn text<'a>(node: Node, source: &'a str) -> &'a str {
+    &source[node.byte_range()]
+}
+
+/// 1-based line on which `node` starts
+pub fn line(node: Node) -> usize {
+    node.start_position().row + 1
+}
+
+/// 1-based line on which `node` ends
+pub fn end_line(node: Node) -> usize {
+    node.end_position().row + 1
+}
+
+/// Text of a named field, if present
+pub fn field<'a>(node: Node, name: &str, source: &'a str) -> Option<&'a str> {
+    node.child_by_field_name(name).map(|n| text(n, source))
+}
+
+/// All children of `node`, including anonymous tokens
+pub fn children<'t>(node: Node<'t>) -> impl Iterator<Item = Node<'t>> + 't {
+    (0..node.child_count()).filter_map(move |i| node.child(i))
+}
+
+/// Named children of `node`
+pub fn named_children<'t>(node: Node<'t>) -> impl Iterator<Item = Node<'t>> + 't {
+    (0..node.named_child_count()).filter_map(move |i| node.named_child(i))
+}
+
+/// The declaration header: everything before the `body` field, or the whole node
+pub fn header(node: Node, source: &str) -> String {
+    header_from(node.start_byte(), node, source)
+}
+
+/// Source between byte `start` and the start of `node`'s `body` field,
+/// with whitespace collapsed
+pub fn header_from(start: usize, node: Node, source: &str) -> String {
+    let end = node
+        .child_by_field_name("body")
+        .map_or(node.end_byte(), |body| body.start_byte());
+    collapse_whitespace(&source[start.min(end)..end])
+}
+
+/// Comment nodes directly above `node` with no blank line in between,
+/// in source order
+pub fn leading_comments<'a>(node: Node, source: &'a str) -> Vec<&'a str> {
+    let mut comments = Vec::new();
+    let mut next_row = node.start_position().row;
+    let mut current = node.prev_sibling();
+
+    while let Some(sibling) = current {
+        if sibling.kind() != "comment" || sibling.end_position().row + 1 < next_row {
+            break;
+        }
+        comments.push(text(sibling, source));
+        next_row = sibling.start_position().row;
+        current = sibling.prev_sibling();
+    }
+
+    comments.reverse();
+    comments
+}
+
+/// Strip `/** ... */` delimiters and leading `*` gutters from a block comment
+pub fn clean_block_comment(comment: &str) -> String {
+    let inner = comment
+        .trim()
+        .trim_start_matches("/**")
+        .trim_start_matches("/*")
+        .trim_end_matches("*/");
+
+    inner
+        .lines()
+        .map(|l| {
+            let l = l.trim();
+            l.strip_prefix("* ").or_else(|| l.strip_prefix('*')).unwrap_or(l)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Strip a line-comment `marker` (e.g. `//`) from each line and join them
+pub fn clean_line_comments(lines: &[&str], marker: &str) -> String {
+    lines
+        .iter()
+        .map(|l| {
+            let l = l.trim().trim_start_matches(marker);
+            l.strip_prefix(' ').unwrap_or(l)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Remove surrounding quotes from a string literal
+pub fn unquote(literal: &str) -> &str {
+    literal.trim_matches(|c| c == '"' || c == '\'' || c == '`')
+}
+
+/// Join a router prefix and a route path into `/prefix/path`
+pub fn join_route(prefix: &str, path: &str) -> String {
+    let segments: Vec<&str> = [prefix, path]
+        .iter()
+        .map(|s| s.trim_matches('/'))
+        .filter(|s| !s.is_empty())
+        .collect();
+    format!("/{}", segments.join("/"))
+}
+
+fn collapse_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,392 @@
+use anyhow::Result;
+use std::path::Path;
+use tree_sitter::{Language, Node};
+
+use crate::backends::syntax::{
+    self, children, clean_block_comment, field, header, header_from, join_route, line, named_children, text, unquote,
+};
+use crate::backends::{has_extension, LanguageBackend};
+use crate::entity::CodeEntity;
+use crate::intern::Symbol;
+
+/// Route-registering method names shared by Express and NestJS
+const HTTP_VERBS: [&str; 8] = ["get", "post", "put", "delete", "patch", "options", "head", "all"];
+
+/// Backend for TypeScript and JavaScript sources built on tree-sitter.
+///
+/// Extracts functions (including `const f = () => ...`), classes and their
+/// methods, interfaces, JSDoc comments, NestJS route decorators and
+/// Express-style `app.get("/path", handler)` registrations.
+pub struct TypeScriptBackend {
+    language: &'static str,
+    extensions: &'static [&'static str],
+}
+
+impl TypeScriptBackend {
+    /// Backend for `.ts`/`.tsx` files, enabled as `typescript`
+    pub fn new() -> Self {
+        TypeScriptBackend {
+            language: "typescript",
+            extensions: &["ts", "tsx", "mts", "cts"],
+        }
+    }
+
+    /// Backend for `.js`/`.jsx` files, enabled as `javascript`
+    pub fn javascript() -> Self {
+        TypeScriptBackend {
+            language: "javascript",
+            extensions: &["js", "jsx", "mjs", "cjs"],
+        }
+    }
+}
+
+impl LanguageBackend for TypeScriptBackend {
+    fn language(&self) -> &str {
+        self.language
+    }
+
+    fn handles(&self, path: &Path) -> bool {
+        has_extension(path, self.extensions)
+    }
+
+    fn parse_source(&self, source: &str, file_path: &Path) -> Result<Vec<CodeEntity>> {
+        let tree = syntax::parse(&grammar(file_path), source, file_path)?;
+        let mut visitor = ScriptVisitor {
+            source,
+            file_path,
+            entities: Vec::new(),
+        };
+
+        for statement in named_children(tree.root_node()) {
+            visitor.visit_statement(statement, None);
+        }
+        Ok(visitor.entities)
+    }
+}
+
+fn grammar(path: &Path) -> Language {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("ts" | "mts" | "cts") => tree_sitter_typescript::language_typescript(),
+        Some("tsx") => tree_sitter_typescript::language_tsx(),
+        _ => tree_sitter_javascript::language(),
+    }
+}
+
+struct ScriptVisitor<'a> {
+    source: &'a str,
+    file_path: &'a Path,
+    entities: Vec<CodeEntity>,
+}
+
+impl ScriptVisitor<'_> {
+    /// Visit a top-level statement; `export` is the wrapping `export` statement, if any
+    fn visit_statement(&mut self, node: Node, export: Option<Node>) {
+        match node.kind() {
+            "export_statement" => {
+                if let Some(declaration) = node.child_by_field_name("declaration") {
+                    self.visit_statement(declaration, Some(node));
+                }
+            }
+            "function_declaration" | "generator_function_declaration" => {
+                let Some(name) = field(node, "name", self.source) else { return };
+                let mut entity = self.entity("function", name, node, export);
+                self.fill_function(&mut entity, node);
+                entity.code = header(node, self.source);
+                entity.calculate_complexity();
+                self.entities.push(entity);
+            }
+            "lexical_declaration" | "variable_declaration" => {
+                for declarator in named_children(node).filter(|n| n.kind() == "variable_declarator") {
+                    let Some(value) = declarator.child_by_field_name("value") else { continue };
+                    if !matches!(value.kind(), "arrow_function" | "function_expression" | "function") {
+                        continue;
+                    }
+                    let Some(name) = field(declarator, "name", self.source) else { continue };
+                    let mut entity = self.entity("function", name, node, export);
+                    self.fill_function(&mut entity, value);
+                    entity.code = header_from(node.start_byte(), value, self.source);
+                    entity.calculate_complexity();
+                    self.entities.push(entity);
+                }
+            }
+            "class_declaration" | "abstract_class_declaration" => self.visit_class(node, export),
+            "interface_declaration" => {
+                let Some(name) = field(node, "name", self.source) else { return };
+                let mut entity = self.entity("interface", name, node, export);
+                entity.code = header(node, self.source);
+                if let Some(body) = node.child_by_field_name("body") {
+                    entity.parameters = named_children(body)
+                        .filter_map(|member| field(member, "name", self.source))
+                        .map(str::to_string)
+                        .collect();
+                }
+                self.entities.push(entity);
+            }
+            "expression_statement" if export.is_none() => self.visit_route(node),
+            _ => {}
+        }
+    }
+
+    fn visit_class(&mut self, node: Node, export: Option<Node>) {
+        let Some(name) = field(node, "name", self.source) else { return };
+        let mut class = self.entity("class", name, node, export);
+
+        let decorators: Vec<Node> = export.into_iter().chain([node]).flat_map(own_decorators).collect();
+        class.decorators = decorators.iter().map(|d| Symbol::from(decorator_text(*d, self.source))).collect();
+        let prefix = decorators
+            .iter()
+            .find(|d| decorator_name(**d, self.source) == "Controller")
+            .map(|d| decorator_argument(*d, self.source).unwrap_or_default());
+
+        let start = children(node).find(|c| c.kind() != "decorator").map_or(node.start_byte(), |c| c.start_byte());
+        class.code = header_from(start, node, self.source);
+        let class_internal = class.is_internal;
+        self.entities.push(class);
+
+        let Some(body) = node.child_by_field_name("body") else { return };
+        for member in named_children(body) {
+            if !matches!(member.kind(), "method_definition" | "method_signature" | "abstract_method_signature") {
+                continue;
+            }
+            let Some(name) = field(member, "name", self.source) else { continue };
+
+            let mut method = self.entity("method", name, member, None);
+            let private = children(member)
+                .any(|c| c.kind() == "accessibility_modifier" && text(c, self.source) != "public");
+            method.is_internal = class_internal || private || name.starts_with('#');
+            self.fill_function(&mut method, member);
+
+            // TS puts member decorators before the member, JS inside it
+            let decorators: Vec<Node> = preceding_decorators(member).into_iter().chain(own_decorators(member)).collect();
+            method.decorators = decorators.iter().map(|d| Symbol::from(decorator_text(*d, self.source))).collect();
+            let start = children(member).find(|c| c.kind() != "decorator").map_or(member.start_byte(), |c| c.start_byte());
+            method.code = header_from(start, member, self.source);
+            method.calculate_complexity();
+
+            if let Some(prefix) = &prefix {
+                for decorator in &decorators {
+                    let verb = decorator_name(*decorator, self.source);
+                    if HTTP_VERBS.contains(&verb.to_lowercase().as_str()) {
+                        method.is_api_endpoint = true;
+                        method.http_methods = vec![verb.to_uppercase()];
+                        method.endpoint_path = Some(join_route(prefix, &decorator_argument(*decorator, self.source).unwrap_or_default()));
+                    }
+                }
+            }
+
+            self.entities.push(method);
+        }
+    }
+
+    /// Record `app.get("/path", handler)` style registrations as `route` entities
+    fn visit_route(&mut self, node: Node) {
+        let Some(call) = node.named_child(0).filter(|n| n.kind() == "call_expression") else { return };
+        let Some(callee) = call.child_by_field_name("function").filter(|n| n.kind() == "member_expression") else { return };
+        let Some(verb) = field(callee, "property", self.source) else { return };
+        if !HTTP_VERBS.contains(&verb) {
+            return;
+        }
+
+        // `app.get("setting")` reads configuration; routes also pass a handler
+        let Some(arguments) = call.child_by_field_name("arguments") else { return };
+        let args: Vec<Node> = named_children(arguments).filter(|a| a.kind() != "comment").collect();
+        let path = match args.first() {
+            Some(first) if args.len() >= 2 && matches!(first.kind(), "string" | "template_string") => {
+                unquote(text(*first, self.source))
+            }
+            _ => return,
+        };
+        if !path.starts_with('/') {
+            return;
+        }
+
+        let method = verb.to_uppercase();
+        let mut entity = self.entity("route", &format!("{} {}", method, path), node, None);
+        entity.is_internal = false;
+        entity.is_api_endpoint = true;
+        entity.endpoint_path = Some(path.to_string());
+        entity.http_methods = vec![method];
+        entity.code = text(node, self.source).lines().next().unwrap_or_default().trim().to_string();
+        if let Some(handler) = args.last().filter(|a| a.kind() == "identifier") {
+            entity.parameters = vec![text(*handler, self.source).to_string()];
+        }
+        self.entities.push(entity);
+    }
+
+    fn entity(&self, entity_type: &str, name: &str, node: Node, export: Option<Node>) -> CodeEntity {
+        let mut entity = CodeEntity::new(
+            entity_type.to_string(),
+            name.to_string(),
+            self.file_path.to_path_buf(),
+            line(node),
+        );
+        entity.end_line = syntax::end_line(node);
+        entity.docstring = jsdoc(export.unwrap_or(node), self.source);
+        entity.is_internal = export.is_none();
+        entity
+    }
+
+    fn fill_function(&self, entity: &mut CodeEntity, func: Node) {
+        entity.is_async = children(func).any(|c| c.kind() == "async");
+        entity.parameters = parameters(func, self.source);
+        entity.return_type = field(func, "return_type", self.source)
+            .map(|t| t.trim_start_matches(':').trim().to_string());
+    }
+}
+
+fn parameters(func: Node, source: &str) -> Vec<String> {
+    // Arrow functions with a single unparenthesized parameter
+    if let Some(single) = func.child_by_field_name("parameter") {
+        return vec![text(single, source).to_string()];
+    }
+    let Some(params) = func.child_by_field_name("parameters") else { return Vec::new() };
+
+    named_children(params)
+        .filter(|p| p.kind() != "comment")
+        .map(|p| {
+            let name = p
+                .child_by_field_name("pattern")
+                .or_else(|| p.child_by_field_name("left"))
+                .unwrap_or(p);
+            text(name, source).to_string()
+        })
+        .collect()
+}
+
+/// The `/** ... */` comment directly above `node`, skipping decorators
+fn jsdoc(node: Node, source: &str) -> Option<String> {
+    let mut previous = node.prev_sibling();
+    while let Some(sibling) = previous.filter(|s| s.kind() == "decorator") {
+        previous = sibling.prev_sibling();
+    }
+
+    let comment = text(previous.filter(|p| p.kind() == "comment")?, source);
+    comment.starts_with("/**").then(|| clean_block_comment(comment))
+}
+
+fn own_decorators(node: Node) -> Vec<Node> {
+    children(node).filter(|c| c.kind() == "decorator").collect()
+}
+
+fn preceding_decorators(node: Node) -> Vec<Node> {
+    let mut decorators = Vec::new();
+    let mut previous = node.prev_named_sibling();
+    while let Some(sibling) = previous.filter(|s| s.kind() == "decorator") {
+        decorators.push(sibling);
+        previous = sibling.prev_named_sibling();
+    }
+    decorators.reverse();
+    decorators
+}
+
+/// Decorator source without the leading `@`, e.g. `Get(':id')`
+fn decorator_text(decorator: Node, source: &str) -> String {
+    text(decorator, source).trim_start_matches('@').split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Called name of a decorator: `Get` for `@Get(':id')`
+fn decorator_name<'a>(decorator: Node, source: &'a str) -> &'a str {
+    match decorator.named_child(0) {
+        Some(call) if call.kind() == "call_expression" => field(call, "function", source).unwrap_or_default(),
+        Some(expr) => text(expr, source),
+        None => "",
+    }
+}
+
+/// First string argument of a decorator call
+fn decorator_argument(decorator: Node, source: &str) -> Option<String> {
+    let call = decorator.named_child(0).filter(|c| c.kind() == "call_expression")?;
+    let first = call.child_by_field_name("arguments")?.named_child(0)?;
+    matches!(first.kind(), "string" | "template_string").then(|| unquote(text(first, source)).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typescript_entities() {
+        let source = r#"
+/** A registered user. */
+export interface User {
+  id: number;
+  name?: string;
+}
+
+/**
+ * Load a user by id.
+ * @param id the user id
+ */
+export async function loadUser(id: number, cache = true): Promise<User> {
+  return db.find(id);
+}
+
+const helper = (x: number) => x * 2;
+
+@Controller('users')
+export class UsersController {
+  /** List every user. */
+  @Get()
+  findAll(): User[] { return []; }
+
+  @Post(':id/reset')
+  reset(@Param('id') id: string) {}
+
+  private audit() {}
+}
+"#;
+        let entities = TypeScriptBackend::new().parse_source(source, Path::new("src/users.ts")).unwrap();
+        let find = |name: &str| entities.iter().find(|e| e.name == name).unwrap();
+
+        let user = find("User");
+        assert_eq!(user.entity_type, "interface");
+        assert_eq!(user.parameters, vec!["id", "name"]);
+        assert_eq!(user.docstring.as_deref(), Some("A registered user."));
+
+        let load = find("loadUser");
+        assert!(load.is_async && !load.is_internal);
+        assert_eq!(load.parameters, vec!["id", "cache"]);
+        assert_eq!(load.return_type.as_deref(), Some("Promise<User>"));
+        assert_eq!(load.docstring.as_deref(), Some("Load a user by id.\n@param id the user id"));
+        assert_eq!(load.line_number, 12);
+
+        assert!(find("helper").is_internal);
+
+        let controller = find("UsersController");
+        assert_eq!(controller.decorators, vec!["Controller('users')"]);
+        assert_eq!(controller.code, "class UsersController");
+
+        let find_all = find("findAll");
+        assert_eq!(find_all.docstring.as_deref(), Some("List every user."));
+        assert_eq!(find_all.endpoint_path.as_deref(), Some("/users"));
+        assert_eq!(find_all.http_methods, vec!["GET"]);
+
+        let reset = find("reset");
+        assert_eq!(reset.endpoint_path.as_deref(), Some("/users/:id/reset"));
+        assert_eq!(reset.http_methods, vec!["POST"]);
+
+        assert!(find("audit").is_internal);
+    }
+
+    #[test]
+    fn test_express_routes() {
+        let source = r#"
+const app = express();
+app.set('port', 3000);
+app.get('env');
+
+// List orders.
+app.get('/orders', listOrders);
+router.post("/orders/:id", async (req, res) => {});
+"#;
+        let entities = TypeScriptBackend::javascript().parse_source(source, Path::new("server.js")).unwrap();
+        let routes: Vec<&CodeEntity> = entities.iter().filter(|e| e.entity_type == "route").collect();
+
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].name, "GET /orders");
+        assert_eq!(routes[0].parameters, vec!["listOrders"]);
+        assert!(routes[0].is_api_endpoint);
+        assert_eq!(routes[1].http_methods, vec!["POST"]);
+        assert_eq!(routes[1].endpoint_path.as_deref(), Some("/orders/:id"));
+    }
+}
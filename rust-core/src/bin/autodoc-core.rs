@@ -0,0 +1,551 @@
+// Standalone command-line interface for the Autodoc Rust core
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use autodoc_core::analyzer::RustAnalyzer;
+use autodoc_core::changelog;
+use autodoc_core::config::AnalyzerConfig;
+use autodoc_core::diff::{self, ApiDiff};
+use autodoc_core::docs::DocsIndex;
+use autodoc_core::endpoints;
+use autodoc_core::entity::CodeEntity;
+use autodoc_core::export::{self, ExportFormat};
+use autodoc_core::git;
+use autodoc_core::incremental::IncrementalAnalyzer;
+use autodoc_core::lint::{self, Severity};
+use autodoc_core::metrics::{self, ProjectMetrics};
+use autodoc_core::query::Query;
+use autodoc_core::semver::{self, Bump, SemverReport};
+use autodoc_core::server::{ApiServer, ServerState};
+use autodoc_core::watch::FileWatcher;
+
+#[derive(Parser)]
+#[command(name = "autodoc-core", version, about = "Analyze Python codebases without a Python runtime")]
+struct Cli {
+    /// Path to an autodoc.toml file (defaults to <path>/autodoc.toml when present)
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Additional exclude patterns
+    #[arg(long = "exclude", global = true)]
+    excludes: Vec<String>,
+
+    /// Annotate entities with their last commit, author and date from git blame
+    #[arg(long, global = true)]
+    blame: bool,
+
+    /// Mask likely secrets (keys, tokens, passwords) in extracted code and docstrings
+    #[arg(long, global = true)]
+    redact: bool,
+
+    /// Number of worker threads (default: one per CPU)
+    #[arg(long, global = true)]
+    threads: Option<usize>,
+
+    /// Share one worker pool per thread count across analyzers
+    #[arg(long, global = true)]
+    shared_pool: bool,
+
+    /// Reuse per-file results stored in this directory across runs
+    #[arg(long, global = true)]
+    cache_dir: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Extract entities and print them
+    Analyze {
+        path: PathBuf,
+        /// Print entities as JSON instead of a summary
+        #[arg(long)]
+        json: bool,
+        /// Only keep entities matching a query, e.g. "type:function is:public complexity:>10"
+        #[arg(long, short)]
+        query: Option<Query>,
+    },
+    /// Print project metrics
+    Metrics {
+        path: PathBuf,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Report docstring coverage per package, failing below a threshold
+    Coverage {
+        path: PathBuf,
+        /// Minimum overall coverage in percent (defaults to min_docstring_coverage from the config)
+        #[arg(long)]
+        min: Option<f64>,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check documentation quality against the configured lint rules
+    Lint {
+        path: PathBuf,
+        #[arg(long)]
+        json: bool,
+        /// Exit with status 1 on findings at or above this severity (info, warning, error)
+        #[arg(long, default_value = "error")]
+        fail_on: Severity,
+    },
+    /// Link Markdown/RST documentation to the code it mentions
+    Docs {
+        path: PathBuf,
+        #[arg(long)]
+        json: bool,
+    },
+    /// List detected API endpoints
+    Endpoints {
+        path: PathBuf,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Write entities to a file as JSON, Markdown, SQLite, embedding JSONL, objects.inv or site JSON
+    Export {
+        path: PathBuf,
+        /// Output format: json, markdown, sqlite, embeddings, inventory or site
+        #[arg(long, short, default_value = "json")]
+        format: ExportFormat,
+        /// Output file
+        #[arg(long, short)]
+        output: PathBuf,
+    },
+    /// Compare the public API of two directories or git revisions
+    Diff {
+        old: String,
+        new: String,
+        /// Repository used to resolve git revisions
+        #[arg(long, default_value = ".")]
+        repo: PathBuf,
+        #[arg(long)]
+        json: bool,
+        /// Exit with status 1 when there are changes
+        #[arg(long)]
+        exit_code: bool,
+        /// Exit with status 1 when the changes require at least this bump (major, minor, patch)
+        #[arg(long)]
+        fail_on: Option<Bump>,
+        /// Print a Markdown changelog section with this title instead of the change list
+        #[arg(long, value_name = "TITLE")]
+        changelog: Option<String>,
+        /// Base URL (e.g. a repository blob URL) for changelog entity links
+        #[arg(long)]
+        link_base: Option<String>,
+    },
+    /// Serve entities, metrics and search over HTTP, refreshed on file changes
+    Serve {
+        path: PathBuf,
+        #[arg(long, default_value_t = 7878)]
+        port: u16,
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+    },
+    /// Re-render Markdown and JSON docs whenever sources change
+    Watch {
+        path: PathBuf,
+        /// Output directory for api.md and entities.json
+        #[arg(long, default_value = "docs")]
+        out: PathBuf,
+        /// Milliseconds to wait for a burst of changes to settle
+        #[arg(long, default_value_t = 200)]
+        debounce_ms: u64,
+    },
+}
+
+impl Command {
+    /// Directory to discover `autodoc.toml` in; for diffs, the new side when
+    /// it's a directory and otherwise the repository its revisions come from
+    fn path(&self) -> &Path {
+        match self {
+            Command::Analyze { path, .. }
+            | Command::Metrics { path, .. }
+            | Command::Coverage { path, .. }
+            | Command::Lint { path, .. }
+            | Command::Docs { path, .. }
+            | Command::Endpoints { path, .. }
+            | Command::Export { path, .. }
+            | Command::Serve { path, .. }
+            | Command::Watch { path, .. } => path,
+            Command::Diff { new, .. } if Path::new(new).is_dir() => Path::new(new),
+            Command::Diff { repo, .. } => repo,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    env_logger::init();
+
+    match run(Cli::parse()) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<ExitCode> {
+    let config = load_config(&cli)?;
+    let analyzer = RustAnalyzer::try_with_config(config.clone())?;
+    
+    match &cli.command {
+        Command::Watch { path, out, debounce_ms } => {
+            return watch(analyzer, path, out, Duration::from_millis(*debounce_ms));
+        }
+        Command::Serve { path, port, host } => {
+            return serve(analyzer, &config, path, &format!("{}:{}", host, port));
+        }
+        Command::Diff { old, new, repo, json, exit_code, fail_on, changelog, link_base } => {
+            let old_entities = analyze_side(&analyzer, repo, old)?;
+            let new_entities = analyze_side(&analyzer, repo, new)?;
+            let api_diff = diff::diff_entities(&old_entities, &new_entities);
+            let report = semver::classify_diff(&api_diff);
+            
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&api_diff)?);
+            } else if let Some(title) = changelog {
+                print!("{}", changelog::render_changelog(&api_diff, title, link_base.as_deref()));
+            } else {
+                print_diff(&api_diff, &report);
+            }
+            
+            let failed = (*exit_code && !api_diff.is_empty())
+                || fail_on.map_or(false, |level| report.recommended >= Some(level));
+            return Ok(if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS });
+        }
+        _ => {}
+    }
+    
+    let entities = analyze_path(&analyzer, cli.command.path())?;
+
+    match cli.command {
+        Command::Analyze { json, query, .. } => {
+            let entities: Vec<CodeEntity> = match query {
+                Some(query) => analyzer.install(|| query.filter(&entities).into_iter().cloned().collect()),
+                None => entities,
+            };
+            if json {
+                println!("{}", export::to_json(&entities)?);
+            } else {
+                print_summary(&entities);
+            }
+        }
+        Command::Metrics { json, .. } => {
+            let metrics = ProjectMetrics::compute(&entities, &config);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&metrics)?);
+            } else {
+                print_metrics(&metrics);
+            }
+        }
+        Command::Coverage { path, min, json } => {
+            let (rows, overall) = metrics::coverage_by_package(&entities, &path);
+            let min = min.unwrap_or(config.min_docstring_coverage);
+            
+            if json {
+                let report = serde_json::json!({
+                    "packages": rows,
+                    "total": overall,
+                    "min": min,
+                    "passed": overall.coverage >= min,
+                });
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                println!("{:<40} {:>10} {:>7} {:>9}", "Package", "Documented", "Total", "Coverage");
+                for row in rows.iter().chain(std::iter::once(&overall)) {
+                    println!(
+                        "{:<40} {:>10} {:>7} {:>8.1}%",
+                        row.package, row.documented, row.total, row.coverage,
+                    );
+                }
+            }
+            
+            if overall.coverage < min {
+                eprintln!("Docstring coverage {:.1}% is below the required {:.1}%", overall.coverage, min);
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Command::Lint { json, fail_on, .. } => {
+            let report = lint::lint(&entities, &config)?;
+            
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                for finding in &report.findings {
+                    let entity = &entities[finding.index];
+                    println!(
+                        "{}:{}: {} [{}] {}",
+                        entity.file_path.display(),
+                        entity.line_number,
+                        finding.severity.as_str(),
+                        finding.rule.as_str(),
+                        finding.message,
+                    );
+                }
+                println!(
+                    "\n{} errors, {} warnings, {} info, {} suppressed",
+                    report.count(Severity::Error),
+                    report.count(Severity::Warning),
+                    report.count(Severity::Info),
+                    report.suppressed.len(),
+                );
+            }
+            
+            if !report.passes(fail_on) {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Command::Docs { path, json } => {
+            let index = DocsIndex::build(&path, &analyzer)?;
+            let links = index.link(&entities);
+            
+            if json {
+                let report = serde_json::json!({ "pages": index.pages, "links": links });
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                for page in &index.pages {
+                    println!(
+                        "{}  {} ({} headings, {} references)",
+                        page.path.display(),
+                        page.title().unwrap_or("untitled"),
+                        page.headings.len(),
+                        page.references.len(),
+                    );
+                }
+                for stale in &links.stale {
+                    println!("stale: {}:{}  `{}`", stale.page.display(), stale.line, stale.target);
+                }
+                println!(
+                    "\n{} pages, {} entities referenced, {} stale references, {} public entities never mentioned",
+                    index.pages.len(),
+                    links.documented.len(),
+                    links.stale.len(),
+                    links.unreferenced.len(),
+                );
+            }
+        }
+        Command::Endpoints { json, .. } => {
+            if json {
+                let endpoints: Vec<&CodeEntity> = entities.iter().filter(|e| e.is_api_endpoint).collect();
+                println!("{}", serde_json::to_string_pretty(&endpoints)?);
+            } else {
+                let groups = endpoints::by_version(&entities);
+                let versioned = groups.iter().any(|(version, _)| version.is_some());
+                for (version, endpoints) in groups {
+                    if versioned {
+                        println!("[{}]", version.as_deref().unwrap_or("unversioned"));
+                    }
+                    for endpoint in endpoints {
+                        println!(
+                            "{:<8} {:<40} {} ({}:{})",
+                            endpoint.http_methods.join(","),
+                            endpoint.endpoint_path.as_deref().unwrap_or("?"),
+                            endpoint.name,
+                            endpoint.file_path.display(),
+                            endpoint.line_number,
+                        );
+                    }
+                }
+            }
+        }
+        Command::Export { format, output, .. } => {
+            export::export_to_file(&entities, format, &output)?;
+            eprintln!("Wrote {} entities to {}", entities.len(), output.display());
+        }
+        Command::Watch { .. } | Command::Diff { .. } | Command::Serve { .. } => {
+            unreachable!("handled before the initial analysis")
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn watch(analyzer: RustAnalyzer, root: &Path, out: &Path, debounce: Duration) -> Result<ExitCode> {
+    let mut incremental = IncrementalAnalyzer::new(analyzer);
+    let watcher = FileWatcher::new(root, debounce)?;
+    
+    rebuild(&mut incremental, root, out)?;
+    eprintln!("Watching {} for changes (Ctrl+C to stop)", root.display());
+    
+    loop {
+        let changed = watcher.next_batch(|p| incremental.analyzer().is_source_file(p))?;
+        eprintln!("{} file(s) changed", changed.len());
+        if let Err(e) = rebuild(&mut incremental, root, out) {
+            eprintln!("error: {:#}", e);
+        }
+    }
+}
+
+fn serve(analyzer: RustAnalyzer, config: &AnalyzerConfig, root: &Path, addr: &str) -> Result<ExitCode> {
+    let mut incremental = IncrementalAnalyzer::new(analyzer);
+    let summary = incremental.refresh(root)?;
+    eprintln!("Indexed {}", summary);
+    
+    let server = ApiServer::new(ServerState::new(incremental.entities(), config));
+    let watcher = FileWatcher::new(root, Duration::from_millis(200))?;
+    
+    // Keep the served snapshot current while the HTTP loop runs
+    let live = server.clone();
+    let config = config.clone();
+    let root = root.to_path_buf();
+    std::thread::spawn(move || loop {
+        let changed = match watcher.next_batch(|p| incremental.analyzer().is_source_file(p)) {
+            Ok(changed) => changed,
+            Err(e) => {
+                eprintln!("error: {:#}", e);
+                return;
+            }
+        };
+        match incremental.refresh(&root) {
+            Ok(summary) => {
+                live.update(ServerState::new(incremental.entities(), &config));
+                eprintln!("{} file(s) changed, reindexed {}", changed.len(), summary);
+            }
+            Err(e) => eprintln!("error: {:#}", e),
+        }
+    });
+    
+    eprintln!("Serving on http://{}", addr);
+    server.run(addr)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn rebuild(incremental: &mut IncrementalAnalyzer, root: &Path, out: &Path) -> Result<()> {
+    let summary = incremental.refresh(root)?;
+    let entities = incremental.entities();
+    
+    export::export_to_file(&entities, ExportFormat::Markdown, &out.join("api.md"))?;
+    export::export_to_file(&entities, ExportFormat::Json, &out.join("entities.json"))?;
+    
+    println!("Rebuilt {}: {}", out.display(), summary);
+    Ok(())
+}
+
+/// Analyze one side of a diff: a directory, or otherwise a git revision
+fn analyze_side(analyzer: &RustAnalyzer, repo: &Path, side: &str) -> Result<Vec<CodeEntity>> {
+    let path = Path::new(side);
+    if path.is_dir() {
+        let mut entities = analyze_path(analyzer, path)?;
+        diff::relativize(&mut entities, path);
+        Ok(entities)
+    } else if git::is_revision(repo, side) {
+        git::analyze_revision(analyzer, repo, side)
+    } else {
+        Err(anyhow::anyhow!("{} is neither a directory nor a git revision", side))
+    }
+}
+
+fn print_diff(api_diff: &ApiDiff, report: &SemverReport) {
+    for (change, classified) in api_diff.changes.iter().zip(&report.changes) {
+        println!(
+            "{} {:<8} {}  [{}: {}]",
+            change.kind.marker(),
+            change.entity_type,
+            change.key,
+            classified.bump.as_str(),
+            classified.reason,
+        );
+        if change.kind == diff::ChangeKind::SignatureChanged {
+            println!("    - {}", change.old_signature.as_deref().unwrap_or(""));
+            println!("    + {}", change.new_signature.as_deref().unwrap_or(""));
+        }
+    }
+    println!(
+        "\n{} added, {} removed, {} signature changes, {} docstring changes, {} visibility changes, {} deprecated, {} body changes",
+        api_diff.count(diff::ChangeKind::Added),
+        api_diff.count(diff::ChangeKind::Removed),
+        api_diff.count(diff::ChangeKind::SignatureChanged),
+        api_diff.count(diff::ChangeKind::DocstringChanged),
+        api_diff.count(diff::ChangeKind::VisibilityChanged),
+        api_diff.count(diff::ChangeKind::Deprecated),
+        api_diff.count(diff::ChangeKind::BodyChanged),
+    );
+    if let Some(bump) = report.recommended {
+        println!("Recommended version bump: {}", bump.as_str());
+    }
+}
+
+fn load_config(cli: &Cli) -> Result<AnalyzerConfig> {
+    let mut config = match &cli.config {
+        Some(path) => AnalyzerConfig::from_file(path)?,
+        None if cli.command.path().is_dir() => AnalyzerConfig::discover(cli.command.path())?,
+        None => AnalyzerConfig::default(),
+    };
+    config.exclude.extend(cli.excludes.iter().cloned());
+    config.git_blame |= cli.blame;
+    config.redact_secrets |= cli.redact;
+    config.shared_pool |= cli.shared_pool;
+    if cli.threads.is_some() {
+        config.threads = cli.threads;
+    }
+    if let Some(dir) = &cli.cache_dir {
+        config.cache_dir = Some(dir.clone());
+    }
+    Ok(config)
+}
+
+fn analyze_path(analyzer: &RustAnalyzer, path: &Path) -> Result<Vec<CodeEntity>> {
+    if path.is_dir() {
+        analyzer.analyze_directory(path)
+    } else {
+        analyzer.analyze_file(path)
+    }
+    .with_context(|| format!("Failed to analyze {}", path.display()))
+}
+
+fn print_summary(entities: &[CodeEntity]) {
+    for entity in entities {
+        println!(
+            "{}:{}  {:<8} {}",
+            entity.file_path.display(),
+            entity.line_number,
+            entity.entity_type,
+            entity.name,
+        );
+    }
+    println!("\n{} entities", entities.len());
+}
+
+fn print_metrics(metrics: &ProjectMetrics) {
+    println!("Files:               {}", metrics.files);
+    println!("Entities:            {}", metrics.entities);
+    println!("  Functions:         {}", metrics.functions);
+    println!("  Methods:           {}", metrics.methods);
+    println!("  Classes:           {}", metrics.classes);
+    println!("Async functions:     {}", metrics.async_functions);
+    println!("API endpoints:       {}", metrics.api_endpoints);
+    println!("Docstring coverage:  {:.1}%", metrics.docstring_coverage);
+    println!("Annotation coverage: {:.1}%", metrics.annotation_coverage);
+    println!("Average complexity:  {:.1}", metrics.average_complexity);
+    println!("Max complexity:      {}", metrics.max_complexity);
+    println!("Too complex:         {}", metrics.complex_entities);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_diff_of_revisions_reads_repo_config() {
+        let repo = TempDir::new().unwrap();
+        fs::write(repo.path().join("autodoc.toml"), "[analyzer]\ninclude = [\"src/**\"]\n").unwrap();
+        let repo_arg = repo.path().to_str().unwrap();
+
+        let cli = Cli::parse_from(["autodoc-core", "diff", "v1.0", "HEAD", "--repo", repo_arg]);
+        assert_eq!(cli.command.path(), repo.path());
+        assert_eq!(load_config(&cli).unwrap().include, vec!["src/**"]);
+
+        // A directory on the new side still wins
+        let new = TempDir::new().unwrap();
+        let cli = Cli::parse_from(["autodoc-core", "diff", "v1.0", new.path().to_str().unwrap(), "--repo", repo_arg]);
+        assert!(load_config(&cli).unwrap().include.is_empty());
+    }
+}
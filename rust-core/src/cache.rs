@@ -0,0 +1,195 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::AnalyzerConfig;
+use crate::entity::CodeEntity;
+use crate::identity::fnv1a;
+
+/// Entries are only read back by the crate version that wrote them
+const CACHE_VERSION: &str = crate::VERSION;
+
+/// Per-file analysis results persisted on disk, shared across processes.
+///
+/// Entries live under `<dir>/v<crate version>/` and are keyed by a hash of
+/// the file's path and contents, the analysis root and the analyzer
+/// configuration (with the contents of its query files), so an edited file,
+/// a changed option or query, or a crate upgrade misses instead of
+/// returning stale results. Entities are stored before post-processing
+/// hooks run, since hooks are arbitrary code, and before module paths,
+/// qualified names and ids are derived, since those depend on the
+/// `__init__.py` files around the file.
+pub struct DiskCache {
+    dir: PathBuf,
+    config_hash: u64,
+}
+
+impl DiskCache {
+    /// Open (creating if needed) the cache in `dir` for analyses run with `config`
+    pub fn open(dir: &Path, config: &AnalyzerConfig) -> Result<Self> {
+        let dir = dir.join(format!("v{}", CACHE_VERSION));
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create cache directory: {:?}", dir))?;
+
+        // Options that don't change per-file results must not split the cache
+        let relevant = AnalyzerConfig {
+            threads: None,
+            shared_pool: false,
+            lazy_code: false,
+            lint_severity: BTreeMap::new(),
+            lint_ignore: Vec::new(),
+            include_private: true,
+            include_dunder: true,
+            public_only: false,
+            cache_dir: None,
+            ..config.clone()
+        };
+        let mut config_bytes = serde_json::to_vec(&relevant).context("Failed to serialize analyzer config")?;
+        // The config only names query files, so editing one must change the key too
+        for path in config.query_languages.iter().filter(|l| l.query.is_none()).filter_map(|l| l.query_file.as_ref()) {
+            config_bytes.push(0);
+            config_bytes.extend(fs::read(path).unwrap_or_default());
+        }
+        Ok(DiskCache {
+            dir,
+            config_hash: fnv1a(&config_bytes),
+        })
+    }
+
+    /// Versioned directory holding the entries
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Key for `path` (analyzed relative to `root`) with the given contents
+    pub fn key(&self, path: &Path, root: Option<&Path>, contents: &[u8]) -> u64 {
+        let mut bytes = self.config_hash.to_le_bytes().to_vec();
+        bytes.extend_from_slice(path.to_string_lossy().as_bytes());
+        bytes.push(0);
+        bytes.extend_from_slice(root.map(|r| r.to_string_lossy()).unwrap_or_default().as_bytes());
+        bytes.push(0);
+        bytes.extend_from_slice(contents);
+        fnv1a(&bytes)
+    }
+
+    /// Cached entities for `key`; unreadable or corrupt entries count as misses
+    pub fn get(&self, key: u64) -> Option<Vec<CodeEntity>> {
+        let contents = fs::read(self.entry_path(key)).ok()?;
+        match serde_json::from_slice(&contents) {
+            Ok(entities) => Some(entities),
+            Err(e) => {
+                log::debug!("Ignoring corrupt cache entry {:016x}: {}", key, e);
+                None
+            }
+        }
+    }
+
+    /// Store entities under `key`. The entry is written to a temporary file
+    /// and renamed into place, so concurrent readers never see a partial entry.
+    pub fn put(&self, key: u64, entities: &[CodeEntity]) -> Result<()> {
+        let path = self.entry_path(key);
+        let temp = self.dir.join(format!("{:016x}.{}.tmp", key, std::process::id()));
+        let json = serde_json::to_vec(entities).context("Failed to serialize cache entry")?;
+        fs::write(&temp, json).with_context(|| format!("Failed to write cache entry: {:?}", temp))?;
+        fs::rename(&temp, &path).with_context(|| format!("Failed to write cache entry: {:?}", path))
+    }
+
+    fn entry_path(&self, key: u64) -> PathBuf {
+        self.dir.join(format!("{:016x}.json", key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::RustAnalyzer;
+    use crate::config::QueryLanguage;
+    use tempfile::TempDir;
+
+    fn entry_count(cache_dir: &Path) -> usize {
+        fs::read_dir(cache_dir.join(format!("v{}", CACHE_VERSION))).unwrap().count()
+    }
+
+    #[test]
+    fn test_cache_survives_analyzers_and_misses_on_edit() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache_dir = temp_dir.path().join("cache");
+        let file = temp_dir.path().join("app.py");
+        fs::write(&file, "def handler():\n    \"\"\"Handle it.\"\"\"\n").unwrap();
+        let config = AnalyzerConfig {
+            cache_dir: Some(cache_dir.clone()),
+            ..AnalyzerConfig::default()
+        };
+
+        let first = RustAnalyzer::with_config(config.clone()).analyze_file(&file).unwrap();
+        assert_eq!(entry_count(&cache_dir), 1);
+
+        let second = RustAnalyzer::with_config(config.clone()).analyze_file(&file).unwrap();
+        assert_eq!(entry_count(&cache_dir), 1);
+        assert_eq!(second[0].entity_id, first[0].entity_id);
+        assert_eq!(second[0].docstring.as_deref(), Some("Handle it."));
+
+        fs::write(&file, "def handler():\n    \"\"\"Handle it well.\"\"\"\n").unwrap();
+        let third = RustAnalyzer::with_config(config).analyze_file(&file).unwrap();
+        assert_eq!(entry_count(&cache_dir), 2);
+        assert_eq!(third[0].docstring.as_deref(), Some("Handle it well."));
+    }
+
+    #[test]
+    fn test_cached_entities_follow_package_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let package = temp_dir.path().join("pkg");
+        fs::create_dir(&package).unwrap();
+        let file = package.join("app.py");
+        fs::write(&file, "def handler():\n    pass\n").unwrap();
+        let config = AnalyzerConfig {
+            cache_dir: Some(temp_dir.path().join("cache")),
+            ..AnalyzerConfig::default()
+        };
+
+        let first = RustAnalyzer::with_config(config.clone()).analyze_file(&file).unwrap();
+        assert_eq!(first[0].qualified_name, "app.handler");
+
+        fs::write(package.join("__init__.py"), "").unwrap();
+        let second = RustAnalyzer::with_config(config).analyze_file(&file).unwrap();
+        assert_eq!(entry_count(&temp_dir.path().join("cache")), 1);
+        assert_eq!(second[0].module_path, "pkg.app");
+        assert_eq!(second[0].qualified_name, "pkg.app.handler");
+        assert_ne!(second[0].entity_id, first[0].entity_id);
+    }
+
+    #[test]
+    fn test_keys_depend_on_config_and_corrupt_entries_miss() {
+        let temp_dir = TempDir::new().unwrap();
+        let plain = DiskCache::open(temp_dir.path(), &AnalyzerConfig::default()).unwrap();
+        let redacting = AnalyzerConfig {
+            redact_secrets: true,
+            ..AnalyzerConfig::default()
+        };
+        let redacting = DiskCache::open(temp_dir.path(), &redacting).unwrap();
+
+        let path = Path::new("app.py");
+        assert_ne!(plain.key(path, None, b"x = 1"), redacting.key(path, None, b"x = 1"));
+        assert_ne!(plain.key(path, None, b"x = 1"), plain.key(path, None, b"x = 2"));
+
+        let query_file = temp_dir.path().join("go.scm");
+        fs::write(&query_file, "(function_declaration) @function").unwrap();
+        let querying = AnalyzerConfig {
+            query_languages: vec![QueryLanguage {
+                name: "golang".to_string(),
+                grammar: "go".to_string(),
+                extensions: vec!["go".to_string()],
+                query: None,
+                query_file: Some(query_file.clone()),
+            }],
+            ..AnalyzerConfig::default()
+        };
+        let before = DiskCache::open(temp_dir.path(), &querying).unwrap().key(path, None, b"x = 1");
+        fs::write(&query_file, "(method_declaration) @method").unwrap();
+        assert_ne!(DiskCache::open(temp_dir.path(), &querying).unwrap().key(path, None, b"x = 1"), before);
+
+        let key = plain.key(path, None, b"x = 1");
+        fs::write(plain.dir().join(format!("{:016x}.json", key)), "{not json").unwrap();
+        assert!(plain.get(key).is_none());
+    }
+}
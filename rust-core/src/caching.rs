@@ -0,0 +1,117 @@
+use rustpython_parser::ast;
+use std::collections::BTreeMap;
+
+use crate::walk::{argument, dotted_name, keyword, string_literal};
+
+/// Caching decorators: functools, cachetools, Flask-Caching
+/// (`cache.cached`, `cache.memoize`), Django's `cache_page` and aiocache
+const CACHE_DECORATORS: &[&str] = &["cache", "lru_cache", "cached", "memoize", "cache_page", "cache_control", "ttl_cache", "alru_cache"];
+
+/// Rate-limiting decorators: Flask-Limiter/SlowAPI (`limiter.limit`),
+/// django-ratelimit and the `ratelimit` package
+const RATE_LIMIT_DECORATORS: &[&str] = &["limit", "shared_limit", "ratelimit", "rate_limit", "limits"];
+
+/// Keyword arguments holding a cache lifetime in seconds
+const TTL_KEYWORDS: &[&str] = &["ttl", "timeout", "expire", "expires", "expiration", "max_age"];
+
+/// Record caching and rate limits declared by `decorators` in `metadata`:
+/// `cache` (the decorator), `cache_ttl` (seconds), `cache_maxsize` and
+/// `rate_limit` (comma-separated, e.g. `10/minute,100/day`). Lifetimes
+/// written as arithmetic, like Django's `60 * 15`, are evaluated.
+pub fn annotate(decorators: &[ast::Expr], metadata: &mut BTreeMap<String, String>) {
+    let mut limits: Vec<String> = Vec::new();
+    for decorator in decorators {
+        let (name, call) = match decorator {
+            ast::Expr::Call(call) => (dotted_name(&call.func), Some(call)),
+            other => (dotted_name(other), None),
+        };
+        let Some(name) = name else { continue };
+        let last = name.rsplit('.').next().unwrap_or(&name);
+
+        if CACHE_DECORATORS.contains(&last) {
+            metadata.insert("cache".to_string(), last.to_string());
+            let Some(call) = call else { continue };
+            // cachetools passes its policy: `@cached(cache=TTLCache(maxsize=100, ttl=300))`
+            let policy = match keyword(call, "cache") {
+                Some(ast::Expr::Call(policy)) => policy,
+                _ => call,
+            };
+            let ttl = TTL_KEYWORDS
+                .iter()
+                .find_map(|name| keyword(policy, name))
+                .or_else(|| call.args.first().filter(|_| last == "cache_page"))
+                .and_then(seconds);
+            if let Some(ttl) = ttl {
+                metadata.insert("cache_ttl".to_string(), ttl.to_string());
+            }
+            let maxsize = if last == "lru_cache" { argument(policy, 0, "maxsize") } else { keyword(policy, "maxsize") };
+            let maxsize = maxsize.and_then(|size| match size {
+                ast::Expr::Constant(c) if matches!(c.value, ast::Constant::None) => Some("unbounded".to_string()),
+                other => seconds(other).map(|size| size.to_string()),
+            });
+            if let Some(maxsize) = maxsize {
+                metadata.insert("cache_maxsize".to_string(), maxsize);
+            }
+        } else if RATE_LIMIT_DECORATORS.contains(&last) {
+            let Some(call) = call else { continue };
+            // `limit("10/minute")`, django-ratelimit's `rate="5/m"`, and the
+            // `ratelimit` package's `limits(calls=15, period=900)`
+            let limit = argument(call, 0, "rate")
+                .or_else(|| keyword(call, "limit_value"))
+                .and_then(string_literal)
+                .map(str::to_string)
+                .or_else(|| {
+                    let calls = keyword(call, "calls").and_then(seconds)?;
+                    let period = keyword(call, "period").and_then(seconds).unwrap_or(1);
+                    Some(format!("{}/{}s", calls, period))
+                });
+            if let Some(limit) = limit {
+                limits.extend(limit.split(';').map(|part| part.trim().to_string()).filter(|part| !part.is_empty()));
+            }
+        }
+    }
+    if !limits.is_empty() {
+        metadata.insert("rate_limit".to_string(), limits.join(","));
+    }
+}
+
+/// A non-negative integer constant, or products and sums of them
+fn seconds(expr: &ast::Expr) -> Option<u64> {
+    match expr {
+        ast::Expr::Constant(constant) => match &constant.value {
+            ast::Constant::Int(value) => value.to_string().parse().ok(),
+            ast::Constant::Float(value) if *value >= 0.0 => Some(*value as u64),
+            _ => None,
+        },
+        ast::Expr::BinOp(op) => {
+            let (left, right) = (seconds(&op.left)?, seconds(&op.right)?);
+            match op.op {
+                ast::Operator::Mult => left.checked_mul(right),
+                ast::Operator::Add => left.checked_add(right),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::PythonParser;
+    use std::path::Path;
+
+    #[test]
+    fn test_cache_and_rate_limit_metadata() {
+        let source = "@app.get(\"/feed\")\n@limiter.limit(\"10/minute;100/day\")\n@cache_page(60 * 15)\ndef feed(request):\n    pass\n\n@lru_cache(maxsize=None)\ndef settings():\n    pass\n\n@cached(cache=TTLCache(maxsize=100, ttl=300))\ndef lookup(key):\n    pass\n";
+        let entities = PythonParser::new().parse_source(source, Path::new("views.py")).unwrap();
+
+        let feed = &entities[0].metadata;
+        assert_eq!(feed["cache"], "cache_page");
+        assert_eq!(feed["cache_ttl"], "900");
+        assert_eq!(feed["rate_limit"], "10/minute,100/day");
+        assert_eq!(entities[1].metadata["cache_maxsize"], "unbounded");
+        assert!(!entities[1].metadata.contains_key("cache_ttl"));
+        assert_eq!(entities[2].metadata["cache_ttl"], "300");
+        assert_eq!(entities[2].metadata["cache_maxsize"], "100");
+    }
+}
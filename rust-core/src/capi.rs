@@ -0,0 +1,234 @@
+// C ABI for embedding the analyzer without Python (`capi` feature); the
+// declarations are mirrored in include/autodoc.h
+
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::ptr;
+
+use anyhow::{Context, Result};
+
+use crate::analyzer::RustAnalyzer;
+use crate::config::AnalyzerConfig;
+use crate::entity::CodeEntity;
+use crate::lazy;
+
+/// One entity as plain C data; strings are NUL-terminated UTF-8 owned by
+/// the enclosing `AutodocResult`, and optional ones are NULL when absent
+#[repr(C)]
+pub struct AutodocEntity {
+    pub entity_id: *mut c_char,
+    pub entity_type: *mut c_char,
+    pub name: *mut c_char,
+    pub qualified_name: *mut c_char,
+    pub file_path: *mut c_char,
+    pub line_number: usize,
+    pub end_line: usize,
+    pub docstring: *mut c_char,
+    pub return_type: *mut c_char,
+    /// The signature or declaration line(s)
+    pub code: *mut c_char,
+    pub is_async: bool,
+    pub is_public: bool,
+    pub is_deprecated: bool,
+}
+
+/// Outcome of an analyze call, released with `autodoc_result_free`.
+///
+/// On success `error` is NULL, `json` holds the entities as a JSON array and
+/// `entities`/`len` the same entities as structs. On failure only `error`
+/// is set.
+#[repr(C)]
+pub struct AutodocResult {
+    pub json: *mut c_char,
+    pub entities: *mut AutodocEntity,
+    pub len: usize,
+    pub error: *mut c_char,
+}
+
+/// Analyze one file. `config_json` is an `AnalyzerConfig` as JSON, or NULL
+/// for the defaults.
+///
+/// # Safety
+///
+/// `path` must be a valid NUL-terminated string and `config_json` either
+/// NULL or one.
+#[no_mangle]
+pub unsafe extern "C" fn autodoc_analyze_file(path: *const c_char, config_json: *const c_char) -> *mut AutodocResult {
+    run(|| {
+        let path = string_arg(path, "path")?;
+        analyzer(config_json)?.analyze_file(Path::new(&path))
+    })
+}
+
+/// Analyze in-memory source as if it lived at `path`, which picks the
+/// language backend and may be NULL for Python.
+///
+/// # Safety
+///
+/// `source` must be a valid NUL-terminated string; `path` and
+/// `config_json` either NULL or one.
+#[no_mangle]
+pub unsafe extern "C" fn autodoc_analyze_source(
+    source: *const c_char,
+    path: *const c_char,
+    config_json: *const c_char,
+) -> *mut AutodocResult {
+    run(|| {
+        let source = string_arg(source, "source")?;
+        let path = optional_arg(path)?.unwrap_or_else(|| "<string>".to_string());
+        analyzer(config_json)?.analyze_source(&source, Path::new(&path))
+    })
+}
+
+/// Release a result and every string it owns; NULL is ignored.
+///
+/// # Safety
+///
+/// `result` must come from an `autodoc_analyze_*` call and not have been
+/// freed already.
+#[no_mangle]
+pub unsafe extern "C" fn autodoc_result_free(result: *mut AutodocResult) {
+    if result.is_null() {
+        return;
+    }
+    let result = Box::from_raw(result);
+    free_string(result.json);
+    free_string(result.error);
+    if !result.entities.is_null() {
+        let entities = Box::from_raw(ptr::slice_from_raw_parts_mut(result.entities, result.len));
+        for entity in entities.iter() {
+            for field in [
+                entity.entity_id,
+                entity.entity_type,
+                entity.name,
+                entity.qualified_name,
+                entity.file_path,
+                entity.docstring,
+                entity.return_type,
+                entity.code,
+            ] {
+                free_string(field);
+            }
+        }
+    }
+}
+
+/// Version of the core, as a static string the caller must not free
+#[no_mangle]
+pub extern "C" fn autodoc_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Turn an analysis into a heap-allocated result, catching panics so they
+/// never unwind into the caller
+fn run(analyze: impl FnOnce() -> Result<Vec<CodeEntity>>) -> *mut AutodocResult {
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| analyze().and_then(|entities| success(&entities))));
+    let result = match outcome {
+        Ok(Ok(result)) => result,
+        Ok(Err(e)) => failure(&format!("{:#}", e)),
+        Err(_) => failure("autodoc panicked during analysis"),
+    };
+    Box::into_raw(Box::new(result))
+}
+
+fn success(entities: &[CodeEntity]) -> Result<AutodocResult> {
+    let entities = lazy::materialized(entities);
+    let json = serde_json::to_string(&*entities).context("Failed to serialize entities")?;
+    let structs: Box<[AutodocEntity]> = entities.iter().map(entity_struct).collect();
+    let len = structs.len();
+    let entities = if len == 0 { ptr::null_mut() } else { Box::into_raw(structs).cast::<AutodocEntity>() };
+    Ok(AutodocResult {
+        json: c_string(&json),
+        entities,
+        len,
+        error: ptr::null_mut(),
+    })
+}
+
+fn failure(message: &str) -> AutodocResult {
+    AutodocResult {
+        json: ptr::null_mut(),
+        entities: ptr::null_mut(),
+        len: 0,
+        error: c_string(message),
+    }
+}
+
+fn entity_struct(entity: &CodeEntity) -> AutodocEntity {
+    let optional = |value: Option<&str>| value.map_or(ptr::null_mut(), c_string);
+    AutodocEntity {
+        entity_id: c_string(&entity.entity_id),
+        entity_type: c_string(entity.entity_type.as_str()),
+        name: c_string(&entity.name),
+        qualified_name: c_string(&entity.qualified_name),
+        file_path: c_string(&entity.file_path.to_string_lossy()),
+        line_number: entity.line_number,
+        end_line: entity.end_line,
+        docstring: optional(entity.docstring.as_deref()),
+        return_type: optional(entity.return_type.as_deref()),
+        code: c_string(&entity.code),
+        is_async: entity.is_async,
+        is_public: entity.is_public(),
+        is_deprecated: entity.is_deprecated,
+    }
+}
+
+unsafe fn analyzer(config_json: *const c_char) -> Result<RustAnalyzer> {
+    let config = match optional_arg(config_json)? {
+        Some(json) => serde_json::from_str::<AnalyzerConfig>(&json).context("Invalid config JSON")?,
+        None => AnalyzerConfig::default(),
+    };
+    Ok(RustAnalyzer::with_config(config))
+}
+
+unsafe fn string_arg(value: *const c_char, name: &str) -> Result<String> {
+    optional_arg(value)?.ok_or_else(|| anyhow::anyhow!("`{}` must not be NULL", name))
+}
+
+unsafe fn optional_arg(value: *const c_char) -> Result<Option<String>> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    let value = CStr::from_ptr(value).to_str().context("Argument is not valid UTF-8")?;
+    Ok(Some(value.to_string()))
+}
+
+/// A C copy of `value`; interior NULs, which C can't represent, are dropped
+fn c_string(value: &str) -> *mut c_char {
+    let bytes: Vec<u8> = value.bytes().filter(|&b| b != 0).collect();
+    CString::new(bytes).unwrap_or_default().into_raw()
+}
+
+unsafe fn free_string(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_source_over_c_abi() {
+        let source = CString::new("async def fetch(url):\n    \"\"\"Fetch a page.\"\"\"\n").unwrap();
+        let path = CString::new("client.py").unwrap();
+        unsafe {
+            let result = autodoc_analyze_source(source.as_ptr(), path.as_ptr(), ptr::null());
+            assert!((*result).error.is_null());
+            assert_eq!((*result).len, 1);
+            let entity = &*(*result).entities;
+            assert_eq!(CStr::from_ptr(entity.name).to_str().unwrap(), "fetch");
+            assert_eq!(CStr::from_ptr(entity.docstring).to_str().unwrap(), "Fetch a page.");
+            assert!(entity.is_async);
+            assert!(CStr::from_ptr((*result).json).to_str().unwrap().starts_with('['));
+            autodoc_result_free(result);
+
+            let bad = CString::new("{\"threads\": \"many\"}").unwrap();
+            let result = autodoc_analyze_source(source.as_ptr(), ptr::null(), bad.as_ptr());
+            assert!(CStr::from_ptr((*result).error).to_str().unwrap().starts_with("Invalid config JSON"));
+            autodoc_result_free(result);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::diff::{ApiDiff, ChangeKind, EntityChange};
+
+/// Changelog sections, in the order they are rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Section {
+    Added,
+    Changed,
+    Deprecated,
+    Removed,
+}
+
+impl Section {
+    fn heading(&self) -> &'static str {
+        match self {
+            Section::Added => "Added",
+            Section::Changed => "Changed",
+            Section::Deprecated => "Deprecated",
+            Section::Removed => "Removed",
+        }
+    }
+
+    /// Section a change belongs in; docstring- and body-only edits are left out
+    fn of(change: &EntityChange) -> Option<Section> {
+        match change.kind {
+            ChangeKind::Added => Some(Section::Added),
+            ChangeKind::Removed => Some(Section::Removed),
+            ChangeKind::SignatureChanged => Some(Section::Changed),
+            ChangeKind::Deprecated => Some(Section::Deprecated),
+            ChangeKind::VisibilityChanged if change.new_public == Some(true) => Some(Section::Added),
+            ChangeKind::VisibilityChanged => Some(Section::Removed),
+            ChangeKind::DocstringChanged | ChangeKind::BodyChanged => None,
+        }
+    }
+}
+
+/// Render a Keep a Changelog style Markdown section from an API diff.
+///
+/// Changes are grouped under Added/Changed/Deprecated/Removed headings and
+/// then by package. With `link_base` (e.g. a repository blob URL), entity
+/// names link to their definition.
+pub fn render_changelog(diff: &ApiDiff, title: &str, link_base: Option<&str>) -> String {
+    let mut sections: BTreeMap<Section, BTreeMap<String, Vec<&EntityChange>>> = BTreeMap::new();
+    for change in &diff.changes {
+        if let Some(section) = Section::of(change) {
+            sections
+                .entry(section)
+                .or_default()
+                .entry(package_of(&change.file_path))
+                .or_default()
+                .push(change);
+        }
+    }
+
+    let mut out = format!("## {}\n", title);
+    if sections.is_empty() {
+        out.push_str("\nNo public API changes.\n");
+    }
+    for (section, packages) in sections {
+        let _ = write!(out, "\n### {}\n", section.heading());
+        for (package, changes) in packages {
+            let _ = write!(out, "\n#### `{}`\n\n", package);
+            for change in changes {
+                let _ = writeln!(out, "- {}", entry(change, section, link_base));
+            }
+        }
+    }
+    out
+}
+
+fn entry(change: &EntityChange, section: Section, link_base: Option<&str>) -> String {
+    let name = match link_base {
+        // Removed entities only exist in the old tree, so don't link them
+        Some(base) if section != Section::Removed => format!(
+            "[`{}`]({}/{}#L{})",
+            change.name,
+            base.trim_end_matches('/'),
+            change.file_path,
+            change.line_number
+        ),
+        _ => format!("`{}`", change.name),
+    };
+
+    let mut line = format!("{} {}", change.entity_type, name);
+    match change.kind {
+        ChangeKind::SignatureChanged => {
+            let _ = write!(
+                line,
+                ": `{}` → `{}`",
+                change.old_signature.as_deref().unwrap_or(""),
+                change.new_signature.as_deref().unwrap_or("")
+            );
+        }
+        ChangeKind::VisibilityChanged if section == Section::Removed => line.push_str(" is no longer public"),
+        ChangeKind::VisibilityChanged => line.push_str(" is now public"),
+        ChangeKind::Deprecated => {
+            if let Some(replacement) = &change.replacement {
+                let _ = write!(line, " — use `{}` instead", replacement);
+            }
+        }
+        _ => {
+            let summary = change
+                .new_docstring
+                .as_deref()
+                .or(change.old_docstring.as_deref())
+                .and_then(|doc| doc.lines().map(str::trim).find(|l| !l.is_empty()));
+            if let Some(summary) = summary {
+                let _ = write!(line, " — {}", summary);
+            }
+        }
+    }
+    line
+}
+
+/// Dotted package name of a file's directory, without a leading `src`
+fn package_of(file_path: &str) -> String {
+    let parent = Path::new(file_path).parent().unwrap_or(Path::new(""));
+    let parts: Vec<String> = parent
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .skip_while(|c| c == "src" || c == ".")
+        .collect();
+    if parts.is_empty() {
+        "(root)".to_string()
+    } else {
+        parts.join(".")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::diff_entities;
+    use crate::entity::CodeEntity;
+    use std::path::PathBuf;
+
+    fn entity(path: &str, name: &str, code: &str, doc: Option<&str>) -> CodeEntity {
+        let mut entity = CodeEntity::new("function".to_string(), name.to_string(), PathBuf::from(path), 4);
+        entity.code = code.to_string();
+        entity.docstring = doc.map(str::to_string);
+        entity
+    }
+
+    #[test]
+    fn test_changelog_sections() {
+        let old = vec![
+            entity("src/pkg/api.py", "fetch", "def fetch(url):", None),
+            entity("src/pkg/api.py", "legacy", "def legacy():", Some("Old helper.")),
+            entity("src/pkg/util.py", "gone", "def gone():", None),
+        ];
+        let mut new = vec![
+            entity("src/pkg/api.py", "fetch", "def fetch(url, timeout=None):", None),
+            entity("src/pkg/api.py", "legacy", "def legacy():", Some("Old helper.\n\nDeprecated: use fetch.")),
+            entity("src/pkg/sub/new.py", "fresh", "def fresh():", Some("Brand new.\nDetails.")),
+        ];
+        new.iter_mut().for_each(CodeEntity::detect_deprecation);
+        let changelog = render_changelog(&diff_entities(&old, &new), "1.1.0", Some("https://example.com/blob/main/"));
+
+        let added = changelog.find("### Added").unwrap();
+        let changed = changelog.find("### Changed").unwrap();
+        let deprecated = changelog.find("### Deprecated").unwrap();
+        let removed = changelog.find("### Removed").unwrap();
+        assert!(added < changed && changed < deprecated && deprecated < removed);
+
+        assert!(changelog.starts_with("## 1.1.0\n"));
+        assert!(changelog.contains("#### `pkg.sub`"));
+        assert!(changelog.contains(
+            "- function [`fresh`](https://example.com/blob/main/src/pkg/sub/new.py#L4) — Brand new."
+        ));
+        assert!(changelog.contains("`def fetch(url):` → `def fetch(url, timeout=None):`"));
+        assert!(changelog.contains("- function `gone`"));
+        assert!(changelog.contains("— use `fetch` instead"));
+    }
+
+    #[test]
+    fn test_empty_changelog() {
+        let changelog = render_changelog(&ApiDiff::default(), "Unreleased", None);
+        assert_eq!(changelog, "## Unreleased\n\nNo public API changes.\n");
+    }
+}
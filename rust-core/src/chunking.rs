@@ -0,0 +1,332 @@
+use std::path::Path;
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use rayon::prelude::*;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::context::{entity_source, SourceCache};
+use crate::entity::CodeEntity;
+
+/// Counts tokens the way a language model's tokenizer would
+pub trait Tokenizer: Send + Sync {
+    fn count_tokens(&self, text: &str) -> usize;
+}
+
+/// Dependency-free estimate tuned to land close to BPE tokenizers such as
+/// `cl100k_base` on source code: about four characters per word-token, up
+/// to three digits per number-token, one token per symbol and per line break
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ApproximateTokenizer;
+
+impl Tokenizer for ApproximateTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        static PIECES: OnceLock<Regex> = OnceLock::new();
+        let pieces = PIECES.get_or_init(|| Regex::new(r"\p{L}+|\p{N}+|\n+|[^\s\p{L}\p{N}]").unwrap());
+        pieces
+            .find_iter(text)
+            .map(|piece| {
+                let piece = piece.as_str();
+                let chars = piece.chars().count();
+                match piece.chars().next() {
+                    Some('\n') => 1,
+                    Some(c) if c.is_numeric() => chars.div_ceil(3),
+                    Some(c) if c.is_alphabetic() => chars.div_ceil(4),
+                    _ => 1,
+                }
+            })
+            .sum()
+    }
+}
+
+#[cfg(feature = "tiktoken")]
+pub use self::bpe::TiktokenTokenizer;
+
+#[cfg(feature = "tiktoken")]
+mod bpe {
+    use super::*;
+    use tiktoken_rs::CoreBPE;
+
+    /// Exact counts from one of OpenAI's published BPE encodings
+    pub struct TiktokenTokenizer {
+        bpe: CoreBPE,
+    }
+
+    impl TiktokenTokenizer {
+        /// `cl100k_base`, `o200k_base` or `p50k_base`
+        pub fn new(encoding: &str) -> Result<Self> {
+            let bpe = match encoding {
+                "cl100k_base" => tiktoken_rs::cl100k_base()?,
+                "o200k_base" => tiktoken_rs::o200k_base()?,
+                "p50k_base" => tiktoken_rs::p50k_base()?,
+                other => return Err(anyhow::anyhow!("Unknown tiktoken encoding: {}", other)),
+            };
+            Ok(TiktokenTokenizer { bpe })
+        }
+    }
+
+    impl Tokenizer for TiktokenTokenizer {
+        fn count_tokens(&self, text: &str) -> usize {
+            self.bpe.encode_ordinary(text).len()
+        }
+    }
+}
+
+/// Stand-in used when the crate is built without the `tiktoken` feature
+#[cfg(not(feature = "tiktoken"))]
+pub struct TiktokenTokenizer;
+
+#[cfg(not(feature = "tiktoken"))]
+impl TiktokenTokenizer {
+    pub fn new(_encoding: &str) -> Result<Self> {
+        Err(anyhow::anyhow!("BPE token counting requires the `tiktoken` feature"))
+    }
+}
+
+#[cfg(not(feature = "tiktoken"))]
+impl Tokenizer for TiktokenTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        ApproximateTokenizer.count_tokens(text)
+    }
+}
+
+/// Tokenizer by name: "approx" or a tiktoken encoding such as "cl100k_base"
+pub fn tokenizer_by_name(name: &str) -> Result<Box<dyn Tokenizer>> {
+    match name {
+        "approx" | "approximate" => Ok(Box::new(ApproximateTokenizer)),
+        encoding => Ok(Box::new(TiktokenTokenizer::new(encoding)?)),
+    }
+}
+
+/// Size limits for chunking
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkOptions {
+    /// Upper bound on tokens per chunk (a single overlong line is split to fit)
+    pub max_tokens: usize,
+    /// Tokens of trailing context repeated at the start of the next chunk
+    pub overlap_tokens: usize,
+}
+
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        ChunkOptions {
+            max_tokens: 512,
+            overlap_tokens: 64,
+        }
+    }
+}
+
+/// One piece of an entity or module, with enough metadata to cite it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Chunk {
+    /// Id of the chunked entity; empty for module chunks
+    pub entity_id: String,
+    pub entity_type: String,
+    pub name: String,
+    pub file_path: String,
+    /// 0-based position of this chunk among `chunk_count` chunks of the same source
+    pub chunk_index: usize,
+    pub chunk_count: usize,
+    /// 1-based, inclusive line range within the file
+    pub start_line: usize,
+    pub end_line: usize,
+    pub token_count: usize,
+    pub text: String,
+}
+
+/// A chunk of plain text, lines counted from 0
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextChunk {
+    pub first_line: usize,
+    pub last_line: usize,
+    pub token_count: usize,
+    pub text: String,
+}
+
+/// Split `text` into chunks of whole lines of at most `max_tokens` tokens,
+/// each starting with up to `overlap_tokens` tokens from the end of the previous one
+pub fn chunk_text(text: &str, tokenizer: &dyn Tokenizer, options: ChunkOptions) -> Vec<TextChunk> {
+    let max_tokens = options.max_tokens.max(1);
+
+    // (line index, text, tokens); lines over the limit are cut into pieces
+    let mut segments: Vec<(usize, &str, usize)> = Vec::new();
+    for (line, content) in text.split_inclusive('\n').enumerate() {
+        let tokens = tokenizer.count_tokens(content);
+        if tokens <= max_tokens {
+            segments.push((line, content, tokens));
+            continue;
+        }
+        let pieces = tokens.div_ceil(max_tokens);
+        let step = content.chars().count().div_ceil(pieces).max(1);
+        let mut rest = content;
+        while !rest.is_empty() {
+            let cut = rest.char_indices().nth(step).map_or(rest.len(), |(i, _)| i);
+            let (piece, tail) = rest.split_at(cut);
+            segments.push((line, piece, tokenizer.count_tokens(piece)));
+            rest = tail;
+        }
+    }
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < segments.len() {
+        let mut end = start;
+        let mut tokens = 0;
+        while end < segments.len() && (end == start || tokens + segments[end].2 <= max_tokens) {
+            tokens += segments[end].2;
+            end += 1;
+        }
+        chunks.push(TextChunk {
+            first_line: segments[start].0,
+            last_line: segments[end - 1].0,
+            token_count: tokens,
+            text: segments[start..end].iter().map(|s| s.1).collect(),
+        });
+        if end == segments.len() {
+            break;
+        }
+
+        // Step back over trailing segments for overlap, always moving forward
+        let mut next = end;
+        let mut overlap = 0;
+        while next > start + 1 && overlap + segments[next - 1].2 <= options.overlap_tokens {
+            next -= 1;
+            overlap += segments[next].2;
+        }
+        start = next;
+    }
+    chunks
+}
+
+/// Each entity's source text: its lines from the file when its range is
+/// known, else its `code`
+fn entity_sources(entities: &[CodeEntity]) -> Vec<String> {
+    let mut sources = SourceCache::default();
+    entities
+        .iter()
+        .map(|entity| entity_source(entity, sources.get(entity.source_path())))
+        .collect()
+}
+
+/// Token count of each entity's source text, computed in parallel
+pub fn count_entity_tokens(entities: &[CodeEntity], tokenizer: &dyn Tokenizer) -> Vec<usize> {
+    entity_sources(entities).par_iter().map(|source| tokenizer.count_tokens(source)).collect()
+}
+
+/// Chunks of every entity's source text, in entity order
+pub fn chunk_entities(entities: &[CodeEntity], tokenizer: &dyn Tokenizer, options: ChunkOptions) -> Vec<Chunk> {
+    let sources = entity_sources(entities);
+    entities
+        .par_iter()
+        .zip(sources.par_iter())
+        .flat_map_iter(|(entity, source)| {
+            let pieces = chunk_text(source, tokenizer, options);
+            into_chunks(pieces, entity.line_number, |chunk| {
+                chunk.entity_id = entity.entity_id.clone();
+                chunk.entity_type = entity.entity_type.to_string();
+                chunk.name = entity.name.clone();
+                chunk.file_path = entity.file_path.to_string_lossy().into_owned();
+            })
+        })
+        .collect()
+}
+
+/// Chunks of a whole source file, for module-level context
+pub fn chunk_module(path: &Path, source: &str, tokenizer: &dyn Tokenizer, options: ChunkOptions) -> Vec<Chunk> {
+    let name = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    into_chunks(chunk_text(source, tokenizer, options), 1, |chunk| {
+        chunk.entity_type = "module".to_string();
+        chunk.name = name.clone();
+        chunk.file_path = path.to_string_lossy().into_owned();
+    })
+}
+
+fn into_chunks(pieces: Vec<TextChunk>, first_line: usize, describe: impl Fn(&mut Chunk)) -> Vec<Chunk> {
+    let chunk_count = pieces.len();
+    pieces
+        .into_iter()
+        .enumerate()
+        .map(|(chunk_index, piece)| {
+            let mut chunk = Chunk {
+                entity_id: String::new(),
+                entity_type: String::new(),
+                name: String::new(),
+                file_path: String::new(),
+                chunk_index,
+                chunk_count,
+                start_line: first_line + piece.first_line,
+                end_line: first_line + piece.last_line,
+                token_count: piece.token_count,
+                text: piece.text,
+            };
+            describe(&mut chunk);
+            chunk
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One token per whitespace-separated word
+    struct Words;
+
+    impl Tokenizer for Words {
+        fn count_tokens(&self, text: &str) -> usize {
+            text.split_whitespace().count()
+        }
+    }
+
+    #[test]
+    fn test_chunks_overlap_and_cover_text() {
+        let text = "a b c\nd e\nf g h\ni\nj k\n";
+        let options = ChunkOptions {
+            max_tokens: 5,
+            overlap_tokens: 2,
+        };
+        let chunks = chunk_text(text, &Words, options);
+
+        let texts: Vec<&str> = chunks.iter().map(|c| c.text.as_str()).collect();
+        // "f g h" is too long to fit in the overlap, so the last chunk starts fresh
+        assert_eq!(texts, vec!["a b c\nd e\n", "d e\nf g h\n", "i\nj k\n"]);
+        assert!(chunks.iter().all(|c| c.token_count <= 5));
+        assert_eq!((chunks[2].first_line, chunks[2].last_line), (3, 4));
+    }
+
+    #[test]
+    fn test_overlong_line_is_split() {
+        let text = "one two three four five six seven";
+        let options = ChunkOptions {
+            max_tokens: 3,
+            overlap_tokens: 0,
+        };
+        let chunks = chunk_text(text, &Words, options);
+        assert!(chunks.len() >= 3);
+        assert_eq!(chunks.iter().map(|c| c.text.as_str()).collect::<String>(), text);
+    }
+
+    #[test]
+    fn test_entities_chunk_their_whole_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.py");
+        std::fs::write(&path, "def run(a):\n    b = a + 1\n    c = b * 2\n    d = c - 3\n    return d\n").unwrap();
+        let entities = crate::analyzer::RustAnalyzer::new().analyze_file(&path).unwrap();
+
+        assert_eq!(count_entity_tokens(&entities, &Words), vec![19]);
+        let options = ChunkOptions {
+            max_tokens: 8,
+            overlap_tokens: 0,
+        };
+        let chunks = chunk_entities(&entities, &Words, options);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!((chunks[2].start_line, chunks[2].end_line), (4, 5));
+    }
+
+    #[test]
+    fn test_approximate_counts() {
+        assert_eq!(ApproximateTokenizer.count_tokens("def fetch(url):\n"), 8);
+        assert_eq!(ApproximateTokenizer.count_tokens(""), 0);
+    }
+}
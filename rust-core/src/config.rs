@@ -0,0 +1,227 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::visibility::DEFAULT_PRECEDENCE;
+
+/// Name of the project-level configuration file
+pub const CONFIG_FILE_NAME: &str = "autodoc.toml";
+
+/// Analyzer options shared by the Rust API, the Python bindings and `autodoc.toml`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnalyzerConfig {
+    /// Glob patterns (relative to the analysis root) a file must match; empty includes everything
+    pub include: Vec<String>,
+    /// Glob patterns for files or directories to skip, on top of the built-in excludes
+    pub exclude: Vec<String>,
+    /// Whether directory walking follows symbolic links
+    pub follow_symlinks: bool,
+    /// Number of worker threads; `None` uses Rayon's global pool
+    pub threads: Option<usize>,
+    /// Run on a process-wide pool shared by every analyzer with the same
+    /// `threads`, instead of building a pool per analyzer
+    pub shared_pool: bool,
+    /// Enabled language backends
+    pub languages: Vec<String>,
+    /// Extra languages defined by a tree-sitter grammar and query, as
+    /// `[[analyzer.query_languages]]` tables; they need the `tree-sitter`
+    /// feature and take precedence over the built-in backends
+    pub query_languages: Vec<QueryLanguage>,
+    /// Expected docstring convention: "auto", "google", "numpy" or "sphinx";
+    /// the linter only counts sections written in it
+    pub docstring_style: String,
+    /// Complexity score above which an entity is considered too complex
+    pub max_complexity: u32,
+    /// Minimum docstring coverage (percent) a project is expected to reach
+    pub min_docstring_coverage: f64,
+    /// Severity per lint rule ("off", "info", "warning" or "error"), e.g.
+    /// `missing-docstring = "error"`; unnamed rules keep their default
+    pub lint_severity: BTreeMap<String, String>,
+    /// Glob patterns over file paths or entity names the linter skips; a
+    /// `rule:` prefix limits a pattern to that rule
+    pub lint_ignore: Vec<String>,
+    /// Signals deciding whether a Python entity is internal, strongest first:
+    /// "private-module", "__all__" and "underscore"; leaving one out ignores it
+    pub visibility_precedence: Vec<String>,
+    /// Decorators that make an entity public regardless of its name, e.g.
+    /// `public` for `@atpublic.public`; `#: public-api` comments work too
+    pub public_decorators: Vec<String>,
+    /// Decorators that hide an entity, like a `#: private-api` comment
+    pub private_decorators: Vec<String>,
+    /// Keep entities flagged `is_internal`
+    pub include_private: bool,
+    /// Keep dunder methods other than `__init__`
+    pub include_dunder: bool,
+    /// Keep only public API entities (see `CodeEntity::is_public`); applied
+    /// before results reach callers, so dropped helpers cost nothing downstream
+    pub public_only: bool,
+    /// Give methods without a docstring the one of the base-class method they
+    /// override, marking them `inherited_doc`
+    pub inherit_docstrings: bool,
+    /// Annotate entities with their last commit, author and date from `git blame`
+    pub git_blame: bool,
+    /// Mask likely secrets (keys, tokens, passwords) in extracted code and docstrings
+    pub redact_secrets: bool,
+    /// Report `file_path` relative to the analysis root with `/` separators,
+    /// keeping the on-disk location in `absolute_path`
+    pub relative_paths: bool,
+    /// Leave out machine-generated files instead of tagging their entities
+    /// with `is_generated`
+    pub skip_generated: bool,
+    /// Decode files that aren't valid UTF-8 as Latin-1 instead of skipping them
+    pub transcode_latin1: bool,
+    /// Leave `code` unloaded where it can be sliced back out of the source
+    /// file, loading it on first use; saves memory when only signatures and
+    /// docstrings are needed
+    pub lazy_code: bool,
+    /// Directory for the persistent per-file analysis cache; `None` disables it
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl Default for AnalyzerConfig {
+    fn default() -> Self {
+        AnalyzerConfig {
+            include: Vec::new(),
+            exclude: Vec::new(),
+            follow_symlinks: false,
+            threads: None,
+            shared_pool: false,
+            languages: vec!["python".to_string()],
+            query_languages: Vec::new(),
+            docstring_style: "auto".to_string(),
+            max_complexity: 10,
+            min_docstring_coverage: 0.0,
+            lint_severity: BTreeMap::new(),
+            lint_ignore: Vec::new(),
+            visibility_precedence: DEFAULT_PRECEDENCE.iter().map(|s| s.as_str().to_string()).collect(),
+            public_decorators: vec!["public".to_string()],
+            private_decorators: vec!["private".to_string()],
+            include_private: true,
+            include_dunder: true,
+            public_only: false,
+            inherit_docstrings: false,
+            git_blame: false,
+            redact_secrets: false,
+            relative_paths: true,
+            skip_generated: false,
+            transcode_latin1: true,
+            lazy_code: false,
+            cache_dir: None,
+        }
+    }
+}
+
+/// A language defined at runtime; see `backends::QueryBackend`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryLanguage {
+    /// Name reported as the entities' language
+    pub name: String,
+    /// Bundled tree-sitter grammar to parse with, e.g. "go" or "tsx"
+    pub grammar: String,
+    /// File extensions the language handles
+    pub extensions: Vec<String>,
+    /// Query source; takes precedence over `query_file`
+    #[serde(default)]
+    pub query: Option<String>,
+    /// `.scm` file holding the query
+    #[serde(default)]
+    pub query_file: Option<PathBuf>,
+}
+
+/// Layout of `autodoc.toml`; analyzer options live under `[analyzer]`
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    analyzer: AnalyzerConfig,
+}
+
+impl AnalyzerConfig {
+    /// Parse the `[analyzer]` section of an `autodoc.toml` document
+    pub fn from_toml_str(contents: &str) -> Result<Self> {
+        let file: ConfigFile = toml::from_str(contents)
+            .context("Invalid autodoc configuration")?;
+        Ok(file.analyzer)
+    }
+
+    /// Load configuration from an `autodoc.toml` file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {:?}", path))?;
+        Self::from_toml_str(&contents)
+            .with_context(|| format!("Failed to load config file: {:?}", path))
+    }
+
+    /// Load `autodoc.toml` from a project root, falling back to defaults when absent
+    pub fn discover(root: &Path) -> Result<Self> {
+        let path = config_path(root);
+        if path.is_file() {
+            Self::from_file(&path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// Check whether a language backend is enabled
+    pub fn language_enabled(&self, language: &str) -> bool {
+        self.languages.iter().any(|l| l.eq_ignore_ascii_case(language))
+    }
+}
+
+fn config_path(root: &Path) -> PathBuf {
+    root.join(CONFIG_FILE_NAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_when_section_missing() {
+        let config = AnalyzerConfig::from_toml_str("").unwrap();
+        assert_eq!(config, AnalyzerConfig::default());
+        assert!(config.language_enabled("python"));
+    }
+
+    #[test]
+    fn test_parse_analyzer_section() {
+        let config = AnalyzerConfig::from_toml_str(r#"
+[analyzer]
+include = ["src/**"]
+exclude = ["migrations"]
+threads = 4
+docstring_style = "google"
+max_complexity = 15
+"#).unwrap();
+
+        assert_eq!(config.include, vec!["src/**"]);
+        assert_eq!(config.exclude, vec!["migrations"]);
+        assert_eq!(config.threads, Some(4));
+        assert_eq!(config.docstring_style, "google");
+        assert_eq!(config.max_complexity, 15);
+        assert!(!config.follow_symlinks);
+    }
+
+    #[test]
+    fn test_parse_query_languages() {
+        let config = AnalyzerConfig::from_toml_str(r#"
+[[analyzer.query_languages]]
+name = "golang"
+grammar = "go"
+extensions = ["go"]
+query_file = "queries/go.scm"
+"#).unwrap();
+
+        assert_eq!(config.query_languages.len(), 1);
+        assert_eq!(config.query_languages[0].grammar, "go");
+        assert_eq!(config.query_languages[0].query_file, Some(PathBuf::from("queries/go.scm")));
+        assert!(config.query_languages[0].query.is_none());
+    }
+
+    #[test]
+    fn test_invalid_config_is_an_error() {
+        assert!(AnalyzerConfig::from_toml_str("[analyzer]\nthreads = \"many\"").is_err());
+    }
+}
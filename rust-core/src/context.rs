@@ -0,0 +1,423 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::chunking::Tokenizer;
+use crate::entity::CodeEntity;
+use crate::ident::normalize_identifier;
+use crate::parser::module_docstring;
+
+/// Why an entity is part of a context bundle, in inclusion priority order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Relation {
+    Target,
+    Module,
+    Class,
+    Callee,
+    Type,
+    Caller,
+}
+
+impl Relation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Relation::Target => "target",
+            Relation::Module => "module",
+            Relation::Class => "class",
+            Relation::Callee => "callee",
+            Relation::Type => "type",
+            Relation::Caller => "caller",
+        }
+    }
+}
+
+/// One piece of context: the target's source, or the signature and
+/// docstring of something related to it
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextItem {
+    pub relation: Relation,
+    /// Position of the entity in the analyzed slice; `None` for the module docstring
+    pub index: Option<usize>,
+    pub name: String,
+    pub file_path: String,
+    pub line_number: usize,
+    pub text: String,
+    pub token_count: usize,
+}
+
+/// Everything an LLM needs to summarize one entity, within a token budget
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextBundle {
+    pub items: Vec<ContextItem>,
+    pub token_count: usize,
+    /// Related items left out because they did not fit the budget
+    pub omitted: usize,
+}
+
+impl ContextBundle {
+    /// Plain-text rendering, one section per item
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for item in &self.items {
+            let _ = writeln!(
+                out,
+                "### {} `{}` ({}:{})\n{}\n",
+                item.relation.as_str(),
+                item.name,
+                item.file_path,
+                item.line_number,
+                item.text.trim_end()
+            );
+        }
+        out
+    }
+}
+
+/// Assemble the context pack for `entities[target]`.
+///
+/// The target's full source always comes first (truncated to the budget if
+/// it alone exceeds it). Then, while they fit in `budget` tokens: the module
+/// docstring, the enclosing class, direct callees, types referenced by the
+/// signature, and direct callers. Related entities contribute their
+/// signature and docstring only. Calls are matched by name, preferring
+/// definitions in the target's own file.
+pub fn build_context(
+    entities: &[CodeEntity],
+    target: usize,
+    tokenizer: &dyn Tokenizer,
+    budget: usize,
+) -> Result<ContextBundle> {
+    let entity = entities
+        .get(target)
+        .ok_or_else(|| anyhow::anyhow!("No entity at index {}", target))?;
+    let mut sources = SourceCache::default();
+
+    let mut candidates: Vec<ContextItem> = Vec::new();
+    if let Some(doc) = sources.get(entity.source_path()).and_then(module_docstring) {
+        candidates.push(ContextItem {
+            relation: Relation::Module,
+            index: None,
+            name: entity.file_path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default(),
+            file_path: entity.file_path.to_string_lossy().into_owned(),
+            line_number: 1,
+            text: doc,
+            token_count: 0,
+        });
+    }
+
+    let body = entity_source(entity, sources.get(entity.source_path()));
+    let related = |relation, index: usize| summary_item(relation, index, &entities[index]);
+
+    let mut seen = vec![target];
+    if let Some(class) = enclosing_container(entities, target) {
+        seen.push(class);
+        candidates.push(related(Relation::Class, class));
+    }
+
+    let by_name = index_by_name(entities);
+    let normalized = normalize_identifier(&body);
+    for name in called_names(&normalized) {
+        if let Some(&callee) = by_name.get(name).and_then(|indices| pick(entities, indices, entity)) {
+            if !seen.contains(&callee) {
+                seen.push(callee);
+                candidates.push(related(Relation::Callee, callee));
+            }
+        }
+    }
+
+    let signature = format!(
+        "{} {} {}",
+        entity.code.lines().next().unwrap_or(""),
+        entity.parameters.join(" "),
+        entity.return_type.as_deref().unwrap_or("")
+    );
+    let signature = normalize_identifier(&signature);
+    for (i, other) in entities.iter().enumerate() {
+        if is_type(other) && !seen.contains(&i) && mentions(&signature, &normalize_identifier(&other.name)) {
+            seen.push(i);
+            candidates.push(related(Relation::Type, i));
+        }
+    }
+
+    let call = Regex::new(&format!(r"\b{}\s*\(", regex::escape(&normalize_identifier(&entity.name))))?;
+    for (i, other) in entities.iter().enumerate() {
+        if !is_callable(other) || seen.contains(&i) {
+            continue;
+        }
+        if calls(&normalize_identifier(&entity_source(other, sources.get(other.source_path()))), &call) {
+            seen.push(i);
+            candidates.push(related(Relation::Caller, i));
+        }
+    }
+
+    // Fill the budget in priority order; the target itself is mandatory
+    let mut target_item = ContextItem {
+        relation: Relation::Target,
+        index: Some(target),
+        name: entity.name.clone(),
+        file_path: entity.file_path.to_string_lossy().into_owned(),
+        line_number: entity.line_number,
+        token_count: tokenizer.count_tokens(&body),
+        text: body,
+    };
+    if target_item.token_count > budget {
+        truncate_to_budget(&mut target_item, tokenizer, budget);
+    }
+
+    let mut token_count = target_item.token_count;
+    let mut items = vec![target_item];
+    let mut omitted = 0;
+    candidates.sort_by_key(|item| item.relation);
+    for mut item in candidates {
+        item.token_count = tokenizer.count_tokens(&item.text);
+        if token_count + item.token_count <= budget {
+            token_count += item.token_count;
+            items.push(item);
+        } else {
+            omitted += 1;
+        }
+    }
+
+    Ok(ContextBundle {
+        items,
+        token_count,
+        omitted,
+    })
+}
+
+/// The entity's lines from its file when its range is known, else its `code`
+pub fn entity_source(entity: &CodeEntity, source: Option<&str>) -> String {
+    match source {
+        Some(source) if entity.end_line >= entity.line_number && entity.line_number > 0 => source
+            .split_inclusive('\n')
+            .skip(entity.line_number - 1)
+            .take(entity.end_line - entity.line_number + 1)
+            .collect(),
+        _ => entity.code.clone(),
+    }
+}
+
+/// Source files read on demand, each at most once
+#[derive(Default)]
+pub(crate) struct SourceCache {
+    files: HashMap<String, Option<String>>,
+}
+
+impl SourceCache {
+    pub(crate) fn get(&mut self, path: &Path) -> Option<&str> {
+        self.files
+            .entry(path.to_string_lossy().into_owned())
+            .or_insert_with(|| fs::read_to_string(path).ok())
+            .as_deref()
+    }
+}
+
+fn summary_item(relation: Relation, index: usize, entity: &CodeEntity) -> ContextItem {
+    let mut text = entity.code.lines().next().unwrap_or(&entity.name).to_string();
+    if let Some(doc) = entity.docstring.as_deref().filter(|d| !d.trim().is_empty()) {
+        text.push('\n');
+        text.push_str(doc.trim());
+    }
+    ContextItem {
+        relation,
+        index: Some(index),
+        name: entity.name.clone(),
+        file_path: entity.file_path.to_string_lossy().into_owned(),
+        line_number: entity.line_number,
+        text,
+        token_count: 0,
+    }
+}
+
+/// Cut `item.text` at a line boundary so it fits `budget` tokens
+fn truncate_to_budget(item: &mut ContextItem, tokenizer: &dyn Tokenizer, budget: usize) {
+    const MARKER: &str = "...\n";
+    let budget = budget.saturating_sub(tokenizer.count_tokens(MARKER));
+    let mut kept = String::new();
+    let mut tokens = 0;
+    for line in item.text.split_inclusive('\n') {
+        let line_tokens = tokenizer.count_tokens(line);
+        if tokens + line_tokens > budget {
+            break;
+        }
+        kept.push_str(line);
+        tokens += line_tokens;
+    }
+    kept.push_str(MARKER);
+    item.token_count = tokenizer.count_tokens(&kept);
+    item.text = kept;
+}
+
+/// Callables and types by NFKC-normalized name
+fn index_by_name(entities: &[CodeEntity]) -> HashMap<Cow<'_, str>, Vec<usize>> {
+    let mut by_name: HashMap<Cow<'_, str>, Vec<usize>> = HashMap::new();
+    for (i, entity) in entities.iter().enumerate() {
+        if is_callable(entity) || is_type(entity) {
+            by_name.entry(normalize_identifier(&entity.name)).or_default().push(i);
+        }
+    }
+    by_name
+}
+
+/// The definition a call from `caller` most likely refers to
+fn pick<'a>(entities: &[CodeEntity], candidates: &'a [usize], caller: &CodeEntity) -> Option<&'a usize> {
+    candidates
+        .iter()
+        .find(|&&i| entities[i].file_path == caller.file_path)
+        .or_else(|| candidates.first())
+}
+
+/// Names directly followed by a call parenthesis, in order of first use
+fn called_names(body: &str) -> Vec<&str> {
+    static CALL: OnceLock<Regex> = OnceLock::new();
+    let call = CALL.get_or_init(|| Regex::new(r"([\p{XID_Start}_]\p{XID_Continue}*)\s*\(").unwrap());
+    let mut names: Vec<&str> = Vec::new();
+    // Skip the definition line, which "calls" the entity's own name
+    for line in body.lines().skip(1) {
+        for capture in call.captures_iter(line) {
+            let Some(name) = capture.get(1) else { continue };
+            if !defines(line, name.start()) && !names.contains(&name.as_str()) {
+                names.push(name.as_str());
+            }
+        }
+    }
+    names
+}
+
+/// Whether `body` has a call matching `call` below its definition line
+fn calls(body: &str, call: &Regex) -> bool {
+    body.lines()
+        .skip(1)
+        .any(|line| call.find_iter(line).any(|m| !defines(line, m.start())))
+}
+
+/// Whether the name at `at` in `line` is being defined (`def name(`,
+/// `class name(`) rather than called
+fn defines(line: &str, at: usize) -> bool {
+    let before = line[..at].trim_end();
+    let keyword = before.rsplit(|c: char| !c.is_alphanumeric() && c != '_').next().unwrap_or("");
+    matches!(keyword, "def" | "class")
+}
+
+fn mentions(text: &str, name: &str) -> bool {
+    text.match_indices(name).any(|(i, _)| {
+        let before = text[..i].chars().next_back();
+        let after = text[i + name.len()..].chars().next();
+        let is_word = |c: Option<char>| c.map_or(false, |c| c.is_alphanumeric() || c == '_');
+        !is_word(before) && !is_word(after)
+    })
+}
+
+fn is_callable(entity: &CodeEntity) -> bool {
+    matches!(entity.entity_type.as_str(), "function" | "method")
+}
+
+fn is_type(entity: &CodeEntity) -> bool {
+    matches!(
+        entity.entity_type.as_str(),
+        "class" | "struct" | "enum" | "interface" | "trait" | "typedef"
+    )
+}
+
+/// Innermost class-like entity in the same file whose range contains `target`
+fn enclosing_container(entities: &[CodeEntity], target: usize) -> Option<usize> {
+    let entity = &entities[target];
+    entities
+        .iter()
+        .enumerate()
+        .filter(|&(i, other)| {
+            i != target
+                && (is_type(other) || other.entity_type == "impl")
+                && other.file_path == entity.file_path
+                && other.line_number <= entity.line_number
+                && other.end_line >= entity.line_number
+        })
+        .max_by_key(|(_, other)| other.line_number)
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::RustAnalyzer;
+    use crate::chunking::ApproximateTokenizer;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const SOURCE: &str = r#""""HTTP helpers."""
+
+
+class Response:
+    """A parsed response."""
+
+
+class Client:
+    """Talks to the API."""
+
+    def get(self, url) -> Response:
+        """Fetch a URL."""
+        return parse(send(url))
+
+
+def send(url):
+    """Send a request."""
+    return url
+
+
+def parse(raw):
+    """Parse raw bytes."""
+    return raw
+
+
+def main():
+    Client().get("/")
+
+
+def cached():
+    def get(key):
+        return key
+    return get
+"#;
+
+    #[test]
+    fn test_context_bundle() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("http.py");
+        fs::write(&path, SOURCE).unwrap();
+        let entities = RustAnalyzer::new().analyze_file(&path).unwrap();
+        let target = entities.iter().position(|e| e.name == "get").unwrap();
+
+        let bundle = build_context(&entities, target, &ApproximateTokenizer, 1000).unwrap();
+        let relations: Vec<(&str, &str)> = bundle
+            .items
+            .iter()
+            .map(|item| (item.relation.as_str(), item.name.as_str()))
+            .collect();
+        assert_eq!(
+            relations,
+            vec![
+                ("target", "get"),
+                ("module", "http"),
+                ("class", "Client"),
+                ("callee", "parse"),
+                ("callee", "send"),
+                ("type", "Response"),
+                ("caller", "main"),
+            ]
+        );
+        assert!(bundle.items[0].text.contains("return parse(send(url))"));
+        assert_eq!(bundle.omitted, 0);
+
+        let small = build_context(&entities, target, &ApproximateTokenizer, 40).unwrap();
+        assert!(small.omitted > 0);
+        assert!(small.token_count <= 40);
+    }
+}
@@ -0,0 +1,112 @@
+use rustpython_parser::ast;
+use std::collections::HashMap;
+
+use crate::endpoints;
+use crate::env::{self, EnvVar};
+use crate::flags::Flags;
+use crate::logs::{self, LogEvent};
+use crate::outbound::{self, HttpCall};
+use crate::security::{self, SecurityFinding};
+use crate::sql::Queries;
+use crate::testmap::Exercised;
+use crate::walk::{self, dotted_name, string_literal, BodyVisitor};
+
+/// What the detectors found in a function body
+#[derive(Debug, Default)]
+pub struct BodyFacts {
+    pub env_vars: Vec<EnvVar>,
+    pub log_events: Vec<LogEvent>,
+    pub http_calls: Vec<HttpCall>,
+    pub security_findings: Vec<SecurityFinding>,
+    pub feature_flags: Vec<String>,
+    /// Tables named by SQL, or `None` when the body doesn't touch a database
+    pub tables: Option<Vec<String>>,
+    /// DRF serializers instantiated, in order of first use
+    pub serializers: Vec<String>,
+    /// For tests: imported code called, see `testmap::Exercised`
+    pub exercises: Vec<String>,
+}
+
+/// Run every body detector over a function in a single walk.
+///
+/// Each call's dotted name is computed once and shared by the detectors
+/// keyed on it. `test_imports` turns on test mapping; `line_starts` maps
+/// offsets in the body to lines.
+pub fn scan(
+    body: &[ast::Stmt],
+    decorators: &[ast::Expr],
+    line_starts: &[usize],
+    test_imports: Option<&HashMap<String, String>>,
+) -> BodyFacts {
+    let docstring = match body.first() {
+        Some(ast::Stmt::Expr(first)) if string_literal(&first.value).is_some() => Some(&*first.value),
+        _ => None,
+    };
+    let mut detectors = Detectors {
+        line_starts,
+        docstring,
+        flags: Flags::default(),
+        queries: Queries::default(),
+        exercised: test_imports.map(Exercised::new),
+        facts: BodyFacts::default(),
+    };
+    detectors.flags.decorators(decorators);
+    walk::walk_body(body, &mut detectors);
+
+    let mut facts = detectors.facts;
+    facts.feature_flags = detectors.flags.0;
+    facts.tables = detectors.queries.touches_database.then_some(detectors.queries.tables);
+    facts.exercises = detectors.exercised.map(|exercised| exercised.found).unwrap_or_default();
+    facts
+}
+
+struct Detectors<'s> {
+    line_starts: &'s [usize],
+    docstring: Option<&'s ast::Expr>,
+    flags: Flags,
+    queries: Queries,
+    exercised: Option<Exercised>,
+    facts: BodyFacts,
+}
+
+impl<'a> BodyVisitor<'a> for Detectors<'_> {
+    fn stmt(&mut self, stmt: &'a ast::Stmt) {
+        self.flags.stmt(stmt);
+        if let Some(exercised) = &mut self.exercised {
+            exercised.stmt(stmt);
+        }
+    }
+
+    fn expr(&mut self, expr: &'a ast::Expr) {
+        let callee = match expr {
+            ast::Expr::Call(call) => dotted_name(&call.func),
+            _ => None,
+        };
+        let callee = callee.as_deref();
+
+        if let Some(var) = env::read(expr, callee, self.line_starts) {
+            if !self.facts.env_vars.iter().any(|known| known.name == var.name && known.default == var.default) {
+                self.facts.env_vars.push(var);
+            }
+        }
+        if let ast::Expr::Call(call) = expr {
+            let facts = &mut self.facts;
+            facts.log_events.extend(logs::event(call, self.line_starts));
+            facts.http_calls.extend(outbound::request(call, callee, self.line_starts));
+            facts.security_findings.extend(security::finding(call, callee, self.line_starts));
+            if let Some(name) = callee.filter(|name| endpoints::is_serializer(name)) {
+                if !facts.serializers.iter().any(|known| known == name) {
+                    facts.serializers.push(name.to_string());
+                }
+            }
+        }
+
+        self.flags.expr(expr);
+        if !self.docstring.map_or(false, |docstring| std::ptr::eq(docstring, expr)) {
+            self.queries.expr(expr);
+        }
+        if let Some(exercised) = &mut self.exercised {
+            exercised.expr(expr);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+/// A syntax error pinned to a position in a source file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub file: PathBuf,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column, counted in characters
+    pub column: usize,
+    pub message: String,
+    /// The offending line, without its line ending
+    pub source_line: String,
+}
+
+impl Diagnostic {
+    /// A diagnostic at a 1-based `line` and `column` of `source`
+    pub fn new(file: &Path, source: &str, line: usize, column: usize, message: impl Into<String>) -> Self {
+        let source_line = source
+            .lines()
+            .nth(line.saturating_sub(1))
+            .unwrap_or("")
+            .trim_end_matches('\r')
+            .to_string();
+        Diagnostic {
+            file: file.to_path_buf(),
+            line: line.max(1),
+            column: column.max(1),
+            message: message.into(),
+            source_line,
+        }
+    }
+
+    /// A diagnostic at byte `offset` of `source`, clamped to the source
+    pub fn at_offset(file: &Path, source: &str, offset: usize, message: impl Into<String>) -> Self {
+        let mut offset = offset.min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line = source[..offset].matches('\n').count() + 1;
+        let column = source[line_start..offset].chars().count() + 1;
+        Diagnostic::new(file, source, line, column, message)
+    }
+
+    /// The source line with a caret under the column:
+    ///
+    /// ```text
+    ///   3 | def load(path
+    ///     |              ^
+    /// ```
+    pub fn excerpt(&self) -> String {
+        let gutter = self.line.to_string();
+        // Keep tabs so the caret lines up with the text above it
+        let padding: String = self
+            .source_line
+            .chars()
+            .take(self.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        format!(
+            "{} | {}\n{} | {}^",
+            gutter,
+            self.source_line,
+            " ".repeat(gutter.len()),
+            padding
+        )
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}\n{}",
+            self.file.display(),
+            self.line,
+            self.column,
+            self.message,
+            self.excerpt()
+        )
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_to_position_and_excerpt() {
+        let source = "import os\n\ndef load(path\n    return path\n";
+        let diagnostic = Diagnostic::at_offset(Path::new("app.py"), source, 24, "expected ')'");
+        assert_eq!((diagnostic.line, diagnostic.column), (3, 14));
+        assert_eq!(diagnostic.source_line, "def load(path");
+        assert_eq!(diagnostic.excerpt(), "3 | def load(path\n  |              ^");
+        assert!(diagnostic.to_string().starts_with("app.py:3:14: expected ')'\n"));
+    }
+}
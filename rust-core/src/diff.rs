@@ -0,0 +1,322 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::entity::CodeEntity;
+use crate::lazy;
+
+/// How an entity changed between two analyses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    SignatureChanged,
+    DocstringChanged,
+    VisibilityChanged,
+    /// Newly marked deprecated; covers docstring and body edits made along
+    /// with it, and is reported next to a signature change
+    Deprecated,
+    /// Same signature and docstring, different implementation
+    BodyChanged,
+}
+
+impl ChangeKind {
+    /// Snake-case name, matching the serialized form
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::SignatureChanged => "signature_changed",
+            ChangeKind::DocstringChanged => "docstring_changed",
+            ChangeKind::VisibilityChanged => "visibility_changed",
+            ChangeKind::Deprecated => "deprecated",
+            ChangeKind::BodyChanged => "body_changed",
+        }
+    }
+
+    /// One-character marker used in human-readable output
+    pub fn marker(&self) -> char {
+        match self {
+            ChangeKind::Added => '+',
+            ChangeKind::Removed => '-',
+            ChangeKind::SignatureChanged => '~',
+            ChangeKind::DocstringChanged => '*',
+            ChangeKind::VisibilityChanged => '^',
+            ChangeKind::Deprecated => '!',
+            ChangeKind::BodyChanged => '#',
+        }
+    }
+}
+
+/// A single change to the public API; `old_*` fields describe the entity
+/// before the change and are `None` for additions, `new_*` the reverse
+#[derive(Debug, Clone, Serialize)]
+pub struct EntityChange {
+    pub kind: ChangeKind,
+    /// Key identifying the entity across both analyses
+    pub key: String,
+    pub entity_type: String,
+    pub name: String,
+    pub file_path: String,
+    pub old_signature: Option<String>,
+    pub new_signature: Option<String>,
+    pub old_docstring: Option<String>,
+    pub new_docstring: Option<String>,
+    pub old_public: Option<bool>,
+    pub new_public: Option<bool>,
+    /// Suggested replacement when the entity was deprecated
+    pub replacement: Option<String>,
+    /// Line of the entity in the newer analysis, or the older one if removed
+    pub line_number: usize,
+}
+
+/// Differences between the public API of two analyses
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ApiDiff {
+    pub changes: Vec<EntityChange>,
+}
+
+impl ApiDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Number of changes of the given kind
+    pub fn count(&self, kind: ChangeKind) -> usize {
+        self.changes.iter().filter(|c| c.kind == kind).count()
+    }
+}
+
+/// Compare the public API of two analyses.
+///
+/// Entities are matched on `entity_id`, which follows the qualified name, so
+/// reordering definitions or adding same-named entities elsewhere doesn't
+/// pair up the wrong ones; changes are keyed on the qualified name. Only
+/// when either side predates ids are entities matched on file path and
+/// name in order of appearance, and then callers should make both sides'
+/// paths relative to their analysis roots first. Internal entities only
+/// show up when they become public or stop being public.
+pub fn diff_entities(old: &[CodeEntity], new: &[CodeEntity]) -> ApiDiff {
+    let (old, new) = (lazy::materialized(old), lazy::materialized(new));
+    let (old, new) = (&*old, &*new);
+    let by_id = old.iter().chain(new).all(|e| !e.entity_id.is_empty());
+    let qualified = old.iter().chain(new).all(|e| !e.qualified_name.is_empty());
+    let old_index = index_entities(old, by_id, qualified);
+    let mut new_index = index_entities(new, by_id, qualified);
+    let mut changes = Vec::new();
+
+    for (id, before) in old_index {
+        let after = new_index.remove(&id);
+        let key = if by_id { change_key(before) } else { id };
+        let deprecated = after.map_or(false, |after| after.is_public() && after.is_deprecated && !before.is_deprecated);
+        let kind = match after {
+            None if before.is_public() => Some(ChangeKind::Removed),
+            Some(after) if before.is_public() != after.is_public() => Some(ChangeKind::VisibilityChanged),
+            Some(after) if !after.is_public() => None,
+            Some(after) if before.code != after.code || before.entity_type != after.entity_type => {
+                Some(ChangeKind::SignatureChanged)
+            }
+            Some(_) if deprecated => None,
+            Some(after) if before.docstring != after.docstring => Some(ChangeKind::DocstringChanged),
+            // Either side may come from an analysis that didn't hash sources
+            Some(after)
+                if !before.source_hash.is_empty()
+                    && !after.source_hash.is_empty()
+                    && before.source_hash != after.source_hash =>
+            {
+                Some(ChangeKind::BodyChanged)
+            }
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            changes.push(change(kind, key.clone(), Some(before), after));
+        }
+        if deprecated {
+            changes.push(change(ChangeKind::Deprecated, key, Some(before), after));
+        }
+    }
+
+    for (id, after) in new_index.into_iter().filter(|(_, e)| e.is_public()) {
+        let key = if by_id { change_key(after) } else { id };
+        changes.push(change(ChangeKind::Added, key, None, Some(after)));
+    }
+
+    changes.sort_by(|a, b| a.key.cmp(&b.key).then(a.kind.cmp(&b.kind)));
+    ApiDiff { changes }
+}
+
+/// Strip `root` from every entity's file path
+pub fn relativize(entities: &mut [CodeEntity], root: &Path) {
+    for entity in entities {
+        if let Ok(relative) = entity.file_path.strip_prefix(root) {
+            entity.file_path = relative.into();
+        }
+    }
+}
+
+fn index_entities(entities: &[CodeEntity], by_id: bool, qualified: bool) -> BTreeMap<String, &CodeEntity> {
+    if by_id {
+        return entities.iter().map(|entity| (entity.entity_id.clone(), entity)).collect();
+    }
+
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut index = BTreeMap::new();
+
+    for entity in entities {
+        let name = if qualified { &entity.qualified_name } else { &entity.name };
+        let base = format!("{}::{}", entity.file_path.to_string_lossy().replace('\\', "/"), name);
+        // Disambiguate same-named entities in one file (e.g. methods of
+        // different classes when unqualified, overloads otherwise)
+        let occurrence = seen.entry(base.clone()).or_insert(0);
+        *occurrence += 1;
+        let key = if *occurrence == 1 { base } else { format!("{}#{}", base, occurrence) };
+        index.insert(key, entity);
+    }
+
+    index
+}
+
+/// Qualified name, or the name qualified by the file path for entities
+/// the analysis couldn't place in a module
+fn change_key(entity: &CodeEntity) -> String {
+    if entity.qualified_name.is_empty() {
+        format!("{}::{}", entity.file_path.to_string_lossy().replace('\\', "/"), entity.name)
+    } else {
+        entity.qualified_name.clone()
+    }
+}
+
+fn change(kind: ChangeKind, key: String, before: Option<&CodeEntity>, after: Option<&CodeEntity>) -> EntityChange {
+    let entity = after.or(before).expect("a change has at least one side");
+    EntityChange {
+        kind,
+        key,
+        entity_type: entity.entity_type.to_string(),
+        name: entity.name.clone(),
+        file_path: entity.file_path.to_string_lossy().into_owned(),
+        old_signature: before.map(|e| e.code.clone()),
+        new_signature: after.map(|e| e.code.clone()),
+        old_docstring: before.and_then(|e| e.docstring.clone()),
+        new_docstring: after.and_then(|e| e.docstring.clone()),
+        old_public: before.map(CodeEntity::is_public),
+        new_public: after.map(CodeEntity::is_public),
+        replacement: entity.replacement.clone(),
+        line_number: entity.line_number,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::slice;
+
+    fn entity(name: &str, code: &str) -> CodeEntity {
+        let mut entity = CodeEntity::new(
+            "function".to_string(),
+            name.to_string(),
+            PathBuf::from("api.py"),
+            1,
+        );
+        entity.code = code.to_string();
+        entity
+    }
+
+    #[test]
+    fn test_diff_classifies_changes() {
+        let old = vec![
+            entity("kept", "def kept():"),
+            entity("removed", "def removed():"),
+            entity("changed", "def changed(a):"),
+            entity("_private", "def _private():"),
+        ];
+        let mut documented = entity("kept", "def kept():");
+        documented.docstring = Some("Now documented.".to_string());
+        let new = vec![
+            documented,
+            entity("changed", "def changed(a, b):"),
+            entity("added", "def added():"),
+        ];
+
+        let diff = diff_entities(&old, &new);
+
+        assert_eq!(diff.count(ChangeKind::Added), 1);
+        assert_eq!(diff.count(ChangeKind::Removed), 1);
+        assert_eq!(diff.count(ChangeKind::SignatureChanged), 1);
+        assert_eq!(diff.count(ChangeKind::DocstringChanged), 1);
+
+        let changed = diff.changes.iter().find(|c| c.name == "changed").unwrap();
+        assert_eq!(changed.old_signature.as_deref(), Some("def changed(a):"));
+        assert_eq!(changed.new_signature.as_deref(), Some("def changed(a, b):"));
+    }
+
+    #[test]
+    fn test_visibility_changes() {
+        let old = vec![entity("hidden", "def hidden():"), entity("_exposed", "def _exposed():")];
+        let mut hidden = entity("hidden", "def hidden():");
+        hidden.is_internal = true;
+        let new = vec![hidden, entity("_exposed", "def _exposed():")];
+
+        let diff = diff_entities(&old, &new);
+
+        assert_eq!(diff.changes.len(), 1);
+        let change = &diff.changes[0];
+        assert_eq!(change.kind, ChangeKind::VisibilityChanged);
+        assert_eq!((change.old_public, change.new_public), (Some(true), Some(false)));
+    }
+
+    #[test]
+    fn test_body_changes() {
+        let mut before = entity("run", "def run():");
+        before.source_hash = "1".to_string();
+        let mut after = before.clone();
+        after.source_hash = "2".to_string();
+        let diff = diff_entities(&[before.clone()], &[after]);
+        assert_eq!(diff.count(ChangeKind::BodyChanged), 1);
+
+        let mut unhashed = before.clone();
+        unhashed.source_hash.clear();
+        assert!(diff_entities(&[before], &[unhashed]).is_empty());
+    }
+
+    #[test]
+    fn test_deprecation_alongside_signature_change() {
+        let before = entity("fetch", "def fetch(url):");
+        let mut after = entity("fetch", "def fetch(url, timeout):");
+        after.is_deprecated = true;
+        let diff = diff_entities(slice::from_ref(&before), slice::from_ref(&after));
+        let kinds: Vec<ChangeKind> = diff.changes.iter().map(|c| c.kind).collect();
+        assert_eq!(kinds, vec![ChangeKind::SignatureChanged, ChangeKind::Deprecated]);
+
+        after.code = before.code.clone();
+        after.docstring = Some(".. deprecated:: 2.0".to_string());
+        let diff = diff_entities(&[before], &[after]);
+        assert_eq!(diff.changes.len(), 1);
+        assert_eq!(diff.changes[0].kind, ChangeKind::Deprecated);
+    }
+
+    #[test]
+    fn test_matches_entities_by_id() {
+        let method = |qualified: &str, code: &str| {
+            let mut entity = entity("get", code);
+            entity.qualified_name = qualified.to_string();
+            entity
+        };
+        let mut old = vec![method("api.A.get", "def get(self):"), method("api.B.get", "def get(self, key):")];
+        let mut new = vec![method("api.B.get", "def get(self, key):")];
+        crate::identity::assign_ids(&mut old, None);
+        crate::identity::assign_ids(&mut new, None);
+
+        let diff = diff_entities(&old, &new);
+        assert_eq!(diff.changes.len(), 1);
+        assert_eq!(diff.changes[0].kind, ChangeKind::Removed);
+        assert_eq!(diff.changes[0].key, "api.A.get");
+    }
+
+    #[test]
+    fn test_identical_analyses_have_no_changes() {
+        let entities = vec![entity("same", "def same():")];
+        assert!(diff_entities(&entities, &entities).is_empty());
+    }
+}
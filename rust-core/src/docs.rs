@@ -0,0 +1,390 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use walkdir::WalkDir;
+
+use crate::analyzer::RustAnalyzer;
+use crate::entity::CodeEntity;
+
+/// Markup flavours of prose documentation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocFormat {
+    Markdown,
+    ReStructuredText,
+}
+
+impl DocFormat {
+    fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "md" | "markdown" => Some(DocFormat::Markdown),
+            "rst" => Some(DocFormat::ReStructuredText),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Heading {
+    pub level: usize,
+    pub text: String,
+    pub line: usize,
+}
+
+/// A mention of a code symbol in prose, e.g. `` `parse()` `` or ``:func:`pkg.parse` ``
+#[derive(Debug, Clone, Serialize)]
+pub struct CodeReference {
+    pub target: String,
+    pub line: usize,
+    /// Whether the mention is unambiguously code (a role, a call or a dotted path)
+    /// rather than any backticked word
+    pub explicit: bool,
+}
+
+/// One indexed documentation file
+#[derive(Debug, Clone, Serialize)]
+pub struct DocPage {
+    pub path: PathBuf,
+    pub format: DocFormat,
+    pub headings: Vec<Heading>,
+    pub references: Vec<CodeReference>,
+}
+
+impl DocPage {
+    /// Title of the page: its first top-level heading
+    pub fn title(&self) -> Option<&str> {
+        self.headings.iter().min_by_key(|h| h.level).map(|h| h.text.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DocLocation {
+    pub page: PathBuf,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StaleReference {
+    pub target: String,
+    pub page: PathBuf,
+    pub line: usize,
+}
+
+/// How prose documentation relates to the analyzed code
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DocLinks {
+    /// Entity key (`path::qualified.name`) to the places mentioning it
+    pub documented: BTreeMap<String, Vec<DocLocation>>,
+    /// Explicit code references that match no entity
+    pub stale: Vec<StaleReference>,
+    /// Public entities no documentation page mentions
+    pub unreferenced: Vec<String>,
+}
+
+/// Index of the Markdown and reStructuredText files under a project root
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DocsIndex {
+    pub pages: Vec<DocPage>,
+}
+
+impl DocsIndex {
+    /// Index every `.md`/`.rst` file under `root`, honouring the analyzer's excludes
+    pub fn build(root: &Path, analyzer: &RustAnalyzer) -> Result<Self> {
+        let mut pages = Vec::new();
+
+        for entry in WalkDir::new(root)
+            .follow_links(analyzer.config().follow_symlinks)
+            .into_iter()
+            .filter_entry(|e| !analyzer.should_exclude(e.path()))
+        {
+            let entry = entry?;
+            let path = entry.path();
+            let Some(format) = DocFormat::from_path(path) else { continue };
+            if !path.is_file() {
+                continue;
+            }
+
+            let content = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {:?}", path))?;
+            pages.push(parse_page(path, format, &content));
+        }
+
+        pages.sort_by(|a, b| a.path.cmp(&b.path));
+        log::debug!("Indexed {} documentation pages under {:?}", pages.len(), root);
+        Ok(DocsIndex { pages })
+    }
+
+    /// Link references to entities by name, reporting stale references and
+    /// public entities that no page mentions
+    pub fn link(&self, entities: &[CodeEntity]) -> DocLinks {
+        let mut by_name: HashMap<&str, Vec<&CodeEntity>> = HashMap::new();
+        for entity in entities {
+            by_name.entry(entity.name.as_str()).or_default().push(entity);
+        }
+
+        let mut links = DocLinks::default();
+        for page in &self.pages {
+            for reference in &page.references {
+                match by_name.get(short_name(&reference.target)) {
+                    Some(matches) => {
+                        for entity in matches {
+                            links.documented.entry(entity_key(entity)).or_default().push(DocLocation {
+                                page: page.path.clone(),
+                                line: reference.line,
+                            });
+                        }
+                    }
+                    None if reference.explicit => links.stale.push(StaleReference {
+                        target: reference.target.clone(),
+                        page: page.path.clone(),
+                        line: reference.line,
+                    }),
+                    None => {}
+                }
+            }
+        }
+
+        links.unreferenced = entities
+            .iter()
+            .filter(|e| e.is_public())
+            .map(entity_key)
+            .filter(|key| !links.documented.contains_key(key))
+            .collect();
+        links
+    }
+
+    /// Record the pages mentioning each entity in its `doc_pages` metadata
+    pub fn annotate(&self, entities: &mut [CodeEntity]) {
+        let links = self.link(entities);
+        for entity in entities {
+            if let Some(locations) = links.documented.get(&entity_key(entity)) {
+                let mut pages: Vec<String> = locations.iter().map(|l| l.page.to_string_lossy().into_owned()).collect();
+                pages.dedup();
+                entity.metadata.insert("doc_pages".to_string(), pages.join(","));
+            }
+        }
+    }
+}
+
+/// Extract headings and code references from one documentation file
+pub fn parse_page(path: &Path, format: DocFormat, content: &str) -> DocPage {
+    let (headings, references) = match format {
+        DocFormat::Markdown => parse_markdown(content),
+        DocFormat::ReStructuredText => parse_rst(content),
+    };
+    DocPage {
+        path: path.to_path_buf(),
+        format,
+        headings,
+        references,
+    }
+}
+
+fn parse_markdown(content: &str) -> (Vec<Heading>, Vec<CodeReference>) {
+    let mut headings = Vec::new();
+    let mut references = Vec::new();
+    let mut in_fence = false;
+    let lines: Vec<&str> = content.lines().collect();
+
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let hashes = trimmed.chars().take_while(|&c| c == '#').count();
+        if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+            headings.push(Heading {
+                level: hashes,
+                text: trimmed[hashes..].trim().trim_end_matches('#').trim().to_string(),
+                line: i + 1,
+            });
+        } else if let Some(level) = lines.get(i + 1).and_then(|next| setext_level(next)) {
+            if !trimmed.is_empty() {
+                headings.push(Heading { level, text: trimmed.to_string(), line: i + 1 });
+            }
+        }
+
+        for span in inline_code().captures_iter(line) {
+            push_reference(&mut references, &span[1], i + 1, false);
+        }
+    }
+
+    (headings, references)
+}
+
+fn setext_level(underline: &str) -> Option<usize> {
+    let underline = underline.trim();
+    if underline.len() >= 2 && underline.chars().all(|c| c == '=') {
+        Some(1)
+    } else if underline.len() >= 2 && underline.chars().all(|c| c == '-') {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+fn parse_rst(content: &str) -> (Vec<Heading>, Vec<CodeReference>) {
+    let mut headings = Vec::new();
+    let mut references = Vec::new();
+    // Section levels follow the order in which underline characters first appear
+    let mut adornments: Vec<char> = Vec::new();
+    let mut in_literal = false;
+    let lines: Vec<&str> = content.lines().collect();
+
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if in_literal {
+            if trimmed.is_empty() || line.starts_with(char::is_whitespace) {
+                continue;
+            }
+            in_literal = false;
+        }
+        if trimmed.ends_with("::") {
+            in_literal = true;
+        }
+
+        if let Some(adornment) = lines.get(i + 1).and_then(|next| rst_adornment(next, trimmed)) {
+            let level = match adornments.iter().position(|&c| c == adornment) {
+                Some(index) => index + 1,
+                None => {
+                    adornments.push(adornment);
+                    adornments.len()
+                }
+            };
+            headings.push(Heading { level, text: trimmed.to_string(), line: i + 1 });
+            continue;
+        }
+
+        for role in rst_role().captures_iter(line) {
+            push_reference(&mut references, &role[1], i + 1, true);
+        }
+        for literal in rst_literal().captures_iter(line) {
+            push_reference(&mut references, &literal[1], i + 1, false);
+        }
+    }
+
+    (headings, references)
+}
+
+/// The underline character if `underline` adorns `title` as an RST section heading
+fn rst_adornment(underline: &str, title: &str) -> Option<char> {
+    let underline = underline.trim_end();
+    let first = underline.chars().next()?;
+    let is_adornment = "=-~^*+#\"'`:.".contains(first)
+        && underline.chars().all(|c| c == first)
+        && !title.is_empty()
+        && underline.chars().count() >= title.chars().count();
+    is_adornment.then_some(first)
+}
+
+fn push_reference(references: &mut Vec<CodeReference>, raw: &str, line: usize, from_role: bool) {
+    // Roles may carry an explicit target: :func:`title <pkg.target>`
+    let target = match (raw.find('<'), raw.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &raw[start + 1..end],
+        _ => raw,
+    };
+    let target = target.trim().trim_start_matches(['~', '!']);
+    if !identifier_path().is_match(target) {
+        return;
+    }
+
+    let explicit = from_role || target.ends_with("()") || target.contains('.') || target.contains("::");
+    references.push(CodeReference {
+        target: target.to_string(),
+        line,
+        explicit,
+    });
+}
+
+/// Last path segment of a reference without call parentheses: `pkg.mod.run()` → `run`
+fn short_name(target: &str) -> &str {
+    let target = target.trim_end_matches("()");
+    target.rsplit(['.', ':']).next().unwrap_or(target)
+}
+
+fn entity_key(entity: &CodeEntity) -> String {
+    let name = match entity.qualified_name.as_str() {
+        "" => entity.name.as_str(),
+        qualified => qualified,
+    };
+    format!("{}::{}", entity.file_path.to_string_lossy().replace('\\', "/"), name)
+}
+
+fn inline_code() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"`([^`\n]+)`").unwrap())
+}
+
+fn rst_role() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r":(?:[a-z]+:)?(?:func|meth|class|mod|attr|obj|data|exc|const):`([^`]+)`").unwrap())
+}
+
+fn rst_literal() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"``([^`]+)``").unwrap())
+}
+
+fn identifier_path() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^[A-Za-z_]\w*(?:(?:\.|::)[A-Za-z_]\w*)*(?:\(\))?$").unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_markdown_skips_fences() {
+        let content = "# Guide\n\nCall `load_config()` or `autodoc.render`.\n\n```python\n`ignored`\n```\n\nUsage\n-----\nRun `npm install` first.\n";
+        let page = parse_page(Path::new("docs/guide.md"), DocFormat::Markdown, content);
+
+        assert_eq!(page.title(), Some("Guide"));
+        assert_eq!(page.headings.len(), 2);
+        assert_eq!(page.headings[1].level, 2);
+
+        let targets: Vec<&str> = page.references.iter().map(|r| r.target.as_str()).collect();
+        assert_eq!(targets, vec!["load_config()", "autodoc.render"]);
+        assert!(page.references.iter().all(|r| r.explicit));
+    }
+
+    #[test]
+    fn test_parse_rst_roles_and_levels() {
+        let content = "Reference\n=========\n\nSee :func:`~pkg.api.fetch` and :class:`the client <pkg.Client>`.\n\nDetails\n-------\n\nUse ``retry`` here::\n\n    ``not_a_reference``\n";
+        let page = parse_page(Path::new("index.rst"), DocFormat::ReStructuredText, content);
+
+        let headings: Vec<(usize, &str)> = page.headings.iter().map(|h| (h.level, h.text.as_str())).collect();
+        assert_eq!(headings, vec![(1, "Reference"), (2, "Details")]);
+
+        let targets: Vec<&str> = page.references.iter().map(|r| r.target.as_str()).collect();
+        assert_eq!(targets, vec!["pkg.api.fetch", "pkg.Client", "retry"]);
+    }
+
+    #[test]
+    fn test_link_reports_stale_and_unreferenced() {
+        let entity = |name: &str| CodeEntity::new("function".to_string(), name.to_string(), PathBuf::from("api.py"), 1);
+        let entities = vec![entity("fetch"), entity("undocumented"), entity("_private")];
+        let index = DocsIndex {
+            pages: vec![parse_page(
+                Path::new("README.md"),
+                DocFormat::Markdown,
+                "Use `fetch()` (formerly `pkg.download()`), not `true`.",
+            )],
+        };
+
+        let links = index.link(&entities);
+        assert_eq!(links.documented.keys().collect::<Vec<_>>(), vec!["api.py::fetch"]);
+        assert_eq!(links.stale.len(), 1);
+        assert_eq!(links.stale[0].target, "pkg.download()");
+        assert_eq!(links.unreferenced, vec!["api.py::undocumented"]);
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::OnceLock;
+
+use rayon::prelude::*;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::context::{entity_source, SourceCache};
+use crate::entity::CodeEntity;
+use crate::identity::fnv1a;
+
+/// How closely the members of a duplicate group match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateKind {
+    /// Identical once comments, whitespace and the entity's own name are ignored
+    Exact,
+    /// Shingle similarity at or above the threshold
+    Near,
+}
+
+impl DuplicateKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DuplicateKind::Exact => "exact",
+            DuplicateKind::Near => "near",
+        }
+    }
+}
+
+/// Entities that are copies of one another
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateGroup {
+    pub kind: DuplicateKind,
+    /// Indices into the analyzed slice, ascending; the first is the canonical copy
+    pub members: Vec<usize>,
+    /// Lowest Jaccard similarity that joined the group; 1.0 for exact groups
+    pub similarity: f64,
+}
+
+/// Tuning for `find_duplicates`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DuplicateOptions {
+    /// Bodies with fewer normalized tokens are ignored, so trivial one-liners
+    /// such as `pass` or `return self.x` are not reported
+    pub min_tokens: usize,
+    /// Also look for near-duplicates by comparing token shingles
+    pub near: bool,
+    /// Tokens per shingle
+    pub shingle_size: usize,
+    /// Minimum Jaccard similarity of two shingle sets for a near-duplicate
+    pub threshold: f64,
+}
+
+impl Default for DuplicateOptions {
+    fn default() -> Self {
+        DuplicateOptions {
+            min_tokens: 20,
+            near: false,
+            shingle_size: 4,
+            threshold: 0.8,
+        }
+    }
+}
+
+/// Group copy-pasted entities of the same type.
+///
+/// Bodies are read from the source files (falling back to `code`), then
+/// reduced to tokens without comments or whitespace, with the entity's own
+/// name replaced by a placeholder so renamed copies still match. Equal token
+/// sequences form exact groups. With `options.near`, exact groups and the
+/// remaining entities are compared pairwise by shingle similarity, and
+/// transitively similar ones are merged into near groups that include every
+/// exact copy. Groups are ordered by their first member.
+pub fn find_duplicates(entities: &[CodeEntity], options: DuplicateOptions) -> Vec<DuplicateGroup> {
+    let mut sources = SourceCache::default();
+    let bodies: Vec<String> = entities
+        .iter()
+        .map(|entity| entity_source(entity, sources.get(entity.source_path())))
+        .collect();
+    let tokens: Vec<Vec<String>> = bodies
+        .par_iter()
+        .zip(entities.par_iter())
+        .map(|(body, entity)| normalized_tokens(body, &entity.name))
+        .collect();
+
+    let mut buckets: BTreeMap<(&str, u64), Vec<usize>> = BTreeMap::new();
+    for (index, entity) in entities.iter().enumerate() {
+        if tokens[index].len() >= options.min_tokens {
+            let hash = fnv1a(tokens[index].join(" ").as_bytes());
+            buckets.entry((entity.entity_type.as_str(), hash)).or_default().push(index);
+        }
+    }
+    let buckets: Vec<Vec<usize>> = buckets.into_values().collect();
+
+    let mut groups: Vec<DuplicateGroup> = buckets
+        .iter()
+        .filter(|members| members.len() > 1)
+        .map(|members| DuplicateGroup {
+            kind: DuplicateKind::Exact,
+            members: members.clone(),
+            similarity: 1.0,
+        })
+        .collect();
+    if options.near {
+        groups.extend(near_groups(entities, &tokens, &buckets, options));
+    }
+    groups.sort_by_key(|group| group.members[0]);
+    groups
+}
+
+/// Record duplicates in entity metadata: `duplicate_of` (exact) or
+/// `similar_to` (near) holds the entity_id of the group's first member
+pub fn mark_duplicates(entities: &mut [CodeEntity], groups: &[DuplicateGroup]) {
+    for group in groups {
+        let canonical = entities[group.members[0]].entity_id.clone();
+        let key = match group.kind {
+            DuplicateKind::Exact => "duplicate_of",
+            DuplicateKind::Near => "similar_to",
+        };
+        for &member in &group.members[1..] {
+            entities[member].metadata.insert(key.to_string(), canonical.clone());
+        }
+    }
+}
+
+/// Merge buckets whose representatives' shingle sets are similar enough
+fn near_groups(
+    entities: &[CodeEntity],
+    tokens: &[Vec<String>],
+    buckets: &[Vec<usize>],
+    options: DuplicateOptions,
+) -> Vec<DuplicateGroup> {
+    let shingles: Vec<HashSet<u64>> = buckets
+        .par_iter()
+        .map(|members| shingle_set(&tokens[members[0]], options.shingle_size.max(1)))
+        .collect();
+
+    let edges: Vec<(usize, usize, f64)> = (0..buckets.len())
+        .into_par_iter()
+        .flat_map_iter(|a| {
+            let shingles = &shingles;
+            (a + 1..buckets.len()).filter_map(move |b| {
+                let (first, second) = (&entities[buckets[a][0]], &entities[buckets[b][0]]);
+                if first.entity_type != second.entity_type {
+                    return None;
+                }
+                let (len_a, len_b) = (shingles[a].len(), shingles[b].len());
+                // Jaccard similarity can't exceed the ratio of the set sizes
+                if (len_a.min(len_b) as f64) < options.threshold * len_a.max(len_b) as f64 {
+                    return None;
+                }
+                let shared = shingles[a].intersection(&shingles[b]).count();
+                let similarity = shared as f64 / (len_a + len_b - shared) as f64;
+                (similarity >= options.threshold).then_some((a, b, similarity))
+            })
+        })
+        .collect();
+
+    let mut parent: Vec<usize> = (0..buckets.len()).collect();
+    for &(a, b, _) in &edges {
+        let (ra, rb) = (root(&mut parent, a), root(&mut parent, b));
+        parent[ra.max(rb)] = ra.min(rb);
+    }
+
+    let mut components: HashMap<usize, (Vec<usize>, f64)> = HashMap::new();
+    for &(a, _, similarity) in &edges {
+        let component = components.entry(root(&mut parent, a)).or_insert((Vec::new(), 1.0));
+        component.1 = component.1.min(similarity);
+    }
+    for (bucket, members) in buckets.iter().enumerate() {
+        if let Some(component) = components.get_mut(&root(&mut parent, bucket)) {
+            component.0.extend(members);
+        }
+    }
+
+    components
+        .into_values()
+        .map(|(mut members, similarity)| {
+            members.sort_unstable();
+            DuplicateGroup {
+                kind: DuplicateKind::Near,
+                members,
+                similarity,
+            }
+        })
+        .collect()
+}
+
+/// Union-find lookup with path halving
+fn root(parent: &mut [usize], mut node: usize) -> usize {
+    while parent[node] != node {
+        parent[node] = parent[parent[node]];
+        node = parent[node];
+    }
+    node
+}
+
+/// Tokens of `body` without comments, with `name` replaced by `$name`
+fn normalized_tokens(body: &str, name: &str) -> Vec<String> {
+    static TOKEN: OnceLock<Regex> = OnceLock::new();
+    let token = TOKEN.get_or_init(|| {
+        Regex::new(r#"[A-Za-z_][A-Za-z0-9_]*|\d+(?:\.\d+)?|"(?:[^"\\]|\\.)*"|'(?:[^'\\]|\\.)*'|\S"#).unwrap()
+    });
+    body.lines()
+        .filter(|line| {
+            let line = line.trim_start();
+            !line.starts_with('#') && !line.starts_with("//")
+        })
+        .flat_map(|line| token.find_iter(line))
+        .map(|m| match m.as_str() {
+            t if t == name => "$name".to_string(),
+            t => t.to_string(),
+        })
+        .collect()
+}
+
+fn shingle_set(tokens: &[String], size: usize) -> HashSet<u64> {
+    tokens
+        .windows(size.min(tokens.len()).max(1))
+        .map(|window| fnv1a(window.join(" ").as_bytes()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::RustAnalyzer;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const SOURCE: &str = r#"def total(items):
+    result = 0
+    for item in items:
+        if item.price > 0:
+            result += item.price * item.quantity
+    return result
+
+
+def sum_prices(items):
+    # copied from total
+    result = 0
+    for item in items:
+        if item.price > 0:
+            result += item.price * item.quantity
+    return result
+
+
+def sum_costs(items):
+    result = 0
+    for item in items:
+        if item.cost > 0:
+            result += item.cost * item.quantity
+    return result
+
+
+def noop():
+    pass
+
+
+def other_noop():
+    pass
+"#;
+
+    fn analyze() -> (TempDir, Vec<CodeEntity>) {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("totals.py");
+        fs::write(&path, SOURCE).unwrap();
+        let entities = RustAnalyzer::new().analyze_file(&path).unwrap();
+        (temp_dir, entities)
+    }
+
+    fn names<'a>(entities: &'a [CodeEntity], group: &DuplicateGroup) -> Vec<&'a str> {
+        group.members.iter().map(|&i| entities[i].name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_exact_duplicates_ignore_names_and_comments() {
+        let (_dir, mut entities) = analyze();
+        let groups = find_duplicates(&entities, DuplicateOptions::default());
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].kind, DuplicateKind::Exact);
+        assert_eq!(names(&entities, &groups[0]), vec!["total", "sum_prices"]);
+
+        mark_duplicates(&mut entities, &groups);
+        let total_id = entities[groups[0].members[0]].entity_id.clone();
+        assert_eq!(entities[groups[0].members[1]].metadata.get("duplicate_of"), Some(&total_id));
+    }
+
+    #[test]
+    fn test_near_duplicates_include_exact_copies() {
+        let (_dir, entities) = analyze();
+        let options = DuplicateOptions {
+            near: true,
+            threshold: 0.5,
+            ..DuplicateOptions::default()
+        };
+        let groups = find_duplicates(&entities, options);
+
+        let near: Vec<&DuplicateGroup> = groups.iter().filter(|g| g.kind == DuplicateKind::Near).collect();
+        assert_eq!(near.len(), 1);
+        assert_eq!(names(&entities, near[0]), vec!["total", "sum_prices", "sum_costs"]);
+        assert!(near[0].similarity >= 0.5 && near[0].similarity < 1.0);
+    }
+}
@@ -0,0 +1,627 @@
+use regex::Regex;
+use rustpython_parser::ast;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::entity::CodeEntity;
+use crate::parser::expr_to_string;
+use crate::walk::{self, argument, dotted_name, keyword, literal};
+
+/// Where a request carries an endpoint parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParamLocation {
+    Path,
+    Query,
+    Body,
+    Header,
+    Cookie,
+    Form,
+    File,
+    /// Injected by the framework (`Depends`, `Security`) rather than sent
+    Dependency,
+}
+
+impl ParamLocation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ParamLocation::Path => "path",
+            ParamLocation::Query => "query",
+            ParamLocation::Body => "body",
+            ParamLocation::Header => "header",
+            ParamLocation::Cookie => "cookie",
+            ParamLocation::Form => "form",
+            ParamLocation::File => "file",
+            ParamLocation::Dependency => "dependency",
+        }
+    }
+
+    /// The location a FastAPI parameter function declares, e.g. `Query(...)`
+    fn from_marker(name: &str) -> Option<Self> {
+        match name {
+            "Path" => Some(ParamLocation::Path),
+            "Query" => Some(ParamLocation::Query),
+            "Body" => Some(ParamLocation::Body),
+            "Header" => Some(ParamLocation::Header),
+            "Cookie" => Some(ParamLocation::Cookie),
+            "Form" => Some(ParamLocation::Form),
+            "File" | "UploadFile" => Some(ParamLocation::File),
+            "Depends" | "Security" => Some(ParamLocation::Dependency),
+            _ => None,
+        }
+    }
+}
+
+impl std::str::FromStr for ParamLocation {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "path" => Ok(ParamLocation::Path),
+            "query" => Ok(ParamLocation::Query),
+            "body" => Ok(ParamLocation::Body),
+            "header" => Ok(ParamLocation::Header),
+            "cookie" => Ok(ParamLocation::Cookie),
+            "form" => Ok(ParamLocation::Form),
+            "file" => Ok(ParamLocation::File),
+            "dependency" => Ok(ParamLocation::Dependency),
+            other => Err(anyhow::anyhow!("Unknown parameter location: {}", other)),
+        }
+    }
+}
+
+/// One row of an endpoint's parameter table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointParam {
+    pub name: String,
+    pub location: ParamLocation,
+    /// Annotation, or the converter of a path parameter (`<int:id>` -> `int`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotation: Option<String>,
+    /// Default as written, e.g. `10` or `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    pub required: bool,
+}
+
+/// Types FastAPI reads from the query string rather than the body
+const SCALARS: &[&str] = &[
+    "str", "int", "float", "bool", "bytes", "UUID", "datetime", "date", "time", "timedelta", "Decimal", "Any",
+];
+
+/// Containers that stay query parameters when their items are scalars
+const CONTAINERS: &[&str] = &[
+    "Optional", "Union", "Annotated", "Literal", "List", "list", "Set", "set", "Sequence", "Iterable", "Tuple", "tuple",
+    "FrozenSet", "frozenset", "Dict", "dict",
+];
+
+/// Framework objects passed to handlers that are not request parameters
+const INJECTED: &[&str] = &["Request", "Response", "WebSocket", "BackgroundTasks", "HttpRequest", "HTTPConnection"];
+
+/// Placeholders in a route template with their converter, in order:
+/// `{id}`, `{id:int}` (FastAPI/Starlette), `<id>`, `<int:id>` (Flask,
+/// Django) and `(?P<id>...)` (Django regex routes)
+pub fn path_params(template: &str) -> Vec<(String, Option<String>)> {
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+    let placeholder = PLACEHOLDER.get_or_init(|| {
+        Regex::new(r"\{(\w+)(?::(\w+))?\}|\(\?P<(\w+)>|<(?:(\w+):)?(\w+)>").unwrap()
+    });
+    placeholder
+        .captures_iter(template)
+        .filter_map(|c| {
+            let (name, converter) = match (c.get(1), c.get(3), c.get(5)) {
+                (Some(name), _, _) => (name, c.get(2)),
+                (_, Some(name), _) => (name, None),
+                (_, _, Some(name)) => (name, c.get(4)),
+                _ => return None,
+            };
+            Some((name.as_str().to_string(), converter.map(|m| m.as_str().to_string())))
+        })
+        .collect()
+}
+
+/// `entity_type` of routers imported from another module and mounted with
+/// a prefix; see `link_routers`
+pub const ROUTER_ENTITY: &str = "router";
+
+/// Bounds the mounts followed from one router, so cycles end
+const MAX_MOUNT_DEPTH: usize = 8;
+
+/// Decorators pinning a handler to an API version, e.g. fastapi-versioning's
+/// `@version(2)`
+const VERSION_DECORATORS: &[&str] = &["version", "api_version", "versioned"];
+
+/// The API version in a path or prefix: `/api/v2/users` gives `v2`
+pub fn path_version(path: &str) -> Option<String> {
+    static VERSION: OnceLock<Regex> = OnceLock::new();
+    let version = VERSION.get_or_init(|| Regex::new(r"(?i)(?:^|/)(v\d+(?:\.\d+)*)(?:/|$)").unwrap());
+    version.captures(path).map(|c| c[1].to_ascii_lowercase())
+}
+
+/// URL prefixes of module-level routers, by variable name: `APIRouter` and
+/// `Blueprint` `prefix`/`url_prefix` arguments, joined with the prefix of
+/// any `include_router`/`register_blueprint` call mounting them
+pub fn router_prefixes(module: &[ast::Stmt]) -> HashMap<String, String> {
+    let mut prefixes: HashMap<String, String> = HashMap::new();
+    for stmt in module {
+        if let ast::Stmt::Assign(assign) = stmt {
+            let ast::Expr::Call(call) = &*assign.value else { continue };
+            let Some(prefix) = prefix(call) else { continue };
+            for target in &assign.targets {
+                if let ast::Expr::Name(name) = target {
+                    prefixes.insert(name.id.to_string(), prefix.clone());
+                }
+            }
+        } else if let Some((_, router, at)) = mount(stmt) {
+            let own = prefixes.remove(&router).unwrap_or_default();
+            prefixes.insert(router, format!("{}{}", at.trim_end_matches('/'), own));
+        }
+    }
+    prefixes
+}
+
+fn prefix(call: &ast::ExprCall) -> Option<String> {
+    keyword(call, "prefix")
+        .or_else(|| keyword(call, "url_prefix"))
+        .and_then(walk::string_literal)
+        .map(str::to_string)
+}
+
+/// A statement mounting a router with a prefix, as the object mounted on,
+/// the router and the prefix: `app.include_router(users.router,
+/// prefix="/v1")` gives `("app", "users.router", "/v1")`
+pub fn mount(stmt: &ast::Stmt) -> Option<(String, String, String)> {
+    let ast::Stmt::Expr(expr) = stmt else { return None };
+    let ast::Expr::Call(call) = &*expr.value else { return None };
+    let ast::Expr::Attribute(method) = &*call.func else { return None };
+    if !matches!(method.attr.as_str(), "include_router" | "register_blueprint") {
+        return None;
+    }
+    Some((dotted_name(&method.value)?, dotted_name(call.args.first()?)?, prefix(call)?))
+}
+
+/// The object a handler is registered on: `router` for `@router.get("/")`
+pub fn router_of(decorators: &[ast::Expr]) -> Option<String> {
+    decorators.iter().find_map(|decorator| {
+        let ast::Expr::Call(call) = decorator else { return None };
+        let ast::Expr::Attribute(method) = &*call.func else { return None };
+        dotted_name(&method.value)
+    })
+}
+
+/// The API version of an endpoint: from a versioning decorator, else its
+/// path, else the prefix of the router it is registered on
+pub fn api_version(decorators: &[ast::Expr], path: Option<&str>, prefixes: &HashMap<String, String>) -> Option<String> {
+    let calls = || {
+        decorators.iter().filter_map(|decorator| match decorator {
+            ast::Expr::Call(call) => Some(call),
+            _ => None,
+        })
+    };
+    let declared = calls().find_map(|call| {
+        let name = dotted_name(&call.func)?;
+        if !VERSION_DECORATORS.contains(&name.rsplit('.').next().unwrap_or(&name)) {
+            return None;
+        }
+        let version = literal(call.args.first()?)?;
+        Some(if version.starts_with(['v', 'V']) { version.to_ascii_lowercase() } else { format!("v{}", version) })
+    });
+    declared.or_else(|| path.and_then(path_version)).or_else(|| {
+        calls().find_map(|call| {
+            let ast::Expr::Attribute(method) = &*call.func else { return None };
+            let router = dotted_name(&method.value)?;
+            prefixes.get(&router).and_then(|prefix| path_version(prefix))
+        })
+    })
+}
+
+/// Versions of endpoints on routers mounted from other modules.
+///
+/// An endpoint the parser found no version for keeps its router in
+/// `metadata["router"]`; a `router` entity in the mounting module records
+/// the dotted path it mounts in `metadata["mounts"]` and the prefix in
+/// `endpoint_path`. Mount paths match a router's module path exactly or as
+/// a suffix, which covers relative imports, and mounts of mounts are
+/// followed until a prefix names a version.
+pub fn link_routers(entities: &mut [CodeEntity]) {
+    let mounts: Vec<(&str, String, &str)> = entities
+        .iter()
+        .filter(|entity| entity.entity_type == ROUTER_ENTITY)
+        .filter_map(|entity| {
+            let target = entity.metadata.get("mounts")?;
+            let on = entity.metadata.get("mounted_on")?;
+            let on = format!("{}.{}", entity.module_path, on);
+            Some((target.as_str(), on, entity.endpoint_path.as_deref().unwrap_or_default()))
+        })
+        .collect();
+    if mounts.is_empty() {
+        return;
+    }
+    let mounted = |router: &str| {
+        mounts.iter().find(|(target, _, _)| {
+            router == *target || router.strip_suffix(*target).map_or(false, |head| head.ends_with('.'))
+        })
+    };
+
+    let mut versions: Vec<(usize, String)> = Vec::new();
+    for (index, entity) in entities.iter().enumerate() {
+        if !entity.is_api_endpoint || entity.api_version.is_some() {
+            continue;
+        }
+        let Some(router) = entity.metadata.get("router") else { continue };
+        let mut router = format!("{}.{}", entity.module_path, router);
+        for _ in 0..MAX_MOUNT_DEPTH {
+            let Some((_, on, prefix)) = mounted(&router) else { break };
+            if let Some(version) = path_version(prefix) {
+                versions.push((index, version));
+                break;
+            }
+            router = on.clone();
+        }
+    }
+    for (index, version) in versions {
+        entities[index].api_version = Some(version);
+    }
+}
+
+/// Endpoints grouped by `api_version`, oldest version first and
+/// unversioned endpoints last
+pub fn by_version(entities: &[CodeEntity]) -> Vec<(Option<String>, Vec<&CodeEntity>)> {
+    let mut groups: Vec<(Option<String>, Vec<&CodeEntity>)> = Vec::new();
+    for entity in entities.iter().filter(|e| e.is_api_endpoint) {
+        match groups.iter_mut().find(|(version, _)| *version == entity.api_version) {
+            Some((_, members)) => members.push(entity),
+            None => groups.push((entity.api_version.clone(), vec![entity])),
+        }
+    }
+    groups.sort_by_key(|(version, _)| {
+        let numbers: Option<Vec<u64>> = version.as_deref().map(|v| {
+            v.trim_start_matches('v').split('.').map(|part| part.parse().unwrap_or(u64::MAX)).collect()
+        });
+        (numbers.is_none(), numbers)
+    });
+    groups
+}
+
+/// The parameter table of an endpoint handler with arguments `args`,
+/// mounted at `path` and answering `methods`.
+///
+/// Explicit markers win (`q: int = Query(10)`, `Annotated[str, Header()]`);
+/// otherwise names in the path template are path parameters, class-typed
+/// parameters of methods with a body are the body, and everything else is
+/// read from the query string. `self`, `request` and framework objects are
+/// left out; path placeholders the handler doesn't name come last.
+pub fn parameters(path: Option<&str>, methods: &[String], args: &ast::Arguments) -> Vec<EndpointParam> {
+    let placeholders = path.map(path_params).unwrap_or_default();
+    let has_body = methods.iter().any(|m| matches!(m.as_str(), "POST" | "PUT" | "PATCH"));
+
+    let mut params = Vec::new();
+    let all = args.posonlyargs.iter().chain(&args.args).chain(&args.kwonlyargs);
+    for (index, arg) in all.enumerate() {
+        let name = arg.def.arg.as_str();
+        let annotation = arg.def.annotation.as_deref();
+        let rendered = annotation.map(expr_to_string);
+        if index == 0 && matches!(name, "self" | "cls") {
+            continue;
+        }
+        if rendered.as_deref().map_or(matches!(name, "request" | "req"), is_injected) {
+            continue;
+        }
+
+        let marker = arg
+            .default
+            .as_deref()
+            .and_then(|default| marker_call(default).map(|call| (call, true)))
+            .or_else(|| annotated_marker(annotation).map(|call| (call, false)));
+        let (location, default) = match marker {
+            Some(((location, call), in_default)) => {
+                let value = argument(call, 0, "default");
+                let default = match (in_default, location) {
+                    (_, ParamLocation::Dependency) => value.map(expr_to_string),
+                    (true, _) => value.filter(|v| !is_ellipsis(v)).map(render_default),
+                    (false, _) => arg.default.as_deref().map(render_default),
+                };
+                (location, default)
+            }
+            None => {
+                let location = if placeholders.iter().any(|(placeholder, _)| placeholder == name) {
+                    ParamLocation::Path
+                } else if has_body && rendered.as_deref().map_or(false, is_model) {
+                    ParamLocation::Body
+                } else {
+                    ParamLocation::Query
+                };
+                (location, arg.default.as_deref().map(render_default))
+            }
+        };
+
+        let converter = placeholders.iter().find(|(placeholder, _)| placeholder == name).and_then(|(_, c)| c.clone());
+        params.push(EndpointParam {
+            name: name.to_string(),
+            location,
+            annotation: rendered.or(converter),
+            required: default.is_none() && location != ParamLocation::Dependency,
+            default,
+        });
+    }
+
+    for (name, converter) in placeholders {
+        if !params.iter().any(|param| param.name == name) {
+            params.push(EndpointParam {
+                name,
+                location: ParamLocation::Path,
+                annotation: converter,
+                default: None,
+                required: true,
+            });
+        }
+    }
+    params
+}
+
+/// `Query(...)`, `fastapi.Depends(get_db)` and the location they declare
+pub(crate) fn marker_call(expr: &ast::Expr) -> Option<(ParamLocation, &ast::ExprCall)> {
+    let ast::Expr::Call(call) = expr else { return None };
+    let name = dotted_name(&call.func)?;
+    let location = ParamLocation::from_marker(name.rsplit('.').next().unwrap_or(&name))?;
+    Some((location, call))
+}
+
+/// The marker inside `Annotated[int, Query(gt=0)]`
+pub(crate) fn annotated_marker(annotation: Option<&ast::Expr>) -> Option<(ParamLocation, &ast::ExprCall)> {
+    let ast::Expr::Subscript(subscript) = annotation? else { return None };
+    if !dotted_name(&subscript.value).map_or(false, |name| name.ends_with("Annotated")) {
+        return None;
+    }
+    let ast::Expr::Tuple(items) = &*subscript.slice else { return None };
+    items.elts.iter().skip(1).find_map(marker_call)
+}
+
+fn render_default(expr: &ast::Expr) -> String {
+    literal(expr).map_or_else(|| expr_to_string(expr), |value| match expr {
+        ast::Expr::Constant(c) if matches!(c.value, ast::Constant::Str(_)) => format!("{:?}", value),
+        _ => value,
+    })
+}
+
+fn is_ellipsis(expr: &ast::Expr) -> bool {
+    matches!(expr, ast::Expr::Constant(c) if matches!(c.value, ast::Constant::Ellipsis))
+}
+
+fn is_injected(annotation: &str) -> bool {
+    let name = annotation.rsplit('.').next().unwrap_or(annotation);
+    INJECTED.contains(&name)
+}
+
+/// Whether an annotation names a model rather than scalars: `UserIn`,
+/// `list[Item]`, but not `int`, `Optional[str]` or `list[int] | None`
+fn is_model(annotation: &str) -> bool {
+    model_names(annotation).next().is_some()
+}
+
+/// The classes an annotation names, as written: `Page[schemas.User] | None`
+/// gives `Page` and `schemas.User`
+fn model_names(annotation: &str) -> impl Iterator<Item = &str> {
+    annotation
+        .split(['[', ']', ',', '|'])
+        .map(str::trim)
+        .filter(|part| !part.is_empty() && !part.contains('('))
+        .filter(|part| {
+            let last = part.rsplit('.').next().unwrap_or(part);
+            !SCALARS.contains(&last) && !CONTAINERS.contains(&last) && last.starts_with(char::is_uppercase)
+        })
+}
+
+/// The `response_model=` of a route decorator, as written
+pub fn response_model(decorators: &[ast::Expr]) -> Option<String> {
+    decorators.iter().find_map(|decorator| match decorator {
+        ast::Expr::Call(call) => keyword(call, "response_model")
+            .filter(|model| literal(model).is_none())
+            .map(expr_to_string),
+        _ => None,
+    })
+}
+
+/// Whether a call to `callee` instantiates a DRF serializer, e.g.
+/// `UserSerializer(data=...)`
+pub(crate) fn is_serializer(callee: &str) -> bool {
+    let last = callee.rsplit('.').next().unwrap_or(callee);
+    last.ends_with("Serializer") && last != "Serializer"
+}
+
+/// The `serializer_class` of a DRF class-based view
+pub fn serializer_class(body: &[ast::Stmt]) -> Option<String> {
+    body.iter().find_map(|stmt| {
+        let ast::Stmt::Assign(assign) = stmt else { return None };
+        let is_attribute = assign
+            .targets
+            .iter()
+            .any(|target| matches!(target, ast::Expr::Name(name) if name.id.as_str() == "serializer_class"));
+        if is_attribute { dotted_name(&assign.value) } else { None }
+    })
+}
+
+/// Link endpoints and the classes describing their payloads.
+///
+/// Body parameters are request models and `response_model=` a response
+/// model; DRF serializers are both, except on views that only read. Names
+/// resolve like base classes: by qualified name, then within the
+/// endpoint's module, then by a class name only one class has. Endpoints
+/// get the models' entity IDs in `request_models`/`response_models`, and
+/// models the endpoints' in `used_by_endpoints`.
+pub fn link_models(entities: &mut [CodeEntity]) {
+    let mut by_qualified: HashMap<&str, usize> = HashMap::new();
+    let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, entity) in entities.iter().enumerate().filter(|(_, e)| e.entity_type == "class") {
+        by_qualified.insert(entity.qualified_name.as_str(), index);
+        by_name.entry(entity.name.as_str()).or_default().push(index);
+    }
+    let resolve = |entity: &CodeEntity, name: &str| -> Option<usize> {
+        if let Some(&index) = by_qualified.get(name) {
+            return Some(index);
+        }
+        if let Some(&index) = by_qualified.get(format!("{}.{}", entity.module_path, name).as_str()) {
+            return Some(index);
+        }
+        let short = name.rsplit('.').next().unwrap_or(name);
+        match by_name.get(short).map(Vec::as_slice) {
+            Some([only]) => Some(*only),
+            Some(candidates) => candidates.iter().copied().find(|&c| entities[c].module_path == entity.module_path),
+            None => None,
+        }
+    };
+
+    let mut links: Vec<(usize, Vec<usize>, Vec<usize>)> = Vec::new();
+    for (index, entity) in entities.iter().enumerate() {
+        if !entity.is_api_endpoint && entity.serializers.is_empty() {
+            continue;
+        }
+        let mut request: Vec<&str> = entity
+            .endpoint_params
+            .iter()
+            .filter(|param| param.location == ParamLocation::Body)
+            .filter_map(|param| param.annotation.as_deref())
+            .flat_map(model_names)
+            .collect();
+        let mut response: Vec<&str> = entity.response_model.as_deref().into_iter().flat_map(model_names).collect();
+        if !reads_only(entity) {
+            request.extend(entity.serializers.iter().map(String::as_str));
+        }
+        response.extend(entity.serializers.iter().map(String::as_str));
+
+        let resolved = |names: Vec<&str>| {
+            let mut models: Vec<usize> = Vec::new();
+            for model in names.into_iter().filter_map(|name| resolve(entity, name)) {
+                if model != index && !models.contains(&model) {
+                    models.push(model);
+                }
+            }
+            models
+        };
+        let (request, response) = (resolved(request), resolved(response));
+        if !request.is_empty() || !response.is_empty() {
+            links.push((index, request, response));
+        }
+    }
+
+    for entity in entities.iter_mut() {
+        entity.request_models.clear();
+        entity.response_models.clear();
+        entity.used_by_endpoints.clear();
+    }
+    for (endpoint, request, response) in links {
+        let endpoint_id = entities[endpoint].entity_id.clone();
+        for &model in request.iter().chain(&response) {
+            if !entities[model].used_by_endpoints.contains(&endpoint_id) {
+                entities[model].used_by_endpoints.push(endpoint_id.clone());
+            }
+        }
+        entities[endpoint].request_models = request.iter().map(|&model| entities[model].entity_id.clone()).collect();
+        entities[endpoint].response_models = response.iter().map(|&model| entities[model].entity_id.clone()).collect();
+    }
+}
+
+/// Views that only answer reads, whose serializers describe responses alone
+fn reads_only(entity: &CodeEntity) -> bool {
+    if entity.is_api_endpoint {
+        entity.http_methods.iter().all(|method| matches!(method.as_str(), "GET" | "HEAD" | "OPTIONS" | "DELETE"))
+    } else {
+        matches!(entity.name.as_str(), "get" | "list" | "retrieve" | "destroy" | "delete")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::PythonParser;
+    use std::path::Path;
+
+    #[test]
+    fn test_path_params() {
+        assert_eq!(
+            path_params("/users/{user_id}/posts/<int:post_id>/(?P<slug>[-\\w]+)"),
+            vec![
+                ("user_id".to_string(), None),
+                ("post_id".to_string(), Some("int".to_string())),
+                ("slug".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_endpoint_parameter_table() {
+        let source = "@app.post(\"/orgs/{org}/users/{user_id}\")\nasync def create(request: Request, org: str, user_id: int, user: UserIn, notify: bool = False, tags: Optional[List[str]] = None, token: str = Header(...), db = Depends(get_db), *, limit: Annotated[int, Query(le=100)] = 10):\n    pass\n";
+        let entities = PythonParser::new().parse_source(source, Path::new("api.py")).unwrap();
+
+        let table: Vec<_> = entities[0]
+            .endpoint_params
+            .iter()
+            .map(|p| (p.name.as_str(), p.location.as_str(), p.annotation.as_deref(), p.default.as_deref(), p.required))
+            .collect();
+        assert_eq!(
+            table,
+            vec![
+                ("org", "path", Some("str"), None, true),
+                ("user_id", "path", Some("int"), None, true),
+                ("user", "body", Some("UserIn"), None, true),
+                ("notify", "query", Some("bool"), Some("False"), false),
+                ("tags", "query", Some("Optional[List[str]]"), Some("None"), false),
+                ("token", "header", Some("str"), None, true),
+                ("db", "dependency", None, Some("get_db"), false),
+                ("limit", "query", Some("Annotated[int, Query(...)]"), Some("10"), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_api_versions() {
+        let source = "from fastapi import APIRouter\n\nrouter = APIRouter(prefix=\"/users\")\nlegacy = APIRouter()\n\n@router.get(\"/\")\ndef list_users():\n    pass\n\n@legacy.get(\"/api/v1/users\")\ndef list_users_v1():\n    pass\n\n@legacy.get(\"/health\")\n@version(10)\ndef health():\n    pass\n\napp.include_router(router, prefix=\"/v2\")\n";
+        let entities = PythonParser::new().parse_source(source, Path::new("api.py")).unwrap();
+        let versions: Vec<Option<&str>> = entities.iter().map(|e| e.api_version.as_deref()).collect();
+        assert_eq!(versions, vec![Some("v2"), Some("v1"), Some("v10")]);
+
+        let groups: Vec<Option<String>> = by_version(&entities).into_iter().map(|(version, _)| version).collect();
+        assert_eq!(groups, vec![Some("v1".to_string()), Some("v2".to_string()), Some("v10".to_string())]);
+    }
+
+    #[test]
+    fn test_versions_from_routers_mounted_elsewhere() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("routes")).unwrap();
+        std::fs::write(dir.path().join("routes/__init__.py"), "").unwrap();
+        std::fs::write(
+            dir.path().join("routes/users.py"),
+            "from fastapi import APIRouter\n\nrouter = APIRouter(prefix=\"/users\")\n\n@router.get(\"/\")\ndef list_users():\n    pass\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("main.py"),
+            "from fastapi import FastAPI\nfrom routes import users\n\napp = FastAPI()\napp.include_router(users.router, prefix=\"/api/v1\")\n",
+        )
+        .unwrap();
+
+        let entities = crate::analyzer::RustAnalyzer::new().analyze_directory(dir.path()).unwrap();
+        let find = |name: &str| entities.iter().find(|e| e.name == name).unwrap();
+        let mount = find("users.router");
+        assert_eq!(mount.entity_type, ROUTER_ENTITY);
+        assert_eq!(mount.metadata["mounts"], "routes.users.router");
+        assert_eq!(find("list_users").api_version.as_deref(), Some("v1"));
+    }
+
+    #[test]
+    fn test_link_models() {
+        let source = "class UserIn(BaseModel):\n    name: str\n\nclass UserOut(BaseModel):\n    id: int\n\nclass OrderSerializer(serializers.ModelSerializer):\n    pass\n\n@app.post(\"/users\", response_model=UserOut)\ndef create_user(user: UserIn):\n    pass\n\nclass OrderList(generics.ListCreateAPIView):\n    serializer_class = OrderSerializer\n";
+        let entities = crate::analyzer::RustAnalyzer::new().analyze_source(source, Path::new("api.py")).unwrap();
+        let find = |name: &str| entities.iter().find(|e| e.name == name).unwrap();
+        let (user_in, user_out, orders) = (find("UserIn"), find("UserOut"), find("OrderSerializer"));
+        let (create, list) = (find("create_user"), find("OrderList"));
+
+        assert_eq!(create.request_models, vec![user_in.entity_id.clone()]);
+        assert_eq!(create.response_models, vec![user_out.entity_id.clone()]);
+        assert_eq!(user_out.used_by_endpoints, vec![create.entity_id.clone()]);
+        assert_eq!(list.serializers, vec!["OrderSerializer"]);
+        assert_eq!(list.response_models, vec![orders.entity_id.clone()]);
+        assert_eq!(orders.used_by_endpoints, vec![list.entity_id.clone()]);
+    }
+}
@@ -1,52 +1,342 @@
+use anyhow::Result;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::endpoints::EndpointParam;
+use crate::env::EnvVar;
+use crate::intern::{SharedPath, Symbol};
+use crate::lazy::{self, CodeSpan};
+use crate::logs::LogEvent;
+use crate::metrics::percentage;
+use crate::outbound::HttpCall;
+use crate::security::SecurityFinding;
+use crate::testmap::{TestCase, UsageExample};
 
 /// Core entity representing a code element (function, class, etc.)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodeEntity {
-    pub entity_type: String,
+    /// Stable identifier that survives line shifts; see `identity::assign_ids`
+    #[serde(default)]
+    pub entity_id: String,
+    /// Hash of the signature, docstring and decorators
+    #[serde(default)]
+    pub content_hash: String,
+    /// Hash of the whole definition's source with indentation, blank lines and
+    /// trailing whitespace normalized away; empty when the span is unknown
+    #[serde(default)]
+    pub source_hash: String,
+    /// Kind of definition; interned, like `file_path` and `decorators`
+    pub entity_type: Symbol,
     pub name: String,
-    pub file_path: PathBuf,
+    /// Dotted path from the module down, e.g. `pkg.api.Client.get`; see
+    /// `hierarchy::qualify`
+    #[serde(default)]
+    pub qualified_name: String,
+    /// Dotted import path of the defining module, e.g. `pkg.api`; see
+    /// `modules::module_path`
+    #[serde(default)]
+    pub module_path: String,
+    /// Relative to the analysis root with `/` separators when analyzed as
+    /// part of a tree; see `paths::normalize_entity_paths`
+    pub file_path: SharedPath,
+    /// Where the file lives on disk, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub absolute_path: Option<SharedPath>,
     pub line_number: usize,
+    /// Last line of the definition; 0 when the backend doesn't track spans
+    #[serde(default)]
+    pub end_line: usize,
+    /// UTF-8 byte offset of the definition in the source; `None` when unknown.
+    /// For transcoded Latin-1 files offsets are into the decoded text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_offset: Option<usize>,
+    /// UTF-8 byte offset just past the definition
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_offset: Option<usize>,
+    /// 1-based character column where the definition starts; 0 when unknown
+    #[serde(default)]
+    pub column: usize,
+    /// 1-based character column just past the definition; 0 when unknown
+    #[serde(default)]
+    pub end_column: usize,
+    /// `entity_id` of the enclosing class or function; `None` at module level
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    /// Ids of the entities defined directly inside this one, in source order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<String>,
+    /// Base classes as written in the definition, e.g. `Generic[T]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bases: Vec<String>,
+    /// `qualified_name` of the base-class method this method overrides; see
+    /// `inheritance::resolve`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overrides: Option<String>,
+    /// How the override's parameters differ from the base method's, a
+    /// `SignatureChange` name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub override_change: Option<String>,
     pub docstring: Option<String>,
+    /// Signature or header; empty while deferred, see `load_code`
     pub code: String,
+    /// Byte range of the deferred `code` in the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_span: Option<CodeSpan>,
     pub is_async: bool,
-    pub decorators: Vec<String>,
+    pub decorators: Vec<Symbol>,
     pub parameters: Vec<String>,
+    /// Annotation of each parameter, aligned with `parameters`; `None` for
+    /// backends of languages where types aren't optional
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameter_types: Option<Vec<Option<String>>>,
     pub return_type: Option<String>,
     pub is_internal: bool,
     pub is_api_endpoint: bool,
     pub endpoint_path: Option<String>,
     pub http_methods: Vec<String>,
+    /// Path, query and body parameters of an endpoint
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub endpoint_params: Vec<EndpointParam>,
+    /// API version from a versioning decorator, the path or its router prefix, e.g. `v2`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_version: Option<String>,
+    /// `response_model=` of the route decorator, as written
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_model: Option<String>,
+    /// DRF serializers the view declares or instantiates
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub serializers: Vec<String>,
+    /// Entity IDs of the classes describing the request body; see `endpoints::link_models`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub request_models: Vec<String>,
+    /// Entity IDs of the classes describing the response
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub response_models: Vec<String>,
+    /// Entity IDs of endpoints taking or returning this class
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub used_by_endpoints: Vec<String>,
+    /// Endpoint or view requires an authenticated user; see `auth::Auth`
+    #[serde(default)]
+    pub auth_required: bool,
+    /// What enforces authentication: a dependency, decorator, mixin or DRF authentication classes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_scheme: Option<String>,
+    /// Permissions, roles, scopes or DRF permission classes required
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub permissions: Vec<String>,
+    /// For tests: imported code the body calls, as dotted names
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exercises: Vec<String>,
+    /// Entity IDs of tests calling this entity; see `testmap::link`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tested_by: Vec<String>,
+    /// Concrete cases from `@pytest.mark.parametrize`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub test_cases: Vec<TestCase>,
+    /// For tests: call snippets lifted from the body; see `testmap::examples`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub harvested_examples: Vec<UsageExample>,
+    /// Usage examples harvested from the tests calling this entity
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<UsageExample>,
     pub complexity_score: u32,
+    #[serde(default)]
+    pub is_deprecated: bool,
+    /// Suggested replacement taken from the deprecation notice, e.g. `fetch`
+    #[serde(default)]
+    pub replacement: Option<String>,
+    /// Defined in a machine-generated file, see `generated::is_generated`
+    #[serde(default)]
+    pub is_generated: bool,
+    /// Class defines its own `__init__` or `__new__`; its `parameters` are then the
+    /// constructor's, without `self`/`cls`
+    #[serde(default)]
+    pub has_custom_init: bool,
+    /// Class derives from `Exception` or another exception, possibly through
+    /// analyzed classes; see `inheritance::resolve`
+    #[serde(default)]
+    pub is_exception: bool,
+    /// Class derives from `ABC`, uses `ABCMeta` or declares abstract methods
+    #[serde(default)]
+    pub is_abstract: bool,
+    /// Class named `...Mixin`
+    #[serde(default)]
+    pub is_mixin: bool,
+    /// Class follows a singleton pattern: a `singleton` decorator or metaclass,
+    /// or an `_instance` attribute handed out by `__new__` or an accessor
+    #[serde(default)]
+    pub is_singleton: bool,
+    /// `docstring` was copied from the overridden base method; see
+    /// `AnalyzerConfig::inherit_docstrings`
+    #[serde(default)]
+    pub inherited_doc: bool,
+    /// Environment variables read in the body, or behind a settings class's fields
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_vars: Vec<EnvVar>,
+    /// Body runs SQL or calls a database API (`cursor.execute`, `text(...)`)
+    #[serde(default)]
+    pub touches_database: bool,
+    /// Normalized tables named by SQL in the body
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tables: Vec<String>,
+    /// Body makes outbound HTTP requests
+    #[serde(default)]
+    pub makes_external_calls: bool,
+    /// Outbound HTTP requests, with method and URL when literal
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub http_calls: Vec<HttpCall>,
+    /// Security-sensitive calls in the body: `eval`, `pickle.loads`, `shell=True`, ...
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub security_findings: Vec<SecurityFinding>,
+    /// Feature flags and config constants the body is gated on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub feature_flags: Vec<String>,
+    /// Log calls in the body, with level and message template
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub log_events: Vec<LogEvent>,
+    /// Free-form annotations attached by post-processing hooks
+    pub metadata: BTreeMap<String, String>,
 }
 
 impl CodeEntity {
     pub fn new(
-        entity_type: String,
+        entity_type: impl Into<Symbol>,
         name: String,
-        file_path: PathBuf,
+        file_path: impl Into<SharedPath>,
         line_number: usize,
     ) -> Self {
         CodeEntity {
-            entity_type,
+            entity_id: String::new(),
+            content_hash: String::new(),
+            source_hash: String::new(),
+            entity_type: entity_type.into(),
             name,
-            file_path,
+            qualified_name: String::new(),
+            module_path: String::new(),
+            file_path: file_path.into(),
+            absolute_path: None,
             line_number,
+            end_line: 0,
+            start_offset: None,
+            end_offset: None,
+            column: 0,
+            end_column: 0,
+            parent_id: None,
+            children: Vec::new(),
+            bases: Vec::new(),
+            overrides: None,
+            override_change: None,
             docstring: None,
             code: String::new(),
+            code_span: None,
             is_async: false,
             decorators: Vec::new(),
             parameters: Vec::new(),
+            parameter_types: None,
             return_type: None,
             is_internal: false,
             is_api_endpoint: false,
             endpoint_path: None,
             http_methods: Vec::new(),
+            endpoint_params: Vec::new(),
+            api_version: None,
+            response_model: None,
+            serializers: Vec::new(),
+            request_models: Vec::new(),
+            response_models: Vec::new(),
+            used_by_endpoints: Vec::new(),
+            auth_required: false,
+            auth_scheme: None,
+            permissions: Vec::new(),
+            exercises: Vec::new(),
+            tested_by: Vec::new(),
+            test_cases: Vec::new(),
+            harvested_examples: Vec::new(),
+            examples: Vec::new(),
             complexity_score: 1,
+            is_deprecated: false,
+            replacement: None,
+            is_generated: false,
+            has_custom_init: false,
+            is_exception: false,
+            is_abstract: false,
+            is_mixin: false,
+            is_singleton: false,
+            inherited_doc: false,
+            env_vars: Vec::new(),
+            touches_database: false,
+            tables: Vec::new(),
+            makes_external_calls: false,
+            http_calls: Vec::new(),
+            security_findings: Vec::new(),
+            feature_flags: Vec::new(),
+            log_events: Vec::new(),
+            metadata: BTreeMap::new(),
         }
     }
 
+    /// Whether the entity belongs to the public API.
+    ///
+    /// Dunder names such as `__init__` are public; other underscore-prefixed
+    /// names and entities flagged `is_internal` are not.
+    pub fn is_public(&self) -> bool {
+        let dunder = self.name.starts_with("__") && self.name.ends_with("__");
+        !self.is_internal && (dunder || !self.name.starts_with('_'))
+    }
+
+    /// Percentage of annotated parameters and return, see `annotation_counts`
+    pub fn annotation_coverage(&self) -> Option<f64> {
+        self.annotation_counts().map(|(annotated, total)| percentage(annotated, total))
+    }
+
+    /// Annotated slots and all slots (parameters plus the return) of a
+    /// function or method whose backend tracks annotations
+    pub fn annotation_counts(&self) -> Option<(usize, usize)> {
+        if !matches!(self.entity_type.as_str(), "function" | "method") {
+            return None;
+        }
+        annotation_counts(&self.name, &self.parameters, self.parameter_types.as_deref()?, self.return_type.is_some())
+    }
+
+    /// Path to read the entity's file from: `absolute_path` when known, else `file_path`
+    pub fn source_path(&self) -> &Path {
+        self.absolute_path.as_ref().map_or(self.file_path.as_path(), SharedPath::as_path)
+    }
+
+    /// Whether `code` is materialized rather than deferred by
+    /// `AnalyzerConfig::lazy_code`
+    pub fn is_code_loaded(&self) -> bool {
+        self.code_span.is_none()
+    }
+
+    /// The entity's code, sliced from the file without keeping it when
+    /// deferred; empty if the file no longer matches
+    pub fn code_text(&self) -> Cow<'_, str> {
+        match self.code_span {
+            Some(span) => match lazy::load_span(self.source_path(), span) {
+                Ok(code) => Cow::Owned(code),
+                Err(e) => {
+                    log::warn!("{:#}", e);
+                    Cow::Borrowed("")
+                }
+            },
+            None => Cow::Borrowed(&self.code),
+        }
+    }
+
+    /// The entity's code, slicing it from the file first if it was deferred
+    pub fn load_code(&mut self) -> Result<&str> {
+        if let Some(span) = self.code_span {
+            self.code = lazy::load_span(self.source_path(), span)?;
+            self.code_span = None;
+        }
+        Ok(&self.code)
+    }
+
     /// Calculate complexity score based on various factors
     pub fn calculate_complexity(&mut self) {
         let mut score = 1;
@@ -102,9 +392,68 @@ impl CodeEntity {
     }
 }
 
+impl CodeEntity {
+    /// Flag the entity as deprecated from a `deprecated` decorator/attribute
+    /// or a deprecation notice in its docstring: the `.. deprecated::`
+    /// directive, a `Deprecated:` (Google) or underlined `Deprecated`
+    /// (NumPy) section, or a JSDoc `@deprecated` tag. Prose merely
+    /// mentioning deprecation doesn't count.
+    pub fn detect_deprecation(&mut self) {
+        let decorator = self.decorators.iter().find(|d| {
+            let name = d.trim_start_matches('@').split('(').next().unwrap_or(d).trim();
+            name.rsplit(['.', ':']).next() == Some("deprecated")
+        });
+        if let Some(decorator) = decorator.cloned() {
+            self.mark_deprecated(&decorator);
+            return;
+        }
+
+        let Some(doc) = self.docstring.as_deref() else { return };
+        let lines: Vec<&str> = doc.lines().map(str::trim).collect();
+        let marker = lines.iter().enumerate().position(|(i, l)| {
+            let l = l.to_ascii_lowercase();
+            let underlined = || lines.get(i + 1).map_or(false, |next| next.len() >= 3 && next.chars().all(|c| c == '-'));
+            l.starts_with(".. deprecated::")
+                || l.starts_with("deprecated:")
+                || l == "@deprecated"
+                || l.starts_with("@deprecated ")
+                || (l == "deprecated" && underlined())
+        });
+        if let Some(start) = marker {
+            // The notice runs to the end of its paragraph
+            let notice = lines[start..]
+                .iter()
+                .take_while(|l| !l.is_empty())
+                .copied()
+                .collect::<Vec<_>>()
+                .join(" ");
+            self.mark_deprecated(&notice);
+        }
+    }
+
+    /// Mark the entity deprecated, taking the replacement hint from `notice`
+    /// ("use fetch() instead", "replaced by `Client.get`") when none is known yet
+    pub fn mark_deprecated(&mut self, notice: &str) {
+        self.is_deprecated = true;
+        if self.replacement.is_none() {
+            self.replacement = replacement_hint(notice);
+        }
+    }
+}
+
+fn replacement_hint(notice: &str) -> Option<String> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let re = PATTERN.get_or_init(|| {
+        Regex::new(r#"(?i)\b(?:use|replaced by|in favou?r of|superseded by)\s+[`'"]?:?(?:\w+:`)?~?([A-Za-z_][\w.:]*)"#)
+            .unwrap()
+    });
+    re.captures(notice)
+        .map(|cap| cap[1].trim_end_matches(['.', ':']).to_string())
+        .filter(|name| !name.is_empty() && !matches!(name.as_str(), "the" | "a" | "an" | "this"))
+}
+
 fn extract_path_from_decorator(decorator: &str) -> Option<String> {
     // Simple regex to extract path from decorators like @route("/api/users")
-    use regex::Regex;
     let re = Regex::new(r#"["']([^"']+)["']"#).ok()?;
     
     re.captures(decorator)
@@ -113,7 +462,6 @@ fn extract_path_from_decorator(decorator: &str) -> Option<String> {
 }
 
 fn extract_http_methods_from_decorator(decorator: &str) -> Vec<String> {
-    use regex::Regex;
     let mut methods = Vec::new();
     
     // Check for specific method decorators like @app.get, @app.post
@@ -143,6 +491,55 @@ fn extract_http_methods_from_decorator(decorator: &str) -> Vec<String> {
     methods
 }
 
+/// Annotation counts shared with the Python wrapper. `self` and `cls` are
+/// not counted, nor is the return of `__init__`, which is always `None`.
+pub(crate) fn annotation_counts(
+    name: &str,
+    parameters: &[String],
+    parameter_types: &[Option<String>],
+    has_return: bool,
+) -> Option<(usize, usize)> {
+    if parameter_types.len() != parameters.len() {
+        return None;
+    }
+    let (mut annotated, mut total) = (0, 0);
+    for (parameter, annotation) in parameters.iter().zip(parameter_types) {
+        if matches!(parameter.as_str(), "self" | "cls" | "*") && annotation.is_none() {
+            continue;
+        }
+        total += 1;
+        annotated += usize::from(annotation.is_some());
+    }
+    if name != "__init__" {
+        total += 1;
+        annotated += usize::from(has_return);
+    }
+    Some((annotated, total))
+}
+
+/// Test fixtures, e.g. `CodeEntity::stub("method", "Client.get", "api.py").lines(3, 5)`
+#[cfg(test)]
+impl CodeEntity {
+    /// Entity named after the last segment of `qualified`, at line 1
+    pub(crate) fn stub(kind: &str, qualified: &str, path: &str) -> Self {
+        let name = qualified.rsplit('.').next().unwrap_or(qualified);
+        let mut entity = CodeEntity::new(kind.to_string(), name.to_string(), std::path::PathBuf::from(path), 1);
+        entity.qualified_name = qualified.to_string();
+        entity
+    }
+
+    pub(crate) fn lines(mut self, start: usize, end: usize) -> Self {
+        self.line_number = start;
+        self.end_line = end;
+        self
+    }
+
+    pub(crate) fn doc(mut self, docstring: &str) -> Self {
+        self.docstring = Some(docstring.to_string());
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -163,6 +560,24 @@ mod tests {
         assert!(!entity.is_async);
     }
 
+    #[test]
+    fn test_is_public() {
+        let mut entity = CodeEntity::new(
+            "method".to_string(),
+            "__init__".to_string(),
+            PathBuf::from("test.py"),
+            1,
+        );
+        assert!(entity.is_public());
+        
+        entity.name = "_helper".to_string();
+        assert!(!entity.is_public());
+        
+        entity.name = "helper".to_string();
+        entity.is_internal = true;
+        assert!(!entity.is_public());
+    }
+
     #[test]
     fn test_complexity_calculation() {
         let mut entity = CodeEntity::new(
@@ -190,10 +605,39 @@ mod tests {
             20,
         );
         
-        entity.decorators = vec!["@app.route('/api/users')".to_string()];
+        entity.decorators = vec!["@app.route('/api/users')".into()];
         entity.detect_api_endpoint();
         
         assert!(entity.is_api_endpoint);
         assert_eq!(entity.endpoint_path, Some("/api/users".to_string()));
     }
+
+    #[test]
+    fn test_deprecation_detection() {
+        let mut entity = CodeEntity::new(
+            "function".to_string(),
+            "old_fetch".to_string(),
+            PathBuf::from("api.py"),
+            5,
+        );
+        entity.docstring = Some("Fetch a URL.\n\n.. deprecated:: 2.0\n   Use :func:`fetch` instead.".to_string());
+        entity.detect_deprecation();
+        assert!(entity.is_deprecated);
+        assert_eq!(entity.replacement.as_deref(), Some("fetch"));
+
+        let mut rust = CodeEntity::new("function".to_string(), "old".to_string(), PathBuf::from("lib.rs"), 1);
+        rust.decorators = vec!["deprecated(since = \"1.2\", note = \"use `Client::get` instead\")".into()];
+        rust.detect_deprecation();
+        assert_eq!(rust.replacement.as_deref(), Some("Client::get"));
+
+        let mut plain = CodeEntity::new("function".to_string(), "f".to_string(), PathBuf::from("a.py"), 1);
+        plain.docstring = Some("Replaces the deprecated helper.\n\nDeprecated arguments are ignored.".to_string());
+        plain.detect_deprecation();
+        assert!(!plain.is_deprecated);
+
+        let mut google = CodeEntity::new("function".to_string(), "g".to_string(), PathBuf::from("a.py"), 1);
+        google.docstring = Some("Load data.\n\nDeprecated:\n    Use `load_all` instead.".to_string());
+        google.detect_deprecation();
+        assert_eq!(google.replacement.as_deref(), Some("load_all"));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,179 @@
+use rustpython_parser::ast;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::entity::CodeEntity;
+use crate::location::line_at;
+use crate::walk::{argument, dotted_name, keyword, literal, string_literal};
+
+/// Calls that read a variable named by their first argument, with the
+/// fallback as their second
+const READERS: &[&str] = &[
+    "os.getenv",
+    "getenv",
+    "os.environ.get",
+    "environ.get",
+    "os.environ.setdefault",
+    "environ.setdefault",
+];
+
+/// An environment variable an entity reads
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvVar {
+    pub name: String,
+    /// Fallback when it is a literal, as text: `8080`, `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    pub line: usize,
+}
+
+/// One variable across the project, for ops runbooks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EnvVarSummary {
+    pub name: String,
+    /// Distinct literal fallbacks; several mean the readers disagree
+    pub defaults: Vec<String>,
+    /// Qualified names of the reading entities
+    pub used_by: Vec<String>,
+}
+
+/// The variable `expr` reads through `os.environ[...]`, or through a call
+/// to `os.environ.get`/`setdefault` or `os.getenv` named `callee`, keyed on
+/// a literal name. `line_starts` maps offsets in the body to lines.
+pub(crate) fn read(expr: &ast::Expr, callee: Option<&str>, line_starts: &[usize]) -> Option<EnvVar> {
+    let (name, default, offset) = match expr {
+        ast::Expr::Call(call) if callee.map_or(false, |f| READERS.contains(&f)) => {
+            let name = argument(call, 0, "key").and_then(string_literal)?;
+            (name, argument(call, 1, "default").and_then(literal), call.range.start().to_usize())
+        }
+        ast::Expr::Subscript(subscript)
+            if matches!(subscript.ctx, ast::ExprContext::Load)
+                && matches!(dotted_name(&subscript.value).as_deref(), Some("os.environ" | "environ")) =>
+        {
+            (string_literal(&subscript.slice)?, None, subscript.range.start().to_usize())
+        }
+        _ => return None,
+    };
+    Some(EnvVar {
+        name: name.to_string(),
+        default,
+        line: line_at(line_starts, offset),
+    })
+}
+
+/// Variables behind the fields of a pydantic `BaseSettings` class: the
+/// field name (upper-cased) after any `env_prefix`, unless `Field(...)`
+/// names the variable with `env`, `validation_alias` or `alias`
+pub fn settings_fields(body: &[ast::Stmt], line_starts: &[usize]) -> Vec<EnvVar> {
+    let prefix = env_prefix(body).unwrap_or_default();
+    body.iter()
+        .filter_map(|stmt| {
+            let ast::Stmt::AnnAssign(field) = stmt else { return None };
+            let ast::Expr::Name(target) = &*field.target else { return None };
+            let field_name = target.id.as_str();
+            if field_name.starts_with('_') || field_name == "model_config" {
+                return None;
+            }
+
+            let call = match field.value.as_deref() {
+                Some(ast::Expr::Call(call)) if dotted_name(&call.func).map_or(false, |f| f.ends_with("Field")) => Some(call),
+                _ => None,
+            };
+            let explicit = call.and_then(|call| {
+                ["env", "validation_alias", "alias"]
+                    .iter()
+                    .find_map(|name| keyword(call, name).and_then(string_literal))
+            });
+            let default = match call {
+                Some(call) => argument(call, 0, "default").and_then(literal),
+                None => field.value.as_deref().and_then(literal),
+            };
+            Some(EnvVar {
+                name: explicit.map_or_else(|| format!("{}{}", prefix, field_name).to_uppercase(), str::to_string),
+                default,
+                line: line_at(line_starts, field.range.start().to_usize()),
+            })
+        })
+        .collect()
+}
+
+/// `env_prefix` from `model_config = SettingsConfigDict(...)` or an inner
+/// `class Config`
+fn env_prefix(body: &[ast::Stmt]) -> Option<String> {
+    body.iter().find_map(|stmt| match stmt {
+        ast::Stmt::Assign(assign) => {
+            let targets_config = assign
+                .targets
+                .iter()
+                .any(|t| matches!(t, ast::Expr::Name(name) if name.id.as_str() == "model_config"));
+            let ast::Expr::Call(call) = &*assign.value else { return None };
+            if !targets_config {
+                return None;
+            }
+            keyword(call, "env_prefix").and_then(string_literal).map(str::to_string)
+        }
+        ast::Stmt::ClassDef(config) if config.name.as_str() == "Config" => config.body.iter().find_map(|stmt| {
+            let ast::Stmt::Assign(assign) = stmt else { return None };
+            let named = assign
+                .targets
+                .iter()
+                .any(|t| matches!(t, ast::Expr::Name(name) if name.id.as_str() == "env_prefix"));
+            if !named {
+                return None;
+            }
+            string_literal(&assign.value).map(str::to_string)
+        }),
+        _ => None,
+    })
+}
+
+/// Every variable read anywhere in `entities`, by name
+pub fn project_env_vars(entities: &[CodeEntity]) -> Vec<EnvVarSummary> {
+    let mut by_name: BTreeMap<&str, EnvVarSummary> = BTreeMap::new();
+    for entity in entities {
+        for var in &entity.env_vars {
+            let summary = by_name.entry(var.name.as_str()).or_insert_with(|| EnvVarSummary {
+                name: var.name.clone(),
+                defaults: Vec::new(),
+                used_by: Vec::new(),
+            });
+            if let Some(default) = &var.default {
+                if !summary.defaults.contains(default) {
+                    summary.defaults.push(default.clone());
+                }
+            }
+            let user = if entity.qualified_name.is_empty() { &entity.name } else { &entity.qualified_name };
+            if !summary.used_by.contains(user) {
+                summary.used_by.push(user.clone());
+            }
+        }
+    }
+    by_name.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::PythonParser;
+    use std::path::Path;
+
+    #[test]
+    fn test_env_vars() {
+        let source = "import os\n\ndef connect():\n    host = os.environ[\"DB_HOST\"]\n    port = int(os.getenv(\"DB_PORT\", 5432))\n    os.environ[\"SEEN\"] = \"1\"\n    return os.environ.get(\"DB_HOST\", \"localhost\")\n\nclass Settings(BaseSettings):\n    model_config = SettingsConfigDict(env_prefix=\"app_\")\n    debug: bool = False\n    token: str = Field(\"\", alias=\"API_TOKEN\")\n";
+        let entities = PythonParser::new().parse_source(source, Path::new("config.py")).unwrap();
+
+        let reads: Vec<(&str, Option<&str>, usize)> = entities[0]
+            .env_vars
+            .iter()
+            .map(|var| (var.name.as_str(), var.default.as_deref(), var.line))
+            .collect();
+        assert_eq!(reads, vec![("DB_HOST", None, 4), ("DB_PORT", Some("5432"), 5), ("DB_HOST", Some("localhost"), 7)]);
+
+        let fields: Vec<(&str, Option<&str>)> = entities[1].env_vars.iter().map(|var| (var.name.as_str(), var.default.as_deref())).collect();
+        assert_eq!(fields, vec![("APP_DEBUG", Some("False")), ("API_TOKEN", Some(""))]);
+
+        let summary = project_env_vars(&entities);
+        let db_host = summary.iter().find(|var| var.name == "DB_HOST").unwrap();
+        assert_eq!((db_host.defaults.clone(), db_host.used_by.clone()), (vec!["localhost".to_string()], vec!["connect".to_string()]));
+    }
+}
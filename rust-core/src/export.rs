@@ -0,0 +1,521 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::context::{entity_source, SourceCache};
+use crate::entity::CodeEntity;
+use crate::inventory::{self, InventoryOptions};
+use crate::lazy;
+use crate::security;
+use crate::site;
+
+/// Output formats supported by the exporters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Markdown,
+    Sqlite,
+    /// One normalized text record per entity, as JSON lines
+    Embeddings,
+    /// Sphinx `objects.inv` for intersphinx, see `inventory::to_objects_inv`
+    Inventory,
+    /// Docusaurus pages and sidebar as JSON, see `site::build_site`
+    Site,
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(ExportFormat::Json),
+            "markdown" | "md" => Ok(ExportFormat::Markdown),
+            "sqlite" | "db" => Ok(ExportFormat::Sqlite),
+            "embeddings" | "jsonl" => Ok(ExportFormat::Embeddings),
+            "inventory" | "inv" | "objects.inv" => Ok(ExportFormat::Inventory),
+            "site" | "docusaurus" => Ok(ExportFormat::Site),
+            other => Err(anyhow::anyhow!("Unknown export format: {}", other)),
+        }
+    }
+}
+
+/// Write entities to `path` in the requested format
+pub fn export_to_file(entities: &[CodeEntity], format: ExportFormat, path: &Path) -> Result<()> {
+    match format {
+        ExportFormat::Json => write_string(path, &to_json(entities)?),
+        ExportFormat::Markdown => write_string(path, &to_markdown(entities)),
+        ExportFormat::Sqlite => to_sqlite(entities, path),
+        ExportFormat::Embeddings => write_string(path, &to_embedding_jsonl(entities)?),
+        ExportFormat::Inventory => write_bytes(path, &inventory::to_objects_inv(entities, &InventoryOptions::default())?),
+        ExportFormat::Site => write_string(path, &site::to_site_json(entities, "api")?),
+    }
+}
+
+fn write_string(path: &Path, contents: &str) -> Result<()> {
+    write_bytes(path, contents.as_bytes())
+}
+
+fn write_bytes(path: &Path, contents: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+    }
+    std::fs::write(path, contents)
+        .with_context(|| format!("Failed to write export: {:?}", path))
+}
+
+/// Serialize entities as a pretty-printed JSON array
+pub fn to_json(entities: &[CodeEntity]) -> Result<String> {
+    serde_json::to_string_pretty(&*lazy::materialized(entities)).context("Failed to serialize entities")
+}
+
+/// Render entities as a Markdown reference grouped by file
+pub fn to_markdown(entities: &[CodeEntity]) -> String {
+    let entities = lazy::materialized(entities);
+    let entities = &*entities;
+    let mut by_file: BTreeMap<String, Vec<&CodeEntity>> = BTreeMap::new();
+    for entity in entities {
+        by_file
+            .entry(entity.file_path.to_string_lossy().into_owned())
+            .or_default()
+            .push(entity);
+    }
+
+    let names: HashMap<&str, &str> = entities.iter().map(|e| (e.entity_id.as_str(), e.qualified_name.as_str())).collect();
+
+    let mut out = String::from("# API Reference\n");
+    for (file, mut file_entities) in by_file {
+        file_entities.sort_by_key(|e| e.line_number);
+        let _ = write!(out, "\n## `{}`\n", file);
+
+        for entity in file_entities {
+            let _ = write!(out, "\n### {} `{}`\n\n", entity.entity_type, entity.name);
+            if !entity.code.is_empty() {
+                let _ = write!(out, "```{}\n{}\n```\n\n", fence_language(&entity.file_path), entity.code);
+            }
+            if entity.is_deprecated {
+                match entity.replacement.as_deref() {
+                    Some(replacement) => {
+                        let _ = writeln!(out, "**Deprecated:** use `{}` instead.\n", replacement);
+                    }
+                    None => out.push_str("**Deprecated.**\n\n"),
+                }
+            }
+            if entity.is_api_endpoint {
+                let _ = writeln!(
+                    out,
+                    "**Endpoint:** `{} {}`\n",
+                    entity.http_methods.join(", "),
+                    entity.endpoint_path.as_deref().unwrap_or("?"),
+                );
+                if !entity.endpoint_params.is_empty() {
+                    out.push_str("| Parameter | In | Type | Default | Required |\n|---|---|---|---|---|\n");
+                    for param in &entity.endpoint_params {
+                        let _ = writeln!(
+                            out,
+                            "| `{}` | {} | {} | {} | {} |",
+                            param.name,
+                            param.location.as_str(),
+                            param.annotation.as_deref().map_or_else(String::new, |a| format!("`{}`", a.replace('|', "\\|"))),
+                            param.default.as_deref().map_or_else(String::new, |d| format!("`{}`", d.replace('|', "\\|"))),
+                            if param.required { "yes" } else { "no" },
+                        );
+                    }
+                    out.push('\n');
+                }
+            }
+            if entity.auth_required || entity.auth_scheme.is_some() || !entity.permissions.is_empty() {
+                let mut auth = String::from(if entity.auth_required { "required" } else { "not required" });
+                if let Some(scheme) = entity.auth_scheme.as_deref() {
+                    let _ = write!(auth, " via `{}`", scheme);
+                }
+                if !entity.permissions.is_empty() {
+                    let permissions: Vec<String> = entity.permissions.iter().map(|p| format!("`{}`", p)).collect();
+                    let _ = write!(auth, "; permissions {}", permissions.join(", "));
+                }
+                let _ = writeln!(out, "**Auth:** {}\n", auth);
+            } else if entity.is_api_endpoint {
+                out.push_str("**Auth:** none (public)\n\n");
+            }
+            if !entity.tested_by.is_empty() {
+                let tests: Vec<String> = entity.tested_by.iter().map(|id| format!("`{}`", names.get(id.as_str()).copied().unwrap_or(id.as_str()))).collect();
+                let count = if tests.len() == 1 { "1 test".to_string() } else { format!("{} tests", tests.len()) };
+                let _ = writeln!(out, "**Covered by {}:** {}\n", count, tests.join(", "));
+            }
+            if let Some(example) = entity.examples.first() {
+                let test = names.get(example.test_id.as_str()).copied().unwrap_or(example.test_id.as_str());
+                let _ = writeln!(out, "**Example** (from `{}`):\n\n```python\n{}\n```\n", test, example.code);
+            }
+            if !entity.test_cases.is_empty() {
+                out.push_str("**Cases:**\n\n");
+                for case in &entity.test_cases {
+                    let args: Vec<String> = case.args.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+                    match case.id.as_deref() {
+                        Some(id) => {
+                            let _ = writeln!(out, "- {}: `{}`", id, args.join(", "));
+                        }
+                        None => {
+                            let _ = writeln!(out, "- `{}`", args.join(", "));
+                        }
+                    }
+                }
+                out.push('\n');
+            }
+            if let Some(cache) = entity.metadata.get("cache") {
+                let mut cached = format!("`{}`", cache);
+                if let Some(ttl) = entity.metadata.get("cache_ttl") {
+                    let _ = write!(cached, ", TTL {}s", ttl);
+                }
+                if let Some(maxsize) = entity.metadata.get("cache_maxsize") {
+                    let _ = write!(cached, ", max size {}", maxsize);
+                }
+                let _ = writeln!(out, "**Cached:** {}\n", cached);
+            }
+            if let Some(limits) = entity.metadata.get("rate_limit") {
+                let limits: Vec<String> = limits.split(',').map(|limit| format!("`{}`", limit)).collect();
+                let _ = writeln!(out, "**Rate limit:** {}\n", limits.join(", "));
+            }
+            for (label, ids) in [
+                ("Request model", &entity.request_models),
+                ("Response model", &entity.response_models),
+                ("Used by endpoints", &entity.used_by_endpoints),
+            ] {
+                if !ids.is_empty() {
+                    let linked: Vec<String> = ids.iter().map(|id| format!("`{}`", names.get(id.as_str()).copied().unwrap_or(id.as_str()))).collect();
+                    let _ = writeln!(out, "**{}:** {}\n", label, linked.join(", "));
+                }
+            }
+            if let Some(base) = entity.overrides.as_deref() {
+                let _ = writeln!(out, "**Overrides:** `{}`\n", base);
+            }
+            if !entity.feature_flags.is_empty() {
+                let flags: Vec<String> = entity.feature_flags.iter().map(|flag| format!("`{}`", flag)).collect();
+                let _ = writeln!(out, "**Behind feature flag:** {}\n", flags.join(", "));
+            }
+            if !entity.http_calls.is_empty() {
+                out.push_str("**External calls:**\n\n");
+                for call in &entity.http_calls {
+                    let method = call.method.as_deref().unwrap_or("?");
+                    let _ = writeln!(out, "- `{} {}` via {}", method, call.url.as_deref().unwrap_or("?"), call.client);
+                }
+                out.push('\n');
+            }
+            if !entity.log_events.is_empty() {
+                out.push_str("**Log events:**\n\n");
+                for event in &entity.log_events {
+                    let _ = writeln!(out, "- `{}` {:?} (line {})", event.level, event.message, event.line);
+                }
+                out.push('\n');
+            }
+            match entity.docstring.as_deref().map(str::trim) {
+                Some(doc) if !doc.is_empty() => {
+                    let _ = writeln!(out, "{}\n", doc);
+                }
+                _ => out.push_str("_No documentation._\n\n"),
+            }
+            match (entity.metadata.get("git_date"), entity.metadata.get("git_author")) {
+                (Some(date), Some(author)) => {
+                    let _ = writeln!(
+                        out,
+                        "<sub>Defined at line {} · Last updated {} by {}</sub>",
+                        entity.line_number, date, author
+                    );
+                }
+                _ => {
+                    let _ = writeln!(out, "<sub>Defined at line {}</sub>", entity.line_number);
+                }
+            }
+        }
+    }
+
+    out.push_str(&security::appendix(entities));
+    out
+}
+
+/// Body lines kept in an embedding record; the start of a function says the
+/// most about it and long bodies would drown out the docstring
+const EMBEDDING_BODY_LINES: usize = 30;
+
+/// Text record for embedding models and vector stores
+#[derive(Debug, Clone, Serialize)]
+pub struct EmbeddingRecord {
+    pub id: String,
+    pub qualified_name: String,
+    pub entity_type: String,
+    pub file_path: String,
+    pub line_number: usize,
+    /// Qualified name, signature, docstring with whitespace normalized, and
+    /// the first lines of the body, separated by blank lines
+    pub text: String,
+}
+
+/// One embedding record per entity, in entity order
+pub fn embedding_records(entities: &[CodeEntity]) -> Vec<EmbeddingRecord> {
+    let entities = lazy::materialized(entities);
+    let mut sources = SourceCache::default();
+
+    entities
+        .iter()
+        .map(|entity| {
+            let qualified_name = if entity.qualified_name.is_empty() { entity.name.clone() } else { entity.qualified_name.clone() };
+            let mut sections = vec![qualified_name.clone()];
+            if let Some(signature) = entity.code.lines().next().map(str::trim).filter(|s| !s.is_empty()) {
+                sections.push(signature.to_string());
+            }
+            let docstring = entity.docstring.as_deref().map(normalize_docstring);
+            if let Some(doc) = docstring.filter(|d| !d.is_empty()) {
+                sections.push(doc);
+            }
+            let source = entity_source(entity, sources.get(entity.source_path()));
+            let body: Vec<&str> = body_lines(&source)
+                .into_iter()
+                .map(str::trim_end)
+                .filter(|l| !l.trim().is_empty())
+                .take(EMBEDDING_BODY_LINES)
+                .collect();
+            if !body.is_empty() {
+                sections.push(body.join("\n"));
+            }
+
+            EmbeddingRecord {
+                id: entity.entity_id.clone(),
+                qualified_name,
+                entity_type: entity.entity_type.to_string(),
+                file_path: entity.file_path.to_string_lossy().replace('\\', "/"),
+                line_number: entity.line_number,
+                text: sections.join("\n\n"),
+            }
+        })
+        .collect()
+}
+
+/// Serialize embedding records as JSON lines
+pub fn to_embedding_jsonl(entities: &[CodeEntity]) -> Result<String> {
+    let mut out = String::new();
+    for record in embedding_records(entities) {
+        let line = serde_json::to_string(&record).context("Failed to serialize embedding record")?;
+        out.push_str(&line);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Lines of an entity's source below its header and leading docstring
+fn body_lines(source: &str) -> Vec<&str> {
+    let lines: Vec<&str> = source.lines().collect();
+    // The header ends at the line opening the block, so multi-line signatures are skipped whole
+    let header = lines
+        .iter()
+        .position(|line| matches!(line.trim_end().chars().last(), Some(':' | '{')))
+        .map_or(1, |index| index + 1)
+        .min(lines.len());
+    let mut body = &lines[header..];
+
+    // The docstring is already in the record, normalized
+    let first = body.iter().position(|line| !line.trim().is_empty()).unwrap_or(body.len());
+    if let Some(opening) = body.get(first).map(|line| line.trim_start().trim_start_matches(['r', 'R', 'u', 'U'])) {
+        if let Some(quote) = ["\"\"\"", "'''"].into_iter().find(|quote| opening.starts_with(quote)) {
+            let last = if opening[quote.len()..].contains(quote) {
+                Some(first)
+            } else {
+                body[first + 1..].iter().position(|line| line.contains(quote)).map(|offset| first + 1 + offset)
+            };
+            body = &body[last.map_or(body.len(), |last| last + 1)..];
+        }
+    }
+    body.to_vec()
+}
+
+/// Collapse each docstring paragraph onto one line
+fn normalize_docstring(doc: &str) -> String {
+    doc.split("\n\n")
+        .map(|paragraph| paragraph.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Code fence language for a source file, picked from its extension
+pub(crate) fn fence_language(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("rs") => "rust",
+        Some("ts" | "tsx" | "mts" | "cts") => "typescript",
+        Some("js" | "jsx" | "mjs" | "cjs") => "javascript",
+        Some("go") => "go",
+        Some("h") => "c",
+        Some("hpp" | "hh" | "hxx" | "h++") => "cpp",
+        Some("sql") => "sql",
+        Some("sh" | "bash" | "zsh") => "bash",
+        Some("mk") => "make",
+        _ if matches!(path.file_name().and_then(|n| n.to_str()), Some("Makefile" | "makefile" | "GNUmakefile")) => "make",
+        _ => "python",
+    }
+}
+
+#[cfg(feature = "sqlite")]
+const SQLITE_SCHEMA: &str = "
+DROP TABLE IF EXISTS entities;
+CREATE TABLE entities (
+    id INTEGER PRIMARY KEY,
+    entity_id TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    entity_type TEXT NOT NULL,
+    name TEXT NOT NULL,
+    file_path TEXT NOT NULL,
+    line_number INTEGER NOT NULL,
+    docstring TEXT,
+    code TEXT NOT NULL,
+    is_async INTEGER NOT NULL,
+    decorators TEXT NOT NULL,
+    parameters TEXT NOT NULL,
+    return_type TEXT,
+    is_internal INTEGER NOT NULL,
+    is_api_endpoint INTEGER NOT NULL,
+    endpoint_path TEXT,
+    http_methods TEXT NOT NULL,
+    complexity_score INTEGER NOT NULL,
+    is_deprecated INTEGER NOT NULL,
+    replacement TEXT,
+    metadata TEXT NOT NULL
+);
+CREATE INDEX idx_entities_entity_id ON entities(entity_id);
+CREATE INDEX idx_entities_name ON entities(name);
+CREATE INDEX idx_entities_file ON entities(file_path);
+";
+
+/// Write entities into an `entities` table of a SQLite database.
+///
+/// List and map fields are stored as JSON text.
+#[cfg(feature = "sqlite")]
+pub fn to_sqlite(entities: &[CodeEntity], path: &Path) -> Result<()> {
+    use rusqlite::{params, Connection};
+
+    let entities = lazy::materialized(entities);
+
+    let mut conn = Connection::open(path)
+        .with_context(|| format!("Failed to open database: {:?}", path))?;
+    conn.execute_batch(SQLITE_SCHEMA)?;
+
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO entities (entity_id, content_hash, entity_type, name, file_path, line_number,
+                docstring, code, is_async, decorators, parameters, return_type, is_internal,
+                is_api_endpoint, endpoint_path, http_methods, complexity_score, is_deprecated,
+                replacement, metadata)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18,
+                ?19, ?20)",
+        )?;
+
+        for entity in entities.iter() {
+            stmt.execute(params![
+                entity.entity_id,
+                entity.content_hash,
+                entity.entity_type.as_str(),
+                entity.name,
+                entity.file_path.to_string_lossy().into_owned(),
+                entity.line_number as i64,
+                entity.docstring,
+                entity.code,
+                entity.is_async,
+                serde_json::to_string(&entity.decorators)?,
+                serde_json::to_string(&entity.parameters)?,
+                entity.return_type,
+                entity.is_internal,
+                entity.is_api_endpoint,
+                entity.endpoint_path,
+                serde_json::to_string(&entity.http_methods)?,
+                entity.complexity_score,
+                entity.is_deprecated,
+                entity.replacement,
+                serde_json::to_string(&entity.metadata)?,
+            ])?;
+        }
+    }
+    tx.commit()?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "sqlite"))]
+pub fn to_sqlite(_entities: &[CodeEntity], _path: &Path) -> Result<()> {
+    Err(anyhow::anyhow!("SQLite export requires the `sqlite` feature"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn sample() -> Vec<CodeEntity> {
+        let mut func = CodeEntity::new(
+            "function".to_string(),
+            "get_users".to_string(),
+            PathBuf::from("api.py"),
+            3,
+        );
+        func.code = "def get_users():".to_string();
+        func.docstring = Some("List users.".to_string());
+        func.decorators = vec!["app.get(\"/users\")".into()];
+        func.detect_api_endpoint();
+        vec![func]
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let json = to_json(&sample()).unwrap();
+        let parsed: Vec<CodeEntity> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed[0].name, "get_users");
+    }
+
+    #[test]
+    fn test_markdown_groups_by_file() {
+        let markdown = to_markdown(&sample());
+        assert!(markdown.contains("## `api.py`"));
+        assert!(markdown.contains("### function `get_users`"));
+        assert!(markdown.contains("**Endpoint:** `GET /users`"));
+        assert!(markdown.contains("List users."));
+    }
+
+    #[test]
+    fn test_embedding_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api.py");
+        std::fs::write(
+            &path,
+            "class Store:\n    def get(\n        self,\n    ):\n        \"\"\"Return the\n        cached value.\n\n        Never blocks.\n        \"\"\"\n\n        return self.cache\n",
+        )
+        .unwrap();
+        let entities = crate::analyzer::RustAnalyzer::new().analyze_file(&path).unwrap();
+
+        let records = embedding_records(&entities);
+        assert_eq!(records[1].qualified_name, "api.Store.get");
+        assert_eq!(
+            records[1].text,
+            format!("api.Store.get\n\n{}\n\nReturn the cached value.\nNever blocks.\n\n        return self.cache", entities[1].code.lines().next().unwrap().trim())
+        );
+        assert_eq!(to_embedding_jsonl(&sample()).unwrap().lines().count(), 1);
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!("md".parse::<ExportFormat>().unwrap(), ExportFormat::Markdown);
+        assert!("xml".parse::<ExportFormat>().is_err());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_export() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("entities.db");
+        to_sqlite(&sample(), &path).unwrap();
+
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        let name: String = conn
+            .query_row("SELECT name FROM entities", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(name, "get_users");
+    }
+}
@@ -0,0 +1,135 @@
+use rustpython_parser::ast;
+
+use crate::walk::{self, dotted_name, string_literal, BodyVisitor};
+
+/// Flag client methods that take the flag key as their first string
+/// argument: LaunchDarkly, Unleash, Flipper-style `is_enabled`, and
+/// django-waffle's helpers (which take the request first)
+const FLAG_METHODS: &[&str] = &[
+    "variation",
+    "bool_variation",
+    "string_variation",
+    "variation_detail",
+    "is_enabled",
+    "is_feature_enabled",
+    "feature_enabled",
+    "flag_is_active",
+    "switch_is_active",
+    "sample_is_active",
+];
+
+/// Decorators gating a view behind a flag, e.g. waffle's `@waffle_flag("x")`
+const FLAG_DECORATORS: &[&str] = &["waffle_flag", "waffle_switch", "feature_flag", "flag_required", "feature_required"];
+
+/// Objects holding configuration, whose upper-case attributes are settings
+const SETTINGS_OBJECTS: &[&str] = &["settings", "config", "conf", "flags", "features"];
+
+/// Feature flags gating a function: keys passed to flag clients and flag
+/// decorators, flag-like settings (`settings.FEATURE_X`, `ENABLE_Y`,
+/// `Z_ENABLED`) read anywhere, and upper-case constants tested by an `if`,
+/// `while` or conditional expression. Walk the body with it after
+/// `decorators`; names are in order of first use.
+#[derive(Default)]
+pub(crate) struct Flags(pub Vec<String>);
+
+impl Flags {
+    /// Keys of flag decorators
+    pub fn decorators(&mut self, decorators: &[ast::Expr]) {
+        for decorator in decorators {
+            if let ast::Expr::Call(call) = decorator {
+                if let Some(key) = flag_key(call, FLAG_DECORATORS) {
+                    self.add(key);
+                }
+            }
+        }
+    }
+
+    fn add(&mut self, name: &str) {
+        if !self.0.iter().any(|flag| flag == name) {
+            self.0.push(name.to_string());
+        }
+    }
+
+    /// Upper-case names and settings attributes in a condition
+    fn condition(&mut self, test: &ast::Expr) {
+        struct Constants<'f>(&'f mut Flags);
+        impl<'a> BodyVisitor<'a> for Constants<'_> {
+            fn expr(&mut self, expr: &'a ast::Expr) {
+                match expr {
+                    ast::Expr::Name(name) if is_constant(name.id.as_str()) => self.0.add(name.id.as_str()),
+                    ast::Expr::Attribute(attr) if is_setting(attr) => self.0.add(attr.attr.as_str()),
+                    _ => {}
+                }
+            }
+        }
+        walk::walk_expr(test, &mut Constants(self));
+    }
+}
+
+impl<'a> BodyVisitor<'a> for Flags {
+    fn stmt(&mut self, stmt: &'a ast::Stmt) {
+        match stmt {
+            ast::Stmt::If(s) => self.condition(&s.test),
+            ast::Stmt::While(s) => self.condition(&s.test),
+            _ => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &'a ast::Expr) {
+        match expr {
+            ast::Expr::IfExp(e) => self.condition(&e.test),
+            ast::Expr::Attribute(attr) if is_setting(attr) && is_flag_name(attr.attr.as_str()) => self.add(attr.attr.as_str()),
+            ast::Expr::Call(call) => {
+                if let Some(key) = flag_key(call, FLAG_METHODS) {
+                    self.add(key);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The first string argument of a call to one of `functions`
+fn flag_key<'a>(call: &'a ast::ExprCall, functions: &[&str]) -> Option<&'a str> {
+    let name = match &*call.func {
+        ast::Expr::Attribute(attr) => attr.attr.as_str(),
+        func => return dotted_name(func).filter(|name| functions.contains(&name.as_str())).and_then(|_| first_string(call)),
+    };
+    functions.contains(&name).then(|| first_string(call)).flatten()
+}
+
+fn first_string(call: &ast::ExprCall) -> Option<&str> {
+    call.args.iter().find_map(string_literal)
+}
+
+/// `settings.X`, `app.config.X`: an upper-case attribute of a settings object
+fn is_setting(attr: &ast::ExprAttribute) -> bool {
+    let owner = dotted_name(&attr.value);
+    let owner = owner.as_deref().and_then(|name| name.rsplit('.').next()).unwrap_or_default();
+    SETTINGS_OBJECTS.contains(&owner.to_ascii_lowercase().as_str()) && is_constant(attr.attr.as_str())
+}
+
+/// `FEATURE_X`, `FF_X`, `ENABLE_X`, `X_ENABLED`, `X_FLAG`
+fn is_flag_name(name: &str) -> bool {
+    ["FEATURE_", "FF_", "ENABLE_", "USE_"].iter().any(|prefix| name.starts_with(prefix))
+        || ["_ENABLED", "_FLAG", "_FEATURE"].iter().any(|suffix| name.ends_with(suffix))
+}
+
+/// `ALL_CAPS` with at least two letters, the convention for constants
+fn is_constant(name: &str) -> bool {
+    name.chars().filter(|c| c.is_ascii_uppercase()).count() >= 2
+        && name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::PythonParser;
+    use std::path::Path;
+
+    #[test]
+    fn test_feature_flags() {
+        let source = "@waffle_flag(\"beta_ui\")\ndef checkout(request, user):\n    if ld_client.variation(\"new-checkout\", user, False):\n        return new_flow()\n    limit = 10 if settings.FEATURE_BULK else 1\n    if USE_FAST_PATH and user.active:\n        pass\n    timeout = settings.TIMEOUT\n    return unleash.is_enabled(\"new-checkout\")\n";
+        let entities = PythonParser::new().parse_source(source, Path::new("views.py")).unwrap();
+        assert_eq!(entities[0].feature_flags, vec!["beta_ui", "new-checkout", "FEATURE_BULK", "USE_FAST_PATH"]);
+    }
+}
@@ -0,0 +1,74 @@
+use std::path::Path;
+
+/// Only the head of a file is searched for generator banners
+const HEADER_LEN: usize = 2048;
+
+/// File name suffixes produced by common code generators
+const GENERATED_SUFFIXES: &[&str] = &[
+    "_pb2.py",
+    "_pb2.pyi",
+    "_pb2_grpc.py",
+    ".pb.go",
+    "_grpc.pb.go",
+    ".pb.rs",
+    "_pb.js",
+    "_pb.d.ts",
+    ".generated.ts",
+    "_generated.py",
+    "_generated.rs",
+];
+
+/// Banners generators put at the top of their output, matched case-insensitively
+const GENERATED_MARKERS: &[&str] = &[
+    "@generated",
+    "generated by",
+    "autogenerated",
+    "auto-generated",
+    "do not edit",
+];
+
+/// Whether `path` with contents `source` looks machine-generated, judged by
+/// its file name or a generator banner in its first comment lines
+pub fn is_generated(path: &Path, source: &str) -> bool {
+    let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    if GENERATED_SUFFIXES.iter().any(|suffix| name.ends_with(suffix)) {
+        return true;
+    }
+
+    let mut end = source.len().min(HEADER_LEN);
+    while !source.is_char_boundary(end) {
+        end -= 1;
+    }
+    source[..end]
+        .lines()
+        .map(str::trim_start)
+        .filter(|line| is_comment(line))
+        .take(10)
+        .any(|line| {
+            let line = line.to_ascii_lowercase();
+            GENERATED_MARKERS.iter().any(|marker| line.contains(marker))
+        })
+}
+
+fn is_comment(line: &str) -> bool {
+    ["#", "//", "/*", "*", "--", "\"\"\"", "'''"]
+        .iter()
+        .any(|prefix| line.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_markers_and_names() {
+        assert!(is_generated(Path::new("api/users_pb2.py"), "import sys\n"));
+        assert!(is_generated(Path::new("client.py"), "#!/usr/bin/env python\n# Generated by openapi-generator\n"));
+        assert!(is_generated(Path::new("api.go"), "// Code generated by protoc-gen-go. DO NOT EDIT.\npackage api\n"));
+        assert!(is_generated(Path::new("schema.rs"), "// @generated\n"));
+
+        assert!(!is_generated(Path::new("app.py"), "def handler():\n    \"\"\"Handle it.\"\"\"\n"));
+        // Only comments count, not code that happens to mention a marker
+        assert!(!is_generated(Path::new("gen.py"), "MARKER = '@generated'\n"));
+    }
+}
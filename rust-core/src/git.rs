@@ -0,0 +1,223 @@
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::analyzer::RustAnalyzer;
+use crate::entity::CodeEntity;
+use crate::modules;
+
+/// Run a git command in `repo` and return its stdout
+fn git(repo: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Check whether `rev` names a commit in `repo`
+pub fn is_revision(repo: &Path, rev: &str) -> bool {
+    git(repo, &["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", rev)]).is_ok()
+}
+
+/// Analyze the source files of a git revision without checking it out.
+///
+/// Entity file paths are relative to the repository root, which is also
+/// what the include globs match and module paths start from.
+pub fn analyze_revision(analyzer: &RustAnalyzer, repo: &Path, rev: &str) -> Result<Vec<CodeEntity>> {
+    let listing = git(repo, &["ls-tree", "-r", "--name-only", rev])
+        .with_context(|| format!("Failed to list files at revision {}", rev))?;
+
+    let listed: HashSet<&Path> = listing.lines().map(Path::new).collect();
+    let files: Vec<&str> = listing
+        .lines()
+        .filter(|p| analyzer.selects(Path::new(p)))
+        .collect();
+    log::debug!("Analyzing {} source files at revision {}", files.len(), rev);
+
+    let results: Vec<Result<Vec<CodeEntity>>> = files
+        .par_iter()
+        .map(|path| {
+            let source = git(repo, &["show", &format!("{}:{}", rev, path)])?;
+            let module = modules::listed_module_path(Path::new(path), &listed);
+            analyzer.analyze_source_as(&source, Path::new(path), &module)
+        })
+        .collect();
+
+    let mut entities = Vec::new();
+    for (path, result) in files.iter().zip(results) {
+        match result {
+            Ok(file_entities) => entities.extend(file_entities),
+            Err(e) => log::warn!("Skipping {}@{}: {:#}", path, rev, e),
+        }
+    }
+
+    Ok(entities)
+}
+
+/// Author and commit that last touched a line
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BlameLine {
+    commit: String,
+    author: String,
+    timestamp: i64,
+}
+
+/// Annotate entities with the most recent commit touching their lines.
+///
+/// Sets the `git_commit`, `git_author` and `git_date` (UTC, `YYYY-MM-DD`)
+/// metadata keys. Files that git doesn't track and uncommitted lines are
+/// skipped. Entities without an `end_line` are blamed on their first line.
+pub fn blame_entities(repo: &Path, entities: &mut [CodeEntity]) -> Result<()> {
+    let mut by_file: BTreeMap<PathBuf, Vec<usize>> = BTreeMap::new();
+    for (index, entity) in entities.iter().enumerate() {
+        by_file.entry(entity.source_path().to_path_buf()).or_default().push(index);
+    }
+
+    let blames: Vec<(Vec<usize>, Vec<Option<BlameLine>>)> = by_file
+        .into_par_iter()
+        .filter_map(|(file, indices)| {
+            let file = file.canonicalize().unwrap_or(file);
+            match blame_file(repo, &file) {
+                Ok(lines) => Some((indices, lines)),
+                Err(e) => {
+                    log::debug!("No blame for {}: {:#}", file.display(), e);
+                    None
+                }
+            }
+        })
+        .collect();
+
+    for (indices, lines) in blames {
+        for index in indices {
+            let entity = &mut entities[index];
+            let first = entity.line_number.max(1);
+            let last = entity.end_line.max(first).min(lines.len());
+            let latest = lines
+                .get(first - 1..last)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .max_by_key(|line| line.timestamp);
+            if let Some(line) = latest {
+                entity.metadata.insert("git_commit".to_string(), line.commit.clone());
+                entity.metadata.insert("git_author".to_string(), line.author.clone());
+                entity.metadata.insert("git_date".to_string(), format_date(line.timestamp));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Per-line blame of a working-tree file; `None` for uncommitted lines
+fn blame_file(repo: &Path, file: &Path) -> Result<Vec<Option<BlameLine>>> {
+    let output = git(repo, &["blame", "--line-porcelain", "--", &file.to_string_lossy()])?;
+
+    let mut lines = Vec::new();
+    let mut current = BlameLine {
+        commit: String::new(),
+        author: String::new(),
+        timestamp: 0,
+    };
+    for line in output.lines() {
+        if line.starts_with('\t') {
+            let committed = current.commit.bytes().any(|b| b != b'0');
+            lines.push(committed.then(|| current.clone()));
+        } else if let Some(author) = line.strip_prefix("author ") {
+            current.author = author.to_string();
+        } else if let Some(time) = line.strip_prefix("author-time ") {
+            current.timestamp = time.trim().parse().unwrap_or(0);
+        } else if let Some(sha) = line.split(' ').next().filter(|t| t.len() == 40 && t.bytes().all(|b| b.is_ascii_hexdigit())) {
+            current.commit = sha.to_string();
+        }
+    }
+    Ok(lines)
+}
+
+/// `YYYY-MM-DD` of a Unix timestamp in UTC
+fn format_date(timestamp: i64) -> String {
+    // Days-to-civil conversion from Howard Hinnant's date algorithms
+    let z = timestamp.div_euclid(86_400) + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AnalyzerConfig;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn init_repo() -> Option<TempDir> {
+        let dir = TempDir::new().unwrap();
+        let run = |args: &[&str]| git(dir.path(), args).ok();
+        run(&["init", "-q"])?;
+        run(&["config", "user.email", "test@example.com"])?;
+        run(&["config", "user.name", "Test"])?;
+        fs::write(dir.path().join("mod.py"), "def first(): pass").unwrap();
+        run(&["add", "."])?;
+        run(&["commit", "-q", "-m", "first"])?;
+        fs::write(dir.path().join("mod.py"), "def second(): pass").unwrap();
+        Some(dir)
+    }
+
+    #[test]
+    fn test_analyze_revision_reads_committed_content() {
+        // Skip quietly when git isn't available in the test environment
+        let Some(repo) = init_repo() else { return };
+
+        assert!(is_revision(repo.path(), "HEAD"));
+        assert!(!is_revision(repo.path(), "does-not-exist"));
+
+        let entities = analyze_revision(&RustAnalyzer::new(), repo.path(), "HEAD").unwrap();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].name, "first");
+        assert_eq!(entities[0].file_path, Path::new("mod.py"));
+
+        let config = AnalyzerConfig { include: vec!["src/**".to_string()], ..AnalyzerConfig::default() };
+        let elsewhere = analyze_revision(&RustAnalyzer::with_config(config), repo.path(), "HEAD").unwrap();
+        assert!(elsewhere.is_empty());
+    }
+
+    #[test]
+    fn test_blame_entities() {
+        let Some(repo) = init_repo() else { return };
+        // Restore the committed content; uncommitted lines carry no blame
+        fs::write(repo.path().join("mod.py"), "def first(): pass").unwrap();
+
+        let mut entities = RustAnalyzer::new().analyze_file(&repo.path().join("mod.py")).unwrap();
+        blame_entities(repo.path(), &mut entities).unwrap();
+
+        assert_eq!(entities[0].metadata["git_author"], "Test");
+        assert_eq!(entities[0].metadata["git_commit"].len(), 40);
+    }
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(1_700_000_000), "2023-11-14");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+    }
+}
@@ -0,0 +1,118 @@
+use rayon::prelude::*;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::entity::CodeEntity;
+
+/// Entity text a grep can look at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GrepField {
+    Name,
+    Code,
+    Docstring,
+}
+
+impl GrepField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GrepField::Name => "name",
+            GrepField::Code => "code",
+            GrepField::Docstring => "docstring",
+        }
+    }
+
+    fn text<'a>(&self, entity: &'a CodeEntity) -> &'a str {
+        match self {
+            GrepField::Name => &entity.name,
+            GrepField::Code => &entity.code,
+            GrepField::Docstring => entity.docstring.as_deref().unwrap_or(""),
+        }
+    }
+}
+
+impl std::str::FromStr for GrepField {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "name" => Ok(GrepField::Name),
+            "code" => Ok(GrepField::Code),
+            "docstring" | "doc" => Ok(GrepField::Docstring),
+            other => Err(anyhow::anyhow!("Unknown grep field: {}", other)),
+        }
+    }
+}
+
+/// One regex match inside an entity field
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GrepMatch {
+    /// Position of the entity in the searched slice
+    pub index: usize,
+    pub field: GrepField,
+    /// Byte range of the match within the field text
+    pub start: usize,
+    pub end: usize,
+    /// 1-based line and column (in characters) within the field text
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+}
+
+/// Every match of `regex` in the given fields of `entities`.
+///
+/// Entities are scanned in parallel; results come back ordered by entity,
+/// then by field as listed in `fields`, then by position.
+pub fn grep(entities: &[CodeEntity], regex: &Regex, fields: &[GrepField]) -> Vec<GrepMatch> {
+    entities
+        .par_iter()
+        .enumerate()
+        .flat_map_iter(move |(index, entity)| {
+            fields.iter().flat_map(move |&field| {
+                let text = field.text(entity);
+                regex.find_iter(text).map(move |m| {
+                    let before = &text[..m.start()];
+                    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+                    GrepMatch {
+                        index,
+                        field,
+                        start: m.start(),
+                        end: m.end(),
+                        line: before.matches('\n').count() + 1,
+                        column: before[line_start..].chars().count() + 1,
+                        text: m.as_str().to_string(),
+                    }
+                })
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn entity(name: &str, code: &str, doc: Option<&str>) -> CodeEntity {
+        let mut entity = CodeEntity::new("function".to_string(), name.to_string(), PathBuf::from("a.py"), 1);
+        entity.code = code.to_string();
+        entity.docstring = doc.map(str::to_string);
+        entity
+    }
+
+    #[test]
+    fn test_grep_positions() {
+        let entities = vec![
+            entity("fetch", "def fetch(url, retries=3):", Some("Fetch a URL.\n\nRetries on failure.")),
+            entity("parse", "def parse(body):", None),
+        ];
+        let regex = Regex::new(r"(?i)retries").unwrap();
+        let matches = grep(&entities, &regex, &[GrepField::Code, GrepField::Docstring]);
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!((matches[0].field, matches[0].line, matches[0].column), (GrepField::Code, 1, 16));
+        assert_eq!((matches[1].field, matches[1].line, matches[1].column), (GrepField::Docstring, 3, 1));
+        assert_eq!(&entities[0].docstring.as_deref().unwrap()[matches[1].start..matches[1].end], "Retries");
+        assert!(grep(&entities, &regex, &[GrepField::Name]).is_empty());
+    }
+}
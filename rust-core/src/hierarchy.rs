@@ -0,0 +1,134 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use crate::entity::CodeEntity;
+use crate::schema::SCHEMA_ENTITY;
+
+/// Index of each entity's parent among the entities of one file.
+///
+/// An entity's parent is the innermost other entity whose line span holds
+/// it, so classes own their methods and functions their nested functions
+/// in every backend that records `end_line`. Module-level entities have no
+/// parent; schema entities take no part, as tables created inside a
+/// migration function aren't members of it.
+pub fn parents(entities: &[CodeEntity]) -> Vec<Option<usize>> {
+    let mut order: Vec<usize> = (0..entities.len())
+        .filter(|&i| entities[i].entity_type != SCHEMA_ENTITY)
+        .collect();
+    order.sort_by_key(|&i| (entities[i].line_number, Reverse(entities[i].end_line)));
+
+    let mut parents: Vec<Option<usize>> = vec![None; entities.len()];
+    let mut open: Vec<usize> = Vec::new();
+    for index in order {
+        let (start, end) = (entities[index].line_number, entities[index].end_line.max(entities[index].line_number));
+        while open.last().map_or(false, |&top| entities[top].end_line < end) {
+            open.pop();
+        }
+        parents[index] = open.last().copied();
+        if entities[index].end_line > start {
+            open.push(index);
+        }
+    }
+    parents
+}
+
+/// Fill in `parent_id` and `children` for the entities of one file, as
+/// found by `parents`. Ids must already be assigned.
+pub fn link(entities: &mut [CodeEntity]) {
+    let parents = parents(entities);
+    for entity in entities.iter_mut() {
+        entity.parent_id = None;
+        entity.children.clear();
+    }
+    for (child, parent) in parents.into_iter().enumerate() {
+        let Some(parent) = parent else { continue };
+        let parent_id = entities[parent].entity_id.clone();
+        let child_id = entities[child].entity_id.clone();
+        entities[child].parent_id = Some(parent_id);
+        entities[parent].children.push(child_id);
+    }
+}
+
+/// Complete `qualified_name` for the entities of one file. Backends that
+/// track nesting while visiting fill in the part below the module
+/// (`Client.get`); other entities are named after their chain of
+/// `parents`. `module` goes in front unless it is empty. Ids are derived
+/// from the result, so this runs before they are assigned.
+pub fn qualify(entities: &mut [CodeEntity], module: &str) {
+    let parents = parents(entities);
+    let mut local: Vec<Option<String>> = entities
+        .iter()
+        .map(|entity| (!entity.qualified_name.is_empty()).then(|| entity.qualified_name.clone()))
+        .collect();
+
+    fn resolve(index: usize, entities: &[CodeEntity], parents: &[Option<usize>], local: &mut [Option<String>]) -> String {
+        if let Some(name) = &local[index] {
+            return name.clone();
+        }
+        let entity = &entities[index];
+        let name = match parents[index] {
+            Some(parent) => format!("{}.{}", resolve(parent, entities, parents, local), entity.name),
+            None => entity.name.clone(),
+        };
+        local[index] = Some(name.clone());
+        name
+    }
+
+    for index in 0..entities.len() {
+        let name = resolve(index, entities, &parents, &mut local);
+        entities[index].qualified_name = if module.is_empty() { name } else { format!("{}.{}", module, name) };
+    }
+}
+
+/// Drop links to entities that are no longer in the set, e.g. after
+/// visibility filtering
+pub fn prune(entities: &mut [CodeEntity]) {
+    let present: HashSet<String> = entities.iter().map(|e| e.entity_id.clone()).collect();
+    for entity in entities.iter_mut() {
+        if entity.parent_id.as_ref().map_or(false, |id| !present.contains(id)) {
+            entity.parent_id = None;
+        }
+        entity.children.retain(|id| present.contains(id));
+    }
+}
+
+/// Index of each entity by id, for walking `parent_id` and `children`
+pub fn index_by_id(entities: &[CodeEntity]) -> HashMap<&str, usize> {
+    entities
+        .iter()
+        .enumerate()
+        .map(|(index, entity)| (entity.entity_id.as_str(), index))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::PythonParser;
+    use std::path::Path;
+
+    #[test]
+    fn test_nesting() {
+        let source = "class Client:\n    def get(self):\n        def retry():\n            pass\n\n    def close(self):\n        pass\n\ndef main():\n    pass\n";
+        let mut entities = PythonParser::new().parse_source(source, Path::new("api.py")).unwrap();
+        qualify(&mut entities, "pkg.api");
+        crate::identity::assign_ids(&mut entities, None);
+        link(&mut entities);
+
+        let names: Vec<&str> = entities.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["Client", "get", "retry", "close", "main"]);
+        let parent = |i: usize| {
+            let ids = index_by_id(&entities);
+            entities[i].parent_id.as_deref().map(|id| entities[ids[id]].name.as_str())
+        };
+        assert_eq!((parent(0), parent(1), parent(2), parent(3), parent(4)), (None, Some("Client"), Some("get"), Some("Client"), None));
+        assert_eq!(entities[0].children, vec![entities[1].entity_id.clone(), entities[3].entity_id.clone()]);
+
+        assert_eq!(entities[2].qualified_name, "pkg.api.Client.get.retry");
+        assert_eq!(entities[4].qualified_name, "pkg.api.main");
+
+        entities.remove(2);
+        prune(&mut entities);
+        assert!(entities[1].children.is_empty());
+    }
+}
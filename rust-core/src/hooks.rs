@@ -0,0 +1,62 @@
+use crate::entity::CodeEntity;
+
+/// Post-processing rule invoked for every entity before it is returned.
+///
+/// Hooks may mutate or annotate the entity in place; returning `false` drops
+/// it from the results. Plain closures can be used as hooks.
+pub trait EntityHook: Send + Sync {
+    fn process(&self, entity: &mut CodeEntity) -> bool;
+}
+
+impl<F> EntityHook for F
+where
+    F: Fn(&mut CodeEntity) -> bool + Send + Sync,
+{
+    fn process(&self, entity: &mut CodeEntity) -> bool {
+        self(entity)
+    }
+}
+
+/// Run every hook over the entities, in registration order.
+///
+/// Once a hook drops an entity, later hooks are not invoked for it.
+pub fn apply_hooks(hooks: &[Box<dyn EntityHook>], entities: &mut Vec<CodeEntity>) {
+    if hooks.is_empty() {
+        return;
+    }
+
+    entities.retain_mut(|entity| hooks.iter().all(|hook| hook.process(entity)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn entity(name: &str) -> CodeEntity {
+        CodeEntity::new(
+            "function".to_string(),
+            name.to_string(),
+            PathBuf::from("test.py"),
+            1,
+        )
+    }
+
+    #[test]
+    fn test_hooks_annotate_and_drop() {
+        let hooks: Vec<Box<dyn EntityHook>> = vec![
+            Box::new(|e: &mut CodeEntity| !e.name.starts_with("test_")),
+            Box::new(|e: &mut CodeEntity| {
+                e.metadata.insert("team".to_string(), "docs".to_string());
+                true
+            }),
+        ];
+
+        let mut entities = vec![entity("run"), entity("test_run")];
+        apply_hooks(&hooks, &mut entities);
+
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].name, "run");
+        assert_eq!(entities[0].metadata.get("team"), Some(&"docs".to_string()));
+    }
+}
@@ -0,0 +1,36 @@
+use std::borrow::Cow;
+
+use unicode_normalization::{is_nfkc_quick, IsNormalized, UnicodeNormalization};
+
+/// NFKC-normalize an identifier, as Python does when parsing, so that `ﬁle`
+/// and `file` or full-width and ASCII spellings name the same thing.
+///
+/// ASCII and already-normalized text is returned borrowed. Also safe to apply
+/// to whole source snippets before searching them for identifiers.
+pub fn normalize_identifier(name: &str) -> Cow<'_, str> {
+    if name.is_ascii() || is_nfkc_quick(name.chars()) == IsNormalized::Yes {
+        Cow::Borrowed(name)
+    } else {
+        Cow::Owned(name.nfkc().collect())
+    }
+}
+
+/// Whether two identifiers are equal once normalized
+pub fn same_identifier(a: &str, b: &str) -> bool {
+    a == b || normalize_identifier(a) == normalize_identifier(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nfkc_identifiers() {
+        assert!(matches!(normalize_identifier("load_file"), Cow::Borrowed(_)));
+        assert!(matches!(normalize_identifier("café"), Cow::Borrowed(_)));
+        assert_eq!(normalize_identifier("\u{fb01}le"), "file");
+        assert_eq!(normalize_identifier("ｆｕｌｌ"), "full");
+        assert!(same_identifier("\u{212b}ngstr\u{f6}m", "\u{c5}ngstr\u{f6}m"));
+        assert!(!same_identifier("a", "b"));
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::entity::CodeEntity;
+use crate::intern::Symbol;
+
+/// 64-bit FNV-1a; unlike `DefaultHasher` its output is fixed across Rust
+/// versions and platforms, so hashes can be persisted
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// Fill in `entity_id` and `content_hash` for a batch of entities.
+///
+/// The id hashes the entity kind and its `qualified_name`, so it survives
+/// line shifts, edits to the entity itself and changes elsewhere in the
+/// file; run `hierarchy::qualify` first. Entities without a qualified name
+/// fall back to their name qualified by the file path (relative to `root`
+/// when given). Only redefinitions of the same name in the same scope are
+/// told apart by their order of appearance. The content hash covers the
+/// signature, docstring and decorators.
+pub fn assign_ids(entities: &mut [CodeEntity], root: Option<&Path>) {
+    let mut seen: HashMap<(Symbol, String), usize> = HashMap::new();
+
+    for entity in entities.iter_mut() {
+        let qualified = if entity.qualified_name.is_empty() {
+            let path = root
+                .and_then(|root| entity.file_path.strip_prefix(root).ok())
+                .unwrap_or(entity.file_path.as_path())
+                .to_string_lossy()
+                .replace('\\', "/");
+            format!("{}::{}", path, entity.name)
+        } else {
+            entity.qualified_name.clone()
+        };
+
+        let occurrence = seen.entry((entity.entity_type.clone(), qualified.clone())).or_insert(0);
+        *occurrence += 1;
+        let key = match *occurrence {
+            1 => format!("{}\0{}", entity.entity_type, qualified),
+            n => format!("{}\0{}#{}", entity.entity_type, qualified, n),
+        };
+        entity.entity_id = format!("{:016x}", fnv1a(key.as_bytes()));
+
+        let content = format!(
+            "{}\0{}\0{}",
+            entity.code,
+            entity.docstring.as_deref().unwrap_or(""),
+            entity.decorators.join("\n")
+        );
+        entity.content_hash = format!("{:016x}", fnv1a(content.as_bytes()));
+    }
+}
+
+/// Fill in `source_hash` from the lines each entity spans in `source`.
+///
+/// Indentation common to the span, blank lines, trailing whitespace and
+/// line endings don't count, so moving a function into a class or
+/// reformatting whitespace leaves the hash alone while any edit to the body
+/// changes it. Entities without a known span keep an empty hash.
+pub fn hash_sources(entities: &mut [CodeEntity], source: &str) {
+    let lines: Vec<&str> = source.lines().collect();
+    for entity in entities.iter_mut() {
+        if entity.line_number == 0 || entity.end_line < entity.line_number {
+            continue;
+        }
+        let Some(span) = lines.get(entity.line_number - 1..entity.end_line.min(lines.len())) else {
+            continue;
+        };
+        let kept: Vec<&str> = span.iter().map(|line| line.trim_end()).filter(|line| !line.is_empty()).collect();
+        // Only ASCII indentation counts, so `indent` is a char boundary in every line
+        let indent = kept
+            .iter()
+            .map(|line| line.len() - line.trim_start_matches([' ', '\t']).len())
+            .min()
+            .unwrap_or(0);
+        let normalized: Vec<&str> = kept.iter().map(|line| line.get(indent..).unwrap_or(line)).collect();
+        entity.source_hash = format!("{:016x}", fnv1a(normalized.join("\n").as_bytes()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn entity(root: &str, line: usize, code: &str) -> CodeEntity {
+        let mut entity = CodeEntity::new(
+            "function".to_string(),
+            "handler".to_string(),
+            PathBuf::from(root).join("pkg/api.py"),
+            line,
+        );
+        entity.code = code.to_string();
+        entity
+    }
+
+    #[test]
+    fn test_ids_survive_moves_and_edits() {
+        let mut before = vec![entity("/checkout/a", 3, "def handler():")];
+        let mut after = vec![entity("/checkout/b", 40, "def handler(request):")];
+        assign_ids(&mut before, Some(Path::new("/checkout/a")));
+        assign_ids(&mut after, Some(Path::new("/checkout/b")));
+
+        assert_eq!(before[0].entity_id, after[0].entity_id);
+        assert_ne!(before[0].content_hash, after[0].content_hash);
+        assert_eq!(before[0].entity_id.len(), 16);
+    }
+
+    #[test]
+    fn test_source_hash_ignores_layout() {
+        let mut entities = vec![entity("/r", 1, "def handler():"), entity("/r", 5, "def handler():")];
+        entities[0].end_line = 3;
+        entities[1].end_line = 8;
+        let source = "def handler():\n    x = 1\n    return x\nclass Moved:\n    def handler():  \r\n\n        x = 1\n        return x\n";
+        hash_sources(&mut entities, source);
+        assert_eq!(entities[0].source_hash, entities[1].source_hash);
+
+        hash_sources(&mut entities, &source.replace("return x\nclass", "return x + 1\nclass"));
+        assert_ne!(entities[0].source_hash, entities[1].source_hash);
+
+        // Non-ASCII whitespace isn't indentation and mustn't split a character
+        hash_sources(&mut entities, " \u{a0}x\n\u{a0}y\n\u{a0}z\n");
+        assert_eq!(entities[0].source_hash.len(), 16);
+    }
+
+    #[test]
+    fn test_duplicate_names_get_distinct_ids() {
+        let mut entities = vec![entity("/r", 1, "def handler():"), entity("/r", 9, "def handler():")];
+        assign_ids(&mut entities, None);
+        assert_ne!(entities[0].entity_id, entities[1].entity_id);
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+    }
+
+    #[test]
+    fn test_ids_ignore_same_names_in_other_scopes() {
+        let analyzer = crate::analyzer::RustAnalyzer::new();
+        let path = Path::new("api.py");
+        let before = analyzer.analyze_source("class B:\n    def get(self):\n        pass\n", path).unwrap();
+        let after = analyzer
+            .analyze_source("class A:\n    def get(self):\n        pass\n\nclass B:\n    def get(self):\n        pass\n", path)
+            .unwrap();
+        assert_eq!(before[1].qualified_name, "api.B.get");
+        assert_eq!(after[3].entity_id, before[1].entity_id);
+        assert_eq!(after[3].parent_id.as_ref(), Some(&after[2].entity_id));
+    }
+}
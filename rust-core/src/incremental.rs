@@ -0,0 +1,177 @@
+use anyhow::Result;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::analyzer::RustAnalyzer;
+use crate::endpoints;
+use crate::entity::CodeEntity;
+use crate::inheritance;
+use crate::schema;
+use crate::testmap;
+
+/// Per-file analysis results kept between refreshes
+struct CachedFile {
+    modified: Option<SystemTime>,
+    len: u64,
+    entities: Vec<CodeEntity>,
+}
+
+/// Outcome of one incremental refresh
+#[derive(Debug, Clone, Default)]
+pub struct RefreshSummary {
+    /// Files (re-)analyzed because they were new or changed
+    pub analyzed: usize,
+    /// Files whose cached results were reused
+    pub reused: usize,
+    /// Files dropped because they no longer exist
+    pub removed: usize,
+    /// Files that failed to analyze
+    pub failed: usize,
+    /// Total entities after the refresh
+    pub entities: usize,
+    pub duration: Duration,
+}
+
+impl std::fmt::Display for RefreshSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} entities in {}ms ({} analyzed, {} reused, {} removed, {} failed)",
+            self.entities,
+            self.duration.as_millis(),
+            self.analyzed,
+            self.reused,
+            self.removed,
+            self.failed,
+        )
+    }
+}
+
+/// In-memory cache that only re-analyzes files whose size or modification
+/// time changed since the previous refresh
+pub struct IncrementalAnalyzer {
+    analyzer: RustAnalyzer,
+    files: HashMap<PathBuf, CachedFile>,
+    /// Root of the last refresh, which decides what counts as a test file
+    root: Option<PathBuf>,
+}
+
+impl IncrementalAnalyzer {
+    pub fn new(analyzer: RustAnalyzer) -> Self {
+        IncrementalAnalyzer {
+            analyzer,
+            files: HashMap::new(),
+            root: None,
+        }
+    }
+
+    /// Bring the cache up to date with the files under `root`
+    pub fn refresh(&mut self, root: &Path) -> Result<RefreshSummary> {
+        let start = Instant::now();
+        let mut summary = RefreshSummary::default();
+
+        let current = self.analyzer.collect_source_files(root)?;
+        self.root = Some(root.to_path_buf());
+
+        let current_set: HashSet<&PathBuf> = current.iter().collect();
+        let before = self.files.len();
+        self.files.retain(|path, _| current_set.contains(path));
+        summary.removed = before - self.files.len();
+
+        let mut stale = Vec::new();
+        for path in current {
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+            let modified = metadata.modified().ok();
+            match self.files.get(&path) {
+                Some(cached) if cached.modified == modified && cached.len == metadata.len() => {
+                    summary.reused += 1;
+                }
+                _ => stale.push((path, modified, metadata.len())),
+            }
+        }
+
+        let analyzer = &self.analyzer;
+        let results: Vec<_> = analyzer.install(|| {
+            stale
+                .into_par_iter()
+                .map(|(path, modified, len)| {
+                    let result = analyzer.analyze_file_in(&path, Some(root));
+                    (path, modified, len, result)
+                })
+                .collect()
+        });
+
+        for (path, modified, len, result) in results {
+            summary.analyzed += 1;
+            match result {
+                Ok(entities) => {
+                    self.files.insert(path, CachedFile { modified, len, entities });
+                }
+                Err(e) => {
+                    summary.failed += 1;
+                    log::warn!("Skipping {}: {:#}", path.display(), e);
+                    self.files.remove(&path);
+                }
+            }
+        }
+
+        summary.entities = self.files.values().map(|f| f.entities.len()).sum();
+        summary.duration = start.elapsed();
+        Ok(summary)
+    }
+
+    /// All cached entities, ordered by file path
+    pub fn entities(&self) -> Vec<CodeEntity> {
+        let mut paths: Vec<&PathBuf> = self.files.keys().collect();
+        paths.sort();
+        let mut entities: Vec<CodeEntity> = paths
+            .into_iter()
+            .flat_map(|p| self.files[p].entities.iter().cloned())
+            .collect();
+        schema::link_models(&mut entities);
+        inheritance::resolve(&mut entities, self.analyzer.config());
+        endpoints::link_models(&mut entities);
+        endpoints::link_routers(&mut entities);
+        testmap::link(&mut entities, self.root.as_deref());
+        entities
+    }
+
+    pub fn analyzer(&self) -> &RustAnalyzer {
+        &self.analyzer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_refresh_reuses_unchanged_files() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.py"), "def a(): pass").unwrap();
+        fs::write(temp_dir.path().join("b.py"), "def b(): pass").unwrap();
+
+        let mut incremental = IncrementalAnalyzer::new(RustAnalyzer::new());
+        let first = incremental.refresh(temp_dir.path()).unwrap();
+        assert_eq!(first.analyzed, 2);
+        assert_eq!(first.entities, 2);
+
+        fs::write(temp_dir.path().join("b.py"), "def b(): pass\ndef c(): pass").unwrap();
+        let second = incremental.refresh(temp_dir.path()).unwrap();
+        assert_eq!(second.analyzed, 1);
+        assert_eq!(second.reused, 1);
+        assert_eq!(second.entities, 3);
+
+        fs::remove_file(temp_dir.path().join("a.py")).unwrap();
+        let third = incremental.refresh(temp_dir.path()).unwrap();
+        assert_eq!(third.removed, 1);
+        assert_eq!(third.analyzed, 0);
+        assert_eq!(incremental.entities().len(), 2);
+    }
+}
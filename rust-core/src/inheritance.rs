@@ -0,0 +1,350 @@
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use anyhow::anyhow;
+
+use crate::config::AnalyzerConfig;
+use crate::entity::CodeEntity;
+use crate::hierarchy;
+
+/// How an overriding method's parameters compare with the base method's
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureChange {
+    /// Same parameters in the same order
+    Same,
+    /// Takes every parameter the base does plus more, or `*args`/`**kwargs`
+    Widened,
+    /// Drops parameters the base takes
+    Narrowed,
+    /// Renames or reorders parameters
+    Changed,
+}
+
+impl SignatureChange {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignatureChange::Same => "same",
+            SignatureChange::Widened => "widened",
+            SignatureChange::Narrowed => "narrowed",
+            SignatureChange::Changed => "changed",
+        }
+    }
+
+    /// Compare the parameter lists of a base method and its override,
+    /// ignoring the leading `self`/`cls`. Defaults aren't tracked, so extra
+    /// trailing parameters count as widening.
+    pub fn compare(base: &[String], method: &[String]) -> Self {
+        let (base, method) = (receiverless(base), receiverless(method));
+        if base == method {
+            return SignatureChange::Same;
+        }
+        let (base_named, method_named) = (named(base), named(method));
+        let variadic = |params: &[String], prefix: &str| params.iter().any(|p| p.len() > prefix.len() && p.starts_with(prefix) && !p[prefix.len()..].starts_with('*'));
+        let covers = |wide: &[String], narrow: &[String]| {
+            (variadic(wide, "*") || !variadic(narrow, "*")) && (variadic(wide, "**") || !variadic(narrow, "**"))
+        };
+
+        if (method_named.starts_with(&base_named) && covers(method, base))
+            || (variadic(method, "*") && variadic(method, "**"))
+        {
+            SignatureChange::Widened
+        } else if base_named.starts_with(&method_named) && covers(base, method) {
+            SignatureChange::Narrowed
+        } else {
+            SignatureChange::Changed
+        }
+    }
+}
+
+impl FromStr for SignatureChange {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "same" => Ok(SignatureChange::Same),
+            "widened" => Ok(SignatureChange::Widened),
+            "narrowed" => Ok(SignatureChange::Narrowed),
+            "changed" => Ok(SignatureChange::Changed),
+            other => Err(anyhow!("Unknown signature change: {}", other)),
+        }
+    }
+}
+
+fn receiverless(params: &[String]) -> &[String] {
+    match params.first().map(String::as_str) {
+        Some("self") | Some("cls") => &params[1..],
+        _ => params,
+    }
+}
+
+fn named(params: &[String]) -> Vec<&String> {
+    params.iter().filter(|p| !p.starts_with('*')).collect()
+}
+
+/// Base classes that make a class an exception when they can't be resolved
+/// to an analyzed class; names ending in `Error`, `Exception` or `Warning`
+/// count too
+const EXCEPTION_BASES: &[&str] = &[
+    "BaseException",
+    "BaseExceptionGroup",
+    "GeneratorExit",
+    "KeyboardInterrupt",
+    "StopAsyncIteration",
+    "StopIteration",
+    "SystemExit",
+];
+
+/// Fill in everything derived from the class hierarchy of `entities`:
+/// `overrides`/`override_change` on methods, `is_exception` on classes and,
+/// with `inherit_docstrings`, docstrings of undocumented overrides
+pub fn resolve(entities: &mut [CodeEntity], config: &AnalyzerConfig) {
+    let graph = ClassGraph::build(entities);
+    detect_overrides(entities, &graph);
+    mark_exceptions(entities, &graph);
+    inherit_docstrings(entities, config.inherit_docstrings);
+}
+
+/// Resolved bases of every class entity.
+///
+/// Bases are matched by qualified name, then by name within the same
+/// module, then by a name that only one analyzed class has; generic
+/// arguments are ignored. Bases that don't resolve, e.g. from third-party
+/// packages, are kept as `None` so their names can still be inspected.
+struct ClassGraph {
+    bases: HashMap<usize, Vec<Option<usize>>>,
+}
+
+impl ClassGraph {
+    fn build(entities: &[CodeEntity]) -> Self {
+        let classes: Vec<usize> = (0..entities.len()).filter(|&i| entities[i].entity_type == "class").collect();
+        let mut by_qualified: HashMap<&str, usize> = HashMap::new();
+        let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+        for &class in &classes {
+            by_qualified.insert(entities[class].qualified_name.as_str(), class);
+            by_name.entry(entities[class].name.as_str()).or_default().push(class);
+        }
+
+        let resolve = |class: &CodeEntity, base: &str| -> Option<usize> {
+            let base = base_name(base);
+            if base.is_empty() || base.contains('(') {
+                return None;
+            }
+            if let Some(&index) = by_qualified.get(base) {
+                return Some(index);
+            }
+            if let Some(&index) = by_qualified.get(format!("{}.{}", class.module_path, base).as_str()) {
+                return Some(index);
+            }
+            let short = base.rsplit('.').next().unwrap_or(base);
+            match by_name.get(short).map(Vec::as_slice) {
+                Some([only]) => Some(*only),
+                Some(candidates) => candidates
+                    .iter()
+                    .copied()
+                    .find(|&c| entities[c].module_path == class.module_path),
+                None => None,
+            }
+        };
+        let bases = classes
+            .iter()
+            .map(|&class| {
+                let entity = &entities[class];
+                let resolved = entity
+                    .bases
+                    .iter()
+                    .map(|base| resolve(entity, base).filter(|&b| b != class))
+                    .collect();
+                (class, resolved)
+            })
+            .collect();
+        ClassGraph { bases }
+    }
+
+    fn classes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.bases.keys().copied()
+    }
+
+    /// Ancestors of `class` in lookup order (depth-first, left to right),
+    /// each listed once
+    fn ancestors(&self, class: usize) -> Vec<usize> {
+        let parents = |class: usize| self.bases.get(&class).into_iter().flatten().rev().flatten().copied();
+        let mut order = Vec::new();
+        let mut seen = HashSet::from([class]);
+        let mut stack: Vec<usize> = parents(class).collect();
+        while let Some(next) = stack.pop() {
+            if !seen.insert(next) {
+                continue;
+            }
+            order.push(next);
+            stack.extend(parents(next));
+        }
+        order
+    }
+}
+
+/// A base as written, without generic arguments: `Generic[T]` -> `Generic`
+fn base_name(base: &str) -> &str {
+    base.split('[').next().unwrap_or_default().trim()
+}
+
+/// Mark methods that redefine a method of an ancestor class; the nearest
+/// definition along the first base wins
+fn detect_overrides(entities: &mut [CodeEntity], graph: &ClassGraph) {
+    let found: Vec<(usize, String, SignatureChange)> = {
+        let ids = hierarchy::index_by_id(entities);
+        let methods: HashMap<usize, HashMap<&str, usize>> = graph
+            .classes()
+            .map(|class| {
+                let members = entities[class]
+                    .children
+                    .iter()
+                    .filter_map(|id| ids.get(id.as_str()).copied())
+                    .filter(|&child| entities[child].entity_type == "method")
+                    .map(|child| (entities[child].name.as_str(), child))
+                    .collect();
+                (class, members)
+            })
+            .collect();
+
+        let mut found = Vec::new();
+        for (&class, members) in &methods {
+            let ancestors = graph.ancestors(class);
+            for (&name, &method) in members {
+                let base_method = ancestors.iter().find_map(|ancestor| methods[ancestor].get(name).copied());
+                if let Some(base_method) = base_method {
+                    let change = SignatureChange::compare(&entities[base_method].parameters, &entities[method].parameters);
+                    found.push((method, entities[base_method].qualified_name.clone(), change));
+                }
+            }
+        }
+        found
+    };
+
+    for entity in entities.iter_mut() {
+        entity.overrides = None;
+        entity.override_change = None;
+    }
+    for (method, base, change) in found {
+        entities[method].overrides = Some(base);
+        entities[method].override_change = Some(change.as_str().to_string());
+    }
+}
+
+/// Copy the docstring of the nearest documented base method onto overrides
+/// without one, following `overrides` up the chain. Docstrings inherited by
+/// an earlier run are dropped first, so results don't depend on how often
+/// this ran.
+fn inherit_docstrings(entities: &mut [CodeEntity], enabled: bool) {
+    for entity in entities.iter_mut().filter(|e| e.inherited_doc) {
+        entity.docstring = None;
+        entity.inherited_doc = false;
+    }
+    if !enabled {
+        return;
+    }
+
+    let inherited: Vec<(usize, String)> = {
+        let mut by_qualified: HashMap<&str, usize> = HashMap::new();
+        for (index, entity) in entities.iter().enumerate().filter(|(_, e)| e.entity_type == "method") {
+            by_qualified.entry(entity.qualified_name.as_str()).or_insert(index);
+        }
+        let documented = |entity: &CodeEntity| entity.docstring.as_deref().map_or(false, |d| !d.trim().is_empty());
+
+        let mut inherited = Vec::new();
+        for (index, entity) in entities.iter().enumerate().filter(|(_, e)| !documented(e)) {
+            let mut seen = HashSet::from([index]);
+            let mut next = entity.overrides.as_deref();
+            while let Some(&base) = next.and_then(|name| by_qualified.get(name)) {
+                if !seen.insert(base) {
+                    break;
+                }
+                if documented(&entities[base]) {
+                    inherited.extend(entities[base].docstring.clone().map(|doc| (index, doc)));
+                    break;
+                }
+                next = entities[base].overrides.as_deref();
+            }
+        }
+        inherited
+    };
+
+    for (index, docstring) in inherited {
+        entities[index].docstring = Some(docstring);
+        entities[index].inherited_doc = true;
+    }
+}
+
+/// Mark classes with an exception among their unresolved bases, directly
+/// or through an ancestor
+fn mark_exceptions(entities: &mut [CodeEntity], graph: &ClassGraph) {
+    let raises = |class: usize| {
+        let entity = &entities[class];
+        entity.bases.iter().zip(&graph.bases[&class]).any(|(base, resolved)| {
+            let short = base_name(base).rsplit('.').next().unwrap_or_default();
+            resolved.is_none()
+                && (EXCEPTION_BASES.contains(&short)
+                    || ["Error", "Exception", "Warning"].iter().any(|suffix| short.ends_with(suffix)))
+        })
+    };
+    let exceptions: Vec<usize> = graph
+        .classes()
+        .filter(|&class| raises(class) || graph.ancestors(class).into_iter().any(&raises))
+        .collect();
+
+    for entity in entities.iter_mut() {
+        entity.is_exception = false;
+    }
+    for class in exceptions {
+        entities[class].is_exception = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::RustAnalyzer;
+    use std::path::Path;
+
+    #[test]
+    fn test_detect_overrides() {
+        let source = "class Base:\n    def get(self, key):\n        pass\n\n    def put(self, key, value):\n        pass\n\n    def close(self):\n        pass\n\nclass Cache(Base):\n    def get(self, key, default):\n        pass\n\n    def put(self, key):\n        pass\n\nclass Remote(Cache):\n    def close(self, *args, **kwargs):\n        pass\n\n    def fetch(self):\n        pass\n";
+        let mut entities = RustAnalyzer::new().analyze_source(source, Path::new("store.py")).unwrap();
+        resolve(&mut entities, &AnalyzerConfig::default());
+
+        let method = |class: &str, name: &str| {
+            let qualified = format!("store.{}.{}", class, name);
+            entities.iter().find(|e| e.qualified_name == qualified).unwrap()
+        };
+        let overrides = |class: &str, name: &str| {
+            let entity = method(class, name);
+            (entity.overrides.as_deref(), entity.override_change.as_deref())
+        };
+        assert_eq!(overrides("Cache", "get"), (Some("store.Base.get"), Some("widened")));
+        assert_eq!(overrides("Cache", "put"), (Some("store.Base.put"), Some("narrowed")));
+        assert_eq!(overrides("Remote", "close"), (Some("store.Base.close"), Some("widened")));
+        assert_eq!(overrides("Remote", "fetch"), (None, None));
+
+        let config = AnalyzerConfig {
+            inherit_docstrings: true,
+            ..AnalyzerConfig::default()
+        };
+        entities.iter_mut().find(|e| e.qualified_name == "store.Base.close").unwrap().docstring = Some("Release the store.".to_string());
+        resolve(&mut entities, &config);
+        let close = entities.iter().find(|e| e.qualified_name == "store.Remote.close").unwrap();
+        assert_eq!((close.docstring.as_deref(), close.inherited_doc), (Some("Release the store."), true));
+        resolve(&mut entities, &AnalyzerConfig::default());
+        let close = entities.iter().find(|e| e.qualified_name == "store.Remote.close").unwrap();
+        assert_eq!((close.docstring.as_deref(), close.inherited_doc), (None, false));
+        assert_eq!(SignatureChange::compare(&["self".into(), "a".into()], &["self".into(), "b".into()]), SignatureChange::Changed);
+    }
+
+    #[test]
+    fn test_exceptions_through_ancestors() {
+        let source = "class AppError(Exception):\n    pass\n\nclass NotFound(AppError):\n    pass\n\nclass Timeout(requests.Timeout):\n    pass\n\nclass ErrorPage:\n    pass\n\nclass Handler(ErrorPage):\n    pass\n";
+        let mut entities = RustAnalyzer::new().analyze_source(source, Path::new("errors.py")).unwrap();
+        resolve(&mut entities, &AnalyzerConfig::default());
+
+        let exceptions: Vec<&str> = entities.iter().filter(|e| e.is_exception).map(|e| e.name.as_str()).collect();
+        assert_eq!(exceptions, vec!["AppError", "NotFound"]);
+    }
+}
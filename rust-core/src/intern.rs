@@ -0,0 +1,334 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Borrow;
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Deduplicating string pool handing out shared `Arc<str>` handles.
+///
+/// Used when converting large result sets so that repeated values such as
+/// file paths, entity types and decorators are stored once.
+#[derive(Debug, Default)]
+pub struct StringInterner {
+    strings: HashSet<Arc<str>>,
+}
+
+impl StringInterner {
+    pub fn new() -> Self {
+        StringInterner::default()
+    }
+
+    /// Return the shared handle for `value`, allocating it on first use
+    pub fn intern(&mut self, value: &str) -> Arc<str> {
+        if let Some(existing) = self.strings.get(value) {
+            return Arc::clone(existing);
+        }
+
+        let interned: Arc<str> = Arc::from(value);
+        self.strings.insert(Arc::clone(&interned));
+        interned
+    }
+
+    /// Number of distinct strings in the pool
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Approximate bytes held by the pool, counting each string's allocation
+    /// and its slot in the set
+    pub fn heap_bytes(&self) -> usize {
+        self.strings.capacity() * std::mem::size_of::<Arc<str>>()
+            + self.strings.iter().map(|s| s.len() + 2 * std::mem::size_of::<usize>()).sum::<usize>()
+    }
+}
+
+const SHARDS: usize = 16;
+
+/// Smallest shard size at which unused values are swept
+const MIN_SWEEP: usize = 64;
+
+/// Process-wide pool backing `Symbol` and `SharedPath`.
+///
+/// Split into shards so parallel parsers rarely wait on the same lock. Once a
+/// shard has doubled since its last sweep it drops the values only the pool
+/// still holds, so memory follows the live results rather than every string
+/// seen by the process.
+struct Pool<T: ?Sized> {
+    shards: [Mutex<Shard<T>>; SHARDS],
+}
+
+struct Shard<T: ?Sized> {
+    values: HashSet<Arc<T>>,
+    sweep_at: usize,
+}
+
+impl<T: ?Sized + Eq + Hash> Pool<T>
+where
+    for<'a> Arc<T>: From<&'a T>,
+{
+    fn new() -> Self {
+        Pool {
+            shards: std::array::from_fn(|_| {
+                Mutex::new(Shard {
+                    values: HashSet::new(),
+                    sweep_at: MIN_SWEEP,
+                })
+            }),
+        }
+    }
+
+    fn intern(&self, value: &T) -> Arc<T> {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let shard = &self.shards[hasher.finish() as usize % SHARDS];
+        let mut shard = shard.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(existing) = shard.values.get(value) {
+            return Arc::clone(existing);
+        }
+
+        if shard.values.len() >= shard.sweep_at {
+            // A count of one means no entity refers to the value any more;
+            // new handles are only made under this lock, so it can't revive
+            shard.values.retain(|v| Arc::strong_count(v) > 1);
+            shard.sweep_at = (shard.values.len() * 2).max(MIN_SWEEP);
+        }
+        let interned: Arc<T> = Arc::from(value);
+        shard.values.insert(Arc::clone(&interned));
+        interned
+    }
+}
+
+/// Interned string for low-cardinality entity fields such as `entity_type`
+/// and decorators.
+///
+/// Values come from a process-wide pool, so equal symbols share one
+/// allocation and cloning only bumps a reference count. Values are released
+/// from the pool once no symbol refers to them.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(Arc<str>);
+
+impl Symbol {
+    pub fn new(value: &str) -> Self {
+        static POOL: OnceLock<Pool<str>> = OnceLock::new();
+        Symbol(POOL.get_or_init(Pool::new).intern(value))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The shared handle, e.g. for the Python bindings
+    pub fn into_arc(self) -> Arc<str> {
+        self.0
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Symbol {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(value: &str) -> Self {
+        Symbol::new(value)
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(value: String) -> Self {
+        Symbol::new(&value)
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for Symbol {
+    fn eq(&self, other: &String) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Serialize for Symbol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Symbol {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Symbol::from)
+    }
+}
+
+/// Interned file path; like `Symbol`, equal paths share one allocation from
+/// a process-wide pool that releases paths no entity uses any more
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SharedPath(Arc<Path>);
+
+impl SharedPath {
+    pub fn new(path: &Path) -> Self {
+        static POOL: OnceLock<Pool<Path>> = OnceLock::new();
+        SharedPath(POOL.get_or_init(Pool::new).intern(path))
+    }
+
+    pub fn as_path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Deref for SharedPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for SharedPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Borrow<Path> for SharedPath {
+    fn borrow(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl From<&Path> for SharedPath {
+    fn from(path: &Path) -> Self {
+        SharedPath::new(path)
+    }
+}
+
+impl From<PathBuf> for SharedPath {
+    fn from(path: PathBuf) -> Self {
+        SharedPath::new(&path)
+    }
+}
+
+impl PartialEq<Path> for SharedPath {
+    fn eq(&self, other: &Path) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&Path> for SharedPath {
+    fn eq(&self, other: &&Path) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<PathBuf> for SharedPath {
+    fn eq(&self, other: &PathBuf) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl fmt::Debug for SharedPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl Serialize for SharedPath {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Serialize::serialize(&*self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SharedPath {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        PathBuf::deserialize(deserializer).map(SharedPath::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern_shares_allocations() {
+        let mut interner = StringInterner::new();
+        let a = interner.intern("src/app.py");
+        let b = interner.intern("src/app.py");
+        let c = interner.intern("function");
+
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &c));
+        assert_eq!(interner.len(), 2);
+    }
+
+    #[test]
+    fn test_symbols_and_paths_are_pooled() {
+        let a = Symbol::from("method".to_string());
+        let b = Symbol::new("method");
+        assert!(Arc::ptr_eq(&a.clone().into_arc(), &b.into_arc()));
+        assert_eq!(a, "method");
+        assert_eq!(serde_json::to_string(&a).unwrap(), "\"method\"");
+
+        let p = SharedPath::from(PathBuf::from("src/app.py"));
+        let q = SharedPath::new(Path::new("src/app.py"));
+        assert!(Arc::ptr_eq(&p.0, &q.0));
+        assert_eq!(p, Path::new("src/app.py"));
+        assert_eq!(p.file_name().unwrap(), "app.py");
+    }
+
+    #[test]
+    fn test_pool_releases_unused_values() {
+        let pool = Pool::<str>::new();
+        let kept = pool.intern("kept");
+        for i in 0..10_000 {
+            pool.intern(&format!("decorator_{i}"));
+        }
+
+        let held: usize = pool.shards.iter().map(|s| s.lock().unwrap().values.len()).sum();
+        assert!(held <= SHARDS * MIN_SWEEP, "pool kept {held} values");
+        assert!(Arc::ptr_eq(&kept, &pool.intern("kept")));
+    }
+}
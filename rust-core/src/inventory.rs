@@ -0,0 +1,123 @@
+use anyhow::{Context, Result};
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io::Write;
+
+use crate::entity::CodeEntity;
+use crate::sphinx::{self, SphinxObject};
+
+/// Settings for an intersphinx `objects.inv`
+#[derive(Debug, Clone)]
+pub struct InventoryOptions {
+    /// Project name in the header; the first entity's top-level package when empty
+    pub project: String,
+    pub version: String,
+    /// Page documenting a module relative to the docs root, with `{module}`
+    /// standing for its dotted path
+    pub page: String,
+}
+
+impl Default for InventoryOptions {
+    fn default() -> Self {
+        InventoryOptions {
+            project: String::new(),
+            version: String::new(),
+            page: "{module}.html".to_string(),
+        }
+    }
+}
+
+/// Serialize the Python entities as a Sphinx inventory (version 2), the
+/// file intersphinx fetches to link other projects' docs to ours.
+///
+/// Each object is listed under its fully qualified name with the `py`
+/// domain role Sphinx would register, anchored on its module's page.
+pub fn to_objects_inv(entities: &[CodeEntity], options: &InventoryOptions) -> Result<Vec<u8>> {
+    let objects = sphinx::describe(entities);
+    let project = match options.project.as_str() {
+        "" => objects
+            .first()
+            .and_then(|object| object.module.split('.').next())
+            .unwrap_or_default(),
+        project => project,
+    };
+
+    let mut out = format!(
+        "# Sphinx inventory version 2\n# Project: {}\n# Version: {}\n# The remainder of this file is compressed using zlib.\n",
+        project, options.version
+    )
+    .into_bytes();
+
+    let mut body = String::new();
+    let mut seen = HashSet::new();
+    for object in &objects {
+        let (name, role) = (full_name(object), role(object.objtype));
+        if !seen.insert((name.clone(), role)) {
+            continue;
+        }
+        let page = options.page.replace("{module}", &object.module);
+        let (priority, anchor) = match object.objtype {
+            "module" => (0, format!("module-{}", name)),
+            _ => (1, "$".to_string()),
+        };
+        let _ = writeln!(body, "{} py:{} {} {}#{} -", name, role, priority, page, anchor);
+    }
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(body.as_bytes()).context("Failed to compress inventory")?;
+    out.extend(encoder.finish().context("Failed to compress inventory")?);
+    Ok(out)
+}
+
+/// `pkg.api.Client.get`; modules are named by their path alone
+fn full_name(object: &SphinxObject) -> String {
+    match object.objtype {
+        "module" => object.module.clone(),
+        _ if object.module.is_empty() => object.name.clone(),
+        _ => format!("{}.{}", object.module, object.name),
+    }
+}
+
+/// Role Sphinx registers an object under; class and static methods are
+/// cross-referenced as methods
+fn role(objtype: &'static str) -> &'static str {
+    match objtype {
+        "classmethod" | "staticmethod" => "method",
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::RustAnalyzer;
+    use flate2::read::ZlibDecoder;
+    use std::io::Read;
+    use std::path::Path;
+
+    #[test]
+    fn test_objects_inv() {
+        let source = "class Client:\n    @classmethod\n    def connect(cls):\n        pass\n\ndef main():\n    pass\n";
+        let entities = RustAnalyzer::new().analyze_source(source, Path::new("client.py")).unwrap();
+        let options = InventoryOptions {
+            version: "1.2".to_string(),
+            page: "api/{module}.html".to_string(),
+            ..InventoryOptions::default()
+        };
+        let inventory = to_objects_inv(&entities, &options).unwrap();
+
+        let header = "# Sphinx inventory version 2\n# Project: client\n# Version: 1.2\n# The remainder of this file is compressed using zlib.\n";
+        assert!(inventory.starts_with(header.as_bytes()));
+        let mut body = String::new();
+        ZlibDecoder::new(&inventory[header.len()..]).read_to_string(&mut body).unwrap();
+        assert_eq!(
+            body,
+            "client py:module 0 api/client.html#module-client -\n\
+             client.Client py:class 1 api/client.html#$ -\n\
+             client.Client.connect py:method 1 api/client.html#$ -\n\
+             client.main py:function 1 api/client.html#$ -\n"
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::entity::CodeEntity;
+use crate::identity::fnv1a;
+use crate::intern::SharedPath;
+use crate::source::read_source;
+
+/// Where deferred code lives in its file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeSpan {
+    /// UTF-8 byte range of the code
+    pub start: usize,
+    pub end: usize,
+    /// `fnv1a` of the code, checked when it is read back
+    pub hash: u64,
+}
+
+/// Drop `code` from entities whose code appears verbatim in `source` within
+/// their line range, recording its byte span so it can be sliced back out on
+/// demand. Entities whose code was synthesized or rewritten (normalized
+/// signatures, redacted secrets, hook edits) keep it. Returns the number of
+/// entities deferred.
+pub fn defer_code(entities: &mut [CodeEntity], source: &str) -> usize {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+
+    let mut deferred = 0;
+    for entity in entities.iter_mut() {
+        if entity.code.is_empty() || entity.code_span.is_some() || entity.line_number == 0 {
+            continue;
+        }
+        let Some(&start) = line_starts.get(entity.line_number - 1) else {
+            continue;
+        };
+        let end = line_starts
+            .get(entity.end_line.max(entity.line_number))
+            .copied()
+            .unwrap_or(source.len());
+        if let Some(offset) = source[start..end].find(entity.code.as_str()) {
+            entity.code_span = Some(CodeSpan {
+                start: start + offset,
+                end: start + offset + entity.code.len(),
+                hash: fnv1a(entity.code.as_bytes()),
+            });
+            entity.code = String::new();
+            deferred += 1;
+        }
+    }
+    deferred
+}
+
+/// Read the deferred code at `span` from `path`
+pub fn load_span(path: &Path, span: CodeSpan) -> Result<String> {
+    let source = read_source(path)?;
+    slice(&source, span, path)
+}
+
+/// Materialize deferred code for every entity, reading each file once
+pub fn load_code(entities: &mut [CodeEntity]) -> Result<()> {
+    let mut by_file: HashMap<SharedPath, Vec<usize>> = HashMap::new();
+    for (index, entity) in entities.iter().enumerate() {
+        if entity.code_span.is_some() {
+            by_file.entry(SharedPath::new(entity.source_path())).or_default().push(index);
+        }
+    }
+
+    for (path, indices) in by_file {
+        let source = read_source(&path)?;
+        for index in indices {
+            if let Some(span) = entities[index].code_span {
+                entities[index].code = slice(&source, span, &path)?;
+                entities[index].code_span = None;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `entities` with deferred code materialized, for consumers that read
+/// `code`; borrowed as is when nothing is deferred. Code that can no longer
+/// be read back stays empty, with a warning.
+pub fn materialized(entities: &[CodeEntity]) -> Cow<'_, [CodeEntity]> {
+    if entities.iter().all(CodeEntity::is_code_loaded) {
+        return Cow::Borrowed(entities);
+    }
+    let mut entities = entities.to_vec();
+    if let Err(e) = load_code(&mut entities) {
+        log::warn!("Some deferred code is unavailable: {:#}", e);
+        for entity in entities.iter_mut().filter(|e| !e.is_code_loaded()) {
+            if let Err(e) = entity.load_code() {
+                log::debug!("{:#}", e);
+            }
+        }
+    }
+    Cow::Owned(entities)
+}
+
+fn slice(source: &str, span: CodeSpan, path: &Path) -> Result<String> {
+    source
+        .get(span.start..span.end)
+        .filter(|code| fnv1a(code.as_bytes()) == span.hash)
+        .map(str::to_string)
+        .with_context(|| format!("Deferred code no longer matches {:?}; was the file modified?", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::RustAnalyzer;
+    use crate::config::AnalyzerConfig;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_deferred_code_matches_eager_code() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("app.py");
+        fs::write(&path, "def load(path):\n    return path\n\n\ndef save(path, data = None):\n    pass\n").unwrap();
+
+        let eager = RustAnalyzer::new().analyze_file(&path).unwrap();
+        let config = AnalyzerConfig {
+            lazy_code: true,
+            ..AnalyzerConfig::default()
+        };
+        let mut lazy = RustAnalyzer::with_config(config).analyze_file(&path).unwrap();
+
+        // `save` is spaced differently from its normalized signature, so it stays eager
+        assert!(lazy[0].code.is_empty() && !lazy[0].is_code_loaded());
+        assert!(lazy[1].is_code_loaded());
+        assert_eq!(lazy[0].content_hash, eager[0].content_hash);
+
+        assert_eq!(lazy[0].load_code().unwrap(), eager[0].code);
+        load_code(&mut lazy).unwrap();
+        let codes: Vec<&str> = lazy.iter().map(|e| e.code.as_str()).collect();
+        assert_eq!(codes, eager.iter().map(|e| e.code.as_str()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_edited_files_and_consumers() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("app.py");
+        fs::write(&path, "def load(path):\n    return path\n").unwrap();
+        let config = AnalyzerConfig {
+            lazy_code: true,
+            ..AnalyzerConfig::default()
+        };
+        let eager = RustAnalyzer::new().analyze_file(&path).unwrap();
+        let lazy = RustAnalyzer::with_config(config).analyze_file(&path).unwrap();
+        assert!(!lazy[0].is_code_loaded());
+        assert!(crate::export::to_markdown(&lazy).contains(&eager[0].code));
+        assert_eq!(materialized(&lazy)[0].code, eager[0].code);
+
+        // Same length, different text: the span is in bounds but stale
+        fs::write(&path, "def lord(path):\n    return path\n").unwrap();
+        assert!(lazy[0].clone().load_code().is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use regex::Regex;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use crate::entity::CodeEntity;
+
+/// Lines searched for a header; licenses sit above the first import
+const HEADER_LINES: usize = 30;
+
+/// The license header of one source file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FileLicense {
+    /// SPDX license expression, e.g. `Apache-2.0 OR MIT`
+    pub spdx: Option<String>,
+    /// Copyright notices without comment markers, e.g. `Copyright 2024 Acme Inc.`
+    pub copyright: Vec<String>,
+}
+
+impl FileLicense {
+    /// Read the header at the top of `source`
+    pub fn detect(source: &str) -> Self {
+        static SPDX: OnceLock<Regex> = OnceLock::new();
+        static COPYRIGHT: OnceLock<Regex> = OnceLock::new();
+        let spdx = SPDX.get_or_init(|| Regex::new(r"SPDX-License-Identifier:\s*(.+?)\s*(?:\*/|-->)?\s*$").unwrap());
+        let copyright = COPYRIGHT.get_or_init(|| Regex::new(r"(?i)^(?:copyright\b|\(c\)|©)").unwrap());
+
+        let mut license = FileLicense::default();
+        for line in source.lines().take(HEADER_LINES) {
+            if license.spdx.is_none() {
+                license.spdx = spdx.captures(line).map(|c| c[1].to_string());
+            }
+            let text = strip_comment(line);
+            // `copyright = "2024, Acme"` in a Sphinx conf.py is code, not a notice
+            if copyright.is_match(text) && !text.contains('=') {
+                license.copyright.push(text.to_string());
+            }
+        }
+        license
+    }
+
+    /// Stamp the header onto a file's entities as `metadata["license"]`
+    /// and `metadata["copyright"]`; there is no module entity, so like
+    /// `module_path` every entity of the file carries it
+    pub fn apply(&self, entities: &mut [CodeEntity]) {
+        for entity in entities {
+            if let Some(spdx) = &self.spdx {
+                entity.metadata.insert("license".to_string(), spdx.clone());
+            }
+            if !self.copyright.is_empty() {
+                entity.metadata.insert("copyright".to_string(), self.copyright.join("; "));
+            }
+        }
+    }
+}
+
+/// `# text`, `// text`, ` * text` -> `text`
+fn strip_comment(line: &str) -> &str {
+    line.trim()
+        .trim_start_matches(['#', '/', '*', '-', ';', '!'])
+        .trim_end_matches("*/")
+        .trim()
+}
+
+/// A file whose header doesn't carry the expected license
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MissingHeader {
+    pub file_path: String,
+    /// The SPDX expression found instead, if any
+    pub found: Option<String>,
+    pub has_copyright: bool,
+}
+
+/// Files among `entities` without an SPDX identifier, or with one other
+/// than `expected` when it is given; in path order
+pub fn missing_headers(entities: &[CodeEntity], expected: Option<&str>) -> Vec<MissingHeader> {
+    let mut files: BTreeMap<String, MissingHeader> = BTreeMap::new();
+    for entity in entities {
+        let file_path = entity.file_path.to_string_lossy().replace('\\', "/");
+        files.entry(file_path.clone()).or_insert_with(|| MissingHeader {
+            file_path,
+            found: entity.metadata.get("license").cloned(),
+            has_copyright: entity.metadata.contains_key("copyright"),
+        });
+    }
+    files
+        .into_values()
+        .filter(|file| match (&file.found, expected) {
+            (None, _) => true,
+            (Some(found), Some(expected)) => !found.eq_ignore_ascii_case(expected),
+            (Some(_), None) => false,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::RustAnalyzer;
+    use std::path::Path;
+
+    #[test]
+    fn test_detect_header() {
+        let source = "#!/usr/bin/env python\n# SPDX-License-Identifier: Apache-2.0 OR MIT\n# Copyright (c) 2024 Acme Inc.\n\nimport os\n";
+        let license = FileLicense::detect(source);
+        assert_eq!(license.spdx.as_deref(), Some("Apache-2.0 OR MIT"));
+        assert_eq!(license.copyright, vec!["Copyright (c) 2024 Acme Inc."]);
+
+        let c = FileLicense::detect("/*\n * Copyright 2023 Example\n * SPDX-License-Identifier: GPL-2.0-only */\n");
+        assert_eq!((c.spdx.as_deref(), c.copyright.len()), (Some("GPL-2.0-only"), 1));
+    }
+
+    #[test]
+    fn test_missing_headers() {
+        let analyzer = RustAnalyzer::new();
+        let mut entities = analyzer
+            .analyze_source("# SPDX-License-Identifier: MIT\ndef a():\n    pass\n", Path::new("a.py"))
+            .unwrap();
+        entities.extend(analyzer.analyze_source("def b():\n    pass\n", Path::new("b.py")).unwrap());
+        assert_eq!(entities[0].metadata.get("license").map(String::as_str), Some("MIT"));
+
+        let missing: Vec<String> = missing_headers(&entities, None).into_iter().map(|f| f.file_path).collect();
+        assert_eq!(missing, vec!["b.py"]);
+        assert_eq!(missing_headers(&entities, Some("Apache-2.0")).len(), 2);
+    }
+}
@@ -0,0 +1,747 @@
+use std::collections::{BTreeMap, HashSet};
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use glob::Pattern;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::config::AnalyzerConfig;
+use crate::context::SourceCache;
+use crate::entity::CodeEntity;
+use crate::metrics::has_docstring;
+
+/// A documentation check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    /// Public function, method or type without a docstring
+    MissingDocstring,
+    /// Docstring that leaves some parameters undescribed
+    MissingParamDocs,
+    /// Docstring without a returns section for an annotated return value
+    MissingReturnDocs,
+    /// Docstring of a public function, class or endpoint without an examples
+    /// section or doctest; reported one severity level higher for endpoints
+    MissingExamples,
+    /// Python function not in `snake_case` or class not in `CapWords`
+    NamingConvention,
+    /// Python definition or parameter named after a builtin, e.g. `list`
+    ShadowedBuiltin,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 6] = [
+        Rule::MissingDocstring,
+        Rule::MissingParamDocs,
+        Rule::MissingReturnDocs,
+        Rule::MissingExamples,
+        Rule::NamingConvention,
+        Rule::ShadowedBuiltin,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Rule::MissingDocstring => "missing-docstring",
+            Rule::MissingParamDocs => "missing-param-docs",
+            Rule::MissingReturnDocs => "missing-return-docs",
+            Rule::MissingExamples => "missing-examples",
+            Rule::NamingConvention => "naming-convention",
+            Rule::ShadowedBuiltin => "shadowed-builtin",
+        }
+    }
+
+    /// Severity used when the configuration doesn't name the rule
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            Rule::MissingDocstring | Rule::MissingParamDocs | Rule::NamingConvention | Rule::ShadowedBuiltin => {
+                Severity::Warning
+            }
+            Rule::MissingReturnDocs | Rule::MissingExamples => Severity::Info,
+        }
+    }
+}
+
+impl std::str::FromStr for Rule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Rule::ALL
+            .into_iter()
+            .find(|rule| rule.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| anyhow::anyhow!("Unknown lint rule: {}", s))
+    }
+}
+
+/// How much a finding matters; `Off` disables a rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Off => "off",
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+
+    /// One level up, stopping at `Error`; `Off` stays off
+    pub fn escalated(self) -> Severity {
+        match self {
+            Severity::Off => Severity::Off,
+            Severity::Info => Severity::Warning,
+            Severity::Warning | Severity::Error => Severity::Error,
+        }
+    }
+}
+
+impl std::str::FromStr for Severity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" | "ignore" => Ok(Severity::Off),
+            "info" => Ok(Severity::Info),
+            "warning" | "warn" => Ok(Severity::Warning),
+            "error" => Ok(Severity::Error),
+            other => Err(anyhow::anyhow!("Unknown lint severity: {}", other)),
+        }
+    }
+}
+
+/// Docstring convention whose sections count as documentation; `Auto`
+/// accepts any of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DocStyle {
+    #[default]
+    Auto,
+    Google,
+    Numpy,
+    Sphinx,
+}
+
+impl std::str::FromStr for DocStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" | "" => Ok(DocStyle::Auto),
+            "google" => Ok(DocStyle::Google),
+            "numpy" | "numpydoc" => Ok(DocStyle::Numpy),
+            "sphinx" | "rest" => Ok(DocStyle::Sphinx),
+            other => Err(anyhow::anyhow!("Unknown docstring style: {}", other)),
+        }
+    }
+}
+
+/// One rule violated by one entity
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    pub rule: Rule,
+    pub severity: Severity,
+    /// Position of the entity in the linted slice
+    pub index: usize,
+    pub message: String,
+}
+
+/// Findings of a lint run, ordered by entity, then rule
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LintReport {
+    pub findings: Vec<Finding>,
+    /// Findings silenced by an `autodoc: ignore[...]` comment on the
+    /// definition, kept so suppressions can be audited
+    pub suppressed: Vec<Finding>,
+}
+
+impl LintReport {
+    /// Number of findings at `severity`
+    pub fn count(&self, severity: Severity) -> usize {
+        self.findings.iter().filter(|f| f.severity == severity).count()
+    }
+
+    /// Whether no finding is at or above `severity`
+    pub fn passes(&self, severity: Severity) -> bool {
+        !self.findings.iter().any(|f| f.severity >= severity)
+    }
+}
+
+/// Severities, ignore patterns and docstring style resolved from `AnalyzerConfig`
+#[derive(Debug, Clone)]
+pub struct LintRules {
+    severities: BTreeMap<Rule, Severity>,
+    /// Globs over file paths and entity names, optionally limited to one rule
+    ignore: Vec<(Option<Rule>, Pattern)>,
+    /// Sections outside this convention don't document parameters or returns
+    style: DocStyle,
+}
+
+impl LintRules {
+    /// Read `lint_severity`, `lint_ignore` and `docstring_style`; unknown
+    /// rules, severities or styles and malformed patterns are errors so a
+    /// typo can't silently disable a gate
+    pub fn from_config(config: &AnalyzerConfig) -> Result<Self> {
+        let mut severities: BTreeMap<Rule, Severity> =
+            Rule::ALL.into_iter().map(|rule| (rule, rule.default_severity())).collect();
+        for (rule, severity) in &config.lint_severity {
+            severities.insert(rule.parse()?, severity.parse()?);
+        }
+
+        let mut ignore = Vec::with_capacity(config.lint_ignore.len());
+        for entry in &config.lint_ignore {
+            // `missing-examples:internal/**` limits a pattern to one rule
+            let (rule, pattern) = match entry.split_once(':') {
+                Some((rule, pattern)) if rule.parse::<Rule>().is_ok() => (rule.parse().ok(), pattern),
+                _ => (None, entry.as_str()),
+            };
+            let pattern = Pattern::new(pattern)
+                .map_err(|e| anyhow::anyhow!("Invalid lint ignore pattern {:?}: {}", entry, e))?;
+            ignore.push((rule, pattern));
+        }
+        Ok(LintRules {
+            severities,
+            ignore,
+            style: config.docstring_style.parse()?,
+        })
+    }
+
+    pub fn severity(&self, rule: Rule) -> Severity {
+        self.severities.get(&rule).copied().unwrap_or_else(|| rule.default_severity())
+    }
+
+    fn is_ignored(&self, rule: Rule, entity: &CodeEntity) -> bool {
+        let path = entity.file_path.to_string_lossy();
+        self.ignore.iter().any(|(only, pattern)| {
+            only.map_or(true, |only| only == rule) && (pattern.matches(&path) || pattern.matches(&entity.name))
+        })
+    }
+}
+
+/// Check the public, hand-written entities against the configured rules.
+///
+/// Rules other than `missing-docstring` only look at entities that have a
+/// docstring, so an undocumented entity yields a single finding. Findings
+/// on a definition carrying `# autodoc: ignore[rule, ...]` (or a bare
+/// `# autodoc: ignore`) go to `suppressed`; the comment may also sit on the
+/// line above. Source files are read to find these comments.
+pub fn lint(entities: &[CodeEntity], config: &AnalyzerConfig) -> Result<LintReport> {
+    let rules = LintRules::from_config(config)?;
+    let mut sources = SourceCache::default();
+    let mut report = LintReport::default();
+    for (index, entity) in entities.iter().enumerate() {
+        if !is_lintable(entity) {
+            continue;
+        }
+        let mut suppression = None;
+        for (rule, message) in check(entity, rules.style) {
+            let mut severity = rules.severity(rule);
+            if rule == Rule::MissingExamples && entity.is_api_endpoint {
+                severity = severity.escalated();
+            }
+            if severity == Severity::Off || rules.is_ignored(rule, entity) {
+                continue;
+            }
+            let finding = Finding {
+                rule,
+                severity,
+                index,
+                message,
+            };
+            let inline = suppression
+                .get_or_insert_with(|| sources.get(entity.source_path()).and_then(|s| suppression_for(s, entity)));
+            match inline {
+                Some(Suppression::All) => report.suppressed.push(finding),
+                Some(Suppression::Rules(suppressed)) if suppressed.contains(&rule) => report.suppressed.push(finding),
+                _ => report.findings.push(finding),
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Rules silenced by an inline comment
+#[derive(Debug, PartialEq)]
+enum Suppression {
+    All,
+    Rules(Vec<Rule>),
+}
+
+/// The `autodoc: ignore` comment on the definition's header lines or on a
+/// comment line right above it
+fn suppression_for(source: &str, entity: &CodeEntity) -> Option<Suppression> {
+    static IGNORE: OnceLock<Regex> = OnceLock::new();
+    let ignore = IGNORE.get_or_init(|| Regex::new(r"(?:#|//|--)\s*autodoc:\s*ignore(?:\[([^\]]*)\])?").unwrap());
+    if entity.line_number == 0 {
+        return None;
+    }
+
+    let lines: Vec<&str> = source.lines().collect();
+    let first = entity.line_number - 1;
+    let above = first
+        .checked_sub(1)
+        .and_then(|i| lines.get(i))
+        .filter(|line| ["#", "//", "--"].iter().any(|p| line.trim_start().starts_with(p)));
+    // Decorators and multi-line signatures: up to the line opening the body
+    let header = lines
+        .iter()
+        .skip(first)
+        .take(entity.end_line.saturating_sub(first).clamp(1, 20));
+    let mut header_lines = Vec::new();
+    for line in header {
+        header_lines.push(*line);
+        let code = line.split('#').next().unwrap_or("").split("//").next().unwrap_or("").trim_end();
+        if code.ends_with(':') || code.ends_with('{') {
+            break;
+        }
+    }
+
+    let captures = above.into_iter().chain(&header_lines).find_map(|line| ignore.captures(line))?;
+    let Some(list) = captures.get(1) else {
+        return Some(Suppression::All);
+    };
+    let mut rules = Vec::new();
+    for name in list.as_str().split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        match name.parse() {
+            Ok(rule) => rules.push(rule),
+            Err(_) => log::warn!("{}:{}: unknown lint rule {:?} in suppression", entity.file_path.display(), entity.line_number, name),
+        }
+    }
+    Some(Suppression::Rules(rules))
+}
+
+/// Every rule `entity` violates, whatever its configured severity
+fn check(entity: &CodeEntity, style: DocStyle) -> Vec<(Rule, String)> {
+    let mut violations = Vec::new();
+    if is_python(entity) {
+        check_naming(entity, &mut violations);
+    }
+
+    let Some(docstring) = entity.docstring.as_deref().filter(|_| has_docstring(entity)) else {
+        if !is_dunder(&entity.name) {
+            violations.push((Rule::MissingDocstring, format!("{} `{}` has no docstring", entity.entity_type, entity.name)));
+        }
+        return violations;
+    };
+
+    let sections = DocSections::parse(docstring, style);
+    if is_callable(entity) {
+        let documented: HashSet<&str> = sections.params.iter().map(|p| p.trim_start_matches('*')).collect();
+        let missing: Vec<&str> = entity
+            .parameters
+            .iter()
+            .map(|p| p.trim_start_matches('*'))
+            .filter(|p| !matches!(*p, "self" | "cls" | "") && !documented.contains(p))
+            .collect();
+        if !missing.is_empty() {
+            violations.push((Rule::MissingParamDocs, format!("undocumented parameters: {}", missing.join(", "))));
+        }
+
+        if returns_value(entity) && !sections.returns {
+            violations.push((Rule::MissingReturnDocs, "return value is not documented".to_string()));
+        }
+    }
+    if entity.is_api_endpoint && !sections.examples {
+        violations.push((Rule::MissingExamples, "endpoint docstring has no examples".to_string()));
+    } else if wants_examples(entity) && !sections.examples {
+        violations.push((Rule::MissingExamples, "docstring has no examples section or doctest".to_string()));
+    }
+    violations
+}
+
+/// Public functions, classes and endpoints lacking usage examples, in the
+/// order examples are most worth adding: endpoints first, then by
+/// complexity and number of parameters. Undocumented entities are included.
+pub fn example_candidates(entities: &[CodeEntity]) -> Vec<usize> {
+    let mut candidates: Vec<usize> = (0..entities.len())
+        .filter(|&i| {
+            let entity = &entities[i];
+            is_lintable(entity)
+                && (entity.is_api_endpoint || wants_examples(entity))
+                && !entity.docstring.as_deref().map_or(false, |doc| DocSections::parse(doc, DocStyle::Auto).examples)
+        })
+        .collect();
+    candidates.sort_by_key(|&i| {
+        let entity = &entities[i];
+        (
+            !entity.is_api_endpoint,
+            std::cmp::Reverse(entity.complexity_score),
+            std::cmp::Reverse(entity.parameters.len()),
+        )
+    });
+    candidates
+}
+
+/// Functions and types are documented with examples; methods are covered
+/// by their class unless they are endpoints
+fn wants_examples(entity: &CodeEntity) -> bool {
+    entity.entity_type != "method"
+}
+
+/// Names PEP 8 leaves to the code they implement: unittest hooks and
+/// `ast`/docutils visitor methods
+const CONVENTIONAL_NAMES: &[&str] = &[
+    "setUp",
+    "tearDown",
+    "setUpClass",
+    "tearDownClass",
+    "setUpModule",
+    "tearDownModule",
+    "asyncSetUp",
+    "asyncTearDown",
+    "addCleanup",
+];
+
+/// Python builtins a definition or parameter shouldn't reuse
+const BUILTINS: &[&str] = &[
+    "abs", "all", "any", "ascii", "bin", "bool", "breakpoint", "bytearray", "bytes", "callable", "chr",
+    "classmethod", "compile", "complex", "delattr", "dict", "dir", "divmod", "enumerate", "eval", "exec",
+    "filter", "float", "format", "frozenset", "getattr", "globals", "hasattr", "hash", "help", "hex", "id",
+    "input", "int", "isinstance", "issubclass", "iter", "len", "list", "locals", "map", "max", "memoryview",
+    "min", "next", "object", "oct", "open", "ord", "pow", "print", "property", "range", "repr", "reversed",
+    "round", "set", "setattr", "slice", "sorted", "staticmethod", "str", "sum", "super", "tuple", "type",
+    "vars", "zip",
+];
+
+/// PEP 8 names: `snake_case` functions, `CapWords` classes, no builtins
+/// reused for module-level definitions or parameters
+fn check_naming(entity: &CodeEntity, violations: &mut Vec<(Rule, String)>) {
+    let name = entity.name.as_str();
+    let bare = name.trim_start_matches('_');
+    match entity.entity_type.as_str() {
+        "function" | "method" => {
+            let conventional = is_dunder(name)
+                || CONVENTIONAL_NAMES.contains(&name)
+                || name.starts_with("visit_")
+                || name.starts_with("depart_");
+            if !conventional && bare.chars().any(char::is_uppercase) {
+                violations.push((Rule::NamingConvention, format!("function `{}` should be snake_case", name)));
+            }
+        }
+        "class" => {
+            let cap_words = bare.chars().next().map_or(true, char::is_uppercase) && !bare.contains('_');
+            if !cap_words {
+                violations.push((Rule::NamingConvention, format!("class `{}` should be CapWords", name)));
+            }
+        }
+        _ => {}
+    }
+
+    // Methods are reached through their instance, so only other definitions shadow
+    if entity.entity_type != "method" && BUILTINS.contains(&name) {
+        violations.push((Rule::ShadowedBuiltin, format!("`{}` shadows a builtin", name)));
+    }
+    let shadowing: Vec<&str> = entity
+        .parameters
+        .iter()
+        .map(|p| p.trim_start_matches('*'))
+        .filter(|p| BUILTINS.contains(p))
+        .collect();
+    if !shadowing.is_empty() {
+        violations.push((Rule::ShadowedBuiltin, format!("parameters shadow builtins: {}", shadowing.join(", "))));
+    }
+}
+
+/// Naming rules follow PEP 8, so they only apply to Python sources; paths
+/// without an extension are in-memory sources, parsed as Python
+fn is_python(entity: &CodeEntity) -> bool {
+    entity
+        .file_path
+        .extension()
+        .map_or(true, |ext| matches!(ext.to_str(), Some("py" | "pyi" | "pyw")))
+}
+
+fn is_lintable(entity: &CodeEntity) -> bool {
+    entity.is_public() && !entity.is_generated && (is_callable(entity) || is_type(entity))
+}
+
+fn is_callable(entity: &CodeEntity) -> bool {
+    matches!(entity.entity_type.as_str(), "function" | "method")
+}
+
+fn is_type(entity: &CodeEntity) -> bool {
+    matches!(
+        entity.entity_type.as_str(),
+        "class" | "struct" | "enum" | "interface" | "trait"
+    )
+}
+
+fn is_dunder(name: &str) -> bool {
+    name.len() > 4 && name.starts_with("__") && name.ends_with("__")
+}
+
+/// Annotated with something other than `None`, and not a property, whose
+/// docstring describes the value itself
+fn returns_value(entity: &CodeEntity) -> bool {
+    let is_property = entity
+        .decorators
+        .iter()
+        .any(|d| matches!(d.as_str(), "property" | "cached_property" | "functools.cached_property"));
+    let return_type = entity.return_type.as_deref().map(str::trim).unwrap_or("");
+    !is_property
+        && !is_dunder(&entity.name)
+        && !matches!(return_type, "" | "None" | "NoReturn" | "Never" | "()")
+}
+
+/// What a docstring documents, in Google, NumPy or Sphinx style.
+/// Doctests count as examples in every style.
+#[derive(Debug, Default)]
+struct DocSections<'a> {
+    params: Vec<&'a str>,
+    returns: bool,
+    examples: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Section {
+    Params,
+    Returns,
+    Examples,
+    Other,
+}
+
+impl<'a> DocSections<'a> {
+    fn parse(docstring: &'a str, style: DocStyle) -> Self {
+        let accepts = |wanted: DocStyle| style == DocStyle::Auto || style == wanted;
+        let mut sections = DocSections::default();
+        let lines: Vec<&str> = docstring.lines().collect();
+        let mut current: Option<Section> = None;
+        let mut entry_indent: Option<usize> = None;
+
+        let mut i = 0;
+        while i < lines.len() {
+            let line = lines[i];
+            let trimmed = line.trim();
+            i += 1;
+
+            // Sphinx field lists and doctests can appear anywhere
+            if let Some(field) = trimmed.strip_prefix(':').filter(|_| accepts(DocStyle::Sphinx)) {
+                let (head, _) = field.split_once(':').unwrap_or((field, ""));
+                let mut words = head.split_whitespace();
+                match words.next() {
+                    Some("param" | "parameter" | "arg" | "argument" | "key" | "keyword") => {
+                        if let Some(name) = words.last() {
+                            sections.params.push(name);
+                        }
+                    }
+                    Some("return" | "returns" | "rtype" | "yield" | "yields") => sections.returns = true,
+                    _ => {}
+                }
+                continue;
+            }
+            if trimmed.starts_with(">>>") {
+                sections.examples = true;
+                continue;
+            }
+
+            // `Args:` (Google) or `Parameters` over a dashed underline (NumPy)
+            let underlined = lines.get(i).map_or(false, |next| {
+                let next = next.trim();
+                next.len() >= 3 && next.chars().all(|c| c == '-')
+            });
+            let title = if underlined {
+                Some(trimmed).filter(|_| accepts(DocStyle::Numpy))
+            } else {
+                trimmed.strip_suffix(':').filter(|_| accepts(DocStyle::Google))
+            };
+            if let Some(section) = title.and_then(section_for) {
+                current = Some(section);
+                entry_indent = None;
+                match section {
+                    Section::Returns => sections.returns = true,
+                    Section::Examples => sections.examples = true,
+                    _ => {}
+                }
+                if underlined {
+                    i += 1;
+                }
+                continue;
+            }
+
+            if trimmed.is_empty() || current != Some(Section::Params) {
+                continue;
+            }
+            let indent = line.len() - line.trim_start().len();
+            let base = *entry_indent.get_or_insert(indent);
+            if indent < base {
+                current = None;
+            } else if indent == base {
+                // `name (type): text`, `name: text` or `a, b : type`
+                let head = trimmed.split([':', '(']).next().unwrap_or("");
+                sections.params.extend(head.split(',').map(str::trim).filter(|name| is_param_name(name)));
+            }
+        }
+        sections
+    }
+}
+
+fn section_for(title: &str) -> Option<Section> {
+    match title.trim().to_ascii_lowercase().as_str() {
+        "args" | "arguments" | "parameters" | "params" | "keyword args" | "keyword arguments"
+        | "other parameters" => Some(Section::Params),
+        "returns" | "return" | "yields" | "yield" => Some(Section::Returns),
+        "example" | "examples" => Some(Section::Examples),
+        "raises" | "attributes" | "note" | "notes" | "see also" | "warning" | "warnings" | "todo"
+        | "references" => Some(Section::Other),
+        _ => None,
+    }
+}
+
+fn is_param_name(name: &str) -> bool {
+    let name = name.trim_start_matches('*');
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn function(name: &str, params: &[&str], returns: Option<&str>, doc: Option<&str>) -> CodeEntity {
+        let mut entity = CodeEntity::new("function", name.to_string(), PathBuf::from("pkg/api.py"), 1);
+        entity.parameters = params.iter().map(|p| p.to_string()).collect();
+        entity.return_type = returns.map(str::to_string);
+        entity.docstring = doc.map(str::to_string);
+        entity
+    }
+
+    #[test]
+    fn test_docstring_styles() {
+        let google = DocSections::parse("Fetch.\n\nArgs:\n    url (str): Where.\n        More text.\n    *retries: How often.\n\nReturns:\n    The body.\n", DocStyle::Auto);
+        assert_eq!(google.params, vec!["url", "*retries"]);
+        assert!(google.returns && !google.examples);
+
+        let numpy = DocSections::parse("Fetch.\n\nParameters\n----------\nurl, timeout : str\n    Where.\n\nExamples\n--------\n>>> fetch('x')\n", DocStyle::Auto);
+        assert_eq!(numpy.params, vec!["url", "timeout"]);
+        assert!(numpy.examples && !numpy.returns);
+
+        let sphinx = DocSections::parse("Fetch.\n\n:param str url: Where.\n:rtype: bytes\n", DocStyle::Auto);
+        assert_eq!(sphinx.params, vec!["url"]);
+        assert!(sphinx.returns);
+
+        // A configured style only accepts its own sections
+        let sphinx_as_google = DocSections::parse("Fetch.\n\n:param str url: Where.\n:rtype: bytes\n", DocStyle::Google);
+        assert!(sphinx_as_google.params.is_empty() && !sphinx_as_google.returns);
+        let numpy_as_sphinx = DocSections::parse("Fetch.\n\nExamples\n--------\n>>> fetch('x')\n", DocStyle::Sphinx);
+        assert!(numpy_as_sphinx.examples);
+    }
+
+    #[test]
+    fn test_rules_severities_and_ignores() {
+        let mut endpoint = function("get_user", &["user_id"], None, Some("Get a user.\n\nArgs:\n    user_id: Id.\n"));
+        endpoint.is_api_endpoint = true;
+        let entities = vec![
+            function("undocumented", &[], None, None),
+            function("_private", &[], None, None),
+            function("fetch", &["self", "url", "timeout"], Some("bytes"), Some("Fetch.\n\nArgs:\n    url: Where.\n")),
+            endpoint,
+        ];
+
+        let report = lint(&entities, &AnalyzerConfig::default()).unwrap();
+        let found: Vec<(usize, Rule)> = report.findings.iter().map(|f| (f.index, f.rule)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (0, Rule::MissingDocstring),
+                (2, Rule::MissingParamDocs),
+                (2, Rule::MissingReturnDocs),
+                (2, Rule::MissingExamples),
+                (3, Rule::MissingExamples),
+            ]
+        );
+        assert_eq!(report.findings[1].message, "undocumented parameters: timeout");
+        // Endpoints lacking examples rank a level above other entities
+        assert_eq!((report.findings[3].severity, report.findings[4].severity), (Severity::Info, Severity::Warning));
+        assert!(report.passes(Severity::Error) && !report.passes(Severity::Warning));
+        assert_eq!(example_candidates(&entities), vec![3, 2, 0]);
+
+        let config = AnalyzerConfig {
+            lint_severity: [("missing-docstring".to_string(), "error".to_string()), ("missing-examples".to_string(), "off".to_string())]
+                .into_iter()
+                .collect(),
+            lint_ignore: vec!["missing-return-docs:fetch".to_string()],
+            ..AnalyzerConfig::default()
+        };
+        let report = lint(&entities, &config).unwrap();
+        assert_eq!(report.findings.len(), 2);
+        assert_eq!(report.count(Severity::Error), 1);
+
+        let typo = AnalyzerConfig {
+            lint_severity: [("missing-docstrings".to_string(), "error".to_string())].into_iter().collect(),
+            ..AnalyzerConfig::default()
+        };
+        assert!(lint(&entities, &typo).is_err());
+
+        let sphinx = AnalyzerConfig { docstring_style: "sphinx".to_string(), ..AnalyzerConfig::default() };
+        let report = lint(&entities, &sphinx).unwrap();
+        assert!(report.findings.iter().any(|f| f.index == 3 && f.rule == Rule::MissingParamDocs));
+        let unknown = AnalyzerConfig { docstring_style: "epytext".to_string(), ..AnalyzerConfig::default() };
+        assert!(lint(&entities, &unknown).is_err());
+    }
+
+    #[test]
+    fn test_naming_conventions() {
+        let mut class = function("user_record", &[], None, Some("A user."));
+        class.entity_type = "class".into();
+        let mut method = function("id", &["self"], None, Some("Id."));
+        method.entity_type = "method".into();
+        let entities = vec![
+            function("getUser", &["id"], None, Some("Get.\n\nArgs:\n    id: Which.\n")),
+            class,
+            function("list", &[], None, Some("List.")),
+            method,
+            function("setUp", &[], None, Some("Set up.")),
+            function("GetUser", &[], None, Some("Exported.")),
+        ];
+        let mut go = entities[5].clone();
+        go.file_path = PathBuf::from("api.go").into();
+
+        let report = lint(&[entities, vec![go]].concat(), &AnalyzerConfig::default()).unwrap();
+        let found: Vec<(usize, Rule)> = report
+            .findings
+            .iter()
+            .filter(|f| f.rule != Rule::MissingExamples)
+            .map(|f| (f.index, f.rule))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (0, Rule::NamingConvention),
+                (0, Rule::ShadowedBuiltin),
+                (1, Rule::NamingConvention),
+                (2, Rule::ShadowedBuiltin),
+                (5, Rule::NamingConvention),
+            ]
+        );
+        assert_eq!(report.findings[1].message, "parameters shadow builtins: id");
+    }
+
+    #[test]
+    fn test_inline_suppressions() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("api.py");
+        std::fs::write(
+            &path,
+            "def quiet():  # autodoc: ignore[missing-docstring]\n    pass\n\n\
+             # autodoc: ignore\n\
+             def hush(a):\n    \"\"\"Hush.\"\"\"\n\n\
+             def loud(a):\n    \"\"\"Loud.\"\"\"  # autodoc: ignore[missing-examples]\n",
+        )
+        .unwrap();
+        let entities = crate::analyzer::RustAnalyzer::new().analyze_file(&path).unwrap();
+
+        let report = lint(&entities, &AnalyzerConfig::default()).unwrap();
+        let suppressed: Vec<Rule> = report.suppressed.iter().map(|f| f.rule).collect();
+        assert_eq!(suppressed, vec![Rule::MissingDocstring, Rule::MissingParamDocs, Rule::MissingExamples]);
+        // Only comments on the definition line count, not ones in the body
+        let found: Vec<Rule> = report.findings.iter().map(|f| f.rule).collect();
+        assert_eq!(found, vec![Rule::MissingParamDocs, Rule::MissingExamples]);
+        assert!(report.findings.iter().all(|f| entities[f.index].name == "loud"));
+    }
+}
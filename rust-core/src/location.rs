@@ -0,0 +1,75 @@
+use crate::entity::CodeEntity;
+
+/// Byte offset at which each line of `source` starts
+pub fn line_starts(source: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+        .collect()
+}
+
+/// 1-based line holding byte `offset`, given the `line_starts` of its source
+pub fn line_at(starts: &[usize], offset: usize) -> usize {
+    starts.partition_point(|&start| start <= offset)
+}
+
+/// Fill in byte offsets and columns for the entities of one file.
+///
+/// Backends that know exact ranges set `start_offset`/`end_offset` while
+/// parsing; for the rest the span runs from the first non-blank character
+/// of `line_number` to the end of `end_line`. Columns are 1-based character
+/// counts derived from the offsets.
+pub fn locate(entities: &mut [CodeEntity], source: &str) {
+    let starts = line_starts(source);
+    let line_text = |line: usize| -> Option<(usize, &str)> {
+        let start = *starts.get(line.checked_sub(1)?)?;
+        let end = starts.get(line).map_or(source.len(), |&next| next - 1);
+        Some((start, source[start..end].trim_end_matches('\r')))
+    };
+
+    for entity in entities.iter_mut() {
+        if entity.start_offset.is_none() {
+            entity.start_offset = line_text(entity.line_number)
+                .map(|(start, text)| start + text.len() - text.trim_start().len());
+        }
+        if entity.end_offset.is_none() && entity.end_line >= entity.line_number {
+            entity.end_offset = line_text(entity.end_line).map(|(start, text)| start + text.len());
+        }
+        entity.column = entity.start_offset.map_or(0, |offset| column(source, &starts, offset));
+        entity.end_column = entity.end_offset.map_or(0, |offset| column(source, &starts, offset));
+    }
+}
+
+/// 1-based character column of `offset`; 0 when it isn't a character boundary
+fn column(source: &str, starts: &[usize], offset: usize) -> usize {
+    let line_start = starts[starts.partition_point(|&start| start <= offset) - 1];
+    source
+        .get(line_start..offset)
+        .map_or(0, |prefix| prefix.chars().count() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::PythonParser;
+    use std::path::Path;
+
+    #[test]
+    fn test_offsets_and_columns() {
+        let source = "class Café:\n    def größe(self):\n        pass\n";
+        let mut entities = PythonParser::new().parse_source(source, Path::new("api.py")).unwrap();
+        locate(&mut entities, source);
+
+        let method = &entities[1];
+        let (start, end) = (method.start_offset.unwrap(), method.end_offset.unwrap());
+        assert!(source[start..end].starts_with("def größe") && source[start..end].ends_with("pass"));
+        assert_eq!((method.column, method.end_column), (5, 13));
+
+        // Line-only entities are located from their lines
+        let mut entity = CodeEntity::new("function", "f".to_string(), Path::new("x.sh"), 2);
+        entity.end_line = 3;
+        let mut entities = vec![entity];
+        locate(&mut entities, "#!/bin/sh\n  f() {\n}\n");
+        assert_eq!((entities[0].start_offset, entities[0].end_offset), (Some(12), Some(19)));
+        assert_eq!((entities[0].column, entities[0].end_column), (3, 2));
+    }
+}
@@ -0,0 +1,120 @@
+use rustpython_parser::ast;
+use serde::{Deserialize, Serialize};
+
+use crate::location::line_at;
+use crate::walk::{argument, dotted_name, string_literal, template};
+
+/// Logger methods and the level they log at; `log` takes the level as its
+/// first argument
+const LEVELS: &[(&str, &str)] = &[
+    ("debug", "debug"),
+    ("info", "info"),
+    ("msg", "info"),
+    ("warning", "warning"),
+    ("warn", "warning"),
+    ("error", "error"),
+    ("exception", "error"),
+    ("critical", "critical"),
+    ("fatal", "critical"),
+];
+
+/// Keyword arguments of the standard library's logging calls, which are
+/// not structured fields
+const LOGGING_KEYWORDS: &[&str] = &["exc_info", "stack_info", "stacklevel", "extra"];
+
+/// A log event an entity emits through `logging` or `structlog`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogEvent {
+    /// `debug`, `info`, `warning`, `error` or `critical`
+    pub level: String,
+    /// Message template as written: `%s` placeholders are kept and f-string
+    /// fields become `{...}`
+    pub message: String,
+    /// Structured fields passed as keyword arguments (structlog)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+    pub line: usize,
+}
+
+/// The log event `call` emits, if any. A call counts when its receiver
+/// names a logger (`logger`, `self.log`, `logging`,
+/// `structlog.get_logger()`) and the message is a literal or an f-string.
+pub(crate) fn event(call: &ast::ExprCall, line_starts: &[usize]) -> Option<LogEvent> {
+    let ast::Expr::Attribute(method) = &*call.func else { return None };
+    if !is_logger(&method.value) {
+        return None;
+    }
+    let (level, message) = match method.attr.as_str() {
+        "log" => {
+            let level = argument(call, 0, "level").and_then(level_name);
+            (level, argument(call, 1, "msg"))
+        }
+        name => {
+            let level = LEVELS.iter().find(|(method, _)| *method == name).map(|(_, level)| level.to_string());
+            (level, argument(call, 0, "msg").or_else(|| argument(call, 0, "event")))
+        }
+    };
+    Some(LogEvent {
+        level: level?,
+        message: message.and_then(template)?,
+        fields: call
+            .keywords
+            .iter()
+            .filter_map(|k| k.arg.as_ref().map(|arg| arg.as_str()))
+            .filter(|arg| !LOGGING_KEYWORDS.contains(arg) && !matches!(*arg, "msg" | "event" | "level"))
+            .map(str::to_string)
+            .collect(),
+        line: line_at(line_starts, call.range.start().to_usize()),
+    })
+}
+
+/// Whether `receiver` looks like a logger: a name such as `log`, `logger`
+/// or `audit_log`, a `getLogger`/`get_logger` call, or a logger's `bind()`
+fn is_logger(receiver: &ast::Expr) -> bool {
+    match receiver {
+        ast::Expr::Call(call) => match &*call.func {
+            ast::Expr::Attribute(attr) if matches!(attr.attr.as_str(), "bind" | "new") => is_logger(&attr.value),
+            func => dotted_name(func).map_or(false, |name| name.ends_with("getLogger") || name.ends_with("get_logger")),
+        },
+        _ => dotted_name(receiver).map_or(false, |name| {
+            let last = name.rsplit('.').next().unwrap_or(&name).to_ascii_lowercase();
+            matches!(last.as_str(), "log" | "logging" | "structlog") || last.ends_with("logger") || last.ends_with("_log")
+        }),
+    }
+}
+
+/// `logging.INFO` or `"INFO"` -> `info`
+fn level_name(expr: &ast::Expr) -> Option<String> {
+    let name = match string_literal(expr) {
+        Some(name) => name.to_string(),
+        None => dotted_name(expr)?.rsplit('.').next()?.to_string(),
+    };
+    let name = name.to_ascii_lowercase();
+    LEVELS.iter().find(|(method, _)| *method == name).map(|(_, level)| level.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::PythonParser;
+    use std::path::Path;
+
+    #[test]
+    fn test_log_events() {
+        let source = "def charge(order):\n    logger.info(\"charging order %s\", order.id)\n    log = structlog.get_logger()\n    log.warning(\"card_declined\", order_id=order.id, exc_info=True)\n    self.logger.log(logging.ERROR, f\"retry {order.id} failed\")\n    catalog.error(\"not a logger call\")\n    results.info(\"nor this\")\n";
+        let entities = PythonParser::new().parse_source(source, Path::new("billing.py")).unwrap();
+
+        let events: Vec<(&str, &str, Vec<&str>, usize)> = entities[0]
+            .log_events
+            .iter()
+            .map(|e| (e.level.as_str(), e.message.as_str(), e.fields.iter().map(String::as_str).collect(), e.line))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                ("info", "charging order %s", vec![], 2),
+                ("warning", "card_declined", vec!["order_id"], 4),
+                ("error", "retry {...} failed", vec![], 5),
+            ]
+        );
+    }
+}
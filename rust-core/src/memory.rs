@@ -0,0 +1,239 @@
+use std::collections::{BTreeMap, HashSet};
+use std::mem::{size_of, size_of_val};
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::endpoints::EndpointParam;
+use crate::entity::CodeEntity;
+use crate::env::EnvVar;
+use crate::intern::Symbol;
+use crate::logs::LogEvent;
+use crate::outbound::HttpCall;
+use crate::security::SecurityFinding;
+use crate::testmap::{TestCase, UsageExample};
+
+/// Approximate heap footprint of a result set.
+///
+/// Sizes count string and vector capacities plus the entity structs
+/// themselves; allocator overhead and map nodes are estimated, so treat the
+/// figures as a guide for tuning rather than an exact measurement.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct MemoryUsage {
+    pub entities: usize,
+    pub files: usize,
+    /// The `CodeEntity` structs, excluding what they point to
+    pub entity_bytes: usize,
+    pub code_bytes: usize,
+    pub docstring_bytes: usize,
+    /// Other owned strings and lists: ids, names, links, parameters, endpoints
+    pub string_bytes: usize,
+    /// Interned entity types, decorators and paths, counted once per value
+    pub shared_bytes: usize,
+    pub metadata_bytes: usize,
+    /// Structures built over the entities, such as search indexes and
+    /// string caches, when the caller knows about them
+    pub index_bytes: usize,
+}
+
+impl MemoryUsage {
+    pub fn total_bytes(&self) -> usize {
+        self.entity_bytes
+            + self.code_bytes
+            + self.docstring_bytes
+            + self.string_bytes
+            + self.shared_bytes
+            + self.metadata_bytes
+            + self.index_bytes
+    }
+}
+
+impl std::fmt::Display for MemoryUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "~{} for {} entities from {} files (code {}, docstrings {}, other strings {}, shared {}, metadata {}",
+            format_bytes(self.total_bytes()),
+            self.entities,
+            self.files,
+            format_bytes(self.code_bytes),
+            format_bytes(self.docstring_bytes),
+            format_bytes(self.string_bytes),
+            format_bytes(self.shared_bytes),
+            format_bytes(self.metadata_bytes),
+        )?;
+        if self.index_bytes > 0 {
+            write!(f, ", indexes {}", format_bytes(self.index_bytes))?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Estimate the memory held by `entities`
+pub fn estimate(entities: &[CodeEntity]) -> MemoryUsage {
+    let mut usage = MemoryUsage {
+        entities: entities.len(),
+        entity_bytes: size_of_val(entities),
+        ..MemoryUsage::default()
+    };
+    let mut shared: HashSet<*const u8> = HashSet::new();
+    let mut files: HashSet<*const u8> = HashSet::new();
+
+    for entity in entities {
+        usage.code_bytes += entity.code.capacity();
+        usage.docstring_bytes += entity.docstring.as_ref().map_or(0, String::capacity);
+        usage.string_bytes += entity.entity_id.capacity()
+            + entity.content_hash.capacity()
+            + entity.name.capacity()
+            + entity.return_type.as_ref().map_or(0, String::capacity)
+            + entity.endpoint_path.as_ref().map_or(0, String::capacity)
+            + entity.replacement.as_ref().map_or(0, String::capacity)
+            + entity.parent_id.as_ref().map_or(0, String::capacity)
+            + strings_bytes(&entity.children)
+            + strings_bytes(&entity.bases)
+            + strings_bytes(&entity.tables)
+            + strings_bytes(&entity.feature_flags)
+            + entity.overrides.as_ref().map_or(0, String::capacity)
+            + strings_bytes(&entity.parameters)
+            + entity.parameter_types.as_ref().map_or(0, |types| {
+                types.capacity() * size_of::<Option<String>>()
+                    + types.iter().flatten().map(String::capacity).sum::<usize>()
+            })
+            + strings_bytes(&entity.http_methods)
+            + entity.endpoint_params.capacity() * size_of::<EndpointParam>()
+            + entity
+                .endpoint_params
+                .iter()
+                .map(|param| {
+                    param.name.capacity()
+                        + param.annotation.as_ref().map_or(0, String::capacity)
+                        + param.default.as_ref().map_or(0, String::capacity)
+                })
+                .sum::<usize>()
+            + entity.response_model.as_ref().map_or(0, String::capacity)
+            + entity.api_version.as_ref().map_or(0, String::capacity)
+            + strings_bytes(&entity.serializers)
+            + strings_bytes(&entity.request_models)
+            + strings_bytes(&entity.response_models)
+            + strings_bytes(&entity.used_by_endpoints)
+            + entity.auth_scheme.as_ref().map_or(0, String::capacity)
+            + strings_bytes(&entity.permissions)
+            + strings_bytes(&entity.exercises)
+            + strings_bytes(&entity.tested_by)
+            + entity.test_cases.capacity() * size_of::<TestCase>()
+            + entity
+                .test_cases
+                .iter()
+                .map(|case| {
+                    case.id.as_ref().map_or(0, String::capacity)
+                        + case.args.capacity() * size_of::<(String, String)>()
+                        + case.args.iter().map(|(name, value)| name.capacity() + value.capacity()).sum::<usize>()
+                })
+                .sum::<usize>()
+            + (entity.harvested_examples.capacity() + entity.examples.capacity()) * size_of::<UsageExample>()
+            + entity
+                .harvested_examples
+                .iter()
+                .chain(&entity.examples)
+                .map(|example| example.target.capacity() + example.code.capacity() + example.test_id.capacity())
+                .sum::<usize>()
+            + entity.env_vars.capacity() * size_of::<EnvVar>()
+            + entity
+                .env_vars
+                .iter()
+                .map(|var| var.name.capacity() + var.default.as_ref().map_or(0, String::capacity))
+                .sum::<usize>()
+            + entity.http_calls.capacity() * size_of::<HttpCall>()
+            + entity
+                .http_calls
+                .iter()
+                .map(|call| {
+                    call.client.capacity()
+                        + call.method.as_ref().map_or(0, String::capacity)
+                        + call.url.as_ref().map_or(0, String::capacity)
+                })
+                .sum::<usize>()
+            + entity.security_findings.capacity() * size_of::<SecurityFinding>()
+            + entity.security_findings.iter().map(|finding| finding.message.capacity()).sum::<usize>()
+            + entity.log_events.capacity() * size_of::<LogEvent>()
+            + entity
+                .log_events
+                .iter()
+                .map(|event| event.level.capacity() + event.message.capacity() + strings_bytes(&event.fields))
+                .sum::<usize>()
+            + entity.decorators.capacity() * size_of::<Symbol>();
+        usage.metadata_bytes += metadata_bytes(&entity.metadata);
+
+        let path = entity.file_path.as_path();
+        if files.insert(path as *const Path as *const u8) {
+            usage.shared_bytes += arc_bytes(path.as_os_str().len());
+        }
+        for symbol in std::iter::once(&entity.entity_type).chain(&entity.decorators) {
+            if shared.insert(symbol.as_ptr()) {
+                usage.shared_bytes += arc_bytes(symbol.len());
+            }
+        }
+    }
+    usage.files = files.len();
+    usage
+}
+
+/// Bytes as a short human-readable figure, e.g. `12.5 MiB`
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+fn strings_bytes(strings: &Vec<String>) -> usize {
+    strings.capacity() * size_of::<String>() + strings.iter().map(String::capacity).sum::<usize>()
+}
+
+/// Keys and values plus a rough per-entry share of the B-tree nodes
+fn metadata_bytes(metadata: &BTreeMap<String, String>) -> usize {
+    metadata
+        .iter()
+        .map(|(key, value)| key.capacity() + value.capacity() + 3 * size_of::<String>())
+        .sum()
+}
+
+/// An `Arc` allocation holds the strong and weak counts before the data
+fn arc_bytes(len: usize) -> usize {
+    len + 2 * size_of::<usize>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_estimate_counts_shared_values_once() {
+        let mut first = CodeEntity::new("function", "load".to_string(), PathBuf::from("app.py"), 1);
+        first.code = "def load(): pass".to_string();
+        first.decorators = vec!["cache".into()];
+        let mut second = first.clone();
+        second.docstring = Some("Load it.".to_string());
+        let other = CodeEntity::new("function", "save".to_string(), PathBuf::from("other.py"), 1);
+
+        let usage = estimate(&[first, second, other]);
+        assert_eq!((usage.entities, usage.files), (3, 2));
+        assert_eq!(usage.code_bytes, 32);
+        assert_eq!(usage.docstring_bytes, 8);
+        assert_eq!(
+            usage.shared_bytes,
+            arc_bytes("app.py".len()) + arc_bytes("other.py".len()) + arc_bytes(8) + arc_bytes(5)
+        );
+        assert!(usage.total_bytes() > 3 * size_of::<CodeEntity>());
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+    }
+}
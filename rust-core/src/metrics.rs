@@ -0,0 +1,293 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use crate::config::AnalyzerConfig;
+use crate::entity::CodeEntity;
+
+/// Aggregate statistics over an analyzed project
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProjectMetrics {
+    pub files: usize,
+    pub entities: usize,
+    pub functions: usize,
+    pub methods: usize,
+    pub classes: usize,
+    pub async_functions: usize,
+    pub api_endpoints: usize,
+    pub documented: usize,
+    /// Percentage of entities carrying a docstring
+    pub docstring_coverage: f64,
+    /// Percentage of function parameters and returns carrying a type
+    /// annotation, over backends that track annotations
+    pub annotation_coverage: f64,
+    pub average_complexity: f64,
+    pub max_complexity: u32,
+    /// Entities whose complexity exceeds the configured threshold
+    pub complex_entities: usize,
+}
+
+impl ProjectMetrics {
+    /// Compute metrics, using `config` for thresholds
+    pub fn compute(entities: &[CodeEntity], config: &AnalyzerConfig) -> Self {
+        let mut metrics = ProjectMetrics {
+            entities: entities.len(),
+            ..ProjectMetrics::default()
+        };
+
+        let mut files = HashSet::new();
+        let mut total_complexity: u64 = 0;
+        let (mut annotated, mut annotatable) = (0, 0);
+
+        for entity in entities {
+            files.insert(&entity.file_path);
+
+            match entity.entity_type.as_str() {
+                "function" => metrics.functions += 1,
+                "method" => metrics.methods += 1,
+                "class" => metrics.classes += 1,
+                _ => {}
+            }
+
+            if entity.is_async {
+                metrics.async_functions += 1;
+            }
+            if entity.is_api_endpoint {
+                metrics.api_endpoints += 1;
+            }
+            if has_docstring(entity) {
+                metrics.documented += 1;
+            }
+            if let Some((done, total)) = entity.annotation_counts() {
+                annotated += done;
+                annotatable += total;
+            }
+
+            total_complexity += entity.complexity_score as u64;
+            metrics.max_complexity = metrics.max_complexity.max(entity.complexity_score);
+            if entity.complexity_score > config.max_complexity {
+                metrics.complex_entities += 1;
+            }
+        }
+
+        metrics.files = files.len();
+        metrics.docstring_coverage = percentage(metrics.documented, metrics.entities);
+        metrics.annotation_coverage = percentage(annotated, annotatable);
+        if metrics.entities > 0 {
+            metrics.average_complexity = total_complexity as f64 / metrics.entities as f64;
+        }
+
+        metrics
+    }
+}
+
+/// Docstring coverage of the public entities in one package
+#[derive(Debug, Clone, Serialize)]
+pub struct PackageCoverage {
+    /// Dotted package path relative to the analysis root; "." for the root itself
+    pub package: String,
+    pub documented: usize,
+    pub total: usize,
+    pub coverage: f64,
+}
+
+/// Docstring coverage of public entities grouped by package (directory).
+///
+/// Returns per-package rows sorted by package name, plus the overall total.
+pub fn coverage_by_package(entities: &[CodeEntity], root: &Path) -> (Vec<PackageCoverage>, PackageCoverage) {
+    let mut packages: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+
+    for entity in entities.iter().filter(|e| e.is_public()) {
+        let relative = entity.file_path.strip_prefix(root).unwrap_or(entity.file_path.as_path());
+        let package = relative
+            .parent()
+            .map(|dir| {
+                dir.components()
+                    .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+                    .join(".")
+            })
+            .filter(|p| !p.is_empty())
+            .unwrap_or_else(|| ".".to_string());
+
+        let counts = packages.entry(package).or_insert((0, 0));
+        counts.1 += 1;
+        if has_docstring(entity) {
+            counts.0 += 1;
+        }
+    }
+
+    let rows: Vec<PackageCoverage> = packages
+        .into_iter()
+        .map(|(package, (documented, total))| PackageCoverage {
+            package,
+            documented,
+            total,
+            coverage: percentage(documented, total),
+        })
+        .collect();
+
+    let documented = rows.iter().map(|r| r.documented).sum();
+    let total = rows.iter().map(|r| r.total).sum();
+    let overall = PackageCoverage {
+        package: "TOTAL".to_string(),
+        documented,
+        total,
+        coverage: percentage(documented, total),
+    };
+
+    (rows, overall)
+}
+
+/// Type-annotation coverage of the functions and methods in one module
+#[derive(Debug, Clone, Serialize)]
+pub struct AnnotationCoverage {
+    /// Dotted module path, e.g. `pkg.sub.mod`; "TOTAL" for the overall row
+    pub module: String,
+    /// Annotated parameters and returns
+    pub annotated: usize,
+    pub total: usize,
+    pub coverage: f64,
+}
+
+/// Annotation coverage grouped by module (file), sorted by module, plus the
+/// overall total. Files from backends that don't track annotations are left out.
+pub fn annotation_coverage_by_module(entities: &[CodeEntity]) -> (Vec<AnnotationCoverage>, AnnotationCoverage) {
+    let mut modules: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    for entity in entities {
+        if let Some((annotated, total)) = entity.annotation_counts() {
+            let module = match entity.module_path.is_empty() {
+                true => module_name(&entity.file_path),
+                false => entity.module_path.clone(),
+            };
+            let counts = modules.entry(module).or_insert((0, 0));
+            counts.0 += annotated;
+            counts.1 += total;
+        }
+    }
+
+    let rows: Vec<AnnotationCoverage> = modules
+        .into_iter()
+        .map(|(module, (annotated, total))| AnnotationCoverage {
+            module,
+            annotated,
+            total,
+            coverage: percentage(annotated, total),
+        })
+        .collect();
+
+    let annotated = rows.iter().map(|r| r.annotated).sum();
+    let total = rows.iter().map(|r| r.total).sum();
+    let overall = AnnotationCoverage {
+        module: "TOTAL".to_string(),
+        annotated,
+        total,
+        coverage: percentage(annotated, total),
+    };
+
+    (rows, overall)
+}
+
+/// `pkg/sub/mod.py` → `pkg.sub.mod`; a package's `__init__` is the package
+pub(crate) fn module_name(path: &Path) -> String {
+    let mut parts: Vec<String> = path
+        .with_extension("")
+        .components()
+        .filter_map(|c| match c {
+            std::path::Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect();
+    if parts.len() > 1 && parts.last().map_or(false, |last| last == "__init__") {
+        parts.pop();
+    }
+    parts.join(".")
+}
+
+/// Whether an entity has a non-empty docstring
+pub fn has_docstring(entity: &CodeEntity) -> bool {
+    entity.docstring.as_deref().map_or(false, |d| !d.trim().is_empty())
+}
+
+/// `part` as a percentage of `total`; an empty total counts as fully covered
+pub fn percentage(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        100.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_metrics() {
+        let documented = CodeEntity::stub("function", "documented", "a.py").doc("Does things.");
+        let mut complex = CodeEntity::stub("method", "complex", "b.py");
+        complex.complexity_score = 25;
+
+        let entities = vec![documented, complex, CodeEntity::stub("class", "Thing", "b.py")];
+        let metrics = ProjectMetrics::compute(&entities, &AnalyzerConfig::default());
+
+        assert_eq!(metrics.files, 2);
+        assert_eq!(metrics.functions, 1);
+        assert_eq!(metrics.methods, 1);
+        assert_eq!(metrics.classes, 1);
+        assert_eq!(metrics.documented, 1);
+        assert_eq!(metrics.max_complexity, 25);
+        assert_eq!(metrics.complex_entities, 1);
+        assert!((metrics.docstring_coverage - 100.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_coverage_by_package() {
+        let documented = CodeEntity::stub("function", "documented", "/repo/pkg/sub/a.py").doc("Documented.");
+        let entities = vec![
+            documented,
+            CodeEntity::stub("function", "undocumented", "/repo/pkg/sub/a.py"),
+            CodeEntity::stub("function", "_private", "/repo/pkg/sub/a.py"),
+            CodeEntity::stub("function", "top", "/repo/setup.py"),
+        ];
+
+        let (rows, overall) = coverage_by_package(&entities, Path::new("/repo"));
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].package, ".");
+        assert_eq!(rows[1].package, "pkg.sub");
+        assert_eq!(rows[1].total, 2);
+        assert_eq!(rows[1].coverage, 50.0);
+        assert_eq!(overall.documented, 1);
+        assert_eq!(overall.total, 3);
+    }
+
+    #[test]
+    fn test_annotation_coverage() {
+        let mut typed = CodeEntity::stub("method", "save", "pkg/store.py");
+        typed.parameters = vec!["self".to_string(), "item".to_string(), "*rest".to_string()];
+        typed.parameter_types = Some(vec![None, Some("Item".to_string()), None]);
+        typed.return_type = Some("bool".to_string());
+        let mut init = CodeEntity::stub("method", "__init__", "pkg/__init__.py");
+        init.parameters = vec!["self".to_string(), "path".to_string()];
+        init.parameter_types = Some(vec![None, None]);
+        // Backends that don't track annotations are left out
+        let rust = CodeEntity::stub("function", "load", "src/lib.rs");
+
+        assert_eq!(typed.annotation_counts(), Some((2, 3)));
+        assert_eq!(rust.annotation_coverage(), None);
+
+        let (rows, overall) = annotation_coverage_by_module(&[typed, init, rust]);
+        let modules: Vec<&str> = rows.iter().map(|r| r.module.as_str()).collect();
+        assert_eq!(modules, vec!["pkg", "pkg.store"]);
+        assert_eq!(rows[0].coverage, 0.0);
+        assert_eq!((overall.annotated, overall.total), (2, 4));
+    }
+
+    #[test]
+    fn test_empty_project_is_fully_covered() {
+        let metrics = ProjectMetrics::compute(&[], &AnalyzerConfig::default());
+        assert_eq!(metrics.docstring_coverage, 100.0);
+        assert_eq!(metrics.average_complexity, 0.0);
+    }
+}
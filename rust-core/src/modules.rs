@@ -0,0 +1,107 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::backends::has_extension;
+use crate::metrics::module_name;
+use crate::paths;
+
+/// Directories that hold packages without being one themselves
+const LAYOUT_DIRS: &[&str] = &["src", "lib", "python"];
+
+/// Dotted import path of the module in `file`, analyzed as part of the
+/// tree at `root` when given.
+///
+/// For Python the path follows the package layout on disk: the enclosing
+/// directories with an `__init__.py` are regular packages, and directories
+/// between those and `root` are namespace packages (PEP 420), except for a
+/// leading `src/`, `lib/` or `python/` layout directory. Without a root only
+/// regular packages count. Other languages use the path relative to `root`.
+pub fn module_path(file: &Path, root: Option<&Path>) -> String {
+    if !has_extension(file, &["py", "pyi", "pyw"]) {
+        return match root {
+            Some(root) => module_name(&paths::relative_to(file, root)),
+            None => module_name(Path::new(file.file_name().unwrap_or_default())),
+        };
+    }
+
+    let root = root.map(paths::absolute);
+    python_module(&paths::absolute(file), root.as_deref(), |dir| dir.join("__init__.py").is_file())
+}
+
+/// `module_path` for a file of a tree that isn't on disk, such as a git
+/// revision; `files` lists the tree's files relative to its root, as
+/// `file` is
+pub fn listed_module_path(file: &Path, files: &HashSet<&Path>) -> String {
+    if !has_extension(file, &["py", "pyi", "pyw"]) {
+        return module_name(file);
+    }
+    python_module(file, Some(Path::new("")), |dir| files.contains(dir.join("__init__.py").as_path()))
+}
+
+fn python_module(file: &Path, root: Option<&Path>, is_package: impl Fn(&Path) -> bool) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let stem = file.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    if stem != "__init__" {
+        parts.push(stem);
+    }
+
+    let mut dir: PathBuf = file.parent().map(Path::to_path_buf).unwrap_or_default();
+    while is_package(&dir) {
+        let Some(name) = dir.file_name() else { break };
+        parts.push(name.to_string_lossy().into_owned());
+        dir.pop();
+    }
+
+    if let Some(namespaces) = root.and_then(|root| dir.strip_prefix(root).ok()) {
+        let mut namespaces: Vec<String> = namespaces
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        if namespaces.first().map_or(false, |first| LAYOUT_DIRS.contains(&first.as_str())) {
+            namespaces.remove(0);
+        }
+        parts.extend(namespaces.into_iter().rev());
+    }
+
+    parts.reverse();
+    parts.join(".")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_package_layouts() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for dir in ["src/mypkg/sub", "src/acme/plugins", "scripts"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for init in ["src/mypkg/__init__.py", "src/mypkg/sub/__init__.py", "src/acme/plugins/__init__.py"] {
+            fs::write(root.join(init), "").unwrap();
+        }
+
+        let module = |path: &str, root: Option<&Path>| module_path(&temp_dir.path().join(path), root);
+        assert_eq!(module("src/mypkg/sub/mod.py", Some(root)), "mypkg.sub.mod");
+        assert_eq!(module("src/mypkg/__init__.py", Some(root)), "mypkg");
+        // `acme` has no `__init__.py`: a namespace package
+        assert_eq!(module("src/acme/plugins/foo.py", Some(root)), "acme.plugins.foo");
+        assert_eq!(module("src/acme/plugins/foo.py", None), "plugins.foo");
+        assert_eq!(module("scripts/tool.py", Some(root)), "scripts.tool");
+        assert_eq!(module("src/lib.rs", Some(root)), "src.lib");
+    }
+
+    #[test]
+    fn test_listed_package_layouts() {
+        let files = ["src/mypkg/__init__.py", "src/mypkg/mod.py", "src/acme/plugins/foo.py", "setup.py"];
+        let listed: HashSet<&Path> = files.iter().map(Path::new).collect();
+        let module = |path: &str| listed_module_path(Path::new(path), &listed);
+        assert_eq!(module("src/mypkg/mod.py"), "mypkg.mod");
+        assert_eq!(module("src/mypkg/__init__.py"), "mypkg");
+        assert_eq!(module("src/acme/plugins/foo.py"), "acme.plugins.foo");
+        assert_eq!(module("setup.py"), "setup");
+    }
+}
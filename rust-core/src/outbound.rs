@@ -0,0 +1,117 @@
+use rustpython_parser::ast;
+use serde::{Deserialize, Serialize};
+
+use crate::location::line_at;
+use crate::walk::{argument, dotted_name, string_literal, template};
+
+/// Client libraries whose module-level functions and sessions make requests
+const CLIENT_MODULES: &[&str] = &["requests", "httpx", "aiohttp"];
+
+const VERBS: &[&str] = &["get", "post", "put", "patch", "delete", "head", "options"];
+
+/// An outbound HTTP request an entity makes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpCall {
+    /// Upper-case method; `None` when passed as a variable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// URL when literal, with f-string fields as `{...}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// `requests`, `httpx`, `aiohttp` or `urllib`; `session` for a client
+    /// object whose library isn't visible
+    pub client: String,
+    pub line: usize,
+}
+
+/// The HTTP request `call` makes, if any: module-level calls such as
+/// `requests.get` and `httpx.post`, `urllib.request.urlopen`, and verb
+/// methods on sessions and clients (`session.post`, `self.client.get`)
+/// when the URL is a literal. `callee` is the dotted name of the function.
+pub(crate) fn request(call: &ast::ExprCall, callee: Option<&str>, line_starts: &[usize]) -> Option<HttpCall> {
+    let (client, method, url) = classify(call, callee)?;
+    Some(HttpCall {
+        method,
+        url: url.and_then(template),
+        client: client.to_string(),
+        line: line_at(line_starts, call.range.start().to_usize()),
+    })
+}
+
+type Request<'a> = (&'static str, Option<String>, Option<&'a ast::Expr>);
+
+fn classify<'a>(call: &'a ast::ExprCall, callee: Option<&str>) -> Option<Request<'a>> {
+    if let Some(name) = callee {
+        let (module, function) = name.rsplit_once('.').unwrap_or(("", name));
+        if matches!(name, "urlopen" | "urllib.request.urlopen" | "request.urlopen") {
+            let method = if argument(call, 1, "data").is_some() { "POST" } else { "GET" };
+            return Some(("urllib", Some(method.to_string()), argument(call, 0, "url")));
+        }
+        if let Some(client) = CLIENT_MODULES.iter().copied().find(|client| *client == module) {
+            return verb_request(client, function, call);
+        }
+    }
+
+    // Session and client objects; only trusted with a literal URL, since
+    // `.get` is also how dicts are read
+    let ast::Expr::Attribute(method) = &*call.func else { return None };
+    let client = session_client(&method.value)?;
+    let (client, method, url) = verb_request(client, method.attr.as_str(), call)?;
+    let literal_url = url.and_then(template).map_or(false, |url| url.starts_with("http://") || url.starts_with("https://"));
+    literal_url.then_some((client, method, url))
+}
+
+/// `get(url)` or `request("POST", url)`
+fn verb_request<'a>(client: &'static str, function: &str, call: &'a ast::ExprCall) -> Option<Request<'a>> {
+    if function == "request" {
+        let method = argument(call, 0, "method").and_then(string_literal).map(str::to_ascii_uppercase);
+        return Some((client, method, argument(call, 1, "url")));
+    }
+    if !VERBS.contains(&function) {
+        return None;
+    }
+    Some((client, Some(function.to_ascii_uppercase()), argument(call, 0, "url")))
+}
+
+/// The library behind a receiver named like an HTTP session or client, or
+/// built inline such as `httpx.Client()` or `aiohttp.ClientSession()`
+fn session_client(receiver: &ast::Expr) -> Option<&'static str> {
+    let name = match receiver {
+        ast::Expr::Call(constructor) => dotted_name(&constructor.func)?,
+        _ => dotted_name(receiver)?,
+    };
+    let last = name.rsplit('.').next().unwrap_or(&name).to_ascii_lowercase();
+    if !(last.ends_with("session") || last.ends_with("client") || last == "http") {
+        return None;
+    }
+    let module = name.split('.').next().unwrap_or_default();
+    Some(CLIENT_MODULES.iter().copied().find(|client| *client == module).unwrap_or("session"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::PythonParser;
+    use std::path::Path;
+
+    #[test]
+    fn test_http_calls() {
+        let source = "async def sync(user, session):\n    requests.post(\"https://billing.example.com/charge\", json=user)\n    httpx.request(\"delete\", f\"https://api.example.com/users/{user.id}\")\n    urllib.request.urlopen(url)\n    await session.get(\"https://status.example.com\")\n    cache.get(\"user\")\n    session.get(key)\n";
+        let entities = PythonParser::new().parse_source(source, Path::new("sync.py")).unwrap();
+
+        assert!(entities[0].makes_external_calls);
+        let calls: Vec<(Option<&str>, Option<&str>, &str)> = entities[0]
+            .http_calls
+            .iter()
+            .map(|c| (c.method.as_deref(), c.url.as_deref(), c.client.as_str()))
+            .collect();
+        assert_eq!(
+            calls,
+            vec![
+                (Some("POST"), Some("https://billing.example.com/charge"), "requests"),
+                (Some("DELETE"), Some("https://api.example.com/users/{...}"), "httpx"),
+                (Some("GET"), None, "urllib"),
+                (Some("GET"), Some("https://status.example.com"), "session"),
+            ]
+        );
+    }
+}
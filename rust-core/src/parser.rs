@@ -1,36 +1,70 @@
-use anyhow::{Result, Context};
-use rustpython_parser::{ast, Parse};
+use anyhow::Result;
+use rustpython_parser::{ast::{self, Ranged}, Parse};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
 use std::path::Path;
-use std::fs;
 
+use crate::auth::Auth;
+use crate::caching;
+use crate::detect;
+use crate::diagnostic::Diagnostic;
+use crate::endpoints;
 use crate::entity::CodeEntity;
+use crate::env;
+use crate::ident::normalize_identifier;
+use crate::intern::{SharedPath, Symbol};
+use crate::location::line_at;
+use crate::schema::{normalize_table, table_entity};
+use crate::source::read_source;
+use crate::testmap;
+use crate::visibility::{Enclosing, ModuleVisibility, VisibilityRules};
 
 /// Parser for Python source files using RustPython's parser
-pub struct PythonParser;
+#[derive(Clone)]
+pub struct PythonParser {
+    /// How `is_internal` is decided
+    visibility: VisibilityRules,
+}
 
 impl PythonParser {
     pub fn new() -> Self {
-        PythonParser
+        PythonParser::with_visibility(VisibilityRules::default())
+    }
+
+    /// Parser deciding `is_internal` by `rules`
+    pub fn with_visibility(rules: VisibilityRules) -> Self {
+        PythonParser { visibility: rules }
     }
 
     /// Parse a Python file and extract code entities
     pub fn parse_file(&self, file_path: &Path) -> Result<Vec<CodeEntity>> {
-        let source = fs::read_to_string(file_path)
-            .with_context(|| format!("Failed to read file: {:?}", file_path))?;
-        
+        let source = read_source(file_path)?;
         self.parse_source(&source, file_path)
     }
 
     /// Parse Python source code and extract entities
     pub fn parse_source(&self, source: &str, file_path: &Path) -> Result<Vec<CodeEntity>> {
         let ast = ast::Suite::parse(source, "<embedded>")
-            .map_err(|e| anyhow::anyhow!("Parse error: {:?}", e))?;
+            .map_err(|e| Diagnostic::at_offset(file_path, source, usize::from(e.offset), e.error.to_string()))?;
+        log::trace!("Parsed {:?} ({} top-level statements)", file_path, ast.len());
         
-        let mut entities = Vec::new();
-        let mut visitor = EntityVisitor::new(file_path, source);
+        let mut entities = Vec::with_capacity(ast.len());
+        let visibility = ModuleVisibility::new(&self.visibility, file_path, source, dunder_all(&ast));
+        let mut visitor = EntityVisitor::new(file_path, source, visibility);
+        visitor.router_prefixes = endpoints::router_prefixes(&ast);
+        if testmap::may_hold_tests(file_path) {
+            visitor.test_imports = Some(testmap::imports(&ast));
+        }
+        
+        for stmt in &ast {
+            visitor.visit_stmt(stmt, &mut entities);
+        }
         
-        for stmt in ast {
-            visitor.visit_stmt(&stmt, &mut entities);
+        if is_migration(&ast, source) {
+            visitor.visit_migration(&ast, &mut entities);
+        }
+        if source.contains("include_router") || source.contains("register_blueprint") {
+            visitor.visit_mounts(&ast, &mut entities);
         }
         
         Ok(entities)
@@ -39,163 +73,578 @@ impl PythonParser {
 
 /// Visitor for extracting entities from AST
 struct EntityVisitor<'a> {
-    file_path: &'a Path,
+    file_path: SharedPath,
     source: &'a str,
-    class_context: Vec<String>,
+    /// Byte offset at which each line starts
+    line_starts: Vec<usize>,
+    /// Classes whose bodies are being visited, outermost first
+    classes: Vec<Enclosing>,
+    /// Names of the classes and functions enclosing the statements being
+    /// visited, outermost first
+    scope: Vec<String>,
+    /// Number of functions enclosing the statements being visited
+    function_depth: usize,
+    /// Whether the innermost enclosing scope is a class body
+    in_class_body: bool,
+    visibility: ModuleVisibility<'a>,
+    /// URL prefixes of the module's routers, by variable name
+    router_prefixes: HashMap<String, String>,
+    /// Names the module imports, in test files only
+    test_imports: Option<HashMap<String, String>>,
+    /// Reused by the expression printer, so decorators that are already
+    /// interned and signatures cost one allocation at most
+    buffer: String,
+}
+
+/// The parts of a `def` or `async def` the visitor reads
+struct FunctionDef<'a> {
+    name: &'a str,
+    args: &'a ast::Arguments,
+    body: &'a [ast::Stmt],
+    decorator_list: &'a [ast::Expr],
+    returns: Option<&'a ast::Expr>,
+    start: usize,
+    end: usize,
+    is_async: bool,
 }
 
 impl<'a> EntityVisitor<'a> {
-    fn new(file_path: &'a Path, source: &'a str) -> Self {
+    fn new(file_path: &Path, source: &'a str, visibility: ModuleVisibility<'a>) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
         EntityVisitor {
-            file_path,
+            file_path: SharedPath::new(file_path),
             source,
-            class_context: Vec::new(),
+            line_starts,
+            classes: Vec::new(),
+            scope: Vec::new(),
+            function_depth: 0,
+            in_class_body: false,
+            visibility,
+            router_prefixes: HashMap::new(),
+            test_imports: None,
+            buffer: String::with_capacity(256),
         }
     }
     
     /// Convert byte offset to line number
     fn offset_to_line(&self, offset: usize) -> usize {
-        self.source[..offset]
-            .chars()
-            .filter(|&c| c == '\n')
-            .count() + 1
+        line_at(&self.line_starts, offset)
     }
     
-    /// Extract function signature for regular functions
-    fn extract_function_signature(&self, func: &ast::StmtFunctionDef, is_async: bool) -> String {
-        let prefix = if is_async { "async def" } else { "def" };
-        let params = func.args.args.iter()
-            .map(|arg| {
-                let name = arg.def.arg.to_string();
-                if let Some(annotation) = &arg.def.annotation {
-                    format!("{}: {}", name, expr_to_string(annotation))
-                } else {
-                    name
-                }
-            })
-            .collect::<Vec<_>>()
-            .join(", ");
-            
-        let return_annotation = func.returns.as_ref()
-            .map(|r| format!(" -> {}", expr_to_string(r)))
-            .unwrap_or_default();
-            
-        format!("{} {}({}){}:", prefix, func.name, params, return_annotation)
+    fn new_entity(&self, entity_type: &str, name: &str, start: usize, end: usize) -> CodeEntity {
+        let line_number = self.offset_to_line(start);
+        let name = normalize_identifier(name).into_owned();
+        let mut entity = CodeEntity::new(entity_type, name, self.file_path.clone(), line_number);
+        entity.end_line = self.offset_to_line(end);
+        entity.start_offset = Some(start);
+        entity.end_offset = Some(end);
+        entity.qualified_name = match self.scope.is_empty() {
+            true => entity.name.clone(),
+            false => format!("{}.{}", self.scope.join("."), entity.name),
+        };
+        entity
     }
     
-    /// Extract function signature for async functions
-    fn extract_async_function_signature(&self, func: &ast::StmtAsyncFunctionDef) -> String {
-        let params = func.args.args.iter()
-            .map(|arg| {
-                let name = arg.def.arg.to_string();
-                if let Some(annotation) = &arg.def.annotation {
-                    format!("{}: {}", name, expr_to_string(annotation))
-                } else {
-                    name
-                }
-            })
-            .collect::<Vec<_>>()
-            .join(", ");
-            
-        let return_annotation = func.returns.as_ref()
-            .map(|r| format!(" -> {}", expr_to_string(r)))
-            .unwrap_or_default();
-            
-        format!("async def {}({}){}:", func.name, params, return_annotation)
+    /// Render `expr` with the shared buffer
+    fn render(&mut self, expr: &ast::Expr) -> &str {
+        self.buffer.clear();
+        write_expr(expr, &mut self.buffer);
+        &self.buffer
+    }
+    
+    fn decorators(&mut self, decorator_list: &[ast::Expr]) -> Vec<Symbol> {
+        let mut decorators = Vec::with_capacity(decorator_list.len());
+        for decorator in decorator_list {
+            self.buffer.clear();
+            write_decorator(decorator, &mut self.buffer);
+            decorators.push(Symbol::new(&self.buffer));
+        }
+        decorators
+    }
+    
+    /// Annotations of the parameters `extract_parameters` lists, in order
+    fn parameter_types(&mut self, args: &ast::Arguments) -> Vec<Option<String>> {
+        let mut annotation = |arg: &ast::Arg| arg.annotation.as_deref().map(|annotation| self.render(annotation).to_string());
+        let mut types: Vec<Option<String>> = args.posonlyargs.iter().chain(&args.args).map(|arg| annotation(&arg.def)).collect();
+        match &args.vararg {
+            Some(vararg) => types.push(annotation(vararg)),
+            // The bare `*` separator
+            None if !args.kwonlyargs.is_empty() => types.push(None),
+            None => {}
+        }
+        types.extend(args.kwonlyargs.iter().map(|arg| annotation(&arg.def)));
+        types.extend(args.kwarg.as_deref().map(&mut annotation));
+        types
+    }
+    
+    /// `def name(a, /, b: int = 1, *args, c, **kwargs) -> str:` with
+    /// annotations, defaults and the `/` and `*` markers
+    fn signature(&mut self, func: &FunctionDef) -> String {
+        let args = func.args;
+        let buffer = &mut self.buffer;
+        buffer.clear();
+        buffer.push_str(if func.is_async { "async def " } else { "def " });
+        buffer.push_str(func.name);
+        buffer.push('(');
+        for arg in &args.posonlyargs {
+            write_parameter("", &arg.def, arg.default.as_deref(), buffer);
+        }
+        if !args.posonlyargs.is_empty() {
+            write_marker("/", buffer);
+        }
+        for arg in &args.args {
+            write_parameter("", &arg.def, arg.default.as_deref(), buffer);
+        }
+        match &args.vararg {
+            Some(vararg) => write_parameter("*", vararg, None, buffer),
+            None if !args.kwonlyargs.is_empty() => write_marker("*", buffer),
+            None => {}
+        }
+        for arg in &args.kwonlyargs {
+            write_parameter("", &arg.def, arg.default.as_deref(), buffer);
+        }
+        if let Some(kwarg) = &args.kwarg {
+            write_parameter("**", kwarg, None, buffer);
+        }
+        buffer.push(')');
+        if let Some(returns) = func.returns {
+            buffer.push_str(" -> ");
+            write_expr(returns, buffer);
+        }
+        buffer.push(':');
+        buffer.clone()
     }
 
     fn visit_stmt(&mut self, stmt: &ast::Stmt, entities: &mut Vec<CodeEntity>) {
         use ast::Stmt;
         
         match stmt {
-            Stmt::FunctionDef(func) => self.visit_function(func, entities),
-            Stmt::AsyncFunctionDef(func) => self.visit_async_function(func, entities),
+            Stmt::FunctionDef(func) => self.visit_function(FunctionDef {
+                name: func.name.as_str(),
+                args: &func.args,
+                body: &func.body,
+                decorator_list: &func.decorator_list,
+                returns: func.returns.as_deref(),
+                start: func.range.start().to_usize(),
+                end: func.range.end().to_usize(),
+                is_async: false,
+            }, entities),
+            Stmt::AsyncFunctionDef(func) => self.visit_function(FunctionDef {
+                name: func.name.as_str(),
+                args: &func.args,
+                body: &func.body,
+                decorator_list: &func.decorator_list,
+                returns: func.returns.as_deref(),
+                start: func.range.start().to_usize(),
+                end: func.range.end().to_usize(),
+                is_async: true,
+            }, entities),
             Stmt::ClassDef(class) => self.visit_class(class, entities),
             _ => {}
         }
     }
 
-    fn visit_function(&mut self, func: &ast::StmtFunctionDef, entities: &mut Vec<CodeEntity>) {
-        let line_number = self.offset_to_line(func.range.start().to_usize());
-        let mut entity = CodeEntity::new(
-            if self.class_context.is_empty() { "function" } else { "method" }.to_string(),
-            func.name.to_string(),
-            self.file_path.to_path_buf(),
-            line_number,
-        );
+    fn visit_function(&mut self, func: FunctionDef, entities: &mut Vec<CodeEntity>) {
+        let entity_type = if self.in_class_body { "method" } else { "function" };
+        let mut entity = self.new_entity(entity_type, func.name, func.start, func.end);
+        entity.is_async = func.is_async;
 
         // Extract docstring
-        entity.docstring = extract_docstring(&func.body);
+        entity.docstring = extract_docstring(func.body);
         
         // Extract decorators with enhanced argument parsing
-        entity.decorators = func.decorator_list.iter()
-            .map(|d| extract_decorator_with_args(d))
-            .collect();
+        entity.decorators = self.decorators(func.decorator_list);
+        let marker = self.visibility.marker(&entity.decorators, entity.line_number);
+        entity.is_internal = self.is_internal(&entity.name, marker);
         
         // Extract parameters
-        entity.parameters = extract_parameters(&func.args);
+        entity.parameters = extract_parameters(func.args);
+        entity.parameter_types = Some(self.parameter_types(func.args));
         
         // Extract return type
-        entity.return_type = func.returns.as_ref().map(|r| expr_to_string(r));
+        entity.return_type = func.returns.map(|r| self.render(r).to_string());
         
         // Extract actual function signature
-        entity.code = self.extract_function_signature(func, false);
+        entity.code = self.signature(&func);
+        
+        if let Some(message) = deprecation_warning(func.body) {
+            entity.mark_deprecated(&message);
+        }
+        let is_test = entity.name.starts_with("test");
+        let facts = detect::scan(
+            func.body,
+            func.decorator_list,
+            &self.line_starts,
+            self.test_imports.as_ref().filter(|_| is_test),
+        );
+        entity.env_vars = facts.env_vars;
+        entity.log_events = facts.log_events;
+        entity.http_calls = facts.http_calls;
+        entity.makes_external_calls = !entity.http_calls.is_empty();
+        entity.security_findings = facts.security_findings;
+        entity.feature_flags = facts.feature_flags;
+        if let Some(tables) = facts.tables {
+            entity.touches_database = true;
+            entity.tables = tables;
+        }
+        entity.serializers = facts.serializers;
+        entity.exercises = facts.exercises;
         
         // Detect API endpoints
         entity.detect_api_endpoint();
+        if entity.is_api_endpoint {
+            entity.endpoint_params = endpoints::parameters(entity.endpoint_path.as_deref(), &entity.http_methods, func.args);
+            entity.response_model = endpoints::response_model(func.decorator_list);
+            entity.api_version = endpoints::api_version(func.decorator_list, entity.endpoint_path.as_deref(), &self.router_prefixes);
+            if entity.api_version.is_none() {
+                if let Some(router) = endpoints::router_of(func.decorator_list) {
+                    entity.metadata.insert("router".to_string(), router);
+                }
+            }
+        }
+        if let Some(auth) = Auth::of_function(func.decorator_list, func.args) {
+            auth.apply(&mut entity);
+        }
+        caching::annotate(func.decorator_list, &mut entity.metadata);
+        entity.test_cases = testmap::parametrize(func.decorator_list, self.source);
+        if let Some(imports) = self.test_imports.as_ref().filter(|_| is_test) {
+            entity.harvested_examples = testmap::examples(func.body, imports, self.source, &self.line_starts);
+        }
         
         // Calculate complexity
         entity.calculate_complexity();
         
+        self.scope.push(entity.name.clone());
         entities.push(entity);
+        
+        // Visit nested functions and classes
+        let in_class_body = std::mem::replace(&mut self.in_class_body, false);
+        self.function_depth += 1;
+        for stmt in func.body {
+            self.visit_stmt(stmt, entities);
+        }
+        self.function_depth -= 1;
+        self.in_class_body = in_class_body;
+        self.scope.pop();
     }
 
-    fn visit_async_function(&mut self, func: &ast::StmtAsyncFunctionDef, entities: &mut Vec<CodeEntity>) {
-        let line_number = self.offset_to_line(func.range.start().to_usize());
-        let mut entity = CodeEntity::new(
-            if self.class_context.is_empty() { "function" } else { "method" }.to_string(),
-            func.name.to_string(),
-            self.file_path.to_path_buf(),
-            line_number,
-        );
-
-        entity.is_async = true;
-        entity.docstring = extract_docstring(&func.body);
-        entity.decorators = func.decorator_list.iter()
-            .map(|d| extract_decorator_with_args(d))
-            .collect();
-        entity.parameters = extract_parameters(&func.args);
-        entity.return_type = func.returns.as_ref().map(|r| expr_to_string(r));
-        entity.code = self.extract_async_function_signature(func);
-        entity.detect_api_endpoint();
-        entity.calculate_complexity();
-        
-        entities.push(entity);
+    /// Definitions local to a function are never part of the API
+    fn is_internal(&self, name: &str, marker: Option<bool>) -> bool {
+        self.function_depth > 0 || self.visibility.is_internal(&self.classes, name, marker)
     }
 
     fn visit_class(&mut self, class: &ast::StmtClassDef, entities: &mut Vec<CodeEntity>) {
-        let line_number = self.offset_to_line(class.range.start().to_usize());
-        let mut entity = CodeEntity::new(
-            "class".to_string(),
-            class.name.to_string(),
-            self.file_path.to_path_buf(),
-            line_number,
+        let mut entity = self.new_entity(
+            "class",
+            class.name.as_str(),
+            class.range.start().to_usize(),
+            class.range.end().to_usize(),
         );
 
         entity.docstring = extract_docstring(&class.body);
-        entity.decorators = class.decorator_list.iter()
-            .map(|d| extract_decorator_with_args(d))
-            .collect();
+        entity.decorators = self.decorators(&class.decorator_list);
+        entity.bases = class.bases.iter().map(|base| self.render(base).to_string()).collect();
+        if let Some(metaclass) = class.keywords.iter().find(|k| k.arg.as_ref().map_or(false, |arg| arg.as_str() == "metaclass")) {
+            let metaclass = self.render(&metaclass.value).to_string();
+            entity.metadata.insert("metaclass".to_string(), metaclass);
+        }
+        entity.is_mixin = entity.name.ends_with("Mixin");
+        let marker = self.visibility.marker(&entity.decorators, entity.line_number);
+        entity.is_internal = self.is_internal(&entity.name, marker);
         
+        if entity.bases.iter().any(|base| last_segment(base) == "BaseSettings") {
+            entity.env_vars = env::settings_fields(&class.body, &self.line_starts);
+        }
+        
+        entity.serializers = endpoints::serializer_class(&class.body).into_iter().collect();
+        if let Some(auth) = Auth::of_class(&class.decorator_list, &entity.bases, &class.body) {
+            auth.apply(&mut entity);
+        }
+        
+        // ORM models name their table for cross-linking with schema entities
+        if let Some(table) = extract_table_name(&class.body) {
+            entity.metadata.insert("table".to_string(), normalize_table(&table));
+        }
+        
+        let enclosing = Enclosing {
+            name: entity.name.clone(),
+            marker,
+        };
+        let index = entities.len();
         entities.push(entity);
         
         // Visit methods within the class
-        self.class_context.push(class.name.to_string());
+        self.scope.push(enclosing.name.clone());
+        self.classes.push(enclosing);
+        let in_class_body = std::mem::replace(&mut self.in_class_body, true);
         for stmt in &class.body {
             self.visit_stmt(stmt, entities);
         }
-        self.class_context.pop();
+        self.in_class_body = in_class_body;
+        self.classes.pop();
+        self.scope.pop();
+
+        let prefix = format!("{}.", entities[index].qualified_name);
+        let members: Vec<&CodeEntity> = entities[index + 1..]
+            .iter()
+            .filter(|e| e.entity_type == "method")
+            .filter(|e| e.qualified_name.strip_prefix(&prefix).map_or(false, |rest| !rest.contains('.')))
+            .collect();
+        let has_member = |names: &[&str]| members.iter().any(|m| names.contains(&m.name.as_str()));
+
+        let defined = &entities[index];
+        let metaclass = defined.metadata.get("metaclass").map_or("", String::as_str);
+        let is_abstract = defined.bases.iter().any(|base| last_segment(base) == "ABC")
+            || last_segment(metaclass) == "ABCMeta"
+            || members.iter().flat_map(|m| &m.decorators).any(|d| last_segment(d).starts_with("abstract"));
+        let is_singleton = defined.decorators.iter().any(|d| last_segment(d) == "singleton")
+            || metaclass.contains("Singleton")
+            || (class_attributes(&class.body).any(|name| SINGLETON_ATTRIBUTES.contains(&name))
+                && has_member(&["__new__", "instance", "get_instance", "getInstance"]));
+
+        // Classes are called like their constructor, minus `self`/`cls`
+        let constructor = ["__init__", "__new__"]
+            .iter()
+            .find_map(|name| members.iter().find(|m| m.name == *name))
+            .map(|constructor| {
+                let skip = usize::from(matches!(constructor.parameters.first().map(String::as_str), Some("self" | "cls")));
+                let parameter_types = constructor.parameter_types.as_ref().map(|types| types[skip.min(types.len())..].to_vec());
+                (constructor.parameters[skip..].to_vec(), parameter_types)
+            });
+
+        let defined = &mut entities[index];
+        defined.is_abstract = is_abstract;
+        defined.is_singleton = is_singleton;
+        if let Some((parameters, parameter_types)) = constructor {
+            defined.parameters = parameters;
+            defined.parameter_types = parameter_types;
+            defined.has_custom_init = true;
+        }
+
+        // Methods of a protected view are protected too, unless they say otherwise
+        if let Some(auth) = Auth::of_entity(&entities[index]) {
+            for member in entities[index + 1..].iter_mut() {
+                let is_member = member.entity_type == "method"
+                    && member.qualified_name.strip_prefix(&prefix).map_or(false, |rest| !rest.contains('.'));
+                if is_member && Auth::of_entity(member).is_none() {
+                    auth.apply(member);
+                }
+            }
+        }
+    }
+}
+
+impl EntityVisitor<'_> {
+    /// Routers imported from other modules and mounted here, left as
+    /// `router` entities for `endpoints::link_routers`
+    fn visit_mounts(&self, suite: &[ast::Stmt], entities: &mut Vec<CodeEntity>) {
+        let imports = testmap::imports(suite);
+        for stmt in suite {
+            let Some((on, router, prefix)) = endpoints::mount(stmt) else { continue };
+            let (head, rest) = match router.split_once('.') {
+                Some((head, rest)) => (head, Some(rest)),
+                None => (router.as_str(), None),
+            };
+            let Some(imported) = imports.get(head) else { continue };
+            let target = match rest {
+                Some(rest) => format!("{}.{}", imported, rest),
+                None => imported.clone(),
+            };
+
+            let range = stmt.range();
+            let (start, end) = (range.start().to_usize(), range.end().to_usize());
+            let mut entity = self.new_entity(endpoints::ROUTER_ENTITY, &router, start, end);
+            entity.code = self.source[start..end].to_string();
+            entity.endpoint_path = Some(prefix);
+            entity.metadata.insert("mounts".to_string(), target);
+            entity.metadata.insert("mounted_on".to_string(), on);
+            entities.push(entity);
+        }
+    }
+    
+    /// Emit `schema` entities for tables created or altered in an Alembic `upgrade()`
+    fn visit_migration(&self, suite: &[ast::Stmt], entities: &mut Vec<CodeEntity>) {
+        let upgrade = suite.iter().find_map(|stmt| match stmt {
+            ast::Stmt::FunctionDef(func) if func.name.as_str() == "upgrade" => Some(&func.body),
+            _ => None,
+        });
+        
+        for stmt in upgrade.into_iter().flatten() {
+            match stmt {
+                ast::Stmt::Expr(expr) => self.visit_migration_call(&expr.value, None, entities),
+                // with op.batch_alter_table("users") as batch_op: batch_op.add_column(...)
+                ast::Stmt::With(with) => {
+                    let table = with.items.first().and_then(|item| match &item.context_expr {
+                        ast::Expr::Call(call) if expr_to_string(&call.func).ends_with("batch_alter_table") => {
+                            call.args.first().and_then(string_constant)
+                        }
+                        _ => None,
+                    });
+                    for inner in &with.body {
+                        if let (ast::Stmt::Expr(expr), Some(table)) = (inner, &table) {
+                            self.visit_migration_call(&expr.value, Some(table.as_str()), entities);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    
+    fn visit_migration_call(&self, expr: &ast::Expr, batch_table: Option<&str>, entities: &mut Vec<CodeEntity>) {
+        let ast::Expr::Call(call) = expr else { return };
+        let ast::Expr::Attribute(attr) = &*call.func else { return };
+        let operation = attr.attr.as_str();
+        if operation != "create_table" && operation != "add_column" {
+            return;
+        }
+        
+        // Batch operations take the table from the context manager
+        let (table, column_args) = match batch_table {
+            Some(table) => (table.to_string(), &call.args[..]),
+            None => match call.args.split_first() {
+                Some((first, rest)) => match string_constant(first) {
+                    Some(table) => (table, rest),
+                    None => return,
+                },
+                None => return,
+            },
+        };
+        
+        let columns: Vec<(String, String)> = column_args.iter().filter_map(extract_column).collect();
+        let line_number = self.offset_to_line(call.range.start().to_usize());
+        let code = format!("op.{}(\"{}\")", operation, table);
+        let mut entity = table_entity(&table, &columns, code, &self.file_path, line_number);
+        if operation == "add_column" {
+            entity.metadata.insert("operation".to_string(), "alter".to_string());
+        }
+        entities.push(entity);
+    }
+}
+
+/// Class attributes whose presence, with a `__new__` or accessor method,
+/// marks a singleton
+const SINGLETON_ATTRIBUTES: &[&str] = &["_instance", "__instance", "_instances"];
+
+/// Names assigned at the top level of a class body
+fn class_attributes(body: &[ast::Stmt]) -> impl Iterator<Item = &str> {
+    body.iter().flat_map(|stmt| {
+        let targets: Vec<&ast::Expr> = match stmt {
+            ast::Stmt::Assign(assign) => assign.targets.iter().collect(),
+            ast::Stmt::AnnAssign(assign) => vec![&*assign.target],
+            _ => Vec::new(),
+        };
+        targets.into_iter().filter_map(|target| match target {
+            ast::Expr::Name(name) => Some(name.id.as_str()),
+            _ => None,
+        })
+    })
+}
+
+/// `abc.abstractmethod` -> `abstractmethod`, ignoring any call arguments
+fn last_segment(name: &str) -> &str {
+    let name = name.split('(').next().unwrap_or(name);
+    name.rsplit('.').next().unwrap_or(name)
+}
+
+/// Alembic migrations define `upgrade()` next to a `revision` identifier or
+/// `op.` calls, whatever the file is called and however `op` is imported
+fn is_migration(suite: &[ast::Stmt], source: &str) -> bool {
+    let upgrade = suite
+        .iter()
+        .any(|stmt| matches!(stmt, ast::Stmt::FunctionDef(func) if func.name.as_str() == "upgrade"));
+    let is_revision = |target: &ast::Expr| matches!(target, ast::Expr::Name(name) if name.id.as_str() == "revision");
+    let revision = suite.iter().any(|stmt| match stmt {
+        ast::Stmt::Assign(assign) => assign.targets.iter().any(is_revision),
+        ast::Stmt::AnnAssign(assign) => is_revision(&assign.target),
+        _ => false,
+    });
+    upgrade && (revision || source.contains("op."))
+}
+
+/// `__tablename__ = "..."` (SQLAlchemy) or `class Meta: db_table = "..."` (Django)
+fn extract_table_name(body: &[ast::Stmt]) -> Option<String> {
+    body.iter().find_map(|stmt| match stmt {
+        ast::Stmt::Assign(assign) => {
+            let named = assign.targets.iter().any(|t| {
+                matches!(t, ast::Expr::Name(name) if name.id.as_str() == "__tablename__" || name.id.as_str() == "db_table")
+            });
+            if named { string_constant(&assign.value) } else { None }
+        }
+        ast::Stmt::ClassDef(meta) if meta.name.as_str() == "Meta" => extract_table_name(&meta.body),
+        _ => None,
+    })
+}
+
+/// `sa.Column("name", sa.String(50), ...)` as a column name and type
+fn extract_column(expr: &ast::Expr) -> Option<(String, String)> {
+    let ast::Expr::Call(call) = expr else { return None };
+    if !expr_to_string(&call.func).ends_with("Column") {
+        return None;
+    }
+    let name = string_constant(call.args.first()?)?;
+    let ty = call.args.get(1).map(|ty| {
+        let ty = match ty {
+            ast::Expr::Call(type_call) => &*type_call.func,
+            other => other,
+        };
+        let rendered = expr_to_string(ty);
+        rendered.rsplit('.').next().unwrap_or(&rendered).to_string()
+    });
+    Some((name, ty.unwrap_or_default()))
+}
+
+fn string_constant(expr: &ast::Expr) -> Option<String> {
+    match expr {
+        ast::Expr::Constant(constant) => match &constant.value {
+            ast::Constant::Str(s) => Some(s.to_string()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Names a module exports through `__all__`, or `None` when it never assigns it
+fn dunder_all(suite: &[ast::Stmt]) -> Option<HashSet<String>> {
+    fn is_all(expr: &ast::Expr) -> bool {
+        matches!(expr, ast::Expr::Name(name) if name.id.as_str() == "__all__")
+    }
+    fn names(expr: &ast::Expr) -> Vec<String> {
+        match expr {
+            ast::Expr::List(list) => list.elts.iter().filter_map(string_constant).collect(),
+            ast::Expr::Tuple(tuple) => tuple.elts.iter().filter_map(string_constant).collect(),
+            _ => string_constant(expr).into_iter().collect(),
+        }
     }
+
+    let mut exports: Option<HashSet<String>> = None;
+    for stmt in suite {
+        match stmt {
+            ast::Stmt::Assign(assign) if assign.targets.iter().any(is_all) => {
+                exports = Some(names(&assign.value).into_iter().collect());
+            }
+            ast::Stmt::AnnAssign(assign) if is_all(&assign.target) => {
+                if let Some(value) = &assign.value {
+                    exports = Some(names(value).into_iter().collect());
+                }
+            }
+            ast::Stmt::AugAssign(assign) if is_all(&assign.target) => {
+                exports.get_or_insert_with(HashSet::new).extend(names(&assign.value));
+            }
+            ast::Stmt::Expr(expr) => {
+                let ast::Expr::Call(call) = expr.value.as_ref() else { continue };
+                let ast::Expr::Attribute(method) = call.func.as_ref() else { continue };
+                if is_all(&method.value) && matches!(method.attr.as_str(), "append" | "extend") {
+                    let added = call.args.iter().flat_map(names);
+                    exports.get_or_insert_with(HashSet::new).extend(added);
+                }
+            }
+            _ => {}
+        }
+    }
+    exports.map(|names| names.into_iter().map(|name| normalize_identifier(&name).into_owned()).collect())
 }
 
 /// Extract docstring from function/class body
@@ -212,117 +661,218 @@ fn extract_docstring(body: &[ast::Stmt]) -> Option<String> {
     None
 }
 
+/// Docstring of a Python module, if the source parses and opens with one
+pub fn module_docstring(source: &str) -> Option<String> {
+    let suite = ast::Suite::parse(source, "<embedded>").ok()?;
+    extract_docstring(&suite)
+}
+
+/// Message of a `warnings.warn("...", DeprecationWarning)` call in a function
+/// body (empty when it isn't a plain string)
+fn deprecation_warning(body: &[ast::Stmt]) -> Option<String> {
+    body.iter().find_map(|stmt| match stmt {
+        ast::Stmt::Expr(expr) => {
+            let ast::Expr::Call(call) = &*expr.value else { return None };
+            if !expr_to_string(&call.func).ends_with("warn") {
+                return None;
+            }
+            let category = call.args.get(1).or_else(|| {
+                call.keywords.iter()
+                    .find(|k| k.arg.as_ref().map_or(false, |a| a.as_str() == "category"))
+                    .map(|k| &k.value)
+            })?;
+            let category = expr_to_string(category);
+            if !category.ends_with("DeprecationWarning") && !category.ends_with("FutureWarning") {
+                return None;
+            }
+            Some(call.args.first().and_then(string_constant).unwrap_or_default())
+        }
+        ast::Stmt::If(stmt) => deprecation_warning(&stmt.body).or_else(|| deprecation_warning(&stmt.orelse)),
+        ast::Stmt::With(stmt) => deprecation_warning(&stmt.body),
+        _ => None,
+    })
+}
+
 /// Extract parameter names from function arguments
 fn extract_parameters(args: &ast::Arguments) -> Vec<String> {
-    let mut params = Vec::new();
+    let mut params = Vec::with_capacity(args.posonlyargs.len() + args.args.len() + args.kwonlyargs.len() + 2);
     
-    // Regular args
-    for arg in &args.args {
-        params.push(arg.def.arg.to_string());
+    // Positional-only and regular args
+    for arg in args.posonlyargs.iter().chain(&args.args) {
+        params.push(normalize_identifier(arg.def.arg.as_str()).into_owned());
     }
     
-    // *args
+    // *args, or a bare `*` before keyword-only args
     if let Some(vararg) = &args.vararg {
-        params.push(format!("*{}", vararg.arg));
+        params.push(format!("*{}", normalize_identifier(vararg.arg.as_str())));
+    } else if !args.kwonlyargs.is_empty() {
+        params.push("*".to_string());
+    }
+    
+    // Keyword-only args
+    for arg in &args.kwonlyargs {
+        params.push(normalize_identifier(arg.def.arg.as_str()).into_owned());
     }
     
     // **kwargs
     if let Some(kwarg) = &args.kwarg {
-        params.push(format!("**{}", kwarg.arg));
+        params.push(format!("**{}", normalize_identifier(kwarg.arg.as_str())));
     }
     
     params
 }
 
-/// Extract decorator with full argument parsing
-fn extract_decorator_with_args(expr: &ast::Expr) -> String {
-    // Add a prefix to confirm this function is being called
-    let result = match expr {
-        ast::Expr::Call(call) => {
-            let func_name = expr_to_string(&call.func);
-            
-            // Extract positional arguments
-            let mut args = Vec::new();
-            for arg in &call.args {
-                args.push(extract_simple_value(arg));
-            }
-            
-            // Extract keyword arguments
-            for keyword in &call.keywords {
-                if let Some(arg_name) = &keyword.arg {
-                    args.push(format!("{}={}", arg_name, extract_simple_value(&keyword.value)));
-                }
-            }
-            
-            format!("{}({})", func_name, args.join(", "))
-        }
-        _ => expr_to_string(expr),
+/// Append `b: int = 1` to a signature's parameter list
+fn write_parameter(prefix: &str, arg: &ast::Arg, default: Option<&ast::Expr>, out: &mut String) {
+    write_marker(prefix, out);
+    out.push_str(arg.arg.as_str());
+    if let Some(annotation) = &arg.annotation {
+        out.push_str(": ");
+        write_expr(annotation, out);
+    }
+    if let Some(default) = default {
+        out.push_str(if arg.annotation.is_some() { " = " } else { "=" });
+        write_expr(default, out);
+    }
+}
+
+/// Append a `/` or `*` marker, or the prefix of a parameter, after a comma
+/// unless it opens the list
+fn write_marker(marker: &str, out: &mut String) {
+    if !out.ends_with('(') {
+        out.push_str(", ");
+    }
+    out.push_str(marker);
+}
+
+/// Render a decorator with its arguments, e.g. `app.route("/users", methods=["GET"])`
+fn write_decorator(expr: &ast::Expr, out: &mut String) {
+    let ast::Expr::Call(call) = expr else {
+        write_expr(expr, out);
+        return;
     };
-    
-    result
+    write_expr(&call.func, out);
+    out.push('(');
+    let keywords = call.keywords.iter().filter_map(|k| k.arg.as_ref().map(|arg| (Some(arg.as_str()), &k.value)));
+    let args = call.args.iter().map(|arg| (None, arg)).chain(keywords);
+    for (i, (name, value)) in args.enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        if let Some(name) = name {
+            out.push_str(name);
+            out.push('=');
+        }
+        write_simple_value(value, out);
+    }
+    out.push(')');
 }
 
-/// Extract simple values from expressions for decorator arguments
-fn extract_simple_value(expr: &ast::Expr) -> String {
+/// Render simple values from expressions for decorator arguments
+fn write_simple_value(expr: &ast::Expr, out: &mut String) {
     match expr {
-        ast::Expr::Constant(constant) => {
-            match &constant.value {
-                ast::Constant::Str(s) => format!("\"{}\"", s),
-                ast::Constant::Int(i) => i.to_string(),
-                ast::Constant::Float(f) => f.to_string(),
-                ast::Constant::Bool(b) => b.to_string(),
-                ast::Constant::None => "None".to_string(),
-                _ => "...".to_string(),
+        ast::Expr::Constant(constant) => write_constant(&constant.value, out),
+        ast::Expr::List(list) => {
+            out.push('[');
+            for (i, item) in list.elts.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_simple_value(item, out);
             }
+            out.push(']');
         }
-        ast::Expr::List(list) => {
-            let items = list.elts.iter()
-                .map(|e| extract_simple_value(e))
-                .collect::<Vec<_>>()
-                .join(", ");
-            format!("[{}]", items)
+        ast::Expr::Name(name) => out.push_str(name.id.as_str()),
+        _ => out.push_str("..."),
+    }
+}
+
+fn write_constant(constant: &ast::Constant, out: &mut String) {
+    match constant {
+        ast::Constant::Str(s) => {
+            out.push('"');
+            out.push_str(s);
+            out.push('"');
+        }
+        ast::Constant::Int(i) => {
+            let _ = write!(out, "{}", i);
         }
-        ast::Expr::Name(name) => name.id.to_string(),
-        _ => "...".to_string(),
+        ast::Constant::Float(f) => {
+            let _ = write!(out, "{}", f);
+        }
+        ast::Constant::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        ast::Constant::None => out.push_str("None"),
+        _ => out.push_str("..."),
     }
 }
 
 /// Convert expression to string representation
-fn expr_to_string(expr: &ast::Expr) -> String {
+pub(crate) fn expr_to_string(expr: &ast::Expr) -> String {
+    let mut out = String::new();
+    write_expr(expr, &mut out);
+    out
+}
+
+/// Append the string representation of `expr` to `out`
+fn write_expr(expr: &ast::Expr, out: &mut String) {
     match expr {
-        ast::Expr::Name(name) => name.id.to_string(),
+        ast::Expr::Name(name) => out.push_str(name.id.as_str()),
         ast::Expr::Attribute(attr) => {
-            format!("{}.{}", expr_to_string(&attr.value), attr.attr)
+            write_expr(&attr.value, out);
+            out.push('.');
+            out.push_str(attr.attr.as_str());
         }
         ast::Expr::Call(call) => {
-            let func_name = expr_to_string(&call.func);
+            write_expr(&call.func, out);
             
-            // If there are no arguments, just return function name
+            // Arguments are elided for now, but keep the structure for future enhancement
             if call.args.is_empty() && call.keywords.is_empty() {
-                format!("{}()", func_name)
+                out.push_str("()");
             } else {
-                // For now, just show (...) but keep the structure for future enhancement
-                format!("{}(...)", func_name)
+                out.push_str("(...)");
             }
         }
-        ast::Expr::Constant(constant) => {
-            match &constant.value {
-                ast::Constant::Str(s) => format!("\"{}\"", s),
-                ast::Constant::Int(i) => i.to_string(),
-                ast::Constant::Float(f) => f.to_string(),
-                ast::Constant::Bool(b) => b.to_string(),
-                ast::Constant::None => "None".to_string(),
-                _ => "...".to_string(),
+        ast::Expr::Constant(constant) => write_constant(&constant.value, out),
+        ast::Expr::List(list) => {
+            out.push('[');
+            for (i, item) in list.elts.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_expr(item, out);
             }
+            out.push(']');
         }
-        ast::Expr::List(list) => {
-            let items = list.elts.iter()
-                .map(|e| expr_to_string(e))
-                .collect::<Vec<_>>()
-                .join(", ");
-            format!("[{}]", items)
+        ast::Expr::Subscript(subscript) => {
+            write_expr(&subscript.value, out);
+            out.push('[');
+            match &*subscript.slice {
+                ast::Expr::Tuple(tuple) => write_items(&tuple.elts, out),
+                slice => write_expr(slice, out),
+            }
+            out.push(']');
         }
-        _ => "...".to_string(),
+        ast::Expr::Tuple(tuple) => {
+            out.push('(');
+            write_items(&tuple.elts, out);
+            out.push(')');
+        }
+        // `int | None` unions
+        ast::Expr::BinOp(op) if matches!(op.op, ast::Operator::BitOr) => {
+            write_expr(&op.left, out);
+            out.push_str(" | ");
+            write_expr(&op.right, out);
+        }
+        _ => out.push_str("..."),
+    }
+}
+
+fn write_items(items: &[ast::Expr], out: &mut String) {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        write_expr(item, out);
     }
 }
 
@@ -365,8 +915,146 @@ class MyClass:
         assert_eq!(entities.len(), 2);
         assert_eq!(entities[0].name, "MyClass");
         assert_eq!(entities[0].entity_type, "class");
+        assert_eq!(entities[0].end_line, 6);
         assert_eq!(entities[1].name, "method");
         assert_eq!(entities[1].entity_type, "method");
+        assert_eq!(entities[1].qualified_name, "MyClass.method");
+    }
+
+    #[test]
+    fn test_class_constructor_signature() {
+        let source = "class Client(Base):\n    class Options:\n        pass\n\n    def __init__(self, url: str, *, retries=3, **opts):\n        pass\n\nclass Plain:\n    pass\n";
+        let entities = PythonParser::new().parse_source(source, Path::new("client.py")).unwrap();
+
+        let client = &entities[0];
+        assert_eq!(client.bases, vec!["Base"]);
+        assert!(client.has_custom_init);
+        assert_eq!(client.parameters, vec!["url", "*", "retries", "**opts"]);
+        assert_eq!(client.parameter_types.as_ref().unwrap()[0].as_deref(), Some("str"));
+        assert_eq!(client.parameter_types.as_ref().unwrap().len(), client.parameters.len());
+        let plain = entities.iter().find(|e| e.name == "Plain").unwrap();
+        assert!(!plain.has_custom_init && plain.parameters.is_empty());
+        assert!(!entities[1].has_custom_init);
+    }
+
+    #[test]
+    fn test_positional_and_keyword_only_parameters() {
+        let source = "def fetch(url, /, timeout: float, *, retries: int = 3, **opts):\n    pass\n";
+        let entities = PythonParser::new().parse_source(source, Path::new("fetch.py")).unwrap();
+
+        assert_eq!(entities[0].parameters, vec!["url", "timeout", "*", "retries", "**opts"]);
+        let types = entities[0].parameter_types.as_ref().unwrap();
+        assert_eq!(types[1].as_deref(), Some("float"));
+        assert_eq!(types[2], None);
+        assert_eq!(types[3].as_deref(), Some("int"));
+    }
+
+    #[test]
+    fn test_class_flags() {
+        let source = "class Shape(abc.ABC):\n    pass\n\nclass Plugin:\n    @abstractmethod\n    def run(self):\n        pass\n\nclass JsonMixin:\n    pass\n\nclass Config:\n    _instance = None\n\n    def __new__(cls):\n        pass\n\nclass Registry(metaclass=SingletonMeta):\n    pass\n";
+        let entities = PythonParser::new().parse_source(source, Path::new("flags.py")).unwrap();
+        let class = |name: &str| entities.iter().find(|e| e.name == name).unwrap();
+
+        assert!(class("Shape").is_abstract && class("Plugin").is_abstract);
+        assert!(class("JsonMixin").is_mixin && !class("JsonMixin").is_abstract);
+        assert!(class("Config").is_singleton && class("Registry").is_singleton);
+        assert_eq!(class("Registry").metadata.get("metaclass").map(String::as_str), Some("SingletonMeta"));
+        assert!(!class("Shape").is_singleton);
+    }
+
+    #[test]
+    fn test_alembic_migration_and_model_table() {
+        let source = r#"
+from alembic import op
+import sqlalchemy as sa
+
+def upgrade():
+    op.create_table(
+        "users",
+        sa.Column("id", sa.Integer(), primary_key=True),
+        sa.Column("email", sa.String(255)),
+    )
+    with op.batch_alter_table("users") as batch_op:
+        batch_op.add_column(sa.Column("created_at", sa.DateTime()))
+
+class User(Base):
+    __tablename__ = "users"
+"#;
+        
+        let parser = PythonParser::new();
+        let entities = parser.parse_source(source, Path::new("versions/001_users.py")).unwrap();
+        let schemas: Vec<&CodeEntity> = entities.iter().filter(|e| e.entity_type == "schema").collect();
+        
+        assert_eq!(schemas.len(), 2);
+        assert_eq!(schemas[0].name, "users");
+        assert_eq!(schemas[0].line_number, 6);
+        assert_eq!(schemas[0].metadata["columns"], "id Integer, email String");
+        assert_eq!(schemas[1].parameters, vec!["created_at"]);
+        
+        let model = entities.iter().find(|e| e.name == "User").unwrap();
+        assert_eq!(model.metadata["table"], "users");
+
+        // Found by their markers, not by mentioning alembic
+        let wrapped = source.replace("from alembic import op", "from db.migrations import op");
+        let entities = parser.parse_source(&wrapped, Path::new("db/0001.py")).unwrap();
+        assert_eq!(entities.iter().filter(|e| e.entity_type == "schema").count(), 2);
+
+        let unrelated = "def upgrade():\n    store.create_table(\"cache\")\n";
+        let entities = parser.parse_source(unrelated, Path::new("alembic_helpers.py")).unwrap();
+        assert!(entities.iter().all(|e| e.entity_type != "schema"));
+    }
+
+    #[test]
+    fn test_is_internal_heuristics_and_markers() {
+        let source = r#"
+__all__ = ["Client"]
+__all__ += ("_exported",)
+
+class Client:
+    def get(self):
+        pass
+
+    def _retry(self):
+        pass
+
+def _exported():
+    pass
+
+def helper():
+    pass
+"#;
+
+        let entities = PythonParser::new().parse_source(source, Path::new("api.py")).unwrap();
+        let internal: Vec<(&str, bool)> = entities.iter().map(|e| (e.name.as_str(), e.is_internal)).collect();
+        assert_eq!(
+            internal,
+            vec![("Client", false), ("get", false), ("_retry", true), ("_exported", false), ("helper", true)]
+        );
+
+        let entities = PythonParser::new().parse_source("def run():\n    pass\n", Path::new("_impl.py")).unwrap();
+        assert!(entities[0].is_internal);
+
+        let source = "@public\ndef _exported_too():\n    pass\n\n#: private-api\nclass Client:\n    def get(self):\n        pass\n";
+        let entities = PythonParser::new().parse_source(source, Path::new("api.py")).unwrap();
+        let internal: Vec<bool> = entities.iter().map(|e| e.is_internal).collect();
+        assert_eq!(internal, vec![false, true, true]);
+    }
+
+    #[test]
+    fn test_deprecation_warning() {
+        let source = r#"
+import warnings
+
+def old_fetch(url):
+    warnings.warn("old_fetch() is deprecated, use fetch() instead", DeprecationWarning, stacklevel=2)
+    return fetch(url)
+"#;
+        
+        let parser = PythonParser::new();
+        let entities = parser.parse_source(source, Path::new("test.py")).unwrap();
+        
+        assert!(entities[0].is_deprecated);
+        assert_eq!(entities[0].replacement.as_deref(), Some("fetch"));
     }
 
     #[test]
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use crate::entity::CodeEntity;
+use crate::intern::SharedPath;
+
+/// Lexically normalize `path`: drop `.` components, fold `..` into the
+/// preceding component where there is one, join with `/` on every platform
+/// and upper-case Windows drive letters. The file system is not consulted,
+/// so symlinks are left alone.
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut prefix = String::new();
+    let mut absolute = false;
+    let mut parts: Vec<String> = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Prefix(p) => prefix = normalize_prefix(&p.as_os_str().to_string_lossy()),
+            Component::RootDir => absolute = true,
+            Component::CurDir => {}
+            Component::ParentDir => match parts.last() {
+                Some(last) if last != ".." => {
+                    parts.pop();
+                }
+                _ if absolute => {}
+                _ => parts.push("..".to_string()),
+            },
+            Component::Normal(part) => parts.push(part.to_string_lossy().into_owned()),
+        }
+    }
+
+    let mut normalized = prefix;
+    if absolute {
+        normalized.push('/');
+    }
+    normalized.push_str(&parts.join("/"));
+    if normalized.is_empty() {
+        normalized.push('.');
+    }
+    PathBuf::from(normalized)
+}
+
+/// `path` made absolute against the working directory, then normalized
+pub fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        return normalize(path);
+    }
+    match std::env::current_dir() {
+        Ok(cwd) => normalize(&cwd.join(path)),
+        Err(_) => normalize(path),
+    }
+}
+
+/// `path` relative to `root` with `/` separators, or just normalized when
+/// it lies outside `root`
+pub fn relative_to(path: &Path, root: &Path) -> PathBuf {
+    let (path, root) = (absolute(path), absolute(root));
+    match path.strip_prefix(&root) {
+        Ok(relative) if relative.as_os_str().is_empty() => {
+            path.file_name().map(PathBuf::from).unwrap_or_else(|| path.clone())
+        }
+        Ok(relative) => normalize(relative),
+        Err(_) => path,
+    }
+}
+
+/// Rewrite entity paths for display: relative to `root` when given,
+/// otherwise normalized, with the absolute path kept in `absolute_path`
+pub fn normalize_entity_paths(entities: &mut [CodeEntity], root: Option<&Path>) {
+    let mut resolved: HashMap<SharedPath, (SharedPath, SharedPath)> = HashMap::new();
+    for entity in entities {
+        let (display, full) = resolved
+            .entry(entity.file_path.clone())
+            .or_insert_with(|| {
+                let display = match root {
+                    Some(root) => relative_to(&entity.file_path, root),
+                    None => normalize(&entity.file_path),
+                };
+                (display.into(), absolute(&entity.file_path).into())
+            })
+            .clone();
+        entity.file_path = display;
+        entity.absolute_path = Some(full);
+    }
+}
+
+/// `c:` → `C:`; other prefixes (UNC shares, verbatim paths) keep their form
+/// apart from separators
+fn normalize_prefix(prefix: &str) -> String {
+    let prefix = prefix.replace('\\', "/");
+    match prefix.as_bytes() {
+        [drive, b':'] if drive.is_ascii_alphabetic() => prefix.to_ascii_uppercase(),
+        _ => prefix,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_and_relativize() {
+        assert_eq!(normalize(Path::new("./src/../pkg/./mod.py")), PathBuf::from("pkg/mod.py"));
+        assert_eq!(normalize(Path::new("../shared/mod.py")), PathBuf::from("../shared/mod.py"));
+        assert_eq!(normalize(Path::new("/repo/a/../b.py")), PathBuf::from("/repo/b.py"));
+        assert_eq!(normalize_prefix("c:"), "C:");
+
+        assert_eq!(relative_to(Path::new("/repo/src/app.py"), Path::new("/repo/./")), PathBuf::from("src/app.py"));
+        assert_eq!(relative_to(Path::new("/elsewhere/x.py"), Path::new("/repo")), PathBuf::from("/elsewhere/x.py"));
+    }
+}
@@ -0,0 +1,295 @@
+use std::fmt::Write;
+
+use crate::chunking::{ApproximateTokenizer, Tokenizer};
+use crate::entity::CodeEntity;
+
+/// Renders an entity into a compact, deterministic text block for LLM
+/// prompts.
+///
+/// The block lists the location, signature, parameters, return type,
+/// decorators and flags, the docstring (or an explicit `missing` marker),
+/// doctest examples, and neighbouring definitions from the same scope. When
+/// a token budget is set and exceeded, content is shed in a fixed order:
+/// neighbours (farthest first), examples, the docstring beyond its first
+/// paragraph, and finally trailing lines.
+pub struct PromptBuilder {
+    budget: Option<usize>,
+    max_neighbors: usize,
+    examples: bool,
+    tokenizer: Box<dyn Tokenizer>,
+}
+
+impl Default for PromptBuilder {
+    fn default() -> Self {
+        PromptBuilder {
+            budget: None,
+            max_neighbors: 5,
+            examples: true,
+            tokenizer: Box::new(ApproximateTokenizer),
+        }
+    }
+}
+
+impl PromptBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep the rendered block within `tokens`
+    pub fn with_budget(mut self, tokens: usize) -> Self {
+        self.budget = Some(tokens);
+        self
+    }
+
+    /// List at most `count` neighbouring definitions
+    pub fn with_max_neighbors(mut self, count: usize) -> Self {
+        self.max_neighbors = count;
+        self
+    }
+
+    /// Whether to include doctest examples from the docstring
+    pub fn with_examples(mut self, include: bool) -> Self {
+        self.examples = include;
+        self
+    }
+
+    /// Tokenizer used to enforce the budget
+    pub fn with_tokenizer<T: Tokenizer + 'static>(mut self, tokenizer: T) -> Self {
+        self.tokenizer = Box::new(tokenizer);
+        self
+    }
+
+    /// Same as `with_tokenizer`, for an already boxed tokenizer
+    pub fn with_boxed_tokenizer(mut self, tokenizer: Box<dyn Tokenizer>) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// Render `entities[index]`, using the rest of `entities` for neighbours
+    pub fn render(&self, entities: &[CodeEntity], index: usize) -> String {
+        let Some(entity) = entities.get(index) else { return String::new() };
+        let docstring = entity.docstring.as_deref().map(str::trim).filter(|d| !d.is_empty());
+        let (docstring, examples) = match docstring {
+            Some(doc) if self.examples => split_examples(doc),
+            Some(doc) => (doc.to_string(), Vec::new()),
+            None => (String::new(), Vec::new()),
+        };
+        let mut parts = Parts {
+            header: header(entity),
+            docstring: Some(docstring).filter(|d| !d.is_empty()),
+            examples,
+            neighbors: neighbors(entities, index).into_iter().take(self.max_neighbors).collect(),
+        };
+
+        let Some(budget) = self.budget else { return parts.render() };
+        let fits = |parts: &Parts| self.tokenizer.count_tokens(&parts.render()) <= budget;
+        while !fits(&parts) && !parts.neighbors.is_empty() {
+            parts.neighbors.pop();
+        }
+        if !fits(&parts) {
+            parts.examples.clear();
+        }
+        if !fits(&parts) {
+            if let Some(doc) = &parts.docstring {
+                let first = doc.split("\n\n").next().unwrap_or(doc);
+                if first.len() < doc.len() {
+                    parts.docstring = Some(format!("{} [...]", first));
+                }
+            }
+        }
+
+        let mut text = parts.render();
+        while self.tokenizer.count_tokens(&text) > budget {
+            let mut lines: Vec<&str> = text.lines().collect();
+            if lines.len() <= 1 {
+                break;
+            }
+            lines.pop();
+            text = lines.join("\n") + "\n";
+        }
+        text
+    }
+}
+
+struct Parts {
+    header: String,
+    docstring: Option<String>,
+    examples: Vec<String>,
+    neighbors: Vec<String>,
+}
+
+impl Parts {
+    fn render(&self) -> String {
+        let mut out = self.header.clone();
+        match &self.docstring {
+            Some(doc) => {
+                out.push_str("docstring:\n");
+                for line in doc.lines() {
+                    match line.trim_end() {
+                        "" => out.push('\n'),
+                        line => {
+                            let _ = writeln!(out, "  {}", line);
+                        }
+                    }
+                }
+            }
+            None => out.push_str("docstring: missing\n"),
+        }
+        if !self.examples.is_empty() {
+            out.push_str("examples:\n");
+            for example in &self.examples {
+                let _ = writeln!(out, "  {}", example);
+            }
+        }
+        if !self.neighbors.is_empty() {
+            out.push_str("neighbors:\n");
+            for neighbor in &self.neighbors {
+                let _ = writeln!(out, "  - {}", neighbor);
+            }
+        }
+        out
+    }
+}
+
+fn header(entity: &CodeEntity) -> String {
+    let mut out = format!(
+        "{} `{}` ({}:{})\n",
+        entity.entity_type,
+        entity.name,
+        entity.file_path.to_string_lossy().replace('\\', "/"),
+        entity.line_number
+    );
+    if let Some(signature) = entity.code_text().lines().next().filter(|s| !s.trim().is_empty()) {
+        let _ = writeln!(out, "signature: {}", signature.trim());
+    }
+    if !entity.parameters.is_empty() {
+        let _ = writeln!(out, "parameters: {}", entity.parameters.join(", "));
+    }
+    if let Some(returns) = &entity.return_type {
+        let _ = writeln!(out, "returns: {}", returns);
+    }
+    if !entity.decorators.is_empty() {
+        let decorators: Vec<String> = entity.decorators.iter().map(|d| format!("@{}", d)).collect();
+        let _ = writeln!(out, "decorators: {}", decorators.join(" "));
+    }
+
+    let mut flags = vec![if entity.is_public() { "public".to_string() } else { "private".to_string() }];
+    if entity.is_async {
+        flags.push("async".to_string());
+    }
+    if entity.is_api_endpoint {
+        let endpoint = format!(
+            "endpoint {} {}",
+            entity.http_methods.join("|"),
+            entity.endpoint_path.as_deref().unwrap_or("")
+        );
+        flags.push(endpoint.trim_end().to_string());
+    }
+    if entity.is_deprecated {
+        flags.push(match &entity.replacement {
+            Some(replacement) => format!("deprecated (use {})", replacement),
+            None => "deprecated".to_string(),
+        });
+    }
+    let _ = writeln!(out, "flags: {}", flags.join(", "));
+    out
+}
+
+/// Separate doctest lines (`>>>` prompts and their output) from the prose
+/// of a docstring
+fn split_examples(doc: &str) -> (String, Vec<String>) {
+    let mut prose: Vec<&str> = Vec::new();
+    let mut examples = Vec::new();
+    let mut in_example = false;
+    for line in doc.lines() {
+        let trimmed = line.trim();
+        in_example = trimmed.starts_with(">>>") || (in_example && !trimmed.is_empty());
+        if in_example {
+            examples.push(trimmed.to_string());
+        } else {
+            prose.push(line);
+        }
+    }
+    (prose.join("\n").trim().to_string(), examples)
+}
+
+/// Other definitions in the same scope (same file, same `parent_id`),
+/// nearest first, as `type name: summary`
+fn neighbors(entities: &[CodeEntity], index: usize) -> Vec<String> {
+    let entity = &entities[index];
+    let mut nearby: Vec<&CodeEntity> = entities
+        .iter()
+        .enumerate()
+        .filter(|&(i, e)| i != index && e.file_path == entity.file_path && e.parent_id == entity.parent_id)
+        .map(|(_, e)| e)
+        .collect();
+    nearby.sort_by_key(|e| (e.line_number.abs_diff(entity.line_number), e.line_number));
+
+    nearby
+        .into_iter()
+        .map(|e| {
+            let summary = e
+                .docstring
+                .as_deref()
+                .and_then(|d| d.lines().map(str::trim).find(|l| !l.is_empty()))
+                .unwrap_or("(undocumented)");
+            format!("{} `{}`: {}", e.entity_type, e.name, summary)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATH: &str = "pkg/api.py";
+
+    fn entities() -> Vec<CodeEntity> {
+        let mut get = CodeEntity::stub("method", "get", PATH)
+            .lines(3, 9)
+            .doc("Fetch a resource.\n\nRetries on failure.\n\n>>> Client().get('/')\n<Response 200>");
+        get.code = "def get(self, path: str) -> Response:".to_string();
+        get.parameters = vec!["self".to_string(), "path: str".to_string()];
+        get.return_type = Some("Response".to_string());
+        let mut entities = vec![
+            CodeEntity::stub("class", "Client", PATH).lines(1, 20).doc("HTTP client."),
+            get,
+            CodeEntity::stub("method", "post", PATH).lines(11, 14),
+            CodeEntity::stub("function", "main", PATH).lines(22, 24),
+        ];
+        crate::identity::assign_ids(&mut entities, None);
+        crate::hierarchy::link(&mut entities);
+        entities
+    }
+
+    #[test]
+    fn test_prompt_block() {
+        let prompt = PromptBuilder::new().render(&entities(), 1);
+        assert_eq!(
+            prompt,
+            "method `get` (pkg/api.py:3)\n\
+             signature: def get(self, path: str) -> Response:\n\
+             parameters: self, path: str\n\
+             returns: Response\n\
+             flags: public\n\
+             docstring:\n  Fetch a resource.\n\n  Retries on failure.\n\
+             examples:\n  >>> Client().get('/')\n  <Response 200>\n\
+             neighbors:\n  - method `post`: (undocumented)\n"
+        );
+    }
+
+    #[test]
+    fn test_budget_sheds_in_order() {
+        let entities = entities();
+        let full = PromptBuilder::new().render(&entities, 1);
+        let tokens = ApproximateTokenizer.count_tokens(&full);
+
+        let tight = PromptBuilder::new().with_budget(tokens - 1).render(&entities, 1);
+        assert!(!tight.contains("neighbors:"));
+        assert!(tight.contains("examples:"));
+
+        let tiny = PromptBuilder::new().with_budget(30).render(&entities, 1);
+        assert!(ApproximateTokenizer.count_tokens(&tiny) <= 30);
+        assert!(tiny.starts_with("method `get`"));
+    }
+}
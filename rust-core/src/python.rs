@@ -0,0 +1,2910 @@
+// Python bindings for the Rust core
+
+use pyo3::prelude::*;
+use pyo3::exceptions::{PyException, PyIndexError, PyTypeError, PyValueError};
+use pyo3::types::{PyBytes, PySlice};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use rayon::ThreadPool;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
+
+use crate::analyzer::{self, RustAnalyzer};
+use crate::changelog;
+use crate::chunking::{self, Chunk, ChunkOptions, Tokenizer};
+use crate::config::{AnalyzerConfig, QueryLanguage};
+use crate::context::{self, ContextBundle};
+use crate::diagnostic::Diagnostic;
+use crate::diff::{self, EntityChange};
+use crate::duplicates::{self, DuplicateGroup, DuplicateOptions};
+use crate::endpoints::{self, EndpointParam, ParamLocation};
+use crate::entity::{self, CodeEntity};
+use crate::env::{self, EnvVar, EnvVarSummary};
+use crate::export;
+use crate::grep::{self, GrepField, GrepMatch};
+use crate::hierarchy;
+use crate::ident::normalize_identifier;
+use crate::intern::{StringInterner, Symbol};
+use crate::inventory::{self, InventoryOptions};
+use crate::lint::{self, Finding, LintReport, Severity};
+use crate::logs::LogEvent;
+use crate::lazy;
+use crate::licenses::{self, MissingHeader};
+use crate::memory::{self, MemoryUsage};
+use crate::metrics;
+use crate::outbound::HttpCall;
+use crate::prompt::PromptBuilder;
+use crate::query::Query;
+use crate::search::SearchIndex;
+use crate::security::{SecurityFinding, SecurityRule};
+use crate::semver;
+use crate::site;
+use crate::snippet::{self, Snippet, SnippetOptions, SnippetRange};
+use crate::sphinx::{self, SphinxObject};
+use crate::stats::{FileTiming, RunStats};
+use crate::testmap::{self, TestCase, TestMapping, UsageExample};
+use crate::tree::{self, TreeNode};
+
+// Create a custom Python exception for Rust errors
+pyo3::create_exception!(autodoc_core, RustAnalysisError, PyException);
+// Raised for files that fail to parse; carries SyntaxError-style attributes
+pyo3::create_exception!(autodoc_core, RustSyntaxError, RustAnalysisError);
+
+/// `RustSyntaxError` with `filename`, `lineno`, `offset`, `text`, `msg` and
+/// `diagnostic` set when `error` is a syntax error, else `RustAnalysisError`
+fn analysis_error(error: anyhow::Error) -> PyErr {
+    let Some(diagnostic) = error.downcast_ref::<Diagnostic>() else {
+        return RustAnalysisError::new_err(error.to_string());
+    };
+    let err = RustSyntaxError::new_err(diagnostic.to_string());
+    Python::with_gil(|py| {
+        let value = err.value_bound(py);
+        let attributes = [
+            ("filename", diagnostic.file.to_string_lossy().into_py(py)),
+            ("lineno", diagnostic.line.into_py(py)),
+            ("offset", diagnostic.column.into_py(py)),
+            ("text", diagnostic.source_line.clone().into_py(py)),
+            ("msg", diagnostic.message.clone().into_py(py)),
+            ("diagnostic", PyDiagnostic::from(diagnostic).into_py(py)),
+        ];
+        for (name, attribute) in attributes {
+            if let Err(e) = value.setattr(name, attribute) {
+                return e;
+            }
+        }
+        err.clone_ref(py)
+    })
+}
+
+/// Main entry point for Python bindings
+#[pymodule]
+fn autodoc_core(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    // Route `log` records from the analyzer into Python's logging module
+    pyo3_log::init();
+    
+    m.add_class::<PyCodeEntity>()?;
+    m.add_class::<PyRustAnalyzer>()?;
+    m.add_class::<PyAnalyzerConfig>()?;
+    m.add_class::<PyQueryLanguage>()?;
+    m.add_class::<PyEntityCollection>()?;
+    m.add_class::<EntityIterator>()?;
+    m.add_class::<AnalysisJob>()?;
+    m.add_class::<PyEntityChange>()?;
+    m.add_class::<PyGrepMatch>()?;
+    m.add_class::<PyTreeNode>()?;
+    m.add_class::<PyChunk>()?;
+    m.add_class::<PyContextBundle>()?;
+    m.add_class::<PyContextItem>()?;
+    m.add_class::<PySnippet>()?;
+    m.add_class::<PyDuplicateGroup>()?;
+    m.add_class::<PyRunStats>()?;
+    m.add_class::<PyFileTiming>()?;
+    m.add_class::<PyMemoryUsage>()?;
+    m.add_class::<PyDiagnostic>()?;
+    m.add_class::<PyLintFinding>()?;
+    m.add_class::<PyLintReport>()?;
+    m.add_class::<PySphinxObject>()?;
+    m.add_class::<PyEnvVar>()?;
+    m.add_class::<PyEnvVarSummary>()?;
+    m.add_class::<PyLogEvent>()?;
+    m.add_class::<PyHttpCall>()?;
+    m.add_class::<PySecurityFinding>()?;
+    m.add_class::<PyMissingHeader>()?;
+    m.add_class::<PyTestMapping>()?;
+    m.add_class::<PyEndpointParam>()?;
+    m.add_class::<PyTestCase>()?;
+    m.add_class::<PyUsageExample>()?;
+    m.add_function(wrap_pyfunction!(analyze_source_rust, m)?)?;
+    m.add_function(wrap_pyfunction!(diff_entities, m)?)?;
+    m.add_function(wrap_pyfunction!(recommend_version_bump, m)?)?;
+    m.add_function(wrap_pyfunction!(render_changelog, m)?)?;
+    m.add_function(wrap_pyfunction!(count_tokens, m)?)?;
+    m.add_function(wrap_pyfunction!(chunk_source, m)?)?;
+    m.add_function(wrap_pyfunction!(read_snippet, m)?)?;
+    m.add("RustAnalysisError", m.py().get_type_bound::<RustAnalysisError>())?;
+    m.add("RustSyntaxError", m.py().get_type_bound::<RustSyntaxError>())?;
+    Ok(())
+}
+
+/// Python-compatible wrapper for CodeEntity
+///
+/// Frequently repeated strings (entity type, file path, decorators) are held
+/// as shared `Arc<str>` handles so large result sets don't duplicate them.
+#[pyclass(name = "CodeEntity")]
+#[derive(Clone)]
+pub struct PyCodeEntity {
+    #[pyo3(get, set)]
+    pub entity_id: String,
+    #[pyo3(get, set)]
+    pub content_hash: String,
+    /// Hash of the definition's normalized source, body included
+    #[pyo3(get, set)]
+    pub source_hash: String,
+    pub entity_type: Arc<str>,
+    #[pyo3(get, set)]
+    pub name: String,
+    /// Dotted path from the module down, e.g. "pkg.api.Client.get"
+    #[pyo3(get, set)]
+    pub qualified_name: String,
+    /// Dotted import path of the defining module, e.g. "pkg.api"
+    #[pyo3(get, set)]
+    pub module_path: String,
+    pub file_path: Arc<str>,
+    pub absolute_path: Option<Arc<str>>,
+    #[pyo3(get, set)]
+    pub line_number: usize,
+    /// Last line of the definition; 0 when unknown
+    #[pyo3(get, set)]
+    pub end_line: usize,
+    /// UTF-8 byte offset of the definition in the source, None when unknown
+    #[pyo3(get, set)]
+    pub start_offset: Option<usize>,
+    #[pyo3(get, set)]
+    pub end_offset: Option<usize>,
+    /// 1-based character column where the definition starts; 0 when unknown
+    #[pyo3(get, set)]
+    pub column: usize,
+    #[pyo3(get, set)]
+    pub end_column: usize,
+    /// entity_id of the enclosing class or function, None at module level
+    #[pyo3(get, set)]
+    pub parent_id: Option<String>,
+    #[pyo3(get, set)]
+    pub children: Vec<String>,
+    #[pyo3(get, set)]
+    pub bases: Vec<String>,
+    #[pyo3(get, set)]
+    pub overrides: Option<String>,
+    #[pyo3(get, set)]
+    pub override_change: Option<String>,
+    #[pyo3(get, set)]
+    pub docstring: Option<String>,
+    pub code: String,
+    /// Byte range of `code` in the file while it is deferred
+    pub code_span: Option<lazy::CodeSpan>,
+    #[pyo3(get, set)]
+    pub is_async: bool,
+    pub decorators: Vec<Arc<str>>,
+    #[pyo3(get, set)]
+    pub parameters: Vec<String>,
+    /// Annotation per parameter, or None when the language doesn't track them
+    #[pyo3(get, set)]
+    pub parameter_types: Option<Vec<Option<String>>>,
+    #[pyo3(get, set)]
+    pub return_type: Option<String>,
+    #[pyo3(get, set)]
+    pub is_internal: bool,
+    #[pyo3(get, set)]
+    pub is_api_endpoint: bool,
+    #[pyo3(get, set)]
+    pub route_path: Option<String>,
+    #[pyo3(get, set)]
+    pub http_methods: Vec<String>,
+    #[pyo3(get, set)]
+    pub endpoint_params: Vec<PyEndpointParam>,
+    #[pyo3(get, set)]
+    pub api_version: Option<String>,
+    #[pyo3(get, set)]
+    pub response_model: Option<String>,
+    #[pyo3(get, set)]
+    pub serializers: Vec<String>,
+    #[pyo3(get, set)]
+    pub request_models: Vec<String>,
+    #[pyo3(get, set)]
+    pub response_models: Vec<String>,
+    #[pyo3(get, set)]
+    pub used_by_endpoints: Vec<String>,
+    #[pyo3(get, set)]
+    pub auth_required: bool,
+    #[pyo3(get, set)]
+    pub auth_scheme: Option<String>,
+    #[pyo3(get, set)]
+    pub permissions: Vec<String>,
+    #[pyo3(get, set)]
+    pub exercises: Vec<String>,
+    #[pyo3(get, set)]
+    pub tested_by: Vec<String>,
+    #[pyo3(get, set)]
+    pub test_cases: Vec<PyTestCase>,
+    #[pyo3(get, set)]
+    pub harvested_examples: Vec<PyUsageExample>,
+    #[pyo3(get, set)]
+    pub examples: Vec<PyUsageExample>,
+    #[pyo3(get, set)]
+    pub complexity_score: u32,
+    #[pyo3(get, set)]
+    pub is_deprecated: bool,
+    #[pyo3(get, set)]
+    pub replacement: Option<String>,
+    #[pyo3(get, set)]
+    pub is_generated: bool,
+    #[pyo3(get, set)]
+    pub has_custom_init: bool,
+    #[pyo3(get, set)]
+    pub is_exception: bool,
+    #[pyo3(get, set)]
+    pub is_abstract: bool,
+    #[pyo3(get, set)]
+    pub is_mixin: bool,
+    #[pyo3(get, set)]
+    pub is_singleton: bool,
+    #[pyo3(get, set)]
+    pub inherited_doc: bool,
+    #[pyo3(get, set)]
+    pub env_vars: Vec<PyEnvVar>,
+    #[pyo3(get, set)]
+    pub touches_database: bool,
+    #[pyo3(get, set)]
+    pub tables: Vec<String>,
+    #[pyo3(get, set)]
+    pub makes_external_calls: bool,
+    #[pyo3(get, set)]
+    pub http_calls: Vec<PyHttpCall>,
+    #[pyo3(get, set)]
+    pub security_findings: Vec<PySecurityFinding>,
+    #[pyo3(get, set)]
+    pub feature_flags: Vec<String>,
+    #[pyo3(get, set)]
+    pub log_events: Vec<PyLogEvent>,
+    #[pyo3(get, set)]
+    pub metadata: BTreeMap<String, String>,
+}
+
+#[pymethods]
+impl PyCodeEntity {
+    #[new]
+    fn new(
+        entity_type: String,
+        name: String,
+        file_path: String,
+        line_number: usize,
+    ) -> Self {
+        PyCodeEntity {
+            entity_id: String::new(),
+            content_hash: String::new(),
+            source_hash: String::new(),
+            entity_type: Arc::from(entity_type),
+            name,
+            qualified_name: String::new(),
+            module_path: String::new(),
+            file_path: Arc::from(file_path),
+            absolute_path: None,
+            line_number,
+            end_line: 0,
+            start_offset: None,
+            end_offset: None,
+            column: 0,
+            end_column: 0,
+            parent_id: None,
+            children: Vec::new(),
+            bases: Vec::new(),
+            overrides: None,
+            override_change: None,
+            docstring: None,
+            code: String::new(),
+            code_span: None,
+            is_async: false,
+            decorators: Vec::new(),
+            parameters: Vec::new(),
+            parameter_types: None,
+            return_type: None,
+            is_internal: false,
+            is_api_endpoint: false,
+            route_path: None,
+            http_methods: Vec::new(),
+            endpoint_params: Vec::new(),
+            api_version: None,
+            response_model: None,
+            serializers: Vec::new(),
+            request_models: Vec::new(),
+            response_models: Vec::new(),
+            used_by_endpoints: Vec::new(),
+            auth_required: false,
+            auth_scheme: None,
+            permissions: Vec::new(),
+            exercises: Vec::new(),
+            tested_by: Vec::new(),
+            test_cases: Vec::new(),
+            harvested_examples: Vec::new(),
+            examples: Vec::new(),
+            complexity_score: 1,
+            is_deprecated: false,
+            replacement: None,
+            is_generated: false,
+            has_custom_init: false,
+            is_exception: false,
+            is_abstract: false,
+            is_mixin: false,
+            is_singleton: false,
+            inherited_doc: false,
+            env_vars: Vec::new(),
+            touches_database: false,
+            tables: Vec::new(),
+            makes_external_calls: false,
+            http_calls: Vec::new(),
+            security_findings: Vec::new(),
+            feature_flags: Vec::new(),
+            log_events: Vec::new(),
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    #[getter]
+    fn entity_type(&self) -> &str {
+        &self.entity_type
+    }
+
+    #[setter]
+    fn set_entity_type(&mut self, value: &str) {
+        self.entity_type = Arc::from(value);
+    }
+
+    #[getter]
+    fn file_path(&self) -> &str {
+        &self.file_path
+    }
+
+    #[setter]
+    fn set_file_path(&mut self, value: &str) {
+        self.file_path = Arc::from(value);
+    }
+
+    /// Where the file lives on disk, when known; `file_path` is relative to
+    /// the analyzed directory
+    #[getter]
+    fn absolute_path(&self) -> Option<&str> {
+        self.absolute_path.as_deref()
+    }
+
+    #[getter]
+    fn decorators(&self) -> Vec<&str> {
+        self.decorators.iter().map(|d| &**d).collect()
+    }
+
+    #[setter]
+    fn set_decorators(&mut self, value: Vec<String>) {
+        self.decorators = value.into_iter().map(Arc::from).collect();
+    }
+
+    /// Signature or header, read from the file on first access when the
+    /// analyzer deferred it (`AnalyzerConfig.lazy_code`)
+    #[getter]
+    fn code(&mut self) -> PyResult<String> {
+        self.load_code()
+    }
+
+    #[setter]
+    fn set_code(&mut self, code: String) {
+        self.code = code;
+        self.code_span = None;
+    }
+
+    /// Load deferred code now; a no-op when it is already loaded
+    fn load_code(&mut self) -> PyResult<String> {
+        if self.code_span.is_some() {
+            self.code = self.code_text()?;
+            self.code_span = None;
+        }
+        Ok(self.code.clone())
+    }
+
+    #[getter]
+    fn is_code_loaded(&self) -> bool {
+        self.code_span.is_none()
+    }
+
+    /// Percentage of parameters and return carrying a type annotation, or
+    /// None for entities that aren't functions or lack annotation data
+    #[getter]
+    fn annotation_coverage(&self) -> Option<f64> {
+        if !matches!(&*self.entity_type, "function" | "method") {
+            return None;
+        }
+        let types = self.parameter_types.as_deref()?;
+        entity::annotation_counts(&self.name, &self.parameters, types, self.return_type.is_some())
+            .map(|(annotated, total)| metrics::percentage(annotated, total))
+    }
+
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let dict = pyo3::types::PyDict::new_bound(py);
+        dict.set_item("entity_id", &self.entity_id)?;
+        dict.set_item("content_hash", &self.content_hash)?;
+        dict.set_item("source_hash", &self.source_hash)?;
+        dict.set_item("type", &*self.entity_type)?;
+        dict.set_item("name", &self.name)?;
+        dict.set_item("qualified_name", &self.qualified_name)?;
+        dict.set_item("module_path", &self.module_path)?;
+        dict.set_item("file_path", &*self.file_path)?;
+        dict.set_item("absolute_path", self.absolute_path.as_deref())?;
+        dict.set_item("line_number", &self.line_number)?;
+        dict.set_item("end_line", &self.end_line)?;
+        dict.set_item("start_offset", &self.start_offset)?;
+        dict.set_item("end_offset", &self.end_offset)?;
+        dict.set_item("column", &self.column)?;
+        dict.set_item("end_column", &self.end_column)?;
+        dict.set_item("parent_id", &self.parent_id)?;
+        dict.set_item("children", &self.children)?;
+        dict.set_item("bases", &self.bases)?;
+        dict.set_item("overrides", &self.overrides)?;
+        dict.set_item("override_change", &self.override_change)?;
+        dict.set_item("docstring", &self.docstring)?;
+        dict.set_item("code", self.code_text()?)?;
+        dict.set_item("is_async", &self.is_async)?;
+        dict.set_item("decorators", self.decorators())?;
+        dict.set_item("parameters", &self.parameters)?;
+        dict.set_item("parameter_types", &self.parameter_types)?;
+        dict.set_item("annotation_coverage", self.annotation_coverage())?;
+        dict.set_item("return_type", &self.return_type)?;
+        dict.set_item("is_internal", &self.is_internal)?;
+        dict.set_item("is_api_endpoint", &self.is_api_endpoint)?;
+        dict.set_item("route_path", &self.route_path)?;
+        dict.set_item("http_methods", &self.http_methods)?;
+        let endpoint_params: Vec<_> = self.endpoint_params.iter().map(|item| item.to_dict(py)).collect::<PyResult<_>>()?;
+        dict.set_item("endpoint_params", endpoint_params)?;
+        dict.set_item("api_version", &self.api_version)?;
+        dict.set_item("response_model", &self.response_model)?;
+        dict.set_item("serializers", &self.serializers)?;
+        dict.set_item("request_models", &self.request_models)?;
+        dict.set_item("response_models", &self.response_models)?;
+        dict.set_item("used_by_endpoints", &self.used_by_endpoints)?;
+        dict.set_item("auth_required", &self.auth_required)?;
+        dict.set_item("auth_scheme", &self.auth_scheme)?;
+        dict.set_item("permissions", &self.permissions)?;
+        dict.set_item("exercises", &self.exercises)?;
+        dict.set_item("tested_by", &self.tested_by)?;
+        let test_cases: Vec<_> = self.test_cases.iter().map(|item| item.to_dict(py)).collect::<PyResult<_>>()?;
+        dict.set_item("test_cases", test_cases)?;
+        let harvested_examples: Vec<_> = self.harvested_examples.iter().map(|item| item.to_dict(py)).collect::<PyResult<_>>()?;
+        dict.set_item("harvested_examples", harvested_examples)?;
+        let examples: Vec<_> = self.examples.iter().map(|item| item.to_dict(py)).collect::<PyResult<_>>()?;
+        dict.set_item("examples", examples)?;
+        dict.set_item("complexity_score", &self.complexity_score)?;
+        dict.set_item("is_deprecated", &self.is_deprecated)?;
+        dict.set_item("replacement", &self.replacement)?;
+        dict.set_item("is_generated", &self.is_generated)?;
+        dict.set_item("has_custom_init", &self.has_custom_init)?;
+        dict.set_item("is_exception", &self.is_exception)?;
+        dict.set_item("is_abstract", &self.is_abstract)?;
+        dict.set_item("is_mixin", &self.is_mixin)?;
+        dict.set_item("is_singleton", &self.is_singleton)?;
+        dict.set_item("inherited_doc", &self.inherited_doc)?;
+        let env_vars: Vec<_> = self.env_vars.iter().map(|var| var.to_dict(py)).collect::<PyResult<_>>()?;
+        dict.set_item("env_vars", env_vars)?;
+        dict.set_item("touches_database", &self.touches_database)?;
+        dict.set_item("tables", &self.tables)?;
+        dict.set_item("makes_external_calls", &self.makes_external_calls)?;
+        let http_calls: Vec<_> = self.http_calls.iter().map(|item| item.to_dict(py)).collect::<PyResult<_>>()?;
+        dict.set_item("http_calls", http_calls)?;
+        let security_findings: Vec<_> = self.security_findings.iter().map(|item| item.to_dict(py)).collect::<PyResult<_>>()?;
+        dict.set_item("security_findings", security_findings)?;
+        dict.set_item("feature_flags", &self.feature_flags)?;
+        let log_events: Vec<_> = self.log_events.iter().map(|event| event.to_dict(py)).collect::<PyResult<_>>()?;
+        dict.set_item("log_events", log_events)?;
+        dict.set_item("metadata", &self.metadata)?;
+        Ok(dict.into())
+    }
+}
+
+/// Analyze Python source held in memory (editor buffers, generated code)
+/// without writing it to a temporary file
+#[pyfunction]
+#[pyo3(signature = (source, path="<string>"))]
+fn analyze_source_rust(source: &str, path: &str) -> PyResult<PyEntityCollection> {
+    let entities = RustAnalyzer::new().analyze_source(source, Path::new(path))
+        .map_err(analysis_error)?;
+    
+    Ok(PyEntityCollection::new(entities))
+}
+
+/// Compare the public API of two analyses, e.g. of two releases.
+///
+/// Entities are matched on their `entity_id`, so definitions can move within
+/// a file without showing up as removed and re-added; collections from older
+/// releases without ids fall back to matching on file path and name.
+#[pyfunction]
+fn diff_entities(old: PyRef<'_, PyEntityCollection>, new: PyRef<'_, PyEntityCollection>) -> Vec<PyEntityChange> {
+    diff::diff_entities(old.entities(), new.entities())
+        .changes
+        .into_iter()
+        .map(PyEntityChange::from)
+        .collect()
+}
+
+/// The semver bump ("major", "minor", "patch", or None when nothing changed)
+/// required between two analyses; with `current_version`, the next version
+/// string instead
+#[pyfunction]
+#[pyo3(signature = (old, new, current_version=None))]
+fn recommend_version_bump(
+    old: PyRef<'_, PyEntityCollection>,
+    new: PyRef<'_, PyEntityCollection>,
+    current_version: Option<&str>,
+) -> PyResult<Option<String>> {
+    let api_diff = diff::diff_entities(old.entities(), new.entities());
+    let Some(bump) = semver::classify_diff(&api_diff).recommended else {
+        return Ok(current_version.map(str::to_string));
+    };
+    match current_version {
+        Some(version) => semver::next_version(version, bump)
+            .map(Some)
+            .map_err(|e| RustAnalysisError::new_err(e.to_string())),
+        None => Ok(Some(bump.as_str().to_string())),
+    }
+}
+
+/// Draft a Markdown changelog section for the API changes between two
+/// analyses; `link_base` turns entity names into links to their definition
+#[pyfunction]
+#[pyo3(signature = (old, new, title="Unreleased", link_base=None))]
+fn render_changelog(
+    old: PyRef<'_, PyEntityCollection>,
+    new: PyRef<'_, PyEntityCollection>,
+    title: &str,
+    link_base: Option<&str>,
+) -> String {
+    let api_diff = diff::diff_entities(old.entities(), new.entities());
+    changelog::render_changelog(&api_diff, title, link_base)
+}
+
+/// Number of tokens in `text`. `tokenizer` is "approx" (a fast estimate), a
+/// tiktoken encoding name such as "cl100k_base", or a callable returning the
+/// tokens of a string (e.g. `tiktoken.get_encoding(...).encode`)
+#[pyfunction]
+#[pyo3(signature = (text, tokenizer=None))]
+fn count_tokens(text: &str, tokenizer: Option<&Bound<'_, PyAny>>) -> PyResult<usize> {
+    Ok(resolve_tokenizer(tokenizer)?.count_tokens(text))
+}
+
+/// Split a whole module's source into overlapping chunks of whole lines
+#[pyfunction]
+#[pyo3(signature = (source, path="<string>", max_tokens=512, overlap=64, tokenizer=None))]
+fn chunk_source(
+    py: Python<'_>,
+    source: &str,
+    path: &str,
+    max_tokens: usize,
+    overlap: usize,
+    tokenizer: Option<&Bound<'_, PyAny>>,
+) -> PyResult<Vec<PyChunk>> {
+    let tokenizer = resolve_tokenizer(tokenizer)?;
+    let options = ChunkOptions {
+        max_tokens,
+        overlap_tokens: overlap,
+    };
+    let chunks = py.allow_threads(|| chunking::chunk_module(Path::new(path), source, tokenizer.as_ref(), options));
+    Ok(chunks.into_iter().map(PyChunk::from).collect())
+}
+
+fn resolve_tokenizer(tokenizer: Option<&Bound<'_, PyAny>>) -> PyResult<Box<dyn Tokenizer>> {
+    let Some(tokenizer) = tokenizer else {
+        return Ok(Box::new(chunking::ApproximateTokenizer));
+    };
+    if let Ok(name) = tokenizer.extract::<&str>() {
+        return chunking::tokenizer_by_name(name).map_err(|e| PyValueError::new_err(e.to_string()));
+    }
+    if tokenizer.is_callable() {
+        return Ok(Box::new(PyTokenizer(tokenizer.clone().unbind())));
+    }
+    Err(PyTypeError::new_err("tokenizer must be a name or a callable"))
+}
+
+/// Tokenizer backed by a Python callable returning a sequence of tokens
+struct PyTokenizer(PyObject);
+
+impl Tokenizer for PyTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        Python::with_gil(|py| {
+            self.0
+                .call1(py, (text,))
+                .and_then(|tokens| tokens.bind(py).len())
+                .unwrap_or_else(|e| {
+                    log::warn!("Tokenizer callback failed, estimating instead: {}", e);
+                    chunking::ApproximateTokenizer.count_tokens(text)
+                })
+        })
+    }
+}
+
+/// Excerpt of a file by line range (1-based, inclusive) or UTF-8 byte range,
+/// with `context` lines around it; `trim` keeps context to whole statements
+/// of the enclosing definition and removes the common indentation, `redact`
+/// masks likely secrets
+#[pyfunction]
+#[pyo3(signature = (path, start_line=None, end_line=None, *, start_byte=None, end_byte=None, context=2, trim=false, redact=false))]
+#[allow(clippy::too_many_arguments)]
+fn read_snippet(
+    py: Python<'_>,
+    path: PathBuf,
+    start_line: Option<usize>,
+    end_line: Option<usize>,
+    start_byte: Option<usize>,
+    end_byte: Option<usize>,
+    context: usize,
+    trim: bool,
+    redact: bool,
+) -> PyResult<PySnippet> {
+    let range = match (start_line, start_byte) {
+        (Some(start), None) => SnippetRange::Lines(start, end_line.unwrap_or(start)),
+        (None, Some(start)) => SnippetRange::Bytes(start, end_byte.unwrap_or(start)),
+        _ => return Err(PyValueError::new_err("pass exactly one of start_line and start_byte")),
+    };
+    let options = SnippetOptions { context, trim, redact };
+    py.allow_threads(|| snippet::read_snippet(&path, range, options))
+        .map(PySnippet::from)
+        .map_err(|e| RustAnalysisError::new_err(e.to_string()))
+}
+
+/// Python-compatible wrapper for a Snippet
+#[pyclass(name = "Snippet", frozen)]
+pub struct PySnippet {
+    /// (line number, text, is_focus) for every line of the excerpt
+    #[pyo3(get)]
+    pub lines: Vec<(usize, String, bool)>,
+    #[pyo3(get)]
+    pub start_line: usize,
+    #[pyo3(get)]
+    pub end_line: usize,
+    /// Whether any secrets were masked
+    #[pyo3(get)]
+    pub redacted: bool,
+    snippet: Snippet,
+}
+
+#[pymethods]
+impl PySnippet {
+    /// The excerpt as plain text
+    #[getter]
+    fn text(&self) -> String {
+        self.snippet.text()
+    }
+
+    /// The excerpt with a line-number gutter, requested lines marked `>`
+    fn render(&self) -> String {
+        self.snippet.render()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Snippet(lines {}-{})", self.start_line, self.end_line)
+    }
+}
+
+impl From<Snippet> for PySnippet {
+    fn from(snippet: Snippet) -> Self {
+        let (start_line, end_line) = snippet.line_range();
+        PySnippet {
+            lines: snippet.lines.iter().map(|l| (l.number, l.text.clone(), l.focus)).collect(),
+            start_line,
+            end_line,
+            redacted: snippet.redacted,
+            snippet,
+        }
+    }
+}
+
+/// Python-compatible wrapper for a Chunk
+#[pyclass(name = "Chunk", frozen)]
+#[derive(Clone)]
+pub struct PyChunk {
+    #[pyo3(get)]
+    pub entity_id: String,
+    #[pyo3(get)]
+    pub entity_type: String,
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub file_path: String,
+    #[pyo3(get)]
+    pub chunk_index: usize,
+    #[pyo3(get)]
+    pub chunk_count: usize,
+    #[pyo3(get)]
+    pub start_line: usize,
+    #[pyo3(get)]
+    pub end_line: usize,
+    #[pyo3(get)]
+    pub token_count: usize,
+    #[pyo3(get)]
+    pub text: String,
+}
+
+#[pymethods]
+impl PyChunk {
+    fn __repr__(&self) -> String {
+        format!(
+            "Chunk({:?} {}/{}, lines {}-{}, {} tokens)",
+            self.name,
+            self.chunk_index + 1,
+            self.chunk_count,
+            self.start_line,
+            self.end_line,
+            self.token_count
+        )
+    }
+}
+
+impl From<Chunk> for PyChunk {
+    fn from(chunk: Chunk) -> Self {
+        PyChunk {
+            entity_id: chunk.entity_id,
+            entity_type: chunk.entity_type,
+            name: chunk.name,
+            file_path: chunk.file_path,
+            chunk_index: chunk.chunk_index,
+            chunk_count: chunk.chunk_count,
+            start_line: chunk.start_line,
+            end_line: chunk.end_line,
+            token_count: chunk.token_count,
+            text: chunk.text,
+        }
+    }
+}
+
+/// Python-compatible wrapper for one API change from `diff_entities`
+#[pyclass(name = "EntityChange", frozen)]
+#[derive(Clone)]
+pub struct PyEntityChange {
+    /// added, removed, signature_changed, docstring_changed, visibility_changed or deprecated
+    #[pyo3(get)]
+    pub kind: &'static str,
+    #[pyo3(get)]
+    pub key: String,
+    #[pyo3(get)]
+    pub entity_type: String,
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub file_path: String,
+    #[pyo3(get)]
+    pub line_number: usize,
+    #[pyo3(get)]
+    pub old_signature: Option<String>,
+    #[pyo3(get)]
+    pub new_signature: Option<String>,
+    #[pyo3(get)]
+    pub old_docstring: Option<String>,
+    #[pyo3(get)]
+    pub new_docstring: Option<String>,
+    #[pyo3(get)]
+    pub old_public: Option<bool>,
+    #[pyo3(get)]
+    pub new_public: Option<bool>,
+    #[pyo3(get)]
+    pub replacement: Option<String>,
+    /// Semver bump the change requires: major, minor or patch
+    #[pyo3(get)]
+    pub bump: &'static str,
+    #[pyo3(get)]
+    pub bump_reason: String,
+}
+
+#[pymethods]
+impl PyEntityChange {
+    fn __repr__(&self) -> String {
+        format!("EntityChange({}, {:?})", self.kind, self.key)
+    }
+}
+
+impl From<EntityChange> for PyEntityChange {
+    fn from(change: EntityChange) -> Self {
+        let (bump, bump_reason) = semver::classify(&change);
+        PyEntityChange {
+            kind: change.kind.as_str(),
+            key: change.key,
+            entity_type: change.entity_type,
+            name: change.name,
+            file_path: change.file_path,
+            line_number: change.line_number,
+            old_signature: change.old_signature,
+            new_signature: change.new_signature,
+            old_docstring: change.old_docstring,
+            new_docstring: change.new_docstring,
+            old_public: change.old_public,
+            new_public: change.new_public,
+            replacement: change.replacement,
+            bump: bump.as_str(),
+            bump_reason,
+        }
+    }
+}
+
+/// Analysis results kept in Rust storage.
+///
+/// Supports `len()`, iteration, indexing and slicing; entities are converted
+/// into `CodeEntity` objects only when accessed, so large results never have
+/// to be materialized as a Python list all at once.
+#[pyclass(name = "EntityCollection", sequence)]
+pub struct PyEntityCollection {
+    entities: Vec<CodeEntity>,
+    interner: Mutex<StringInterner>,
+    /// Built on the first `search()` call
+    search_index: OnceLock<SearchIndex>,
+    /// Pool of the analyzer that produced the entities; parallel queries
+    /// run on it rather than Rayon's global pool
+    pool: Option<Arc<ThreadPool>>,
+}
+
+impl PyEntityCollection {
+    pub fn new(entities: Vec<CodeEntity>) -> Self {
+        PyEntityCollection {
+            entities,
+            interner: Mutex::new(StringInterner::new()),
+            search_index: OnceLock::new(),
+            pool: None,
+        }
+    }
+
+    /// Run parallel work on `pool` instead of Rayon's global pool
+    pub fn with_pool(mut self, pool: Option<Arc<ThreadPool>>) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    /// A collection of some of these entities, on the same pool
+    fn subset(&self, entities: Vec<CodeEntity>) -> Self {
+        PyEntityCollection::new(entities).with_pool(self.pool.clone())
+    }
+
+    fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        analyzer::install(self.pool.as_deref(), op)
+    }
+
+    /// The Rust-side entities backing this collection
+    pub fn entities(&self) -> &[CodeEntity] {
+        &self.entities
+    }
+
+    fn get(&self, index: usize) -> Option<PyCodeEntity> {
+        let entity = self.entities.get(index)?.clone();
+        let mut interner = self.interner.lock().unwrap_or_else(|e| e.into_inner());
+        Some(PyCodeEntity::from_entity(entity, &mut interner))
+    }
+}
+
+#[pymethods]
+impl PyEntityCollection {
+    #[new]
+    #[pyo3(signature = (entities=Vec::new()))]
+    fn py_new(entities: Vec<PyCodeEntity>) -> Self {
+        PyEntityCollection::new(entities.iter().map(PyCodeEntity::to_entity).collect())
+    }
+
+    fn __len__(&self) -> usize {
+        self.entities.len()
+    }
+
+    fn __getitem__(&self, py: Python<'_>, index: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+        if let Ok(slice) = index.downcast::<PySlice>() {
+            let indices = slice.indices(self.entities.len() as isize)?;
+            let selected = (0..indices.slicelength)
+                .map(|k| indices.start + k as isize * indices.step)
+                .map(|i| self.entities[i as usize].clone())
+                .collect();
+            return Ok(self.subset(selected).into_py(py));
+        }
+        
+        let index: isize = index.extract()?;
+        let resolved = if index < 0 { index + self.entities.len() as isize } else { index };
+        usize::try_from(resolved)
+            .ok()
+            .and_then(|i| self.get(i))
+            .map(|entity| entity.into_py(py))
+            .ok_or_else(|| PyIndexError::new_err("entity index out of range"))
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> EntityIterator {
+        EntityIterator {
+            collection: slf.into(),
+            index: 0,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("EntityCollection(<{} entities>)", self.entities.len())
+    }
+
+    /// Load deferred code for every entity, reading each file once
+    fn load_code(&mut self, py: Python<'_>) -> PyResult<()> {
+        py.allow_threads(|| lazy::load_code(&mut self.entities))
+            .map_err(|e| RustAnalysisError::new_err(format!("{:#}", e)))?;
+        // An index built before loading saw empty code
+        self.search_index = OnceLock::new();
+        Ok(())
+    }
+
+    /// Approximate memory held by the collection, including the string pool
+    /// shared by the entities handed out to Python
+    fn memory_usage(&self) -> PyMemoryUsage {
+        let mut usage = memory::estimate(&self.entities);
+        usage.index_bytes = self.interner.lock().unwrap_or_else(|e| e.into_inner()).heap_bytes();
+        usage.into()
+    }
+
+    /// Materialize every entity as a Python list
+    fn to_list(&self) -> Vec<PyCodeEntity> {
+        (0..self.entities.len()).filter_map(|i| self.get(i)).collect()
+    }
+
+    /// Entities matching a query such as `type:function is:public complexity:>10`;
+    /// see the Rust `Query` docs for the syntax
+    fn query(&self, py: Python<'_>, query: &str) -> PyResult<PyEntityCollection> {
+        let query = Query::parse(query).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let selected = py.allow_threads(|| self.install(|| query.filter(&self.entities).into_iter().cloned().collect()));
+        Ok(self.subset(selected))
+    }
+
+    /// Ranked full-text search over names, docstrings and code, best match
+    /// first. The index is built on first use and reused afterwards.
+    #[pyo3(signature = (query, limit=20))]
+    fn search(&self, py: Python<'_>, query: &str, limit: usize) -> PyResult<PyEntityCollection> {
+        let hits = py.allow_threads(|| -> anyhow::Result<_> {
+            let index = match self.search_index.get() {
+                Some(index) => index,
+                None => {
+                    let index = self.install(|| SearchIndex::build(&self.entities))?;
+                    self.search_index.get_or_init(|| index)
+                }
+            };
+            index.search(query, limit)
+        })
+        .map_err(|e| RustAnalysisError::new_err(e.to_string()))?;
+        
+        let selected = hits.iter().map(|hit| self.entities[hit.index].clone()).collect();
+        Ok(self.subset(selected))
+    }
+
+    /// Entities arranged as a package → module → class → member tree; paths
+    /// are taken relative to `root`, or to the deepest common directory
+    #[pyo3(signature = (root=None))]
+    fn module_tree(&self, py: Python<'_>, root: Option<PathBuf>) -> PyTreeNode {
+        let tree = py.allow_threads(|| tree::build_tree(&self.entities, root.as_deref()));
+        PyTreeNode::new(tree, self)
+    }
+
+    /// Token count of each entity's source; see `count_tokens` for `tokenizer`
+    #[pyo3(signature = (tokenizer=None))]
+    fn token_counts(&self, py: Python<'_>, tokenizer: Option<&Bound<'_, PyAny>>) -> PyResult<Vec<usize>> {
+        let tokenizer = resolve_tokenizer(tokenizer)?;
+        Ok(py.allow_threads(|| self.install(|| chunking::count_entity_tokens(&self.entities, tokenizer.as_ref()))))
+    }
+
+    /// Every entity's source split into chunks of at most `max_tokens`, with
+    /// `overlap` tokens repeated between consecutive chunks of one entity
+    #[pyo3(signature = (max_tokens=512, overlap=64, tokenizer=None))]
+    fn chunks(
+        &self,
+        py: Python<'_>,
+        max_tokens: usize,
+        overlap: usize,
+        tokenizer: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Vec<PyChunk>> {
+        let tokenizer = resolve_tokenizer(tokenizer)?;
+        let options = ChunkOptions {
+            max_tokens,
+            overlap_tokens: overlap,
+        };
+        let chunks = py.allow_threads(|| self.install(|| chunking::chunk_entities(&self.entities, tokenizer.as_ref(), options)));
+        Ok(chunks.into_iter().map(PyChunk::from).collect())
+    }
+
+    /// Context pack for summarizing one entity (by index or entity_id): its
+    /// source, then as far as `budget` tokens allow, the module docstring,
+    /// enclosing class, callees, referenced types and callers
+    #[pyo3(signature = (target, budget=2000, tokenizer=None))]
+    fn context(
+        &self,
+        py: Python<'_>,
+        target: &Bound<'_, PyAny>,
+        budget: usize,
+        tokenizer: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<PyContextBundle> {
+        let index = match target.extract::<&str>() {
+            Ok(entity_id) => self
+                .entities
+                .iter()
+                .position(|e| e.entity_id == entity_id)
+                .ok_or_else(|| PyValueError::new_err(format!("No entity with id {}", entity_id)))?,
+            Err(_) => target.extract::<usize>()?,
+        };
+        let tokenizer = resolve_tokenizer(tokenizer)?;
+        let bundle = py
+            .allow_threads(|| context::build_context(&self.entities, index, tokenizer.as_ref(), budget))
+            .map_err(|e| PyIndexError::new_err(e.to_string()))?;
+        Ok(PyContextBundle::new(bundle, self))
+    }
+
+    /// Compact, deterministic description of the entity at `index` for LLM
+    /// prompts; with `budget`, neighbours, examples and long docstrings are
+    /// dropped in that order until it fits
+    #[pyo3(signature = (index, budget=None, max_neighbors=5, examples=true, tokenizer=None))]
+    fn prompt(
+        &self,
+        py: Python<'_>,
+        index: usize,
+        budget: Option<usize>,
+        max_neighbors: usize,
+        examples: bool,
+        tokenizer: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<String> {
+        if index >= self.entities.len() {
+            return Err(PyIndexError::new_err("entity index out of range"));
+        }
+        let mut builder = PromptBuilder::new()
+            .with_max_neighbors(max_neighbors)
+            .with_examples(examples)
+            .with_boxed_tokenizer(resolve_tokenizer(tokenizer)?);
+        if let Some(budget) = budget {
+            builder = builder.with_budget(budget);
+        }
+        Ok(py.allow_threads(|| builder.render(&self.entities, index)))
+    }
+
+    /// One JSON line per entity (qualified name, signature, normalized
+    /// docstring and the start of the body) for embedding models
+    fn to_embedding_jsonl(&self, py: Python<'_>) -> PyResult<String> {
+        py.allow_threads(|| export::to_embedding_jsonl(&self.entities))
+            .map_err(|e| RustAnalysisError::new_err(e.to_string()))
+    }
+
+    /// Docusaurus-style site as JSON: an MDX page per module under `base`
+    /// and a sidebar mirroring the package layout
+    #[pyo3(signature = (base="api"))]
+    fn to_site_json(&self, py: Python<'_>, base: &str) -> PyResult<String> {
+        py.allow_threads(|| site::to_site_json(&self.entities, base))
+            .map_err(|e| RustAnalysisError::new_err(e.to_string()))
+    }
+
+    /// Source excerpt of the entity at `index`, with `context` lines around it
+    #[pyo3(signature = (index, context=2, trim=false, redact=false))]
+    fn snippet(&self, py: Python<'_>, index: usize, context: usize, trim: bool, redact: bool) -> PyResult<PySnippet> {
+        let entity = self
+            .entities
+            .get(index)
+            .ok_or_else(|| PyIndexError::new_err("entity index out of range"))?;
+        let range = match (entity.start_offset, entity.end_offset) {
+            (Some(start), Some(end)) => SnippetRange::Bytes(start, end),
+            _ => SnippetRange::Lines(entity.line_number, entity.end_line.max(entity.line_number)),
+        };
+        let options = SnippetOptions { context, trim, redact };
+        py.allow_threads(|| snippet::read_snippet(entity.source_path(), range, options))
+            .map(PySnippet::from)
+            .map_err(|e| RustAnalysisError::new_err(e.to_string()))
+    }
+
+    /// Groups of copy-pasted entities; with `near`, also groups whose token
+    /// shingles are at least `threshold` similar (Jaccard)
+    #[pyo3(signature = (near=false, threshold=0.8, min_tokens=20))]
+    fn duplicates(&self, py: Python<'_>, near: bool, threshold: f64, min_tokens: usize) -> Vec<PyDuplicateGroup> {
+        let options = DuplicateOptions {
+            near,
+            threshold,
+            min_tokens,
+            ..DuplicateOptions::default()
+        };
+        let groups = py.allow_threads(|| self.install(|| duplicates::find_duplicates(&self.entities, options)));
+        groups.into_iter().map(|group| PyDuplicateGroup::new(group, self)).collect()
+    }
+
+    /// Regex matches across the given entity fields ("name", "code",
+    /// "docstring"), scanned in parallel, in entity order
+    #[pyo3(signature = (pattern, fields=None))]
+    fn grep(&self, py: Python<'_>, pattern: &str, fields: Option<Vec<String>>) -> PyResult<Vec<PyGrepMatch>> {
+        let regex = regex::Regex::new(pattern).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let fields = match fields {
+            Some(names) => names
+                .iter()
+                .map(|name| name.parse::<GrepField>())
+                .collect::<anyhow::Result<Vec<_>>>()
+                .map_err(|e| PyValueError::new_err(e.to_string()))?,
+            None => vec![GrepField::Code, GrepField::Docstring],
+        };
+
+        let matches = py.allow_threads(|| self.install(|| grep::grep(&self.entities, &regex, &fields)));
+        Ok(matches
+            .into_iter()
+            .filter_map(|m| Some(PyGrepMatch::new(self.get(m.index)?, m)))
+            .collect())
+    }
+
+    /// Type-annotation coverage (percent) per dotted module, with the
+    /// overall figure under "TOTAL"
+    fn annotation_coverage(&self) -> BTreeMap<String, f64> {
+        let (rows, overall) = metrics::annotation_coverage_by_module(&self.entities);
+        rows.into_iter()
+            .chain(std::iter::once(overall))
+            .map(|row| (row.module, row.coverage))
+            .collect()
+    }
+
+    /// Documentation lint findings under the rule severities and ignore
+    /// patterns of `config` (defaults when omitted)
+    #[pyo3(signature = (config=None))]
+    fn lint(&self, py: Python<'_>, config: Option<PyAnalyzerConfig>) -> PyResult<PyLintReport> {
+        let config: AnalyzerConfig = config.map(Into::into).unwrap_or_default();
+        let report = py
+            .allow_threads(|| lint::lint(&self.entities, &config))
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(PyLintReport::new(report, self))
+    }
+
+    /// The class or function `entity` is defined in, if it is in the collection
+    fn parent(&self, entity: PyRef<'_, PyCodeEntity>) -> Option<PyCodeEntity> {
+        let parent_id = entity.parent_id.as_deref()?;
+        let index = self.entities.iter().position(|e| e.entity_id == parent_id)?;
+        self.get(index)
+    }
+
+    /// Entities defined directly inside `entity`, in source order
+    fn children(&self, entity: PyRef<'_, PyCodeEntity>) -> Vec<PyCodeEntity> {
+        let ids = hierarchy::index_by_id(&self.entities);
+        entity
+            .children
+            .iter()
+            .filter_map(|id| ids.get(id.as_str()))
+            .filter_map(|&index| self.get(index))
+            .collect()
+    }
+
+    /// Public functions, classes and endpoints without usage examples,
+    /// endpoints first and then the most complex
+    fn example_candidates(&self) -> Vec<PyCodeEntity> {
+        lint::example_candidates(&self.entities)
+            .into_iter()
+            .filter_map(|index| self.get(index))
+            .collect()
+    }
+
+    /// Python entities described like Sphinx autodoc objects, for a Sphinx
+    /// extension that shouldn't import the documented package
+    fn sphinx_objects(&self) -> Vec<PySphinxObject> {
+        sphinx::describe(&self.entities).into_iter().map(PySphinxObject::from).collect()
+    }
+
+    /// Files without an SPDX license identifier, or with one other than
+    /// `expected`
+    #[pyo3(signature = (expected=None))]
+    fn missing_license_headers(&self, expected: Option<&str>) -> Vec<PyMissingHeader> {
+        licenses::missing_headers(&self.entities, expected)
+            .into_iter()
+            .map(PyMissingHeader::from)
+            .collect()
+    }
+
+    /// Entities called by tests, each with the IDs of those tests; filled in
+    /// by `analyze_directory`
+    fn test_mapping(&self) -> Vec<PyTestMapping> {
+        testmap::mappings(&self.entities).into_iter().map(PyTestMapping::from).collect()
+    }
+
+    /// Endpoints by API version as `{"v1": EntityCollection, ...}`, oldest
+    /// first; unversioned endpoints come last under `None`
+    fn endpoints_by_version<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, pyo3::types::PyDict>> {
+        let dict = pyo3::types::PyDict::new_bound(py);
+        for (version, members) in endpoints::by_version(&self.entities) {
+            let collection = self.subset(members.into_iter().cloned().collect());
+            dict.set_item(version, Py::new(py, collection)?)?;
+        }
+        Ok(dict)
+    }
+
+    /// Every environment variable the entities read, by name
+    fn env_vars(&self) -> Vec<PyEnvVarSummary> {
+        env::project_env_vars(&self.entities).into_iter().map(PyEnvVarSummary::from).collect()
+    }
+
+    /// Sphinx `objects.inv` contents for intersphinx; `page` is the URI of a
+    /// module's page with `{module}` standing for its dotted path
+    #[pyo3(signature = (project="", version="", page="{module}.html"))]
+    fn objects_inv<'py>(&self, py: Python<'py>, project: &str, version: &str, page: &str) -> PyResult<Bound<'py, PyBytes>> {
+        let options = InventoryOptions {
+            project: project.to_string(),
+            version: version.to_string(),
+            page: page.to_string(),
+        };
+        let inventory = py
+            .allow_threads(|| inventory::to_objects_inv(&self.entities, &options))
+            .map_err(|e| RustAnalysisError::new_err(e.to_string()))?;
+        Ok(PyBytes::new_bound(py, &inventory))
+    }
+}
+
+/// Python-compatible wrapper for a node of `EntityCollection.module_tree()`
+#[pyclass(name = "TreeNode", frozen)]
+#[derive(Clone)]
+pub struct PyTreeNode {
+    #[pyo3(get)]
+    pub name: String,
+    /// package, module or entity
+    #[pyo3(get)]
+    pub kind: &'static str,
+    /// Dotted path from the root, e.g. "pkg.api.Client.get"
+    #[pyo3(get)]
+    pub path: String,
+    /// The entity for entity nodes, None for packages and modules
+    #[pyo3(get)]
+    pub entity: Option<PyCodeEntity>,
+    #[pyo3(get)]
+    pub children: Vec<PyTreeNode>,
+}
+
+impl PyTreeNode {
+    fn new(node: TreeNode, collection: &PyEntityCollection) -> Self {
+        PyTreeNode {
+            name: node.name,
+            kind: node.kind.as_str(),
+            path: node.path,
+            entity: node.entity.and_then(|index| collection.get(index)),
+            children: node.children.into_iter().map(|child| PyTreeNode::new(child, collection)).collect(),
+        }
+    }
+}
+
+#[pymethods]
+impl PyTreeNode {
+    /// The descendant at a dotted path relative to this node
+    fn find(&self, path: &str) -> Option<PyTreeNode> {
+        normalize_identifier(path)
+            .split('.')
+            .filter(|part| !part.is_empty())
+            .try_fold(self, |node, part| node.children.iter().find(|child| child.name == part))
+            .cloned()
+    }
+
+    fn __len__(&self) -> usize {
+        self.children.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("TreeNode({}, {:?}, <{} children>)", self.kind, self.path, self.children.len())
+    }
+}
+
+/// Python-compatible wrapper for a ContextBundle
+#[pyclass(name = "ContextBundle", frozen)]
+pub struct PyContextBundle {
+    #[pyo3(get)]
+    pub items: Vec<PyContextItem>,
+    #[pyo3(get)]
+    pub token_count: usize,
+    /// Related items left out to stay within the budget
+    #[pyo3(get)]
+    pub omitted: usize,
+    bundle: ContextBundle,
+}
+
+impl PyContextBundle {
+    fn new(bundle: ContextBundle, collection: &PyEntityCollection) -> Self {
+        let items = bundle
+            .items
+            .iter()
+            .map(|item| PyContextItem {
+                relation: item.relation.as_str(),
+                entity: item.index.and_then(|index| collection.get(index)),
+                name: item.name.clone(),
+                file_path: item.file_path.clone(),
+                line_number: item.line_number,
+                text: item.text.clone(),
+                token_count: item.token_count,
+            })
+            .collect();
+        PyContextBundle {
+            items,
+            token_count: bundle.token_count,
+            omitted: bundle.omitted,
+            bundle,
+        }
+    }
+}
+
+#[pymethods]
+impl PyContextBundle {
+    /// The bundle as plain text, one section per item
+    fn render(&self) -> String {
+        self.bundle.render()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ContextBundle(<{} items, {} tokens>)", self.items.len(), self.token_count)
+    }
+}
+
+/// Python-compatible wrapper for one item of a ContextBundle
+#[pyclass(name = "ContextItem", frozen)]
+#[derive(Clone)]
+pub struct PyContextItem {
+    /// target, module, class, callee, type or caller
+    #[pyo3(get)]
+    pub relation: &'static str,
+    /// The related entity; None for the module docstring
+    #[pyo3(get)]
+    pub entity: Option<PyCodeEntity>,
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub file_path: String,
+    #[pyo3(get)]
+    pub line_number: usize,
+    #[pyo3(get)]
+    pub text: String,
+    #[pyo3(get)]
+    pub token_count: usize,
+}
+
+/// Python-compatible wrapper for one group from `EntityCollection.duplicates`
+#[pyclass(name = "DuplicateGroup", frozen)]
+#[derive(Clone)]
+pub struct PyDuplicateGroup {
+    /// exact or near
+    #[pyo3(get)]
+    pub kind: &'static str,
+    /// Members in analysis order; the first is the canonical copy
+    #[pyo3(get)]
+    pub entities: Vec<PyCodeEntity>,
+    #[pyo3(get)]
+    pub similarity: f64,
+}
+
+impl PyDuplicateGroup {
+    fn new(group: DuplicateGroup, collection: &PyEntityCollection) -> Self {
+        PyDuplicateGroup {
+            kind: group.kind.as_str(),
+            entities: group.members.iter().filter_map(|&index| collection.get(index)).collect(),
+            similarity: group.similarity,
+        }
+    }
+}
+
+#[pymethods]
+impl PyDuplicateGroup {
+    fn __len__(&self) -> usize {
+        self.entities.len()
+    }
+
+    fn __repr__(&self) -> String {
+        let names: Vec<&str> = self.entities.iter().map(|e| e.name.as_str()).collect();
+        format!("DuplicateGroup({}, {:?}, similarity={:.2})", self.kind, names, self.similarity)
+    }
+}
+
+/// Python-compatible wrapper for one lint `Finding`
+#[pyclass(name = "LintFinding", frozen)]
+#[derive(Clone)]
+pub struct PyLintFinding {
+    /// e.g. missing-docstring
+    #[pyo3(get)]
+    pub rule: &'static str,
+    /// info, warning or error
+    #[pyo3(get)]
+    pub severity: &'static str,
+    #[pyo3(get)]
+    pub entity: PyCodeEntity,
+    #[pyo3(get)]
+    pub message: String,
+}
+
+impl PyLintFinding {
+    fn new(entity: PyCodeEntity, finding: Finding) -> Self {
+        PyLintFinding {
+            rule: finding.rule.as_str(),
+            severity: finding.severity.as_str(),
+            entity,
+            message: finding.message,
+        }
+    }
+}
+
+#[pymethods]
+impl PyLintFinding {
+    fn __repr__(&self) -> String {
+        format!(
+            "LintFinding({}:{}: {} [{}] {})",
+            self.entity.file_path, self.entity.line_number, self.severity, self.rule, self.message
+        )
+    }
+}
+
+/// Python-compatible wrapper for `EntityCollection.lint`
+#[pyclass(name = "LintReport", frozen)]
+pub struct PyLintReport {
+    #[pyo3(get)]
+    pub findings: Vec<PyLintFinding>,
+    /// Findings silenced by `# autodoc: ignore[...]` comments
+    #[pyo3(get)]
+    pub suppressed: Vec<PyLintFinding>,
+    #[pyo3(get)]
+    pub errors: usize,
+    #[pyo3(get)]
+    pub warnings: usize,
+    report: LintReport,
+}
+
+impl PyLintReport {
+    fn new(report: LintReport, collection: &PyEntityCollection) -> Self {
+        let wrap = |findings: &[Finding]| -> Vec<PyLintFinding> {
+            findings
+                .iter()
+                .filter_map(|finding| Some(PyLintFinding::new(collection.get(finding.index)?, finding.clone())))
+                .collect()
+        };
+        PyLintReport {
+            findings: wrap(&report.findings),
+            suppressed: wrap(&report.suppressed),
+            errors: report.count(Severity::Error),
+            warnings: report.count(Severity::Warning),
+            report,
+        }
+    }
+}
+
+#[pymethods]
+impl PyLintReport {
+    /// Whether no finding is at or above `severity` (info, warning or error)
+    #[pyo3(signature = (severity="error"))]
+    fn passes(&self, severity: &str) -> PyResult<bool> {
+        let severity: Severity = severity.parse().map_err(|e: anyhow::Error| PyValueError::new_err(e.to_string()))?;
+        Ok(self.report.passes(severity))
+    }
+
+    fn __len__(&self) -> usize {
+        self.findings.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "LintReport({} findings, {} errors, {} warnings, {} suppressed)",
+            self.findings.len(),
+            self.errors,
+            self.warnings,
+            self.suppressed.len()
+        )
+    }
+}
+
+/// Python-compatible wrapper for one file's entry in `RunStats.file_timings`.
+/// Durations are in seconds.
+#[pyclass(name = "FileTiming", frozen)]
+#[derive(Clone)]
+pub struct PyFileTiming {
+    #[pyo3(get)]
+    pub path: String,
+    #[pyo3(get)]
+    pub read: f64,
+    #[pyo3(get)]
+    pub parse: f64,
+    #[pyo3(get)]
+    pub extract: f64,
+    #[pyo3(get)]
+    pub total: f64,
+    #[pyo3(get)]
+    pub entities: usize,
+    #[pyo3(get)]
+    pub cached: bool,
+    #[pyo3(get)]
+    pub failed: bool,
+    /// transcoded, binary or invalid_utf8 when the file wasn't clean UTF-8
+    #[pyo3(get)]
+    pub issue: Option<&'static str>,
+    #[pyo3(get)]
+    pub generated: bool,
+    #[pyo3(get)]
+    pub diagnostic: Option<PyDiagnostic>,
+}
+
+impl From<&FileTiming> for PyFileTiming {
+    fn from(timing: &FileTiming) -> Self {
+        PyFileTiming {
+            path: timing.path.to_string_lossy().to_string(),
+            read: timing.read.as_secs_f64(),
+            parse: timing.parse.as_secs_f64(),
+            extract: timing.extract.as_secs_f64(),
+            total: timing.total.as_secs_f64(),
+            entities: timing.entities,
+            cached: timing.cached,
+            failed: timing.failed,
+            issue: timing.issue.map(|issue| issue.as_str()),
+            generated: timing.generated,
+            diagnostic: timing.diagnostic.as_ref().map(PyDiagnostic::from),
+        }
+    }
+}
+
+#[pymethods]
+impl PyFileTiming {
+    fn __repr__(&self) -> String {
+        format!("FileTiming({:?}, total={:.3}s)", self.path, self.total)
+    }
+}
+
+/// Python-compatible wrapper for `MemoryUsage`. Sizes are in bytes.
+#[pyclass(name = "MemoryUsage", frozen)]
+#[derive(Clone)]
+pub struct PyMemoryUsage {
+    #[pyo3(get)]
+    pub entities: usize,
+    #[pyo3(get)]
+    pub files: usize,
+    #[pyo3(get)]
+    pub entity_bytes: usize,
+    #[pyo3(get)]
+    pub code_bytes: usize,
+    #[pyo3(get)]
+    pub docstring_bytes: usize,
+    #[pyo3(get)]
+    pub string_bytes: usize,
+    #[pyo3(get)]
+    pub shared_bytes: usize,
+    #[pyo3(get)]
+    pub metadata_bytes: usize,
+    #[pyo3(get)]
+    pub index_bytes: usize,
+    #[pyo3(get)]
+    pub total_bytes: usize,
+    usage: MemoryUsage,
+}
+
+impl From<MemoryUsage> for PyMemoryUsage {
+    fn from(usage: MemoryUsage) -> Self {
+        PyMemoryUsage {
+            entities: usage.entities,
+            files: usage.files,
+            entity_bytes: usage.entity_bytes,
+            code_bytes: usage.code_bytes,
+            docstring_bytes: usage.docstring_bytes,
+            string_bytes: usage.string_bytes,
+            shared_bytes: usage.shared_bytes,
+            metadata_bytes: usage.metadata_bytes,
+            index_bytes: usage.index_bytes,
+            total_bytes: usage.total_bytes(),
+            usage,
+        }
+    }
+}
+
+#[pymethods]
+impl PyMemoryUsage {
+    fn __str__(&self) -> String {
+        self.usage.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("MemoryUsage({})", memory::format_bytes(self.total_bytes))
+    }
+}
+
+/// Python-compatible wrapper for a syntax error `Diagnostic`
+#[pyclass(name = "Diagnostic", frozen)]
+#[derive(Clone)]
+pub struct PyDiagnostic {
+    #[pyo3(get)]
+    pub file: String,
+    #[pyo3(get)]
+    pub line: usize,
+    #[pyo3(get)]
+    pub column: usize,
+    #[pyo3(get)]
+    pub message: String,
+    #[pyo3(get)]
+    pub source_line: String,
+    /// The source line with a caret under the column
+    #[pyo3(get)]
+    pub excerpt: String,
+}
+
+impl From<&Diagnostic> for PyDiagnostic {
+    fn from(diagnostic: &Diagnostic) -> Self {
+        PyDiagnostic {
+            file: diagnostic.file.to_string_lossy().to_string(),
+            line: diagnostic.line,
+            column: diagnostic.column,
+            message: diagnostic.message.clone(),
+            source_line: diagnostic.source_line.clone(),
+            excerpt: diagnostic.excerpt(),
+        }
+    }
+}
+
+#[pymethods]
+impl PyDiagnostic {
+    fn __str__(&self) -> String {
+        format!("{}:{}:{}: {}\n{}", self.file, self.line, self.column, self.message, self.excerpt)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Diagnostic({:?}, line={}, column={}, message={:?})", self.file, self.line, self.column, self.message)
+    }
+}
+
+/// Python-compatible wrapper for `RustAnalyzer.last_run_stats`.
+/// Durations are in seconds.
+#[pyclass(name = "RunStats", frozen)]
+pub struct PyRunStats {
+    #[pyo3(get)]
+    pub files: usize,
+    #[pyo3(get)]
+    pub entities: usize,
+    #[pyo3(get)]
+    pub failed: usize,
+    #[pyo3(get)]
+    pub cache_hits: usize,
+    #[pyo3(get)]
+    pub skipped: usize,
+    #[pyo3(get)]
+    pub transcoded: usize,
+    #[pyo3(get)]
+    pub generated: usize,
+    #[pyo3(get)]
+    pub walk: f64,
+    #[pyo3(get)]
+    pub read: f64,
+    #[pyo3(get)]
+    pub parse: f64,
+    #[pyo3(get)]
+    pub extract: f64,
+    #[pyo3(get)]
+    pub convert: f64,
+    #[pyo3(get)]
+    pub total: f64,
+    #[pyo3(get)]
+    pub file_timings: Vec<PyFileTiming>,
+    #[pyo3(get)]
+    pub memory: PyMemoryUsage,
+    /// Syntax errors of the files that failed to parse
+    #[pyo3(get)]
+    pub diagnostics: Vec<PyDiagnostic>,
+    stats: RunStats,
+}
+
+impl From<RunStats> for PyRunStats {
+    fn from(stats: RunStats) -> Self {
+        PyRunStats {
+            files: stats.files,
+            entities: stats.entities,
+            failed: stats.failed,
+            cache_hits: stats.cache_hits,
+            skipped: stats.skipped,
+            transcoded: stats.transcoded,
+            generated: stats.generated,
+            walk: stats.walk.as_secs_f64(),
+            read: stats.read.as_secs_f64(),
+            parse: stats.parse.as_secs_f64(),
+            extract: stats.extract.as_secs_f64(),
+            convert: stats.convert.as_secs_f64(),
+            total: stats.total.as_secs_f64(),
+            file_timings: stats.file_timings.iter().map(PyFileTiming::from).collect(),
+            memory: stats.memory.into(),
+            diagnostics: stats.diagnostics().map(PyDiagnostic::from).collect(),
+            stats,
+        }
+    }
+}
+
+#[pymethods]
+impl PyRunStats {
+    /// The `n` files that took longest, slowest first
+    #[pyo3(signature = (n=10))]
+    fn slowest(&self, n: usize) -> Vec<PyFileTiming> {
+        self.stats.slowest(n).into_iter().map(PyFileTiming::from).collect()
+    }
+
+    fn __str__(&self) -> String {
+        self.stats.to_string()
+    }
+}
+
+/// Python-compatible wrapper for one match from `EntityCollection.grep`
+#[pyclass(name = "GrepMatch", frozen)]
+#[derive(Clone)]
+pub struct PyGrepMatch {
+    #[pyo3(get)]
+    pub entity: PyCodeEntity,
+    /// name, code or docstring
+    #[pyo3(get)]
+    pub field: &'static str,
+    /// Byte offsets of the match within the field text
+    #[pyo3(get)]
+    pub start: usize,
+    #[pyo3(get)]
+    pub end: usize,
+    #[pyo3(get)]
+    pub line: usize,
+    #[pyo3(get)]
+    pub column: usize,
+    #[pyo3(get)]
+    pub text: String,
+}
+
+impl PyGrepMatch {
+    fn new(entity: PyCodeEntity, m: GrepMatch) -> Self {
+        PyGrepMatch {
+            entity,
+            field: m.field.as_str(),
+            start: m.start,
+            end: m.end,
+            line: m.line,
+            column: m.column,
+            text: m.text,
+        }
+    }
+}
+
+#[pymethods]
+impl PyGrepMatch {
+    fn __repr__(&self) -> String {
+        format!("GrepMatch({}.{}:{}:{}, {:?})", self.entity.name, self.field, self.line, self.column, self.text)
+    }
+}
+
+/// Python-compatible wrapper for a `SphinxObject`
+#[pyclass(name = "SphinxObject", frozen)]
+#[derive(Clone)]
+pub struct PySphinxObject {
+    /// module, class, exception, function, method, classmethod,
+    /// staticmethod or property
+    #[pyo3(get)]
+    pub objtype: &'static str,
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub module: String,
+    #[pyo3(get)]
+    pub signature: String,
+    #[pyo3(get)]
+    pub options: BTreeMap<String, String>,
+    #[pyo3(get)]
+    pub bases: Vec<String>,
+    /// Docstring lines for the directive body
+    #[pyo3(get)]
+    pub content: Vec<String>,
+    #[pyo3(get)]
+    pub file_path: String,
+    #[pyo3(get)]
+    pub line_number: usize,
+}
+
+impl From<SphinxObject> for PySphinxObject {
+    fn from(object: SphinxObject) -> Self {
+        PySphinxObject {
+            objtype: object.objtype,
+            name: object.name,
+            module: object.module,
+            signature: object.signature,
+            options: object.options,
+            bases: object.bases,
+            content: object.content,
+            file_path: object.file_path,
+            line_number: object.line_number,
+        }
+    }
+}
+
+#[pymethods]
+impl PySphinxObject {
+    /// The directive header, e.g. `.. py:method:: Client.get(key)`
+    fn directive(&self) -> String {
+        format!(".. py:{}:: {}{}", self.objtype, self.name, self.signature)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("SphinxObject({:?})", self.directive())
+    }
+}
+
+/// An environment variable an entity reads
+#[pyclass(name = "EnvVar", frozen)]
+#[derive(Clone)]
+pub struct PyEnvVar {
+    #[pyo3(get)]
+    pub name: String,
+    /// Literal fallback as text, or None
+    #[pyo3(get)]
+    pub default: Option<String>,
+    #[pyo3(get)]
+    pub line: usize,
+}
+
+impl From<EnvVar> for PyEnvVar {
+    fn from(var: EnvVar) -> Self {
+        PyEnvVar {
+            name: var.name,
+            default: var.default,
+            line: var.line,
+        }
+    }
+}
+
+impl From<PyEnvVar> for EnvVar {
+    fn from(var: PyEnvVar) -> Self {
+        EnvVar {
+            name: var.name,
+            default: var.default,
+            line: var.line,
+        }
+    }
+}
+
+#[pymethods]
+impl PyEnvVar {
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let dict = pyo3::types::PyDict::new_bound(py);
+        dict.set_item("name", &self.name)?;
+        dict.set_item("default", &self.default)?;
+        dict.set_item("line", self.line)?;
+        Ok(dict.into())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("EnvVar({:?}, default={:?})", self.name, self.default)
+    }
+}
+
+/// A log call an entity makes
+#[pyclass(name = "LogEvent", frozen)]
+#[derive(Clone)]
+pub struct PyLogEvent {
+    #[pyo3(get)]
+    pub level: String,
+    /// Message template as written
+    #[pyo3(get)]
+    pub message: String,
+    /// Structured fields passed as keyword arguments
+    #[pyo3(get)]
+    pub fields: Vec<String>,
+    #[pyo3(get)]
+    pub line: usize,
+}
+
+impl From<LogEvent> for PyLogEvent {
+    fn from(event: LogEvent) -> Self {
+        PyLogEvent {
+            level: event.level,
+            message: event.message,
+            fields: event.fields,
+            line: event.line,
+        }
+    }
+}
+
+impl From<PyLogEvent> for LogEvent {
+    fn from(event: PyLogEvent) -> Self {
+        LogEvent {
+            level: event.level,
+            message: event.message,
+            fields: event.fields,
+            line: event.line,
+        }
+    }
+}
+
+#[pymethods]
+impl PyLogEvent {
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let dict = pyo3::types::PyDict::new_bound(py);
+        dict.set_item("level", &self.level)?;
+        dict.set_item("message", &self.message)?;
+        dict.set_item("fields", &self.fields)?;
+        dict.set_item("line", self.line)?;
+        Ok(dict.into())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("LogEvent({:?}, {:?})", self.level, self.message)
+    }
+}
+
+/// An outbound HTTP request an entity makes
+#[pyclass(name = "HttpCall", frozen)]
+#[derive(Clone)]
+pub struct PyHttpCall {
+    /// Upper-case method, or None when not literal
+    #[pyo3(get)]
+    pub method: Option<String>,
+    /// URL when literal
+    #[pyo3(get)]
+    pub url: Option<String>,
+    #[pyo3(get)]
+    pub client: String,
+    #[pyo3(get)]
+    pub line: usize,
+}
+
+impl From<HttpCall> for PyHttpCall {
+    fn from(item: HttpCall) -> Self {
+        PyHttpCall {
+            method: item.method,
+            url: item.url,
+            client: item.client,
+            line: item.line,
+        }
+    }
+}
+
+impl From<PyHttpCall> for HttpCall {
+    fn from(item: PyHttpCall) -> Self {
+        HttpCall {
+            method: item.method,
+            url: item.url,
+            client: item.client,
+            line: item.line,
+        }
+    }
+}
+
+#[pymethods]
+impl PyHttpCall {
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let dict = pyo3::types::PyDict::new_bound(py);
+        dict.set_item("method", &self.method)?;
+        dict.set_item("url", &self.url)?;
+        dict.set_item("client", &self.client)?;
+        dict.set_item("line", self.line)?;
+        Ok(dict.into())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("HttpCall({} {:?})", self.method.as_deref().unwrap_or("?"), self.url.as_deref().unwrap_or("?"))
+    }
+}
+
+/// A security-sensitive call an entity makes
+#[pyclass(name = "SecurityFinding", frozen)]
+#[derive(Clone)]
+pub struct PySecurityFinding {
+    pub rule: SecurityRule,
+    #[pyo3(get)]
+    pub message: String,
+    #[pyo3(get)]
+    pub line: usize,
+}
+
+impl From<SecurityFinding> for PySecurityFinding {
+    fn from(item: SecurityFinding) -> Self {
+        PySecurityFinding {
+            rule: item.rule,
+            message: item.message,
+            line: item.line,
+        }
+    }
+}
+
+impl From<PySecurityFinding> for SecurityFinding {
+    fn from(item: PySecurityFinding) -> Self {
+        SecurityFinding {
+            rule: item.rule,
+            message: item.message,
+            line: item.line,
+        }
+    }
+}
+
+#[pymethods]
+impl PySecurityFinding {
+    /// Rule name, e.g. `shell-injection`
+    #[getter]
+    fn rule(&self) -> &'static str {
+        self.rule.as_str()
+    }
+
+    /// `info`, `warning` or `error`
+    #[getter]
+    fn severity(&self) -> &'static str {
+        self.rule.severity().as_str()
+    }
+
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let dict = pyo3::types::PyDict::new_bound(py);
+        dict.set_item("rule", self.rule.as_str())?;
+        dict.set_item("severity", self.rule.severity().as_str())?;
+        dict.set_item("message", &self.message)?;
+        dict.set_item("line", self.line)?;
+        Ok(dict.into())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("SecurityFinding({:?}, line={})", self.rule.as_str(), self.line)
+    }
+}
+
+/// A file whose header lacks the expected license
+#[pyclass(name = "MissingHeader", frozen)]
+pub struct PyMissingHeader {
+    #[pyo3(get)]
+    pub file_path: String,
+    /// SPDX expression found instead, or None
+    #[pyo3(get)]
+    pub found: Option<String>,
+    #[pyo3(get)]
+    pub has_copyright: bool,
+}
+
+impl From<MissingHeader> for PyMissingHeader {
+    fn from(missing: MissingHeader) -> Self {
+        PyMissingHeader {
+            file_path: missing.file_path,
+            found: missing.found,
+            has_copyright: missing.has_copyright,
+        }
+    }
+}
+
+#[pymethods]
+impl PyMissingHeader {
+    fn __repr__(&self) -> String {
+        format!("MissingHeader({:?}, found={:?})", self.file_path, self.found)
+    }
+}
+
+/// An entity and the tests calling it
+#[pyclass(name = "TestMapping", frozen)]
+pub struct PyTestMapping {
+    #[pyo3(get)]
+    pub entity_id: String,
+    #[pyo3(get)]
+    pub qualified_name: String,
+    /// Entity IDs of the tests
+    #[pyo3(get)]
+    pub tests: Vec<String>,
+}
+
+impl From<TestMapping> for PyTestMapping {
+    fn from(mapping: TestMapping) -> Self {
+        PyTestMapping {
+            entity_id: mapping.entity_id,
+            qualified_name: mapping.qualified_name,
+            tests: mapping.tests,
+        }
+    }
+}
+
+#[pymethods]
+impl PyTestMapping {
+    fn __repr__(&self) -> String {
+        format!("TestMapping({:?}, tests={})", self.qualified_name, self.tests.len())
+    }
+}
+
+/// A row of an endpoint's parameter table
+#[pyclass(name = "EndpointParam", frozen)]
+#[derive(Clone)]
+pub struct PyEndpointParam {
+    #[pyo3(get)]
+    pub name: String,
+    pub location: ParamLocation,
+    #[pyo3(get)]
+    pub annotation: Option<String>,
+    #[pyo3(get)]
+    pub default: Option<String>,
+    #[pyo3(get)]
+    pub required: bool,
+}
+
+impl From<EndpointParam> for PyEndpointParam {
+    fn from(item: EndpointParam) -> Self {
+        PyEndpointParam {
+            name: item.name,
+            location: item.location,
+            annotation: item.annotation,
+            default: item.default,
+            required: item.required,
+        }
+    }
+}
+
+impl From<PyEndpointParam> for EndpointParam {
+    fn from(item: PyEndpointParam) -> Self {
+        EndpointParam {
+            name: item.name,
+            location: item.location,
+            annotation: item.annotation,
+            default: item.default,
+            required: item.required,
+        }
+    }
+}
+
+#[pymethods]
+impl PyEndpointParam {
+    /// `path`, `query`, `body`, `header`, `cookie`, `form`, `file` or `dependency`
+    #[getter]
+    fn location(&self) -> &'static str {
+        self.location.as_str()
+    }
+
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let dict = pyo3::types::PyDict::new_bound(py);
+        dict.set_item("name", &self.name)?;
+        dict.set_item("location", self.location.as_str())?;
+        dict.set_item("annotation", &self.annotation)?;
+        dict.set_item("default", &self.default)?;
+        dict.set_item("required", self.required)?;
+        Ok(dict.into())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("EndpointParam({:?}, in={:?})", self.name, self.location.as_str())
+    }
+}
+
+/// One case of a parametrized test
+#[pyclass(name = "TestCase", frozen)]
+#[derive(Clone)]
+pub struct PyTestCase {
+    #[pyo3(get)]
+    pub id: Option<String>,
+    /// Argument names with their values as written
+    #[pyo3(get)]
+    pub args: Vec<(String, String)>,
+}
+
+impl From<TestCase> for PyTestCase {
+    fn from(item: TestCase) -> Self {
+        PyTestCase {
+            id: item.id,
+            args: item.args,
+        }
+    }
+}
+
+impl From<PyTestCase> for TestCase {
+    fn from(item: PyTestCase) -> Self {
+        TestCase {
+            id: item.id,
+            args: item.args,
+        }
+    }
+}
+
+#[pymethods]
+impl PyTestCase {
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let dict = pyo3::types::PyDict::new_bound(py);
+        dict.set_item("id", &self.id)?;
+        dict.set_item("args", &self.args)?;
+        Ok(dict.into())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("TestCase({:?}, args={})", self.id, self.args.len())
+    }
+}
+
+/// A usage example harvested from a test
+#[pyclass(name = "UsageExample", frozen)]
+#[derive(Clone)]
+pub struct PyUsageExample {
+    /// Dotted name of the API called
+    #[pyo3(get)]
+    pub target: String,
+    #[pyo3(get)]
+    pub code: String,
+    #[pyo3(get)]
+    pub line: usize,
+    /// Entity ID of the test it came from
+    #[pyo3(get)]
+    pub test_id: String,
+}
+
+impl From<UsageExample> for PyUsageExample {
+    fn from(item: UsageExample) -> Self {
+        PyUsageExample {
+            target: item.target,
+            code: item.code,
+            line: item.line,
+            test_id: item.test_id,
+        }
+    }
+}
+
+impl From<PyUsageExample> for UsageExample {
+    fn from(item: PyUsageExample) -> Self {
+        UsageExample {
+            target: item.target,
+            code: item.code,
+            line: item.line,
+            test_id: item.test_id,
+        }
+    }
+}
+
+#[pymethods]
+impl PyUsageExample {
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let dict = pyo3::types::PyDict::new_bound(py);
+        dict.set_item("target", &self.target)?;
+        dict.set_item("code", &self.code)?;
+        dict.set_item("line", &self.line)?;
+        dict.set_item("test_id", &self.test_id)?;
+        Ok(dict.into())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("UsageExample(target={:?}, line={})", self.target, self.line)
+    }
+}
+
+/// One environment variable across the project
+#[pyclass(name = "EnvVarSummary", frozen)]
+pub struct PyEnvVarSummary {
+    #[pyo3(get)]
+    pub name: String,
+    /// Distinct literal fallbacks
+    #[pyo3(get)]
+    pub defaults: Vec<String>,
+    /// Qualified names of the reading entities
+    #[pyo3(get)]
+    pub used_by: Vec<String>,
+}
+
+impl From<EnvVarSummary> for PyEnvVarSummary {
+    fn from(summary: EnvVarSummary) -> Self {
+        PyEnvVarSummary {
+            name: summary.name,
+            defaults: summary.defaults,
+            used_by: summary.used_by,
+        }
+    }
+}
+
+#[pymethods]
+impl PyEnvVarSummary {
+    fn __repr__(&self) -> String {
+        format!("EnvVarSummary({:?}, used_by={})", self.name, self.used_by.len())
+    }
+}
+
+/// Lazy iterator over an `EntityCollection`
+#[pyclass]
+pub struct EntityIterator {
+    collection: Py<PyEntityCollection>,
+    index: usize,
+}
+
+#[pymethods]
+impl EntityIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> Option<PyCodeEntity> {
+        let entity = self.collection.borrow(py).get(self.index)?;
+        self.index += 1;
+        Some(entity)
+    }
+}
+
+/// Python-compatible wrapper for AnalyzerConfig
+#[pyclass(name = "AnalyzerConfig")]
+#[derive(Clone)]
+pub struct PyAnalyzerConfig {
+    #[pyo3(get, set)]
+    pub include: Vec<String>,
+    #[pyo3(get, set)]
+    pub exclude: Vec<String>,
+    #[pyo3(get, set)]
+    pub follow_symlinks: bool,
+    #[pyo3(get, set)]
+    pub threads: Option<usize>,
+    #[pyo3(get, set)]
+    pub shared_pool: bool,
+    #[pyo3(get, set)]
+    pub languages: Vec<String>,
+    #[pyo3(get, set)]
+    pub query_languages: Vec<PyQueryLanguage>,
+    #[pyo3(get, set)]
+    pub docstring_style: String,
+    #[pyo3(get, set)]
+    pub max_complexity: u32,
+    #[pyo3(get, set)]
+    pub min_docstring_coverage: f64,
+    #[pyo3(get, set)]
+    pub lint_severity: BTreeMap<String, String>,
+    #[pyo3(get, set)]
+    pub lint_ignore: Vec<String>,
+    #[pyo3(get, set)]
+    pub visibility_precedence: Vec<String>,
+    #[pyo3(get, set)]
+    pub public_decorators: Vec<String>,
+    #[pyo3(get, set)]
+    pub private_decorators: Vec<String>,
+    #[pyo3(get, set)]
+    pub include_private: bool,
+    #[pyo3(get, set)]
+    pub include_dunder: bool,
+    #[pyo3(get, set)]
+    pub public_only: bool,
+    #[pyo3(get, set)]
+    pub inherit_docstrings: bool,
+    #[pyo3(get, set)]
+    pub git_blame: bool,
+    #[pyo3(get, set)]
+    pub redact_secrets: bool,
+    #[pyo3(get, set)]
+    pub relative_paths: bool,
+    #[pyo3(get, set)]
+    pub skip_generated: bool,
+    #[pyo3(get, set)]
+    pub transcode_latin1: bool,
+    #[pyo3(get, set)]
+    pub lazy_code: bool,
+    #[pyo3(get, set)]
+    pub cache_dir: Option<PathBuf>,
+}
+
+#[pymethods]
+impl PyAnalyzerConfig {
+    #[new]
+    #[pyo3(signature = (
+        include=None,
+        exclude=None,
+        follow_symlinks=None,
+        threads=None,
+        shared_pool=None,
+        languages=None,
+        query_languages=None,
+        docstring_style=None,
+        max_complexity=None,
+        min_docstring_coverage=None,
+        lint_severity=None,
+        lint_ignore=None,
+        visibility_precedence=None,
+        public_decorators=None,
+        private_decorators=None,
+        include_private=None,
+        include_dunder=None,
+        public_only=None,
+        inherit_docstrings=None,
+        git_blame=None,
+        redact_secrets=None,
+        relative_paths=None,
+        skip_generated=None,
+        transcode_latin1=None,
+        lazy_code=None,
+        cache_dir=None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        include: Option<Vec<String>>,
+        exclude: Option<Vec<String>>,
+        follow_symlinks: Option<bool>,
+        threads: Option<usize>,
+        shared_pool: Option<bool>,
+        languages: Option<Vec<String>>,
+        query_languages: Option<Vec<PyQueryLanguage>>,
+        docstring_style: Option<String>,
+        max_complexity: Option<u32>,
+        min_docstring_coverage: Option<f64>,
+        lint_severity: Option<BTreeMap<String, String>>,
+        lint_ignore: Option<Vec<String>>,
+        visibility_precedence: Option<Vec<String>>,
+        public_decorators: Option<Vec<String>>,
+        private_decorators: Option<Vec<String>>,
+        include_private: Option<bool>,
+        include_dunder: Option<bool>,
+        public_only: Option<bool>,
+        inherit_docstrings: Option<bool>,
+        git_blame: Option<bool>,
+        redact_secrets: Option<bool>,
+        relative_paths: Option<bool>,
+        skip_generated: Option<bool>,
+        transcode_latin1: Option<bool>,
+        lazy_code: Option<bool>,
+        cache_dir: Option<PathBuf>,
+    ) -> Self {
+        let defaults = AnalyzerConfig::default();
+        PyAnalyzerConfig {
+            include: include.unwrap_or(defaults.include),
+            exclude: exclude.unwrap_or(defaults.exclude),
+            follow_symlinks: follow_symlinks.unwrap_or(defaults.follow_symlinks),
+            threads: threads.or(defaults.threads),
+            shared_pool: shared_pool.unwrap_or(defaults.shared_pool),
+            languages: languages.unwrap_or(defaults.languages),
+            query_languages: query_languages.unwrap_or_default(),
+            docstring_style: docstring_style.unwrap_or(defaults.docstring_style),
+            max_complexity: max_complexity.unwrap_or(defaults.max_complexity),
+            min_docstring_coverage: min_docstring_coverage.unwrap_or(defaults.min_docstring_coverage),
+            lint_severity: lint_severity.unwrap_or(defaults.lint_severity),
+            lint_ignore: lint_ignore.unwrap_or(defaults.lint_ignore),
+            visibility_precedence: visibility_precedence.unwrap_or(defaults.visibility_precedence),
+            public_decorators: public_decorators.unwrap_or(defaults.public_decorators),
+            private_decorators: private_decorators.unwrap_or(defaults.private_decorators),
+            include_private: include_private.unwrap_or(defaults.include_private),
+            include_dunder: include_dunder.unwrap_or(defaults.include_dunder),
+            public_only: public_only.unwrap_or(defaults.public_only),
+            inherit_docstrings: inherit_docstrings.unwrap_or(defaults.inherit_docstrings),
+            git_blame: git_blame.unwrap_or(defaults.git_blame),
+            redact_secrets: redact_secrets.unwrap_or(defaults.redact_secrets),
+            relative_paths: relative_paths.unwrap_or(defaults.relative_paths),
+            skip_generated: skip_generated.unwrap_or(defaults.skip_generated),
+            transcode_latin1: transcode_latin1.unwrap_or(defaults.transcode_latin1),
+            lazy_code: lazy_code.unwrap_or(defaults.lazy_code),
+            cache_dir: cache_dir.or(defaults.cache_dir),
+        }
+    }
+
+    /// Load the `[analyzer]` section of an `autodoc.toml` file
+    #[staticmethod]
+    fn from_file(path: &str) -> PyResult<Self> {
+        let config = AnalyzerConfig::from_file(Path::new(path))
+            .map_err(|e| RustAnalysisError::new_err(format!("{:#}", e)))?;
+        Ok(config.into())
+    }
+}
+
+impl From<AnalyzerConfig> for PyAnalyzerConfig {
+    fn from(config: AnalyzerConfig) -> Self {
+        PyAnalyzerConfig {
+            include: config.include,
+            exclude: config.exclude,
+            follow_symlinks: config.follow_symlinks,
+            threads: config.threads,
+            shared_pool: config.shared_pool,
+            languages: config.languages,
+            query_languages: config.query_languages.into_iter().map(Into::into).collect(),
+            docstring_style: config.docstring_style,
+            max_complexity: config.max_complexity,
+            min_docstring_coverage: config.min_docstring_coverage,
+            lint_severity: config.lint_severity,
+            lint_ignore: config.lint_ignore,
+            visibility_precedence: config.visibility_precedence,
+            public_decorators: config.public_decorators,
+            private_decorators: config.private_decorators,
+            include_private: config.include_private,
+            include_dunder: config.include_dunder,
+            public_only: config.public_only,
+            inherit_docstrings: config.inherit_docstrings,
+            git_blame: config.git_blame,
+            redact_secrets: config.redact_secrets,
+            relative_paths: config.relative_paths,
+            skip_generated: config.skip_generated,
+            transcode_latin1: config.transcode_latin1,
+            lazy_code: config.lazy_code,
+            cache_dir: config.cache_dir,
+        }
+    }
+}
+
+impl From<PyAnalyzerConfig> for AnalyzerConfig {
+    fn from(config: PyAnalyzerConfig) -> Self {
+        AnalyzerConfig {
+            include: config.include,
+            exclude: config.exclude,
+            follow_symlinks: config.follow_symlinks,
+            threads: config.threads,
+            shared_pool: config.shared_pool,
+            languages: config.languages,
+            query_languages: config.query_languages.into_iter().map(Into::into).collect(),
+            docstring_style: config.docstring_style,
+            max_complexity: config.max_complexity,
+            min_docstring_coverage: config.min_docstring_coverage,
+            lint_severity: config.lint_severity,
+            lint_ignore: config.lint_ignore,
+            visibility_precedence: config.visibility_precedence,
+            public_decorators: config.public_decorators,
+            private_decorators: config.private_decorators,
+            include_private: config.include_private,
+            include_dunder: config.include_dunder,
+            public_only: config.public_only,
+            inherit_docstrings: config.inherit_docstrings,
+            git_blame: config.git_blame,
+            redact_secrets: config.redact_secrets,
+            relative_paths: config.relative_paths,
+            skip_generated: config.skip_generated,
+            transcode_latin1: config.transcode_latin1,
+            lazy_code: config.lazy_code,
+            cache_dir: config.cache_dir,
+        }
+    }
+}
+
+/// Python-compatible wrapper for QueryLanguage: a language parsed with a
+/// bundled tree-sitter grammar and entities extracted by a query
+#[pyclass(name = "QueryLanguage")]
+#[derive(Clone)]
+pub struct PyQueryLanguage {
+    #[pyo3(get, set)]
+    pub name: String,
+    #[pyo3(get, set)]
+    pub grammar: String,
+    #[pyo3(get, set)]
+    pub extensions: Vec<String>,
+    #[pyo3(get, set)]
+    pub query: Option<String>,
+    #[pyo3(get, set)]
+    pub query_file: Option<PathBuf>,
+}
+
+#[pymethods]
+impl PyQueryLanguage {
+    #[new]
+    #[pyo3(signature = (name, grammar, extensions, query=None, query_file=None))]
+    fn new(
+        name: String,
+        grammar: String,
+        extensions: Vec<String>,
+        query: Option<String>,
+        query_file: Option<PathBuf>,
+    ) -> Self {
+        PyQueryLanguage { name, grammar, extensions, query, query_file }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("QueryLanguage({:?}, grammar={:?})", self.name, self.grammar)
+    }
+}
+
+impl From<QueryLanguage> for PyQueryLanguage {
+    fn from(language: QueryLanguage) -> Self {
+        PyQueryLanguage {
+            name: language.name,
+            grammar: language.grammar,
+            extensions: language.extensions,
+            query: language.query,
+            query_file: language.query_file,
+        }
+    }
+}
+
+impl From<PyQueryLanguage> for QueryLanguage {
+    fn from(language: PyQueryLanguage) -> Self {
+        QueryLanguage {
+            name: language.name,
+            grammar: language.grammar,
+            extensions: language.extensions,
+            query: language.query,
+            query_file: language.query_file,
+        }
+    }
+}
+
+/// Python-compatible wrapper for RustAnalyzer
+#[pyclass(name = "RustAnalyzer")]
+pub struct PyRustAnalyzer {
+    analyzer: Arc<RustAnalyzer>,
+    hooks: Vec<PyObject>,
+}
+
+#[pymethods]
+impl PyRustAnalyzer {
+    #[new]
+    #[pyo3(signature = (exclude_patterns=None, config=None, threads=None, shared_pool=false))]
+    fn new(
+        exclude_patterns: Option<Vec<String>>,
+        config: Option<PyAnalyzerConfig>,
+        threads: Option<usize>,
+        shared_pool: bool,
+    ) -> PyResult<Self> {
+        let mut analyzer = match config {
+            Some(config) => RustAnalyzer::try_with_config(config.into()).map_err(analysis_error)?,
+            None => RustAnalyzer::new(),
+        };
+        if let Some(patterns) = exclude_patterns {
+            let pattern_refs: Vec<&str> = patterns.iter().map(|s| s.as_str()).collect();
+            analyzer = analyzer.with_excludes(pattern_refs);
+        }
+        if shared_pool {
+            analyzer = analyzer.with_shared_pool();
+        }
+        if let Some(threads) = threads {
+            analyzer = analyzer.with_threads(threads);
+        }
+        Ok(PyRustAnalyzer {
+            analyzer: Arc::new(analyzer),
+            hooks: Vec::new(),
+        })
+    }
+
+    /// Number of worker threads used by `analyze_directory`
+    #[getter]
+    fn num_threads(&self) -> usize {
+        self.analyzer.num_threads()
+    }
+
+    /// Register a callable invoked with each entity before it is returned.
+    ///
+    /// The hook may mutate the entity in place and return `None`, return a
+    /// replacement `CodeEntity`, or return `False` to drop the entity.
+    fn add_hook(&mut self, hook: PyObject) {
+        self.hooks.push(hook);
+    }
+
+    fn analyze_file(&self, py: Python<'_>, file_path: &str) -> PyResult<PyEntityCollection> {
+        let entities = self.analyzer.analyze_file(Path::new(file_path))
+            .map_err(analysis_error)?;
+        
+        convert_timed(py, &self.analyzer, &self.hooks, entities)
+    }
+
+    #[pyo3(signature = (source, path="<string>"))]
+    fn analyze_source(&self, py: Python<'_>, source: &str, path: &str) -> PyResult<PyEntityCollection> {
+        let entities = self.analyzer.analyze_source(source, Path::new(path))
+            .map_err(analysis_error)?;
+        
+        Ok(apply_py_hooks(py, &self.hooks, entities)?.with_pool(self.analyzer.thread_pool()))
+    }
+
+    fn analyze_directory(&self, py: Python<'_>, dir_path: &str) -> PyResult<PyEntityCollection> {
+        let entities = self.analyzer.analyze_directory(Path::new(dir_path))
+            .map_err(|e| RustAnalysisError::new_err(e.to_string()))?;
+        
+        convert_timed(py, &self.analyzer, &self.hooks, entities)
+    }
+
+    /// Timings of the last `analyze_file` or `analyze_directory` call, or
+    /// None before the first one
+    fn last_run_stats(&self) -> Option<PyRunStats> {
+        self.analyzer.last_run_stats().map(PyRunStats::from)
+    }
+
+    /// Analyze a directory without blocking the running asyncio event loop.
+    ///
+    /// The analysis is offloaded to the loop's default executor and the GIL is
+    /// released while Rust does the work, so the returned future can be awaited
+    /// from async web handlers or notebook cells.
+    fn analyze_directory_async<'py>(&self, py: Python<'py>, dir_path: &str) -> PyResult<Bound<'py, PyAny>> {
+        let job = AnalysisJob {
+            analyzer: Arc::clone(&self.analyzer),
+            dir_path: PathBuf::from(dir_path),
+            hooks: self.hooks.iter().map(|h| h.clone_ref(py)).collect(),
+        };
+        
+        let event_loop = py.import_bound("asyncio")?.call_method0("get_running_loop")?;
+        event_loop.call_method1("run_in_executor", (py.None(), Py::new(py, job)?))
+    }
+}
+
+/// Deferred directory analysis executed on an executor thread
+#[pyclass]
+pub struct AnalysisJob {
+    analyzer: Arc<RustAnalyzer>,
+    dir_path: PathBuf,
+    hooks: Vec<PyObject>,
+}
+
+#[pymethods]
+impl AnalysisJob {
+    fn __call__(&self, py: Python<'_>) -> PyResult<PyEntityCollection> {
+        let analyzer = Arc::clone(&self.analyzer);
+        let dir_path = self.dir_path.clone();
+        let entities = py.allow_threads(move || analyzer.analyze_directory(&dir_path))
+            .map_err(|e| RustAnalysisError::new_err(e.to_string()))?;
+        
+        convert_timed(py, &self.analyzer, &self.hooks, entities)
+    }
+}
+
+/// `apply_py_hooks`, counting the time as conversion in the analyzer's run stats
+fn convert_timed(
+    py: Python<'_>,
+    analyzer: &RustAnalyzer,
+    hooks: &[PyObject],
+    entities: Vec<CodeEntity>,
+) -> PyResult<PyEntityCollection> {
+    let start = Instant::now();
+    let collection = apply_py_hooks(py, hooks, entities).map(|c| c.with_pool(analyzer.thread_pool()));
+    analyzer.record_convert_time(start.elapsed());
+    collection
+}
+
+/// Pass entities through the registered Python hooks and collect the survivors
+fn apply_py_hooks(py: Python<'_>, hooks: &[PyObject], entities: Vec<CodeEntity>) -> PyResult<PyEntityCollection> {
+    if hooks.is_empty() {
+        return Ok(PyEntityCollection::new(entities));
+    }
+    
+    let mut results = Vec::with_capacity(entities.len());
+    'entities: for entity in convert_entities(entities) {
+        let mut entity = Bound::new(py, entity)?;
+        
+        for hook in hooks {
+            let result = hook.bind(py).call1((entity.clone(),))?;
+            if result.is_none() {
+                continue;
+            }
+            if let Ok(replacement) = result.downcast::<PyCodeEntity>() {
+                entity = replacement.clone();
+            } else if let Ok(keep) = result.extract::<bool>() {
+                if !keep {
+                    continue 'entities;
+                }
+            } else {
+                return Err(PyTypeError::new_err(
+                    "entity hooks must return None, a bool, or a CodeEntity",
+                ));
+            }
+        }
+        
+        results.push(entity.borrow().to_entity());
+    }
+    
+    Ok(PyEntityCollection::new(results))
+}
+
+impl PyCodeEntity {
+    /// Convert an entity; type and decorators are already interned, the
+    /// path string is shared through `interner`
+    pub fn from_entity(entity: CodeEntity, interner: &mut StringInterner) -> Self {
+        PyCodeEntity {
+            entity_id: entity.entity_id,
+            content_hash: entity.content_hash,
+            source_hash: entity.source_hash,
+            entity_type: entity.entity_type.into_arc(),
+            name: entity.name,
+            qualified_name: entity.qualified_name,
+            module_path: entity.module_path,
+            file_path: interner.intern(&entity.file_path.to_string_lossy()),
+            absolute_path: entity.absolute_path.map(|path| interner.intern(&path.to_string_lossy())),
+            line_number: entity.line_number,
+            end_line: entity.end_line,
+            start_offset: entity.start_offset,
+            end_offset: entity.end_offset,
+            column: entity.column,
+            end_column: entity.end_column,
+            parent_id: entity.parent_id,
+            children: entity.children,
+            bases: entity.bases,
+            overrides: entity.overrides,
+            override_change: entity.override_change,
+            docstring: entity.docstring,
+            code: entity.code,
+            code_span: entity.code_span,
+            is_async: entity.is_async,
+            decorators: entity.decorators.into_iter().map(Symbol::into_arc).collect(),
+            parameters: entity.parameters,
+            parameter_types: entity.parameter_types,
+            return_type: entity.return_type,
+            is_internal: entity.is_internal,
+            is_api_endpoint: entity.is_api_endpoint,
+            route_path: entity.endpoint_path,
+            http_methods: entity.http_methods,
+            endpoint_params: entity.endpoint_params.into_iter().map(PyEndpointParam::from).collect(),
+            api_version: entity.api_version,
+            response_model: entity.response_model,
+            serializers: entity.serializers,
+            request_models: entity.request_models,
+            response_models: entity.response_models,
+            used_by_endpoints: entity.used_by_endpoints,
+            auth_required: entity.auth_required,
+            auth_scheme: entity.auth_scheme,
+            permissions: entity.permissions,
+            exercises: entity.exercises,
+            tested_by: entity.tested_by,
+            test_cases: entity.test_cases.into_iter().map(PyTestCase::from).collect(),
+            harvested_examples: entity.harvested_examples.into_iter().map(PyUsageExample::from).collect(),
+            examples: entity.examples.into_iter().map(PyUsageExample::from).collect(),
+            complexity_score: entity.complexity_score,
+            is_deprecated: entity.is_deprecated,
+            replacement: entity.replacement,
+            is_generated: entity.is_generated,
+            has_custom_init: entity.has_custom_init,
+            is_exception: entity.is_exception,
+            is_abstract: entity.is_abstract,
+            is_mixin: entity.is_mixin,
+            is_singleton: entity.is_singleton,
+            inherited_doc: entity.inherited_doc,
+            env_vars: entity.env_vars.into_iter().map(PyEnvVar::from).collect(),
+            touches_database: entity.touches_database,
+            tables: entity.tables,
+            makes_external_calls: entity.makes_external_calls,
+            http_calls: entity.http_calls.into_iter().map(PyHttpCall::from).collect(),
+            security_findings: entity.security_findings.into_iter().map(PySecurityFinding::from).collect(),
+            feature_flags: entity.feature_flags,
+            log_events: entity.log_events.into_iter().map(PyLogEvent::from).collect(),
+            metadata: entity.metadata,
+        }
+    }
+
+    /// `code`, sliced from the file without caching it when deferred
+    fn code_text(&self) -> PyResult<String> {
+        match self.code_span {
+            Some(span) => lazy::load_span(Path::new(self.absolute_path.as_deref().unwrap_or(&*self.file_path)), span)
+                .map_err(|e| RustAnalysisError::new_err(format!("{:#}", e))),
+            None => Ok(self.code.clone()),
+        }
+    }
+
+    /// Convert back into the Rust entity model
+    pub fn to_entity(&self) -> CodeEntity {
+        let mut entity = CodeEntity::new(
+            &*self.entity_type,
+            self.name.clone(),
+            Path::new(&*self.file_path),
+            self.line_number,
+        );
+        entity.entity_id = self.entity_id.clone();
+        entity.content_hash = self.content_hash.clone();
+        entity.source_hash = self.source_hash.clone();
+        entity.qualified_name = self.qualified_name.clone();
+        entity.module_path = self.module_path.clone();
+        entity.absolute_path = self.absolute_path.as_deref().map(|path| Path::new(path).into());
+        entity.end_line = self.end_line;
+        entity.start_offset = self.start_offset;
+        entity.end_offset = self.end_offset;
+        entity.column = self.column;
+        entity.end_column = self.end_column;
+        entity.parent_id = self.parent_id.clone();
+        entity.children = self.children.clone();
+        entity.bases = self.bases.clone();
+        entity.overrides = self.overrides.clone();
+        entity.override_change = self.override_change.clone();
+        entity.docstring = self.docstring.clone();
+        entity.code = self.code.clone();
+        entity.code_span = self.code_span;
+        entity.is_async = self.is_async;
+        entity.decorators = self.decorators.iter().map(|d| Symbol::new(d)).collect();
+        entity.parameters = self.parameters.clone();
+        entity.parameter_types = self.parameter_types.clone();
+        entity.return_type = self.return_type.clone();
+        entity.is_internal = self.is_internal;
+        entity.is_api_endpoint = self.is_api_endpoint;
+        entity.endpoint_path = self.route_path.clone();
+        entity.http_methods = self.http_methods.clone();
+        entity.endpoint_params = self.endpoint_params.iter().cloned().map(EndpointParam::from).collect();
+        entity.api_version = self.api_version.clone();
+        entity.response_model = self.response_model.clone();
+        entity.serializers = self.serializers.clone();
+        entity.request_models = self.request_models.clone();
+        entity.response_models = self.response_models.clone();
+        entity.used_by_endpoints = self.used_by_endpoints.clone();
+        entity.auth_required = self.auth_required;
+        entity.auth_scheme = self.auth_scheme.clone();
+        entity.permissions = self.permissions.clone();
+        entity.exercises = self.exercises.clone();
+        entity.tested_by = self.tested_by.clone();
+        entity.test_cases = self.test_cases.iter().cloned().map(TestCase::from).collect();
+        entity.harvested_examples = self.harvested_examples.iter().cloned().map(UsageExample::from).collect();
+        entity.examples = self.examples.iter().cloned().map(UsageExample::from).collect();
+        entity.complexity_score = self.complexity_score;
+        entity.is_deprecated = self.is_deprecated;
+        entity.replacement = self.replacement.clone();
+        entity.is_generated = self.is_generated;
+        entity.has_custom_init = self.has_custom_init;
+        entity.is_exception = self.is_exception;
+        entity.is_abstract = self.is_abstract;
+        entity.is_mixin = self.is_mixin;
+        entity.is_singleton = self.is_singleton;
+        entity.inherited_doc = self.inherited_doc;
+        entity.env_vars = self.env_vars.iter().cloned().map(EnvVar::from).collect();
+        entity.touches_database = self.touches_database;
+        entity.tables = self.tables.clone();
+        entity.makes_external_calls = self.makes_external_calls;
+        entity.http_calls = self.http_calls.iter().cloned().map(HttpCall::from).collect();
+        entity.security_findings = self.security_findings.iter().cloned().map(SecurityFinding::from).collect();
+        entity.feature_flags = self.feature_flags.clone();
+        entity.log_events = self.log_events.iter().cloned().map(LogEvent::from).collect();
+        entity.metadata = self.metadata.clone();
+        entity
+    }
+}
+
+/// Convert a batch of entities with one interner shared across the batch
+fn convert_entities(entities: Vec<CodeEntity>) -> Vec<PyCodeEntity> {
+    let mut interner = StringInterner::new();
+    entities
+        .into_iter()
+        .map(|e| PyCodeEntity::from_entity(e, &mut interner))
+        .collect()
+}
+
+impl From<CodeEntity> for PyCodeEntity {
+    fn from(entity: CodeEntity) -> Self {
+        PyCodeEntity::from_entity(entity, &mut StringInterner::new())
+    }
+}
@@ -0,0 +1,346 @@
+use anyhow::Result;
+use glob::Pattern;
+use rayon::prelude::*;
+
+use crate::entity::CodeEntity;
+
+/// A parsed entity query such as
+/// `type:function is:public decorator:route complexity:>10 path:src/api/**`.
+///
+/// Terms are whitespace-separated and must all match; `OR` separates
+/// alternatives and a leading `-` negates a term. Values containing spaces
+/// can be quoted (`doc:"retry backoff"`). Bare words match the name.
+///
+/// | Term | Matches |
+/// |------|---------|
+/// | `type:` / `kind:` | entity type |
+/// | `name:` | name, as a glob when it contains `*`/`?`, else a substring |
+/// | `is:` | `public`, `private`, `async`, `endpoint`, `deprecated`, `generated`, `documented`, `undocumented`, `exception`, `abstract`, `mixin`, `singleton`, `database`, `external`, `flagged`, `auth`, `tested` |
+/// | `decorator:` | substring of any decorator |
+/// | `path:` | file path glob (or substring) |
+/// | `doc:` | docstring substring |
+/// | `method:` / `route:` | HTTP method / endpoint path prefix |
+/// | `meta:key` / `meta:key=value` | metadata presence or value |
+/// | `complexity:` / `params:` / `line:` | number, `>N`, `>=N`, `<N`, `<=N` or `N..M` |
+#[derive(Debug, Clone)]
+pub struct Query {
+    alternatives: Vec<Vec<Term>>,
+}
+
+#[derive(Debug, Clone)]
+struct Term {
+    negated: bool,
+    filter: Filter,
+}
+
+#[derive(Debug, Clone)]
+enum Filter {
+    Type(String),
+    Name(TextMatch),
+    Is(Flag),
+    Decorator(String),
+    Path(TextMatch),
+    Doc(String),
+    Method(String),
+    Route(String),
+    Meta(String, Option<String>),
+    Complexity(Comparison),
+    Params(Comparison),
+    Line(Comparison),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Flag {
+    Public,
+    Private,
+    Async,
+    Endpoint,
+    Deprecated,
+    Generated,
+    Documented,
+    Undocumented,
+    Exception,
+    Abstract,
+    Mixin,
+    Singleton,
+    Database,
+    External,
+    Flagged,
+    Auth,
+    Tested,
+}
+
+#[derive(Debug, Clone)]
+enum TextMatch {
+    Glob(Pattern),
+    Contains(String),
+}
+
+impl TextMatch {
+    fn new(value: &str) -> Result<Self> {
+        if value.contains(['*', '?', '[']) {
+            let pattern = Pattern::new(value).map_err(|e| anyhow::anyhow!("Invalid pattern {:?}: {}", value, e))?;
+            Ok(TextMatch::Glob(pattern))
+        } else {
+            Ok(TextMatch::Contains(value.to_lowercase()))
+        }
+    }
+
+    fn matches(&self, text: &str) -> bool {
+        match self {
+            TextMatch::Glob(pattern) => pattern.matches(text),
+            TextMatch::Contains(needle) => text.to_lowercase().contains(needle),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Comparison {
+    Eq(u64),
+    Gt(u64),
+    Ge(u64),
+    Lt(u64),
+    Le(u64),
+    Range(u64, u64),
+}
+
+impl Comparison {
+    fn parse(value: &str) -> Result<Self> {
+        let number = |s: &str| {
+            s.trim()
+                .parse::<u64>()
+                .map_err(|_| anyhow::anyhow!("Expected a number in {:?}", value))
+        };
+        Ok(if let Some(rest) = value.strip_prefix(">=") {
+            Comparison::Ge(number(rest)?)
+        } else if let Some(rest) = value.strip_prefix("<=") {
+            Comparison::Le(number(rest)?)
+        } else if let Some(rest) = value.strip_prefix('>') {
+            Comparison::Gt(number(rest)?)
+        } else if let Some(rest) = value.strip_prefix('<') {
+            Comparison::Lt(number(rest)?)
+        } else if let Some((low, high)) = value.split_once("..") {
+            Comparison::Range(number(low)?, number(high)?)
+        } else {
+            Comparison::Eq(number(value.trim_start_matches('='))?)
+        })
+    }
+
+    fn matches(&self, n: u64) -> bool {
+        match *self {
+            Comparison::Eq(x) => n == x,
+            Comparison::Gt(x) => n > x,
+            Comparison::Ge(x) => n >= x,
+            Comparison::Lt(x) => n < x,
+            Comparison::Le(x) => n <= x,
+            Comparison::Range(low, high) => (low..=high).contains(&n),
+        }
+    }
+}
+
+impl std::str::FromStr for Query {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Query::parse(s)
+    }
+}
+
+impl Query {
+    /// Parse a query string; an empty query matches everything
+    pub fn parse(query: &str) -> Result<Self> {
+        let mut alternatives = vec![Vec::new()];
+        for token in tokenize(query)? {
+            if token == "OR" {
+                alternatives.push(Vec::new());
+                continue;
+            }
+            let term = parse_term(&token)?;
+            alternatives.last_mut().expect("at least one alternative").push(term);
+        }
+        if alternatives.len() > 1 && alternatives.iter().any(Vec::is_empty) {
+            return Err(anyhow::anyhow!("OR needs a term on both sides"));
+        }
+        Ok(Query { alternatives })
+    }
+
+    /// Whether `entity` satisfies the query
+    pub fn matches(&self, entity: &CodeEntity) -> bool {
+        self.alternatives
+            .iter()
+            .any(|terms| terms.iter().all(|term| term.filter.matches(entity) != term.negated))
+    }
+
+    /// Matching entities, evaluated in parallel and kept in their original order
+    pub fn filter<'a>(&self, entities: &'a [CodeEntity]) -> Vec<&'a CodeEntity> {
+        entities.par_iter().filter(|e| self.matches(e)).collect()
+    }
+}
+
+fn parse_term(token: &str) -> Result<Term> {
+    let (negated, token) = match token.strip_prefix('-') {
+        Some(rest) if !rest.is_empty() => (true, rest),
+        _ => (false, token),
+    };
+    let Some((key, value)) = token.split_once(':') else {
+        return Ok(Term {
+            negated,
+            filter: Filter::Name(TextMatch::new(token)?),
+        });
+    };
+    if value.is_empty() {
+        return Err(anyhow::anyhow!("Missing value for `{}:`", key));
+    }
+
+    let filter = match key.to_ascii_lowercase().as_str() {
+        "type" | "kind" => Filter::Type(value.to_ascii_lowercase()),
+        "name" => Filter::Name(TextMatch::new(value)?),
+        "is" => Filter::Is(match value.to_ascii_lowercase().as_str() {
+            "public" => Flag::Public,
+            "private" | "internal" => Flag::Private,
+            "async" => Flag::Async,
+            "endpoint" | "api" => Flag::Endpoint,
+            "deprecated" => Flag::Deprecated,
+            "generated" => Flag::Generated,
+            "documented" => Flag::Documented,
+            "undocumented" => Flag::Undocumented,
+            "exception" => Flag::Exception,
+            "abstract" => Flag::Abstract,
+            "mixin" => Flag::Mixin,
+            "singleton" => Flag::Singleton,
+            "database" | "db" => Flag::Database,
+            "external" => Flag::External,
+            "flagged" => Flag::Flagged,
+            "auth" | "authenticated" => Flag::Auth,
+            "tested" => Flag::Tested,
+            other => return Err(anyhow::anyhow!("Unknown flag `is:{}`", other)),
+        }),
+        "decorator" => Filter::Decorator(value.to_lowercase()),
+        "path" | "file" => Filter::Path(TextMatch::new(value)?),
+        "doc" => Filter::Doc(value.to_lowercase()),
+        "method" => Filter::Method(value.to_ascii_uppercase()),
+        "route" => Filter::Route(value.to_string()),
+        "meta" => match value.split_once('=') {
+            Some((k, v)) => Filter::Meta(k.to_string(), Some(v.to_string())),
+            None => Filter::Meta(value.to_string(), None),
+        },
+        "complexity" => Filter::Complexity(Comparison::parse(value)?),
+        "params" => Filter::Params(Comparison::parse(value)?),
+        "line" => Filter::Line(Comparison::parse(value)?),
+        other => return Err(anyhow::anyhow!("Unknown query field `{}`", other)),
+    };
+    Ok(Term { negated, filter })
+}
+
+impl Filter {
+    fn matches(&self, entity: &CodeEntity) -> bool {
+        match self {
+            Filter::Type(kind) => entity.entity_type.eq_ignore_ascii_case(kind),
+            Filter::Name(matcher) => matcher.matches(&entity.name),
+            Filter::Is(flag) => match flag {
+                Flag::Public => entity.is_public(),
+                Flag::Private => !entity.is_public(),
+                Flag::Async => entity.is_async,
+                Flag::Endpoint => entity.is_api_endpoint,
+                Flag::Deprecated => entity.is_deprecated,
+                Flag::Generated => entity.is_generated,
+                Flag::Documented => entity.docstring.as_deref().map_or(false, |d| !d.trim().is_empty()),
+                Flag::Undocumented => entity.docstring.as_deref().map_or(true, |d| d.trim().is_empty()),
+                Flag::Exception => entity.is_exception,
+                Flag::Abstract => entity.is_abstract,
+                Flag::Mixin => entity.is_mixin,
+                Flag::Singleton => entity.is_singleton,
+                Flag::Database => entity.touches_database,
+                Flag::External => entity.makes_external_calls,
+                Flag::Flagged => !entity.feature_flags.is_empty(),
+                Flag::Auth => entity.auth_required,
+                Flag::Tested => !entity.tested_by.is_empty(),
+            },
+            Filter::Decorator(needle) => entity.decorators.iter().any(|d| d.to_lowercase().contains(needle)),
+            Filter::Path(matcher) => matcher.matches(&entity.file_path.to_string_lossy().replace('\\', "/")),
+            Filter::Doc(needle) => entity
+                .docstring
+                .as_deref()
+                .map_or(false, |d| d.to_lowercase().contains(needle)),
+            Filter::Method(method) => entity.http_methods.iter().any(|m| m == method),
+            Filter::Route(prefix) => entity.endpoint_path.as_deref().map_or(false, |p| p.starts_with(prefix)),
+            Filter::Meta(key, value) => match (entity.metadata.get(key), value) {
+                (Some(actual), Some(expected)) => actual == expected,
+                (Some(_), None) => true,
+                (None, _) => false,
+            },
+            Filter::Complexity(cmp) => cmp.matches(u64::from(entity.complexity_score)),
+            Filter::Params(cmp) => cmp.matches(entity.parameters.len() as u64),
+            Filter::Line(cmp) => cmp.matches(entity.line_number as u64),
+        }
+    }
+}
+
+/// Split on whitespace outside double quotes, dropping the quotes
+fn tokenize(query: &str) -> Result<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in query.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if quoted {
+        return Err(anyhow::anyhow!("Unterminated quote in query"));
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn entity(name: &str, path: &str, complexity: u32) -> CodeEntity {
+        let mut entity = CodeEntity::new("function".to_string(), name.to_string(), PathBuf::from(path), 1);
+        entity.complexity_score = complexity;
+        entity
+    }
+
+    #[test]
+    fn test_query_terms() {
+        let mut route = entity("list_users", "src/api/users.py", 12);
+        route.decorators = vec!["app.route(\"/users\")".into()];
+        route.docstring = Some("Retry with backoff.".to_string());
+        let entities = vec![route, entity("_helper", "src/api/util.py", 20), entity("main", "cli.py", 3)];
+
+        let names = |q: &str| -> Vec<String> {
+            Query::parse(q).unwrap().filter(&entities).iter().map(|e| e.name.clone()).collect()
+        };
+
+        assert_eq!(
+            names("type:function is:public decorator:route complexity:>10 path:src/api/**"),
+            vec!["list_users"]
+        );
+        assert_eq!(names("complexity:>=12 -is:public"), vec!["_helper"]);
+        assert_eq!(names("doc:\"with backoff\""), vec!["list_users"]);
+        assert_eq!(names("main OR name:_*"), vec!["_helper", "main"]);
+        assert_eq!(names("complexity:1..5"), vec!["main"]);
+        assert_eq!(names("").len(), 3);
+    }
+
+    #[test]
+    fn test_invalid_queries() {
+        assert!(Query::parse("colour:red").is_err());
+        assert!(Query::parse("complexity:>lots").is_err());
+        assert!(Query::parse("is:shiny").is_err());
+        assert!(Query::parse("doc:\"open").is_err());
+        assert!(Query::parse("OR main").is_err());
+    }
+}
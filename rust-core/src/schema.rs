@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::entity::CodeEntity;
+
+/// `entity_type` of table definitions extracted from SQL and migrations
+pub const SCHEMA_ENTITY: &str = "schema";
+
+/// Build a `schema` entity for a table created or altered at `line_number`.
+///
+/// Column names become parameters; `metadata["columns"]` lists `name type` pairs
+/// and `metadata["table"]` holds the normalized table name.
+pub fn table_entity(
+    table: &str,
+    columns: &[(String, String)],
+    code: String,
+    file_path: &Path,
+    line_number: usize,
+) -> CodeEntity {
+    let mut entity = CodeEntity::new(
+        SCHEMA_ENTITY.to_string(),
+        table.to_string(),
+        file_path.to_path_buf(),
+        line_number,
+    );
+    entity.code = code;
+    entity.parameters = columns.iter().map(|(name, _)| name.clone()).collect();
+    entity.metadata.insert("table".to_string(), normalize_table(table));
+    entity.metadata.insert(
+        "columns".to_string(),
+        columns
+            .iter()
+            .map(|(name, ty)| format!("{} {}", name, ty).trim().to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+    );
+    entity
+}
+
+/// Lower-cased table name without quoting or a schema qualifier: `"public"."Users"` → `users`
+pub fn normalize_table(table: &str) -> String {
+    let unquoted: String = table.chars().filter(|c| !matches!(c, '"' | '`' | '[' | ']')).collect();
+    unquoted.rsplit('.').next().unwrap_or(&unquoted).to_lowercase()
+}
+
+/// Cross-link schema entities and ORM models mapped to the same table.
+///
+/// Models are classes carrying `metadata["table"]` (from `__tablename__` or
+/// Django's `Meta.db_table`). Schema entities get the models' keys in
+/// `metadata["models"]`; models get the defining locations in `metadata["schema"]`.
+pub fn link_models(entities: &mut [CodeEntity]) {
+    let mut models: HashMap<String, Vec<String>> = HashMap::new();
+    let mut schemas: HashMap<String, Vec<String>> = HashMap::new();
+
+    for entity in entities.iter() {
+        let Some(table) = entity.metadata.get("table") else { continue };
+        let location = entity.file_path.to_string_lossy().replace('\\', "/");
+        if entity.entity_type == SCHEMA_ENTITY {
+            schemas.entry(table.clone()).or_default().push(format!("{}:{}", location, entity.line_number));
+        } else if entity.entity_type == "class" {
+            models.entry(table.clone()).or_default().push(format!("{}::{}", location, entity.name));
+        }
+    }
+
+    for entity in entities.iter_mut() {
+        let Some(table) = entity.metadata.get("table") else { continue };
+        let (key, links) = if entity.entity_type == SCHEMA_ENTITY {
+            ("models", models.get(table))
+        } else {
+            ("schema", schemas.get(table))
+        };
+        if let Some(links) = links {
+            let joined = links.join(",");
+            entity.metadata.insert(key.to_string(), joined);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_link_models() {
+        let columns = vec![("id".to_string(), "INTEGER".to_string())];
+        let schema = table_entity("public.\"Users\"", &columns, "CREATE TABLE users".to_string(), Path::new("schema.sql"), 3);
+        let mut model = CodeEntity::new("class".to_string(), "User".to_string(), PathBuf::from("models.py"), 10);
+        model.metadata.insert("table".to_string(), "users".to_string());
+
+        let mut entities = vec![schema, model];
+        link_models(&mut entities);
+
+        assert_eq!(entities[0].metadata["columns"], "id INTEGER");
+        assert_eq!(entities[0].metadata["models"], "models.py::User");
+        assert_eq!(entities[1].metadata["schema"], "schema.sql:3");
+    }
+}
@@ -0,0 +1,181 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::entity::CodeEntity;
+
+/// One ranked search result, pointing into the indexed entity slice
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SearchHit {
+    pub index: usize,
+    pub score: f32,
+}
+
+#[cfg(feature = "search")]
+pub use self::tantivy_index::SearchIndex;
+
+#[cfg(feature = "search")]
+mod tantivy_index {
+    use super::*;
+    use crate::ident::normalize_identifier;
+    use tantivy::collector::TopDocs;
+    use tantivy::query::QueryParser;
+    use tantivy::schema::{Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, Value, STORED};
+    use tantivy::{doc, Index, IndexReader, IndexWriter, TantivyDocument};
+
+    /// Field boosts: a hit in the name outranks one in the docstring,
+    /// which outranks one in the code
+    const NAME_BOOST: f32 = 3.0;
+    const DOCSTRING_BOOST: f32 = 1.5;
+
+    /// In-memory full-text index over entity names, docstrings and code.
+    ///
+    /// Text is stemmed (English), identifiers are additionally split on
+    /// `snake_case` and `camelCase` boundaries, and all query terms must
+    /// match unless the query says otherwise (`retry OR backoff`). Names,
+    /// code and queries are NFKC-normalized so compatibility spellings of
+    /// an identifier find each other.
+    pub struct SearchIndex {
+        index: Index,
+        reader: IndexReader,
+        name: Field,
+        docstring: Field,
+        code: Field,
+        ordinal: Field,
+    }
+
+    impl SearchIndex {
+        /// Index `entities`; hits refer back to positions in this slice
+        pub fn build(entities: &[CodeEntity]) -> Result<Self> {
+            let entities = crate::lazy::materialized(entities);
+            let text = TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer("en_stem")
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            );
+            let mut builder = Schema::builder();
+            let name = builder.add_text_field("name", text.clone());
+            let docstring = builder.add_text_field("docstring", text.clone());
+            let code = builder.add_text_field("code", text);
+            let ordinal = builder.add_u64_field("ordinal", STORED);
+
+            let index = Index::create_in_ram(builder.build());
+            let mut writer: IndexWriter = index.writer_with_num_threads(1, 50_000_000)?;
+            for (i, entity) in entities.iter().enumerate() {
+                let entity_name = normalize_identifier(&entity.name);
+                writer.add_document(doc!(
+                    name => format!("{} {}", entity_name, split_identifier(&entity_name)),
+                    docstring => entity.docstring.clone().unwrap_or_default(),
+                    code => normalize_identifier(&entity.code).into_owned(),
+                    ordinal => i as u64,
+                ))?;
+            }
+            writer.commit()?;
+
+            let reader = index.reader()?;
+            Ok(SearchIndex {
+                index,
+                reader,
+                name,
+                docstring,
+                code,
+                ordinal,
+            })
+        }
+
+        /// The best `limit` matches for `query`, highest score first.
+        ///
+        /// Query syntax errors are tolerated: the parsable part is used.
+        pub fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>> {
+            let mut parser = QueryParser::for_index(&self.index, vec![self.name, self.docstring, self.code]);
+            parser.set_conjunction_by_default();
+            parser.set_field_boost(self.name, NAME_BOOST);
+            parser.set_field_boost(self.docstring, DOCSTRING_BOOST);
+            let (query, _errors) = parser.parse_query_lenient(&normalize_identifier(query));
+
+            let searcher = self.reader.searcher();
+            let top = searcher.search(&query, &TopDocs::with_limit(limit.max(1)))?;
+            let mut hits = Vec::with_capacity(top.len());
+            for (score, address) in top {
+                let document: TantivyDocument = searcher.doc(address)?;
+                if let Some(index) = document.get_first(self.ordinal).and_then(|v| v.as_u64()) {
+                    hits.push(SearchHit {
+                        index: index as usize,
+                        score,
+                    });
+                }
+            }
+            Ok(hits)
+        }
+    }
+}
+
+/// Stand-in used when the crate is built without the `search` feature
+#[cfg(not(feature = "search"))]
+pub struct SearchIndex;
+
+#[cfg(not(feature = "search"))]
+impl SearchIndex {
+    pub fn build(_entities: &[CodeEntity]) -> Result<Self> {
+        Err(anyhow::anyhow!("Full-text search requires the `search` feature"))
+    }
+
+    pub fn search(&self, _query: &str, _limit: usize) -> Result<Vec<SearchHit>> {
+        Err(anyhow::anyhow!("Full-text search requires the `search` feature"))
+    }
+}
+
+/// `parseHTTPResponse` / `parse_http_response` → `parse HTTP Response` / `parse http response`
+pub fn split_identifier(identifier: &str) -> String {
+    let mut words = String::with_capacity(identifier.len() + 8);
+    let chars: Vec<char> = identifier.chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '_' || c == '-' {
+            words.push(' ');
+            continue;
+        }
+        let boundary = i > 0
+            && c.is_uppercase()
+            && (chars[i - 1].is_lowercase() || chars.get(i + 1).map_or(false, |n| n.is_lowercase()));
+        if boundary && !words.ends_with(' ') {
+            words.push(' ');
+        }
+        words.push(c);
+    }
+    words.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_identifier() {
+        assert_eq!(split_identifier("parseHTTPResponse"), "parse HTTP Response");
+        assert_eq!(split_identifier("retry_with_backoff"), "retry with backoff");
+        assert_eq!(split_identifier("Client"), "Client");
+    }
+
+    #[cfg(feature = "search")]
+    #[test]
+    fn test_ranked_search() {
+        use std::path::PathBuf;
+
+        let entity = |name: &str, doc: &str| {
+            let mut entity = CodeEntity::new("function".to_string(), name.to_string(), PathBuf::from("a.py"), 1);
+            entity.docstring = Some(doc.to_string());
+            entity
+        };
+        let entities = vec![
+            entity("fetch", "Download a URL, retrying with exponential backoff."),
+            entity("retryWithBackoff", "Call a function until it succeeds."),
+            entity("parse", "Parse a response body."),
+        ];
+
+        let index = SearchIndex::build(&entities).unwrap();
+        let hits = index.search("retry backoff", 10).unwrap();
+
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].index, 1, "name matches outrank docstring matches");
+        assert!(index.search("unrelated", 10).unwrap().is_empty());
+    }
+}
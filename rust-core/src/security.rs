@@ -0,0 +1,190 @@
+use rustpython_parser::ast;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use crate::entity::CodeEntity;
+use crate::lint::Severity;
+use crate::location::line_at;
+use crate::walk::{argument, dotted_name, keyword};
+
+/// A security-sensitive pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SecurityRule {
+    /// `eval(...)` of anything
+    Eval,
+    /// `exec(...)` of anything
+    Exec,
+    /// `pickle`/`marshal`/`dill` loads, or `yaml.load` without a safe loader
+    UnsafeDeserialization,
+    /// `subprocess` with `shell=True`, `os.system` or `os.popen`
+    ShellInjection,
+    /// TLS certificate checks turned off with `verify=False`
+    TlsVerificationDisabled,
+    /// `hashlib.md5`/`sha1`, unfit for passwords and signatures
+    WeakHash,
+}
+
+impl SecurityRule {
+    pub const ALL: [SecurityRule; 6] = [
+        SecurityRule::Eval,
+        SecurityRule::Exec,
+        SecurityRule::UnsafeDeserialization,
+        SecurityRule::ShellInjection,
+        SecurityRule::TlsVerificationDisabled,
+        SecurityRule::WeakHash,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SecurityRule::Eval => "eval",
+            SecurityRule::Exec => "exec",
+            SecurityRule::UnsafeDeserialization => "unsafe-deserialization",
+            SecurityRule::ShellInjection => "shell-injection",
+            SecurityRule::TlsVerificationDisabled => "tls-verification-disabled",
+            SecurityRule::WeakHash => "weak-hash",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            SecurityRule::Eval | SecurityRule::Exec | SecurityRule::UnsafeDeserialization | SecurityRule::ShellInjection => {
+                Severity::Error
+            }
+            SecurityRule::TlsVerificationDisabled => Severity::Warning,
+            SecurityRule::WeakHash => Severity::Info,
+        }
+    }
+}
+
+impl std::str::FromStr for SecurityRule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        SecurityRule::ALL
+            .into_iter()
+            .find(|rule| rule.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| anyhow::anyhow!("Unknown security rule: {}", s))
+    }
+}
+
+/// One security-sensitive call in an entity's body
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecurityFinding {
+    pub rule: SecurityRule,
+    /// The call as written, e.g. `subprocess.run(..., shell=True)`
+    pub message: String,
+    pub line: usize,
+}
+
+const DESERIALIZERS: &[&str] = &[
+    "pickle.loads",
+    "pickle.load",
+    "cPickle.loads",
+    "cPickle.load",
+    "dill.loads",
+    "dill.load",
+    "marshal.loads",
+    "marshal.load",
+    "yaml.unsafe_load",
+    "yaml.full_load",
+];
+
+const SAFE_YAML_LOADERS: &[&str] = &["SafeLoader", "CSafeLoader", "BaseLoader"];
+
+/// The finding for `call` if it is security-sensitive; `callee` is the
+/// dotted name of the function
+pub(crate) fn finding(call: &ast::ExprCall, callee: Option<&str>, line_starts: &[usize]) -> Option<SecurityFinding> {
+    let name = callee?;
+    let (rule, message) = match name {
+        "eval" | "builtins.eval" => (SecurityRule::Eval, format!("{}(...)", name)),
+        "exec" | "builtins.exec" => (SecurityRule::Exec, format!("{}(...)", name)),
+        "yaml.load" | "yaml.load_all" if !safe_yaml_loader(call) => {
+            (SecurityRule::UnsafeDeserialization, format!("{}(...) without a safe Loader", name))
+        }
+        name if DESERIALIZERS.contains(&name) => (SecurityRule::UnsafeDeserialization, format!("{}(...)", name)),
+        "os.system" | "os.popen" => (SecurityRule::ShellInjection, format!("{}(...)", name)),
+        name if name.starts_with("subprocess.") && is_true(keyword(call, "shell")) => {
+            (SecurityRule::ShellInjection, format!("{}(..., shell=True)", name))
+        }
+        "hashlib.md5" | "hashlib.sha1" => (SecurityRule::WeakHash, format!("{}(...)", name)),
+        _ if is_false(keyword(call, "verify")) => {
+            (SecurityRule::TlsVerificationDisabled, format!("{}(..., verify=False)", name))
+        }
+        _ => return None,
+    };
+    Some(SecurityFinding {
+        rule,
+        message,
+        line: line_at(line_starts, call.range.start().to_usize()),
+    })
+}
+
+/// Whether a `yaml.load` call names a safe loader
+fn safe_yaml_loader(call: &ast::ExprCall) -> bool {
+    argument(call, 1, "Loader")
+        .and_then(dotted_name)
+        .map_or(false, |loader| SAFE_YAML_LOADERS.iter().any(|safe| loader.rsplit('.').next() == Some(*safe)))
+}
+
+fn is_true(expr: Option<&ast::Expr>) -> bool {
+    matches!(expr, Some(ast::Expr::Constant(c)) if matches!(c.value, ast::Constant::Bool(true)))
+}
+
+fn is_false(expr: Option<&ast::Expr>) -> bool {
+    matches!(expr, Some(ast::Expr::Constant(c)) if matches!(c.value, ast::Constant::Bool(false)))
+}
+
+/// A Markdown "Security" appendix listing every finding by file, or an
+/// empty string when there are none
+pub fn appendix(entities: &[CodeEntity]) -> String {
+    let mut rows: Vec<(&CodeEntity, &SecurityFinding)> = entities
+        .iter()
+        .flat_map(|entity| entity.security_findings.iter().map(move |finding| (entity, finding)))
+        .collect();
+    if rows.is_empty() {
+        return String::new();
+    }
+    rows.sort_by(|a, b| (a.0.file_path.as_path(), a.1.line).cmp(&(b.0.file_path.as_path(), b.1.line)));
+
+    let mut out = String::from("\n## Security\n\n| Severity | Rule | Location | Entity | Call |\n|---|---|---|---|---|\n");
+    for (entity, finding) in rows {
+        let _ = writeln!(
+            out,
+            "| {} | `{}` | `{}:{}` | `{}` | `{}` |",
+            finding.rule.severity().as_str(),
+            finding.rule.as_str(),
+            entity.file_path.to_string_lossy().replace('\\', "/"),
+            finding.line,
+            if entity.qualified_name.is_empty() { &entity.name } else { &entity.qualified_name },
+            finding.message.replace('|', "\\|"),
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::PythonParser;
+    use std::path::Path;
+
+    #[test]
+    fn test_security_findings() {
+        let source = "def load(blob, cmd, path):\n    data = pickle.loads(blob)\n    subprocess.run(cmd, shell=True)\n    subprocess.run([cmd], shell=False)\n    yaml.load(open(path), Loader=yaml.SafeLoader)\n    config = yaml.load(open(path))\n    requests.get(path, verify=False)\n    return eval(data)\n";
+        let entities = PythonParser::new().parse_source(source, Path::new("loader.py")).unwrap();
+
+        let found: Vec<(SecurityRule, usize)> = entities[0].security_findings.iter().map(|f| (f.rule, f.line)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (SecurityRule::UnsafeDeserialization, 2),
+                (SecurityRule::ShellInjection, 3),
+                (SecurityRule::UnsafeDeserialization, 6),
+                (SecurityRule::TlsVerificationDisabled, 7),
+                (SecurityRule::Eval, 8),
+            ]
+        );
+        assert!(appendix(&entities).contains("| error | `shell-injection` | `loader.py:3` | `load` | `subprocess.run(..., shell=True)` |"));
+    }
+}
@@ -0,0 +1,361 @@
+use serde::Serialize;
+
+use crate::diff::{ApiDiff, ChangeKind, EntityChange};
+
+/// Semantic version component a change requires bumping
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl Bump {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Bump::Patch => "patch",
+            Bump::Minor => "minor",
+            Bump::Major => "major",
+        }
+    }
+}
+
+impl std::str::FromStr for Bump {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "patch" => Ok(Bump::Patch),
+            "minor" => Ok(Bump::Minor),
+            "major" => Ok(Bump::Major),
+            other => Err(anyhow::anyhow!("Unknown version bump: {}", other)),
+        }
+    }
+}
+
+/// A diff change with the bump it requires and why
+#[derive(Debug, Clone, Serialize)]
+pub struct ClassifiedChange {
+    pub key: String,
+    pub kind: ChangeKind,
+    pub bump: Bump,
+    pub reason: String,
+}
+
+/// Semver classification of a whole API diff
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SemverReport {
+    pub changes: Vec<ClassifiedChange>,
+    /// Largest bump required by any change; `None` when the API is unchanged
+    pub recommended: Option<Bump>,
+}
+
+/// Classify every change of `diff` and derive the overall bump
+pub fn classify_diff(diff: &ApiDiff) -> SemverReport {
+    let changes: Vec<ClassifiedChange> = diff
+        .changes
+        .iter()
+        .map(|change| {
+            let (bump, reason) = classify(change);
+            ClassifiedChange {
+                key: change.key.clone(),
+                kind: change.kind,
+                bump,
+                reason,
+            }
+        })
+        .collect();
+    let recommended = changes.iter().map(|c| c.bump).max();
+    SemverReport { changes, recommended }
+}
+
+/// The bump a single change requires, with a short explanation
+pub fn classify(change: &EntityChange) -> (Bump, String) {
+    match change.kind {
+        ChangeKind::Added => (Bump::Minor, format!("new public {}", change.entity_type)),
+        ChangeKind::Removed => (Bump::Major, format!("public {} removed", change.entity_type)),
+        ChangeKind::DocstringChanged => (Bump::Patch, "docstring changed".to_string()),
+        ChangeKind::BodyChanged => (Bump::Patch, "implementation changed".to_string()),
+        // Semver 2.0: deprecating functionality calls for a minor release
+        ChangeKind::Deprecated => (Bump::Minor, format!("{} deprecated", change.entity_type)),
+        ChangeKind::VisibilityChanged if change.new_public == Some(false) => {
+            (Bump::Major, format!("{} is no longer public", change.entity_type))
+        }
+        ChangeKind::VisibilityChanged => (Bump::Minor, format!("{} became public", change.entity_type)),
+        ChangeKind::SignatureChanged => classify_signature(
+            change.old_signature.as_deref().unwrap_or(""),
+            change.new_signature.as_deref().unwrap_or(""),
+        ),
+    }
+}
+
+/// Compare two signatures parameter by parameter.
+///
+/// Anything the rules below don't recognize (changed types, return values,
+/// defaults) is treated as breaking rather than guessed to be compatible.
+fn classify_signature(old: &str, new: &str) -> (Bump, String) {
+    let is_async = |s: &str| s.trim_start().starts_with("async ");
+    if is_async(old) != is_async(new) {
+        return (Bump::Major, "sync/async changed".to_string());
+    }
+
+    let (before, old_returns) = parameters(old);
+    let (after, new_returns) = parameters(new);
+    let counterpart = |p: &Parameter| after.iter().find(|q| q.name == p.name);
+
+    if let Some(removed) = before.iter().find(|p| counterpart(p).is_none()) {
+        return (Bump::Major, format!("parameter `{}` removed", removed.name));
+    }
+    let added: Vec<&Parameter> = after.iter().filter(|p| !before.iter().any(|q| q.name == p.name)).collect();
+    if let Some(required) = added.iter().find(|p| !p.optional) {
+        return (Bump::Major, format!("required parameter `{}` added", required.name));
+    }
+    for p in &before {
+        let q = counterpart(p).expect("removed parameters were reported above");
+        if p.optional && !q.optional {
+            return (Bump::Major, format!("parameter `{}` lost its default", p.name));
+        }
+        if !p.keyword_only && q.keyword_only {
+            return (Bump::Major, format!("parameter `{}` became keyword-only", p.name));
+        }
+        if p.annotation != q.annotation {
+            return (Bump::Major, format!("type of parameter `{}` changed", p.name));
+        }
+        if p.default != q.default {
+            return (Bump::Major, format!("default of parameter `{}` changed", p.name));
+        }
+    }
+    if old_returns != new_returns {
+        return (Bump::Major, "return type changed".to_string());
+    }
+
+    // Callers passing arguments positionally break when kept parameters
+    // move; keyword-only parameters can be listed in any order
+    let positional = |params: &[Parameter]| -> Vec<String> {
+        params
+            .iter()
+            .filter(|p| !p.keyword_only && !p.variadic && before.iter().any(|q| q.name == p.name))
+            .map(|p| p.name.clone())
+            .collect()
+    };
+    if positional(&before) != positional(&after) {
+        return (Bump::Major, "parameters reordered".to_string());
+    }
+
+    if let Some(optional) = added.first() {
+        return (Bump::Minor, format!("optional parameter `{}` added", optional.name));
+    }
+    let names = |params: &[Parameter]| params.iter().map(|p| p.name.clone()).collect::<Vec<_>>();
+    if names(&before) != names(&after) {
+        return (Bump::Patch, "keyword-only parameters reordered".to_string());
+    }
+    if compact(old) == compact(new) {
+        return (Bump::Patch, "signature reformatted".to_string());
+    }
+    (Bump::Major, "signature changed".to_string())
+}
+
+struct Parameter {
+    name: String,
+    optional: bool,
+    /// Only passable by keyword: declared after a bare `*` or `*args`
+    keyword_only: bool,
+    /// `*args` or `**kwargs`
+    variadic: bool,
+    /// Type annotation with whitespace removed, empty when absent
+    annotation: String,
+    /// Default value with whitespace removed
+    default: Option<String>,
+}
+
+/// Parameters of a `def f(a, b=1, *args)`-style signature, and its return
+/// type with whitespace removed; `self`/`cls` and the bare `*` and `/`
+/// markers are skipped
+fn parameters(signature: &str) -> (Vec<Parameter>, String) {
+    let (raw, tail) = split_parameters(signature);
+
+    let mut params = Vec::new();
+    let mut keyword_only = false;
+    for p in raw.into_iter().map(str::trim) {
+        let after_star = keyword_only;
+        if p.starts_with('*') && !p.starts_with("**") {
+            keyword_only = true;
+        }
+        if p.is_empty() || matches!(p, "self" | "cls" | "&self" | "&mut self" | "mut self" | "*" | "/") {
+            continue;
+        }
+        let (declared, default) = match default_at(p) {
+            Some(at) => (&p[..at], Some(compact(&p[at + 1..]))),
+            None => (p, None),
+        };
+        let (head, annotation) = declared.split_once(':').unwrap_or((declared, ""));
+        let head = head.trim();
+        let variadic = head.starts_with('*');
+        params.push(Parameter {
+            name: head.trim_start_matches('*').trim_end_matches('?').to_string(),
+            // Defaults, `*args`/`**kwargs` and TypeScript's `b?: T`
+            optional: default.is_some() || variadic || head.ends_with('?'),
+            keyword_only: after_star && !variadic,
+            variadic,
+            annotation: compact(annotation),
+            default,
+        });
+    }
+
+    // `-> int:` in Python and Rust, `: number` in TypeScript
+    let returns = tail.trim().trim_end_matches([':', '{', ';']).trim();
+    let returns = returns.trim_start_matches("->").trim_start_matches(':');
+    (params, compact(returns))
+}
+
+/// Split the top-level parameter list off `signature`, returning the raw
+/// parameters and the text after the closing parenthesis
+fn split_parameters(signature: &str) -> (Vec<&str>, &str) {
+    let Some(open) = signature.find('(') else { return (Vec::new(), "") };
+    // Python has no angle-bracket generics: `<` and `>` there compare
+    let trimmed = signature.trim_start();
+    let generics = !(trimmed.starts_with("def ") || trimmed.starts_with("async def "));
+
+    let mut params = Vec::new();
+    let mut open_brackets = Vec::new();
+    let mut start = open + 1;
+    let mut previous = ' ';
+    for (i, c) in signature[open..].char_indices().map(|(i, c)| (i + open, c)) {
+        match c {
+            '(' | '[' | '{' => open_brackets.push(c),
+            '<' if generics && (previous.is_alphanumeric() || previous == '_') => open_brackets.push(c),
+            // `->` and `=>` arrows inside parameter types are not brackets
+            '>' if previous == '-' || previous == '=' => {}
+            '>' if open_brackets.last() == Some(&'<') => {
+                open_brackets.pop();
+            }
+            ')' | ']' | '}' => {
+                open_brackets.pop();
+                if open_brackets.is_empty() {
+                    params.push(&signature[start..i]);
+                    return (params, &signature[i + c.len_utf8()..]);
+                }
+            }
+            ',' if open_brackets.len() == 1 => {
+                params.push(&signature[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        previous = c;
+    }
+    (params, "")
+}
+
+/// Offset of the `=` introducing a parameter's default, skipping `=>`,
+/// `==`, `!=`, `<=` and `>=`
+fn default_at(param: &str) -> Option<usize> {
+    let bytes = param.as_bytes();
+    (0..bytes.len()).find(|&i| {
+        bytes[i] == b'='
+            && !matches!(bytes.get(i + 1), Some(b'>' | b'='))
+            && !matches!(i.checked_sub(1).map(|j| bytes[j]), Some(b'=' | b'!' | b'<' | b'>'))
+    })
+}
+
+fn compact(text: &str) -> String {
+    text.split_whitespace().collect()
+}
+
+/// Apply `bump` to a `major.minor.patch` version.
+///
+/// Below 1.0.0, breaking changes bump the minor version and everything else
+/// the patch version, following the usual 0.x convention.
+pub fn next_version(version: &str, bump: Bump) -> anyhow::Result<String> {
+    let core = version.trim().trim_start_matches('v');
+    let core = core.split(['-', '+']).next().unwrap_or(core);
+    let parts = core
+        .split('.')
+        .map(|p| p.parse::<u64>())
+        .collect::<Result<Vec<_>, _>>()
+        .ok()
+        .filter(|parts| parts.len() == 3)
+        .ok_or_else(|| anyhow::anyhow!("Not a major.minor.patch version: {}", version))?;
+    let (major, minor, patch) = (parts[0], parts[1], parts[2]);
+
+    let bump = match (major, bump) {
+        (0, Bump::Major) => Bump::Minor,
+        (0, Bump::Minor) => Bump::Patch,
+        (_, bump) => bump,
+    };
+    Ok(match bump {
+        Bump::Major => format!("{}.0.0", major + 1),
+        Bump::Minor => format!("{}.{}.0", major, minor + 1),
+        Bump::Patch => format!("{}.{}.{}", major, minor, patch + 1),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::diff_entities;
+    use crate::entity::CodeEntity;
+    use crate::parser::PythonParser;
+    use std::path::{Path, PathBuf};
+
+    fn entity(name: &str, code: &str) -> CodeEntity {
+        let mut entity = CodeEntity::new("function".to_string(), name.to_string(), PathBuf::from("api.py"), 1);
+        entity.code = code.to_string();
+        entity
+    }
+
+    #[test]
+    fn test_signature_rules() {
+        let bump = |old: &str, new: &str| classify_signature(old, new).0;
+
+        assert_eq!(bump("def f(a):", "def f(a, b=None):"), Bump::Minor);
+        assert_eq!(bump("def f(a):", "def f(a, *args, **kwargs):"), Bump::Minor);
+        assert_eq!(bump("def f(a):", "def f(a, b):"), Bump::Major);
+        assert_eq!(bump("def f(a, b):", "def f(a):"), Bump::Major);
+        assert_eq!(bump("def f(a, b):", "def f(b, a):"), Bump::Major);
+        assert_eq!(bump("def f(a=1):", "def f(a):"), Bump::Major);
+        assert_eq!(bump("def f(a: int):", "def f(a: Dict[str, int]):"), Bump::Major);
+        assert_eq!(bump("def f(a) -> int:", "def f(a) -> str:"), Bump::Major);
+        assert_eq!(bump("def f(a, *, b=1, c=2):", "def f(a, *, c=2, b=1):"), Bump::Patch);
+        assert_eq!(bump("def f(a, *args, b=1, c=2):", "def f(a, *args, c=2, b=1, d=3):"), Bump::Minor);
+        assert_eq!(bump("def f(a, b=1):", "def f(a, *, b=1):"), Bump::Major);
+        assert_eq!(bump("def f(a=x<y, b=1):", "def f(a=x<y, b=1, c=2):"), Bump::Minor);
+        assert_eq!(bump("def f(self, a):", "async def f(self, a):"), Bump::Major);
+        assert_eq!(bump("function f(a: string)", "function f(a: string, b?: number)"), Bump::Minor);
+        assert_eq!(bump("fn f(&self, m: HashMap<K, V>)", "fn f(&self, m: HashMap<K, V>, n: u8)"), Bump::Major);
+    }
+
+    #[test]
+    fn test_recommended_bump() {
+        let old = vec![entity("kept", "def kept():"), entity("gone", "def gone():")];
+        let new = vec![entity("kept", "def kept(flag=False):"), entity("fresh", "def fresh():")];
+
+        let report = classify_diff(&diff_entities(&old, &new));
+        assert_eq!(report.recommended, Some(Bump::Major));
+        assert_eq!(report.changes.iter().filter(|c| c.bump == Bump::Minor).count(), 2);
+
+        assert!(classify_diff(&diff_entities(&old, &old)).recommended.is_none());
+    }
+
+    #[test]
+    fn test_parsed_signatures() {
+        let parse = |source: &str| PythonParser::new().parse_source(source, Path::new("api.py")).unwrap();
+        let bump = |old: &str, new: &str| classify_diff(&diff_entities(&parse(old), &parse(new))).recommended;
+
+        let base = "def fetch(url, /, timeout=30, *, retries=3):\n    pass\n";
+        assert_eq!(bump(base, "def fetch(url, /, timeout=30, *, retries=3, **opts):\n    pass\n"), Some(Bump::Minor));
+        assert_eq!(bump(base, "def fetch(url, /, timeout=60, *, retries=3):\n    pass\n"), Some(Bump::Major));
+        assert_eq!(bump(base, "def fetch(url, /, *, timeout=30, retries=3):\n    pass\n"), Some(Bump::Major));
+        assert_eq!(bump(base, "def fetch(url, /, timeout=30, *, retries):\n    pass\n"), Some(Bump::Major));
+    }
+
+    #[test]
+    fn test_next_version() {
+        assert_eq!(next_version("1.2.3", Bump::Major).unwrap(), "2.0.0");
+        assert_eq!(next_version("v1.2.3", Bump::Minor).unwrap(), "1.3.0");
+        assert_eq!(next_version("0.4.1", Bump::Major).unwrap(), "0.5.0");
+        assert_eq!(next_version("0.4.1", Bump::Minor).unwrap(), "0.4.2");
+        assert!(next_version("1.2", Bump::Patch).is_err());
+    }
+}
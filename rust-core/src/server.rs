@@ -0,0 +1,257 @@
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::json;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tiny_http::{Header, Method, Response, Server};
+
+use crate::config::AnalyzerConfig;
+use crate::entity::CodeEntity;
+use crate::ident::{normalize_identifier, same_identifier};
+use crate::metrics::ProjectMetrics;
+use crate::search::SearchIndex;
+
+/// Default number of results returned by list endpoints
+const DEFAULT_LIMIT: usize = 100;
+
+/// Snapshot of the analyzed project served over HTTP
+pub struct ServerState {
+    entities: Vec<CodeEntity>,
+    metrics: ProjectMetrics,
+    /// Full-text index; `None` without the `search` feature
+    index: Option<SearchIndex>,
+}
+
+impl ServerState {
+    pub fn new(entities: Vec<CodeEntity>, config: &AnalyzerConfig) -> Self {
+        let metrics = ProjectMetrics::compute(&entities, config);
+        let index = SearchIndex::build(&entities)
+            .map_err(|e| log::debug!("Using substring search: {:#}", e))
+            .ok();
+        ServerState { entities, metrics, index }
+    }
+
+    /// Ranked full-text results when indexed, substring matches otherwise
+    fn search(&self, query: &str, limit: usize) -> Vec<&CodeEntity> {
+        let hits = self.index.as_ref().map(|index| index.search(query, limit));
+        match hits {
+            Some(Ok(hits)) => hits.iter().map(|hit| &self.entities[hit.index]).collect(),
+            Some(Err(e)) => {
+                log::warn!("Search for {:?} failed: {:#}", query, e);
+                Vec::new()
+            }
+            None => search(&self.entities, query, limit),
+        }
+    }
+}
+
+/// Small JSON API over an analyzed project.
+///
+/// Routes:
+/// - `GET /health`
+/// - `GET /metrics`
+/// - `GET /endpoints`
+/// - `GET /entities?type=&name=&path=&limit=`
+/// - `GET /search?q=&limit=`
+#[derive(Clone)]
+pub struct ApiServer {
+    state: Arc<RwLock<ServerState>>,
+}
+
+impl ApiServer {
+    pub fn new(state: ServerState) -> Self {
+        ApiServer {
+            state: Arc::new(RwLock::new(state)),
+        }
+    }
+
+    /// Replace the served snapshot, e.g. after the watcher rebuilt it
+    pub fn update(&self, state: ServerState) {
+        *self.state.write().unwrap_or_else(|e| e.into_inner()) = state;
+    }
+
+    /// Serve requests on `addr` until the process exits
+    pub fn run(&self, addr: &str) -> Result<()> {
+        let server = Server::http(addr).map_err(|e| anyhow::anyhow!("Failed to bind {}: {}", addr, e))?;
+        let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+            .expect("static header is valid");
+
+        for request in server.incoming_requests() {
+            let (status, body) = if *request.method() == Method::Get {
+                self.handle(request.url())
+            } else {
+                (405, json!({ "error": "method not allowed" }))
+            };
+
+            let response = Response::from_string(body.to_string())
+                .with_status_code(status)
+                .with_header(content_type.clone());
+            if let Err(e) = request.respond(response) {
+                log::warn!("Failed to send response: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Route a GET request URL to a status code and JSON body
+    pub fn handle(&self, url: &str) -> (u16, serde_json::Value) {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let params = parse_query(query);
+        let limit = params
+            .get("limit")
+            .and_then(|l| l.parse().ok())
+            .unwrap_or(DEFAULT_LIMIT);
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+
+        match path.trim_end_matches('/') {
+            "/health" | "" => (200, json!({ "status": "ok", "entities": state.entities.len() })),
+            "/metrics" => (200, to_json(&state.metrics)),
+            "/endpoints" => {
+                let endpoints: Vec<&CodeEntity> = state.entities.iter().filter(|e| e.is_api_endpoint).collect();
+                (200, to_json(&endpoints))
+            }
+            "/entities" => {
+                let matches: Vec<&CodeEntity> = state
+                    .entities
+                    .iter()
+                    .filter(|e| params.get("type").map_or(true, |t| &e.entity_type == t))
+                    .filter(|e| params.get("name").map_or(true, |n| same_identifier(&e.name, n)))
+                    .filter(|e| {
+                        params
+                            .get("path")
+                            .map_or(true, |p| e.file_path.to_string_lossy().contains(p.as_str()))
+                    })
+                    .take(limit)
+                    .collect();
+                (200, to_json(&matches))
+            }
+            "/search" => match params.get("q") {
+                Some(q) => (200, to_json(&state.search(q, limit))),
+                None => (400, json!({ "error": "missing query parameter 'q'" })),
+            },
+            _ => (404, json!({ "error": "not found" })),
+        }
+    }
+}
+
+/// Rank entities by how well their name and docstring match `query` as a substring
+fn search<'a>(entities: &'a [CodeEntity], query: &str, limit: usize) -> Vec<&'a CodeEntity> {
+    let needle = normalize_identifier(query).to_lowercase();
+    let mut scored: Vec<(u32, &CodeEntity)> = entities
+        .iter()
+        .filter_map(|e| {
+            let name = normalize_identifier(&e.name).to_lowercase();
+            let score = if name == needle {
+                3
+            } else if name.contains(&needle) {
+                2
+            } else if e.docstring.as_deref().map_or(false, |d| d.to_lowercase().contains(&needle)) {
+                1
+            } else {
+                return None;
+            };
+            Some((score, e))
+        })
+        .collect();
+
+    scored.sort_by_key(|(score, _)| Reverse(*score));
+    scored.into_iter().take(limit).map(|(_, e)| e).collect()
+}
+
+fn to_json<T: Serialize>(value: &T) -> serde_json::Value {
+    serde_json::to_value(value).unwrap_or_else(|e| json!({ "error": e.to_string() }))
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn server() -> ApiServer {
+        let mut endpoint = CodeEntity::new(
+            "function".to_string(),
+            "list_users".to_string(),
+            PathBuf::from("app/api.py"),
+            10,
+        );
+        endpoint.docstring = Some("Return every user account.".to_string());
+        endpoint.decorators = vec!["app.get(\"/users\")".into()];
+        endpoint.detect_api_endpoint();
+
+        let helper = CodeEntity::new(
+            "function".to_string(),
+            "format_user".to_string(),
+            PathBuf::from("app/util.py"),
+            3,
+        );
+
+        ApiServer::new(ServerState::new(vec![endpoint, helper], &AnalyzerConfig::default()))
+    }
+
+    #[test]
+    fn test_entities_filters() {
+        let (status, body) = server().handle("/entities?path=util");
+        assert_eq!(status, 200);
+        assert_eq!(body.as_array().unwrap().len(), 1);
+        assert_eq!(body[0]["name"], "format_user");
+    }
+
+    #[test]
+    fn test_search_and_endpoints() {
+        let server = server();
+
+        let (_, results) = server.handle("/search?q=user+account");
+        assert_eq!(results[0]["name"], "list_users");
+
+        let (_, endpoints) = server.handle("/endpoints");
+        assert_eq!(endpoints.as_array().unwrap().len(), 1);
+
+        let (status, _) = server.handle("/search");
+        assert_eq!(status, 400);
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("src%2Fapi+v1"), "src/api v1");
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+}
@@ -0,0 +1,222 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fmt::Write;
+
+use crate::entity::CodeEntity;
+use crate::export::fence_language;
+use crate::lazy;
+use crate::sphinx::prepare_docstring;
+use crate::tree::{self, NodeKind, TreeNode};
+
+/// API reference laid out for Docusaurus and similar static site
+/// generators: one MDX page per module and a sidebar mirroring the packages
+#[derive(Debug, Clone, Serialize)]
+pub struct Site {
+    pub pages: Vec<SitePage>,
+    /// Items for a `sidebars.js` entry, e.g. `{ apiSidebar: site.sidebar }`
+    pub sidebar: Vec<SidebarItem>,
+}
+
+/// One module's reference page
+#[derive(Debug, Clone, Serialize)]
+pub struct SitePage {
+    /// Doc id, which is also the file path without extension: `api/pkg/api`
+    pub id: String,
+    /// Dotted module path
+    pub title: String,
+    pub sidebar_label: String,
+    /// MDX body; prose is escaped so braces and angle brackets in
+    /// docstrings don't parse as JSX
+    pub body: String,
+}
+
+/// A Docusaurus sidebar item
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SidebarItem {
+    Doc {
+        id: String,
+        label: String,
+    },
+    Category {
+        label: String,
+        /// The package's own page, from its `__init__`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        link: Option<CategoryLink>,
+        items: Vec<SidebarItem>,
+    },
+}
+
+/// Page a sidebar category opens
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CategoryLink {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub id: String,
+}
+
+/// Build the site for `entities`, with doc ids under `base` (e.g. `api`)
+pub fn build_site(entities: &[CodeEntity], base: &str) -> Site {
+    let entities = lazy::materialized(entities);
+    let entities = &*entities;
+    let tree = tree::build_tree(entities, None);
+    let mut pages = Vec::new();
+    let sidebar = sidebar_items(&tree, entities, base, &mut pages);
+    Site { pages, sidebar }
+}
+
+/// `build_site` as pretty-printed JSON
+pub fn to_site_json(entities: &[CodeEntity], base: &str) -> Result<String> {
+    serde_json::to_string_pretty(&build_site(entities, base)).context("Failed to serialize site")
+}
+
+fn sidebar_items(package: &TreeNode, entities: &[CodeEntity], base: &str, pages: &mut Vec<SitePage>) -> Vec<SidebarItem> {
+    let mut items = Vec::new();
+    for child in &package.children {
+        match child.kind {
+            NodeKind::Package => {
+                let init = child.children.iter().find(|c| c.kind == NodeKind::Module && c.name == "__init__");
+                let link = init.map(|init| {
+                    let page = page(init, &child.path, entities, base);
+                    let id = page.id.clone();
+                    pages.push(page);
+                    CategoryLink { kind: "doc", id }
+                });
+                let items_below = sidebar_items(child, entities, base, pages);
+                items.push(SidebarItem::Category {
+                    label: child.name.clone(),
+                    link,
+                    items: items_below,
+                });
+            }
+            NodeKind::Module if child.name == "__init__" => {}
+            NodeKind::Module => {
+                let page = page(child, &child.path, entities, base);
+                items.push(SidebarItem::Doc {
+                    id: page.id.clone(),
+                    label: page.sidebar_label.clone(),
+                });
+                pages.push(page);
+            }
+            NodeKind::Entity => {}
+        }
+    }
+    items
+}
+
+/// The page for a module node, titled `title`
+fn page(module: &TreeNode, title: &str, entities: &[CodeEntity], base: &str) -> SitePage {
+    let path = module.path.replace('.', "/");
+    let id = if base.is_empty() { path } else { format!("{}/{}", base.trim_end_matches('/'), path) };
+    let mut body = format!("# `{}`\n", title);
+    for node in &module.children {
+        render_entity(node, entities, 2, &mut body);
+    }
+    SitePage {
+        id,
+        title: title.to_string(),
+        sidebar_label: title.rsplit('.').next().unwrap_or(title).to_string(),
+        body,
+    }
+}
+
+fn render_entity(node: &TreeNode, entities: &[CodeEntity], depth: usize, out: &mut String) {
+    let Some(entity) = node.entity.and_then(|index| entities.get(index)) else { return };
+    let _ = write!(out, "\n{} {} `{}`\n\n", "#".repeat(depth.min(6)), entity.entity_type, entity.name);
+    if !entity.code.is_empty() {
+        let _ = write!(out, "```{}\n{}\n```\n\n", fence_language(&entity.file_path), entity.code);
+    }
+    if entity.is_deprecated {
+        match entity.replacement.as_deref() {
+            Some(replacement) => {
+                let _ = writeln!(out, "**Deprecated:** use `{}` instead.\n", replacement);
+            }
+            None => out.push_str("**Deprecated.**\n\n"),
+        }
+    }
+    if let Some(doc) = entity.docstring.as_deref() {
+        let lines = prepare_docstring(doc);
+        if !lines.is_empty() {
+            out.push_str(&escape_mdx(&lines.join("\n")));
+            out.push_str("\n\n");
+        }
+    }
+    for child in &node.children {
+        render_entity(child, entities, depth + 1, out);
+    }
+}
+
+/// Escape the characters MDX would read as JSX or expressions, leaving
+/// inline code and fenced blocks alone
+pub fn escape_mdx(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_fence = false;
+    for (index, line) in text.split('\n').enumerate() {
+        if index > 0 {
+            out.push('\n');
+        }
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            out.push_str(line);
+            continue;
+        }
+        if in_fence {
+            out.push_str(line);
+            continue;
+        }
+        for (segment, part) in line.split('`').enumerate() {
+            if segment > 0 {
+                out.push('`');
+            }
+            if segment % 2 == 1 {
+                out.push_str(part);
+                continue;
+            }
+            for c in part.chars() {
+                match c {
+                    '{' => out.push_str("\\{"),
+                    '}' => out.push_str("\\}"),
+                    '<' => out.push_str("&lt;"),
+                    '>' => out.push_str("&gt;"),
+                    _ => out.push(c),
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_site_layout() {
+        let entities = vec![
+            CodeEntity::stub("function", "setup", "/repo/pkg/__init__.py").lines(1, 2).doc("Configure {things}."),
+            CodeEntity::stub("class", "Client", "/repo/pkg/api.py")
+                .lines(1, 9)
+                .doc("Talks to <the> server; see `Dict[str, int]`."),
+            CodeEntity::stub("method", "Client.get", "/repo/pkg/api.py").lines(3, 5),
+            CodeEntity::stub("function", "main", "/repo/cli.py").lines(1, 3),
+        ];
+        let site = build_site(&entities, "api");
+
+        assert_eq!(
+            site.sidebar,
+            vec![
+                SidebarItem::Category {
+                    label: "pkg".to_string(),
+                    link: Some(CategoryLink { kind: "doc", id: "api/pkg/__init__".to_string() }),
+                    items: vec![SidebarItem::Doc { id: "api/pkg/api".to_string(), label: "api".to_string() }],
+                },
+                SidebarItem::Doc { id: "api/cli".to_string(), label: "cli".to_string() },
+            ]
+        );
+        let api = site.pages.iter().find(|p| p.id == "api/pkg/api").unwrap();
+        assert!(api.body.contains("## class `Client`"));
+        assert!(api.body.contains("### method `get`"));
+        assert!(api.body.contains("Talks to &lt;the&gt; server; see `Dict[str, int]`."));
+        assert!(site.pages.iter().any(|p| p.title == "pkg" && p.body.contains("Configure \\{things\\}.")));
+    }
+}
@@ -0,0 +1,197 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::ops::Deref;
+use std::path::Path;
+
+/// Files at least this large are memory-mapped instead of read into a `String`
+pub const MMAP_THRESHOLD: u64 = 256 * 1024;
+
+/// Contents of a source file, validated as UTF-8
+pub enum SourceText {
+    Owned(String),
+    /// Mapped read-only; the bytes were checked to be UTF-8 when mapped
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+impl SourceText {
+    /// Whether the contents are served from a memory map
+    pub fn is_mapped(&self) -> bool {
+        !matches!(self, SourceText::Owned(_))
+    }
+}
+
+impl Deref for SourceText {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match self {
+            SourceText::Owned(text) => text,
+            #[cfg(feature = "mmap")]
+            // SAFETY: validated by `std::str::from_utf8` in `RawSource::into_text`
+            SourceText::Mapped(map) => unsafe { std::str::from_utf8_unchecked(map) },
+        }
+    }
+}
+
+/// Files with a NUL byte in their first this many bytes are treated as binary
+pub const BINARY_SNIFF_LEN: usize = 8000;
+
+/// What `read_source_lenient` found in a file that isn't clean UTF-8 text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceIssue {
+    /// Invalid UTF-8, decoded as Latin-1 instead
+    Transcoded,
+    /// Contains NUL bytes, so probably not source code; skipped
+    Binary,
+    /// Invalid UTF-8 with transcoding turned off; skipped
+    InvalidUtf8,
+}
+
+impl SourceIssue {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SourceIssue::Transcoded => "transcoded",
+            SourceIssue::Binary => "binary",
+            SourceIssue::InvalidUtf8 => "invalid_utf8",
+        }
+    }
+}
+
+/// File contents before UTF-8 validation
+enum RawSource {
+    Bytes(Vec<u8>),
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+impl RawSource {
+    fn bytes(&self) -> &[u8] {
+        match self {
+            RawSource::Bytes(bytes) => bytes,
+            #[cfg(feature = "mmap")]
+            RawSource::Mapped(map) => map,
+        }
+    }
+
+    /// Validate as UTF-8 without copying, handing the bytes back on failure
+    fn into_text(self) -> std::result::Result<SourceText, RawSource> {
+        match self {
+            RawSource::Bytes(bytes) => String::from_utf8(bytes)
+                .map(SourceText::Owned)
+                .map_err(|e| RawSource::Bytes(e.into_bytes())),
+            #[cfg(feature = "mmap")]
+            RawSource::Mapped(map) => match std::str::from_utf8(&map) {
+                Ok(_) => Ok(SourceText::Mapped(map)),
+                Err(_) => Err(RawSource::Mapped(map)),
+            },
+        }
+    }
+}
+
+/// Read a file's bytes, memory-mapping it when it is at least
+/// `MMAP_THRESHOLD` bytes and the `mmap` feature is enabled.
+///
+/// Mapping avoids copying large files into the heap. The analyzer defers
+/// the code of entities in mapped files (see `lazy::defer_code`), so it is
+/// sliced from a fresh map when needed rather than copied out up front.
+fn read_raw(path: &Path) -> Result<RawSource> {
+    #[cfg(feature = "mmap")]
+    {
+        let file = fs::File::open(path).with_context(|| format!("Failed to read file: {:?}", path))?;
+        let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+        if len >= MMAP_THRESHOLD {
+            // SAFETY: the map is read-only and short-lived, but a file truncated
+            // by another process while mapped faults with SIGBUS. That is why
+            // `mmap` is opt-in and left out of the default, CLI and Python
+            // builds, whose watch, serve and interactive sessions read files
+            // while editors write them.
+            let map = unsafe { memmap2::Mmap::map(&file) }
+                .with_context(|| format!("Failed to map file: {:?}", path))?;
+            return Ok(RawSource::Mapped(map));
+        }
+    }
+
+    let bytes = fs::read(path).with_context(|| format!("Failed to read file: {:?}", path))?;
+    Ok(RawSource::Bytes(bytes))
+}
+
+/// Read a source file that must be valid UTF-8
+pub fn read_source(path: &Path) -> Result<SourceText> {
+    read_raw(path)?
+        .into_text()
+        .map_err(|_| anyhow::anyhow!("Failed to read file: {:?}: not valid UTF-8", path))
+}
+
+/// Read a source file without failing on its contents.
+///
+/// Files with NUL bytes near the start are reported as binary and not read
+/// further. Invalid UTF-8 is decoded as Latin-1 when `transcode` is set,
+/// which maps every byte to a character and so always succeeds, and is
+/// skipped otherwise. Returns the text, if any, and what was wrong with it.
+pub fn read_source_lenient(path: &Path, transcode: bool) -> Result<(Option<SourceText>, Option<SourceIssue>)> {
+    let raw = read_raw(path)?;
+    let head = &raw.bytes()[..raw.bytes().len().min(BINARY_SNIFF_LEN)];
+    if head.contains(&0) {
+        return Ok((None, Some(SourceIssue::Binary)));
+    }
+
+    match raw.into_text() {
+        Ok(text) => Ok((Some(text), None)),
+        Err(raw) if transcode => {
+            let text = raw.bytes().iter().map(|&b| b as char).collect();
+            Ok((Some(SourceText::Owned(text)), Some(SourceIssue::Transcoded)))
+        }
+        Err(_) => Ok((None, Some(SourceIssue::InvalidUtf8))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_small_and_large_files_read_the_same() {
+        let temp_dir = TempDir::new().unwrap();
+        let small = temp_dir.path().join("small.py");
+        let large = temp_dir.path().join("large.py");
+        fs::write(&small, "def f(): pass\n").unwrap();
+        let big = "def f(): pass\n".repeat((MMAP_THRESHOLD as usize / 14) + 1);
+        fs::write(&large, &big).unwrap();
+
+        let text = read_source(&small).unwrap();
+        assert!(!text.is_mapped());
+        assert_eq!(&*text, "def f(): pass\n");
+
+        let text = read_source(&large).unwrap();
+        assert_eq!(text.is_mapped(), cfg!(feature = "mmap"));
+        assert_eq!(&*text, big);
+
+        let mut invalid = big.into_bytes();
+        invalid.push(0xff);
+        fs::write(&large, invalid).unwrap();
+        assert!(read_source(&large).is_err());
+    }
+
+    #[test]
+    fn test_lenient_reads_transcode_or_skip() {
+        let temp_dir = TempDir::new().unwrap();
+        let latin1 = temp_dir.path().join("latin1.py");
+        let binary = temp_dir.path().join("blob.py");
+        fs::write(&latin1, b"# caf\xe9\ndef f(): pass\n").unwrap();
+        fs::write(&binary, b"def f(): pass\n\0\x01").unwrap();
+
+        let (text, issue) = read_source_lenient(&latin1, true).unwrap();
+        assert_eq!(issue, Some(SourceIssue::Transcoded));
+        assert!(text.unwrap().starts_with("# caf\u{e9}\n"));
+
+        let (text, issue) = read_source_lenient(&latin1, false).unwrap();
+        assert!(text.is_none());
+        assert_eq!(issue, Some(SourceIssue::InvalidUtf8));
+
+        let (text, issue) = read_source_lenient(&binary, true).unwrap();
+        assert!(text.is_none());
+        assert_eq!(issue, Some(SourceIssue::Binary));
+    }
+}
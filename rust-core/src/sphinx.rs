@@ -0,0 +1,220 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+
+use crate::backends::has_extension;
+use crate::entity::CodeEntity;
+
+/// An entity as Sphinx autodoc's documenters describe it, so a Sphinx
+/// extension can emit `py` domain directives without importing the package.
+///
+/// A directive is rendered as `.. py:{objtype}:: {name}{signature}`, then
+/// the `options` as `:key: value` lines and the indented `content`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SphinxObject {
+    /// `module`, `class`, `exception`, `function`, `method`, `classmethod`,
+    /// `staticmethod` or `property`
+    pub objtype: &'static str,
+    /// Dotted name below the module, e.g. `Client.get`
+    pub name: String,
+    /// Dotted module path, passed as the `:module:` option
+    pub module: String,
+    /// Argument list and return annotation without `self`/`cls`, e.g.
+    /// `(url: str, *args) -> bytes`; empty for modules, properties and
+    /// classes without their own constructor
+    pub signature: String,
+    /// Flag options (`async`, `abstractmethod`, ...) with an empty value,
+    /// and valued ones such as `type` for properties
+    pub options: BTreeMap<String, String>,
+    /// Base classes as written, for autodoc's "Bases:" line
+    pub bases: Vec<String>,
+    /// Docstring lines, cleaned like `inspect.cleandoc`
+    pub content: Vec<String>,
+    pub file_path: String,
+    pub line_number: usize,
+}
+
+impl SphinxObject {
+    /// The directive header, e.g. `.. py:method:: Client.get(key)`
+    pub fn directive(&self) -> String {
+        format!(".. py:{}:: {}{}", self.objtype, self.name, self.signature)
+    }
+}
+
+/// Describe the Python entities in `entities` for Sphinx, in order, with a
+/// `module` object before the first entity of each module. Nested functions
+/// and entities of other languages have no autodoc equivalent and are left out.
+pub fn describe(entities: &[CodeEntity]) -> Vec<SphinxObject> {
+    let mut objects = Vec::new();
+    let mut modules_seen = HashSet::new();
+
+    for entity in entities {
+        if !has_extension(entity.file_path.as_path(), &["py", "pyi", "pyw"]) {
+            continue;
+        }
+        let Some(objtype) = objtype(entity) else { continue };
+        if !entity.module_path.is_empty() && modules_seen.insert(entity.module_path.clone()) {
+            objects.push(SphinxObject {
+                objtype: "module",
+                name: entity.module_path.clone(),
+                module: entity.module_path.clone(),
+                signature: String::new(),
+                options: BTreeMap::new(),
+                bases: Vec::new(),
+                content: Vec::new(),
+                file_path: entity.file_path.to_string_lossy().into_owned(),
+                line_number: 1,
+            });
+        }
+
+        let mut options = BTreeMap::new();
+        let mut flag = |name: &str| {
+            options.insert(name.to_string(), String::new());
+        };
+        if entity.is_async {
+            flag("async");
+        }
+        if entity.decorators.iter().any(|d| last_segment(d).starts_with("abstract")) {
+            flag("abstractmethod");
+        }
+        if entity.decorators.iter().any(|d| last_segment(d) == "final") {
+            flag("final");
+        }
+        if objtype == "property" {
+            if let Some(returns) = &entity.return_type {
+                options.insert("type".to_string(), returns.clone());
+            }
+        }
+
+        let name = entity
+            .qualified_name
+            .strip_prefix(&format!("{}.", entity.module_path))
+            .unwrap_or(&entity.qualified_name);
+        let has_signature = match objtype {
+            "property" => false,
+            "class" | "exception" => entity.has_custom_init,
+            _ => true,
+        };
+        objects.push(SphinxObject {
+            objtype,
+            name: if name.is_empty() { entity.name.clone() } else { name.to_string() },
+            module: entity.module_path.clone(),
+            signature: if has_signature { signature(entity) } else { String::new() },
+            options,
+            bases: entity.bases.clone(),
+            content: entity.docstring.as_deref().map(prepare_docstring).unwrap_or_default(),
+            file_path: entity.file_path.to_string_lossy().into_owned(),
+            line_number: entity.line_number,
+        });
+    }
+    objects
+}
+
+/// The `py` domain directive for `entity`, `None` for entities autodoc
+/// doesn't document (nested functions, non-code entities)
+fn objtype(entity: &CodeEntity) -> Option<&'static str> {
+    let decorated = |names: &[&str]| entity.decorators.iter().any(|d| names.contains(&last_segment(d)));
+    match entity.entity_type.as_str() {
+        "class" if entity.is_exception => Some("exception"),
+        "class" => Some("class"),
+        "function" if entity.parent_id.is_none() => Some("function"),
+        "method" if decorated(&["property", "cached_property"]) => Some("property"),
+        "method" if decorated(&["staticmethod"]) => Some("staticmethod"),
+        "method" if decorated(&["classmethod"]) => Some("classmethod"),
+        "method" => Some("method"),
+        _ => None,
+    }
+}
+
+/// `(a: int, *args) -> str`, leaving out the `self`/`cls` autodoc hides
+fn signature(entity: &CodeEntity) -> String {
+    let types = entity.parameter_types.as_deref();
+    let mut out = String::from("(");
+    let mut first = true;
+    for (index, parameter) in entity.parameters.iter().enumerate() {
+        if index == 0 && entity.entity_type == "method" && matches!(parameter.as_str(), "self" | "cls") {
+            continue;
+        }
+        if !first {
+            out.push_str(", ");
+        }
+        first = false;
+        out.push_str(parameter);
+        if let Some(Some(annotation)) = types.and_then(|types| types.get(index)) {
+            let _ = write!(out, ": {}", annotation);
+        }
+    }
+    out.push(')');
+    if let Some(returns) = entity.return_type.as_deref().filter(|_| entity.entity_type != "class") {
+        let _ = write!(out, " -> {}", returns);
+    }
+    out
+}
+
+/// Docstring lines the way `inspect.cleandoc` leaves them: the common
+/// indentation of all lines after the first removed, and leading and
+/// trailing blank lines dropped
+pub fn prepare_docstring(doc: &str) -> Vec<String> {
+    let mut lines: Vec<&str> = doc.lines().collect();
+    let indent = lines
+        .iter()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    if let Some(first) = lines.first_mut() {
+        *first = first.trim_start();
+    }
+    let mut cleaned: Vec<String> = lines
+        .iter()
+        .enumerate()
+        .map(|(i, &line)| {
+            let line = if i == 0 { line } else { line.get(indent..).unwrap_or_else(|| line.trim_start()) };
+            line.trim_end().to_string()
+        })
+        .collect();
+    while cleaned.last().map_or(false, String::is_empty) {
+        cleaned.pop();
+    }
+    let leading = cleaned.iter().take_while(|line| line.is_empty()).count();
+    cleaned.drain(..leading);
+    cleaned
+}
+
+/// `functools.cached_property` -> `cached_property`, ignoring call arguments
+fn last_segment(name: &str) -> &str {
+    let name = name.split('(').next().unwrap_or(name);
+    name.rsplit('.').next().unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::RustAnalyzer;
+    use std::path::Path;
+
+    #[test]
+    fn test_describe_for_autodoc() {
+        let source = "class Client(Base):\n    \"\"\"HTTP client.\n\n    Keeps a pool.\n    \"\"\"\n\n    def __init__(self, url: str):\n        pass\n\n    async def get(self, key) -> bytes:\n        pass\n\n    @property\n    def size(self) -> int:\n        pass\n\nclass Failed(Exception):\n    pass\n";
+        let entities = RustAnalyzer::new().analyze_source(source, Path::new("client.py")).unwrap();
+        let objects = describe(&entities);
+
+        let headers: Vec<String> = objects.iter().map(SphinxObject::directive).collect();
+        assert_eq!(
+            headers,
+            vec![
+                ".. py:module:: client",
+                ".. py:class:: Client(url: str)",
+                ".. py:method:: Client.__init__(url: str)",
+                ".. py:method:: Client.get(key) -> bytes",
+                ".. py:property:: Client.size",
+                ".. py:exception:: Failed",
+            ]
+        );
+        assert_eq!(objects[1].content, vec!["HTTP client.", "", "Keeps a pool."]);
+        assert_eq!(objects[1].bases, vec!["Base"]);
+        assert!(objects[3].options.contains_key("async"));
+        assert_eq!(objects[4].options.get("type").map(String::as_str), Some("int"));
+    }
+}
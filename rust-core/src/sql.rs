@@ -0,0 +1,106 @@
+use regex::Regex;
+use rustpython_parser::ast;
+use std::sync::OnceLock;
+
+use crate::schema::normalize_table;
+use crate::walk::{self, string_literal, BodyVisitor};
+
+/// Methods that send a query to the database: DB-API cursors and
+/// connections, SQLAlchemy sessions, Django's `raw` and pandas readers
+const QUERY_METHODS: &[&str] = &["execute", "executemany", "executescript", "raw", "read_sql", "read_sql_query"];
+
+/// SQLAlchemy's textual SQL constructor
+const TEXT_CONSTRUCTORS: &[&str] = &["text", "sa.text", "sqlalchemy.text"];
+
+/// Tables named by SQL in a function body, and whether the body holds
+/// SQL-looking strings or calls a database API at all. Walk the body
+/// without its docstring, which is not searched.
+#[derive(Default)]
+pub(crate) struct Queries {
+    pub touches_database: bool,
+    pub tables: Vec<String>,
+}
+
+impl<'a> BodyVisitor<'a> for Queries {
+    fn expr(&mut self, expr: &'a ast::Expr) {
+        match expr {
+            ast::Expr::Call(call) if is_query_call(call) => self.touches_database = true,
+            _ => {
+                let Some(text) = string_literal(expr) else { return };
+                if !looks_like_sql(text) {
+                    return;
+                }
+                self.touches_database = true;
+                for table in tables(text) {
+                    if !self.tables.contains(&table) {
+                        self.tables.push(table);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn is_query_call(call: &ast::ExprCall) -> bool {
+    match &*call.func {
+        ast::Expr::Attribute(attr) if QUERY_METHODS.contains(&attr.attr.as_str()) => true,
+        func => walk::dotted_name(func).map_or(false, |name| TEXT_CONSTRUCTORS.contains(&name.as_str())),
+    }
+}
+
+/// Whether `text` starts like a SQL statement
+pub fn looks_like_sql(text: &str) -> bool {
+    static STATEMENT: OnceLock<Regex> = OnceLock::new();
+    let statement = STATEMENT.get_or_init(|| {
+        Regex::new(
+            r"(?is)^\s*(?:select\s.+\sfrom\s|insert\s+into\s|update\s+\S+\s+set\s|delete\s+from\s|(?:create|alter|drop)\s+(?:table|view|index)\s|with\s+\w+\s+as\s*\()",
+        )
+        .unwrap()
+    });
+    statement.is_match(text)
+}
+
+/// Normalized names of the tables a statement reads or writes, in order of
+/// appearance
+pub fn tables(sql: &str) -> Vec<String> {
+    static TABLE: OnceLock<Regex> = OnceLock::new();
+    let table = TABLE.get_or_init(|| {
+        Regex::new(r#"(?i)\b(?:from|join|into|update|table)\s+(?:if\s+(?:not\s+)?exists\s+)?((?:[\w"`\[\]]+\.)?[\w"`\[\]]+)"#).unwrap()
+    });
+    let mut found = Vec::new();
+    for capture in table.captures_iter(sql) {
+        let name = normalize_table(&capture[1]);
+        if !name.is_empty() && !matches!(name.as_str(), "select" | "lateral" | "only") && !found.contains(&name) {
+            found.push(name);
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::PythonParser;
+    use std::path::Path;
+
+    #[test]
+    fn test_tables() {
+        assert_eq!(
+            tables("SELECT u.id FROM public.\"Users\" u JOIN orders o ON o.user_id = u.id WHERE u.id IN (SELECT id FROM bans)"),
+            vec!["users", "orders", "bans"]
+        );
+        assert_eq!(tables("create table if not exists audit_log (id int)"), vec!["audit_log"]);
+        assert!(!looks_like_sql("Select the rows to keep"));
+    }
+
+    #[test]
+    fn test_database_functions() {
+        let source = "def load(cursor, user_id):\n    \"\"\"Select rows from the user table.\"\"\"\n    cursor.execute(\"SELECT name FROM users WHERE id = %s\", (user_id,))\n\ndef purge(session):\n    session.execute(text(query))\n\ndef pure(items):\n    return \"select one\"\n";
+        let entities = PythonParser::new().parse_source(source, Path::new("db.py")).unwrap();
+
+        assert!(entities[0].touches_database);
+        assert_eq!(entities[0].tables, vec!["users"]);
+        assert!(entities[1].touches_database && entities[1].tables.is_empty());
+        assert!(!entities[2].touches_database);
+    }
+}
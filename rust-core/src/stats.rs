@@ -0,0 +1,166 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::diagnostic::Diagnostic;
+use crate::memory::MemoryUsage;
+use crate::source::SourceIssue;
+
+/// Time spent on one file
+#[derive(Debug, Clone, Default)]
+pub struct FileTiming {
+    pub path: PathBuf,
+    /// Reading (or mapping) the file
+    pub read: Duration,
+    /// Running the language backend over the source
+    pub parse: Duration,
+    /// Post-processing: deprecation, redaction, ids, hooks and the cache write
+    pub extract: Duration,
+    pub total: Duration,
+    pub entities: usize,
+    /// Whether the entities came from the on-disk cache
+    pub cached: bool,
+    pub failed: bool,
+    /// Set when the file was transcoded or skipped for its contents
+    pub issue: Option<SourceIssue>,
+    /// Whether the file looked machine-generated
+    pub generated: bool,
+    /// Where parsing failed, when the file failed with a syntax error
+    pub diagnostic: Option<Diagnostic>,
+}
+
+impl FileTiming {
+    pub fn new(path: &Path) -> Self {
+        FileTiming {
+            path: path.to_path_buf(),
+            ..FileTiming::default()
+        }
+    }
+}
+
+/// Timings of the most recent analysis run.
+///
+/// `walk`, `total` and `convert` are wall-clock times. `read`, `parse` and
+/// `extract` are summed over files, so with several worker threads they can
+/// add up to more than `total`.
+#[derive(Debug, Clone, Default)]
+pub struct RunStats {
+    pub files: usize,
+    pub entities: usize,
+    pub failed: usize,
+    pub cache_hits: usize,
+    /// Files skipped as binary or invalid UTF-8
+    pub skipped: usize,
+    /// Files decoded as Latin-1
+    pub transcoded: usize,
+    /// Machine-generated files, whether skipped or tagged
+    pub generated: usize,
+    /// Walking the tree to find source files
+    pub walk: Duration,
+    pub read: Duration,
+    pub parse: Duration,
+    /// Per-file post-processing plus cross-file linking and git blame
+    pub extract: Duration,
+    /// Converting results for the Python bindings, including Python hooks
+    pub convert: Duration,
+    pub total: Duration,
+    /// One entry per file, in analysis order
+    pub file_timings: Vec<FileTiming>,
+    /// Estimated size of the returned entities
+    pub memory: MemoryUsage,
+}
+
+impl RunStats {
+    /// Summarize per-file timings
+    pub fn from_files(file_timings: Vec<FileTiming>) -> Self {
+        let mut stats = RunStats {
+            files: file_timings.len(),
+            ..RunStats::default()
+        };
+        for timing in &file_timings {
+            stats.entities += timing.entities;
+            stats.failed += usize::from(timing.failed);
+            stats.cache_hits += usize::from(timing.cached);
+            stats.generated += usize::from(timing.generated);
+            match timing.issue {
+                Some(SourceIssue::Transcoded) => stats.transcoded += 1,
+                Some(_) => stats.skipped += 1,
+                None => {}
+            }
+            stats.read += timing.read;
+            stats.parse += timing.parse;
+            stats.extract += timing.extract;
+        }
+        stats.file_timings = file_timings;
+        stats
+    }
+
+    /// Syntax errors of the files that failed to parse, in analysis order
+    pub fn diagnostics(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.file_timings.iter().filter_map(|timing| timing.diagnostic.as_ref())
+    }
+
+    /// The `n` files that took longest, slowest first
+    pub fn slowest(&self, n: usize) -> Vec<&FileTiming> {
+        let mut timings: Vec<&FileTiming> = self.file_timings.iter().collect();
+        timings.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.path.cmp(&b.path)));
+        timings.truncate(n);
+        timings
+    }
+}
+
+impl std::fmt::Display for RunStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} entities from {} files in {}ms ({} cached, {} failed, {} skipped, {} transcoded)",
+            self.entities,
+            self.files,
+            self.total.as_millis(),
+            self.cache_hits,
+            self.failed,
+            self.skipped,
+            self.transcoded,
+        )?;
+        write!(
+            f,
+            "walk {}ms, read {}ms, parse {}ms, extract {}ms, convert {}ms",
+            self.walk.as_millis(),
+            self.read.as_millis(),
+            self.parse.as_millis(),
+            self.extract.as_millis(),
+            self.convert.as_millis(),
+        )?;
+        write!(f, "\nmemory {}", self.memory)?;
+        for timing in self.slowest(5) {
+            write!(f, "\n  {}ms {}", timing.total.as_millis(), timing.path.display())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timing(path: &str, millis: u64, entities: usize) -> FileTiming {
+        FileTiming {
+            parse: Duration::from_millis(millis),
+            total: Duration::from_millis(millis),
+            entities,
+            ..FileTiming::new(Path::new(path))
+        }
+    }
+
+    #[test]
+    fn test_totals_and_slowest_files() {
+        let mut failed = timing("c.py", 1, 0);
+        failed.failed = true;
+        let stats = RunStats::from_files(vec![timing("a.py", 5, 2), timing("b.py", 30, 7), failed]);
+
+        assert_eq!((stats.files, stats.entities, stats.failed), (3, 9, 1));
+        assert_eq!(stats.parse, Duration::from_millis(36));
+        let slowest: Vec<&Path> = stats.slowest(2).iter().map(|t| t.path.as_path()).collect();
+        assert_eq!(slowest, vec![Path::new("b.py"), Path::new("a.py")]);
+        assert!(stats.to_string().contains("\n  30ms b.py"));
+    }
+}
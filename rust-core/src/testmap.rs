@@ -0,0 +1,566 @@
+use rustpython_parser::ast::{self, Ranged};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::entity::CodeEntity;
+use crate::location::line_at;
+use crate::walk::{self, argument, dotted_name, keyword, string_literal, BodyVisitor};
+
+/// Most cases recorded for one test, so stacked parametrizations can't blow up
+const MAX_CASES: usize = 100;
+
+/// Longest harvested example, in lines, imports included
+const MAX_EXAMPLE_LINES: usize = 10;
+
+/// Examples kept per entity, shortest first
+const MAX_EXAMPLES: usize = 3;
+
+/// Builtins an example may use without defining them
+const BUILTINS: &[&str] = &[
+    "len", "str", "int", "float", "bool", "bytes", "list", "dict", "set", "tuple", "range", "print", "sorted", "sum",
+    "min", "max", "abs", "any", "all", "isinstance", "type", "repr", "open", "iter", "next", "enumerate", "zip",
+];
+
+/// Production code a test exercises, as `analyze_directory` found it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TestMapping {
+    pub entity_id: String,
+    pub qualified_name: String,
+    /// Entity IDs of the tests calling it
+    pub tests: Vec<String>,
+}
+
+/// One concrete case of a parametrized test
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestCase {
+    /// From `ids=` or `pytest.param(..., id=...)`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Argument names with their values as written in the source
+    pub args: Vec<(String, String)>,
+}
+
+/// A call of a public API lifted out of a test: the call and the lines
+/// arranging its arguments, with the imports they need
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageExample {
+    /// Dotted name of the API called, through the test's imports
+    pub target: String,
+    pub code: String,
+    /// Line of the call in the test file
+    pub line: usize,
+    /// Entity ID of the test it came from, once linked
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub test_id: String,
+}
+
+/// Whether `path` holds tests by pytest's conventions: `test_*.py`,
+/// `*_test.py`, or anything under a `tests`/`test` directory.
+///
+/// Only directories below `root` count, so a checkout cloned into
+/// `~/tests/` doesn't turn every file into a test. Without a root only the
+/// directories of a relative path count.
+pub fn is_test_file(path: &Path, root: Option<&Path>) -> bool {
+    let relative = root.and_then(|root| path.strip_prefix(root).ok()).unwrap_or(path);
+    is_test_name(path) || (relative.is_relative() && under_test_dir(relative))
+}
+
+/// `is_test_file` for the parser, which doesn't know the analysis root:
+/// any directory counts. `clear_non_tests` drops what it gathered from
+/// files that aren't tests below the root.
+pub fn may_hold_tests(path: &Path) -> bool {
+    is_test_name(path) || under_test_dir(path)
+}
+
+fn is_test_name(path: &Path) -> bool {
+    let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+    stem.starts_with("test_") || stem.ends_with("_test")
+}
+
+fn under_test_dir(path: &Path) -> bool {
+    path.parent()
+        .into_iter()
+        .flat_map(Path::components)
+        .any(|part| matches!(part.as_os_str().to_str(), Some("tests" | "test")))
+}
+
+/// Drop the calls and examples gathered from one file's test functions when
+/// the file isn't a test below `root`
+pub fn clear_non_tests(entities: &mut [CodeEntity], root: Option<&Path>) {
+    let Some(first) = entities.first() else { return };
+    if is_test_file(&first.file_path, root) {
+        return;
+    }
+    for entity in entities {
+        entity.exercises.clear();
+        entity.harvested_examples.clear();
+    }
+}
+
+/// Names bound by the imports among `stmts`, mapped to what they import:
+/// `from app.models import User as U` binds `U` to `app.models.User`
+pub fn imports(stmts: &[ast::Stmt]) -> HashMap<String, String> {
+    let mut names = HashMap::new();
+    for stmt in stmts {
+        add_import(stmt, &mut names);
+    }
+    names
+}
+
+fn add_import(stmt: &ast::Stmt, names: &mut HashMap<String, String>) {
+    match stmt {
+        ast::Stmt::Import(import) => {
+            for alias in &import.names {
+                let module = alias.name.as_str();
+                match &alias.asname {
+                    Some(asname) => names.insert(asname.to_string(), module.to_string()),
+                    // `import app.models` binds `app`
+                    None => {
+                        let head = module.split('.').next().unwrap_or(module);
+                        names.insert(head.to_string(), head.to_string())
+                    }
+                };
+            }
+        }
+        ast::Stmt::ImportFrom(from) => {
+            let module = from.module.as_ref().map_or("", |module| module.as_str());
+            for alias in from.names.iter().filter(|alias| alias.name.as_str() != "*") {
+                let local = alias.asname.as_ref().unwrap_or(&alias.name);
+                let full = match module {
+                    "" => alias.name.to_string(),
+                    module => format!("{}.{}", module, alias.name),
+                };
+                names.insert(local.to_string(), full);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Names in scope of a test body that lead to imported code: imports, and
+/// variables holding an instance of an imported class
+struct Resolver {
+    names: HashMap<String, String>,
+}
+
+impl Resolver {
+    /// `client.send` -> `app.Client.send` after `client = Client()`
+    fn resolve(&self, expr: &ast::Expr) -> Option<String> {
+        let name = dotted_name(expr)?;
+        let (head, rest) = match name.split_once('.') {
+            Some((head, rest)) => (head, Some(rest)),
+            None => (name.as_str(), None),
+        };
+        let base = self.names.get(head)?;
+        Some(match rest {
+            Some(rest) => format!("{}.{}", base, rest),
+            None => base.clone(),
+        })
+    }
+
+    /// Follow imports and assignments in `stmt`
+    fn track(&mut self, stmt: &ast::Stmt) {
+        add_import(stmt, &mut self.names);
+        let ast::Stmt::Assign(assign) = stmt else { return };
+        let ([ast::Expr::Name(target)], ast::Expr::Call(call)) = (&assign.targets[..], &*assign.value) else { return };
+        let class = self
+            .resolve(&call.func)
+            .filter(|name| name.rsplit('.').next().map_or(false, |last| last.starts_with(char::is_uppercase)));
+        match class {
+            Some(class) => self.names.insert(target.id.to_string(), class),
+            None => self.names.remove(target.id.as_str()),
+        };
+    }
+}
+
+/// Imported code a test body calls, as dotted names through the module's
+/// `imports` and the test's own; walk the body with it. Calls on a variable
+/// holding an instance, `client = Client(); client.send()`, count as calls
+/// of `Client.send`.
+pub(crate) struct Exercised {
+    resolver: Resolver,
+    pub found: Vec<String>,
+}
+
+impl Exercised {
+    pub fn new(imports: &HashMap<String, String>) -> Self {
+        Exercised {
+            resolver: Resolver { names: imports.clone() },
+            found: Vec::new(),
+        }
+    }
+}
+
+impl<'a> BodyVisitor<'a> for Exercised {
+    fn stmt(&mut self, stmt: &'a ast::Stmt) {
+        self.resolver.track(stmt);
+    }
+
+    fn expr(&mut self, expr: &'a ast::Expr) {
+        let ast::Expr::Call(call) = expr else { return };
+        if let Some(name) = self.resolver.resolve(&call.func) {
+            if !self.found.contains(&name) {
+                self.found.push(name);
+            }
+        }
+    }
+}
+
+/// Self-contained examples in a test body, one per API called.
+///
+/// Each top-level statement calling imported code is an "act" line; the
+/// assignments before it that define the names it uses are its "arrange"
+/// lines, and the imports those lines rely on go on top. Statements using
+/// anything else, such as pytest fixtures or `self`, can't stand alone and
+/// are skipped, as are examples over `MAX_EXAMPLE_LINES` lines.
+pub fn examples(body: &[ast::Stmt], imports: &HashMap<String, String>, source: &str, line_starts: &[usize]) -> Vec<UsageExample> {
+    let mut resolver = Resolver { names: imports.clone() };
+    let mut found: Vec<UsageExample> = Vec::new();
+    for (index, stmt) in body.iter().enumerate() {
+        let mut target = None;
+        walk::for_each_call(std::slice::from_ref(stmt), |call| {
+            if target.is_none() {
+                target = resolver.resolve(&call.func);
+            }
+        });
+        resolver.track(stmt);
+        let Some(target) = target.filter(|target| !found.iter().any(|example| &example.target == target)) else { continue };
+
+        // Walk back through the assignments defining what the call uses
+        let mut needed = free_names(stmt);
+        let mut lines = vec![stmt];
+        for earlier in body[..index].iter().rev() {
+            if !matches!(earlier, ast::Stmt::Assign(_) | ast::Stmt::AnnAssign(_)) {
+                continue;
+            }
+            let defined = stored_names(earlier);
+            if defined.iter().any(|name| needed.contains(name)) {
+                needed.retain(|name| !defined.contains(name));
+                needed.extend(free_names(earlier));
+                lines.push(earlier);
+            }
+        }
+        lines.reverse();
+
+        let mut import_lines: Vec<String> = Vec::new();
+        let mut standalone = true;
+        for name in &needed {
+            match imports.get(name) {
+                Some(full) => import_lines.push(import_line(name, full)),
+                None if BUILTINS.contains(&name.as_str()) => {}
+                None => standalone = false,
+            }
+        }
+        if !standalone {
+            continue;
+        }
+        import_lines.sort();
+        import_lines.dedup();
+
+        let mut code = import_lines.join("\n");
+        if !code.is_empty() {
+            code.push_str("\n\n");
+        }
+        code.push_str(&dedent(&lines.iter().map(|stmt| statement_text(stmt, source)).collect::<Vec<_>>().join("\n")));
+        if code.lines().count() > MAX_EXAMPLE_LINES {
+            continue;
+        }
+        found.push(UsageExample {
+            target,
+            code,
+            line: line_at(line_starts, usize::from(stmt.range().start())),
+            test_id: String::new(),
+        });
+    }
+    found
+}
+
+/// Names a statement reads that it doesn't bind itself
+fn free_names(stmt: &ast::Stmt) -> HashSet<String> {
+    let (loaded, stored) = names(stmt);
+    loaded.into_iter().filter(|name| !stored.contains(name)).collect()
+}
+
+fn stored_names(stmt: &ast::Stmt) -> HashSet<String> {
+    names(stmt).1
+}
+
+/// Names loaded and names stored in a statement
+fn names(stmt: &ast::Stmt) -> (HashSet<String>, HashSet<String>) {
+    #[derive(Default)]
+    struct Names(HashSet<String>, HashSet<String>);
+    impl<'a> BodyVisitor<'a> for Names {
+        fn expr(&mut self, expr: &'a ast::Expr) {
+            if let ast::Expr::Name(name) = expr {
+                match name.ctx {
+                    ast::ExprContext::Load => self.0.insert(name.id.to_string()),
+                    _ => self.1.insert(name.id.to_string()),
+                };
+            }
+        }
+    }
+    let mut names = Names::default();
+    walk::walk_body(std::slice::from_ref(stmt), &mut names);
+    (names.0, names.1)
+}
+
+/// An import statement binding `local` to `full`
+fn import_line(local: &str, full: &str) -> String {
+    match full.rsplit_once('.') {
+        None if local == full => format!("import {}", full),
+        None => format!("import {} as {}", full, local),
+        Some((module, name)) if name == local => format!("from {} import {}", module, name),
+        Some((module, name)) => format!("from {} import {} as {}", module, name, local),
+    }
+}
+
+/// The full lines of a statement, from the start of its first line
+fn statement_text<'s>(stmt: &ast::Stmt, source: &'s str) -> &'s str {
+    let range = stmt.range();
+    let end = usize::from(range.end()).min(source.len());
+    let start = source[..usize::from(range.start()).min(end)].rfind('\n').map_or(0, |newline| newline + 1);
+    &source[start..end]
+}
+
+/// Strip the indentation all non-blank lines share
+fn dedent(text: &str) -> String {
+    let indent = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    text.lines()
+        .map(|line| line.get(indent..).unwrap_or_else(|| line.trim_start()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The cases `@pytest.mark.parametrize` decorators give a test; stacked
+/// decorators multiply out like pytest does. Parametrizations over
+/// computed values, e.g. a module-level `CASES` list, can't be expanded
+/// and are left out.
+pub fn parametrize(decorators: &[ast::Expr], source: &str) -> Vec<TestCase> {
+    let mut cases: Option<Vec<TestCase>> = None;
+    for decorator in decorators {
+        let ast::Expr::Call(call) = decorator else { continue };
+        if !dotted_name(&call.func).map_or(false, |name| name.ends_with("parametrize")) {
+            continue;
+        }
+        let Some(set) = parametrization(call, source) else { continue };
+        cases = Some(match cases {
+            None => set,
+            Some(cases) => cases
+                .iter()
+                .flat_map(|case| {
+                    set.iter().map(move |other| TestCase {
+                        id: case.id.as_ref().zip(other.id.as_ref()).map(|(a, b)| format!("{}-{}", a, b)),
+                        args: case.args.iter().chain(&other.args).cloned().collect(),
+                    })
+                })
+                .take(MAX_CASES)
+                .collect(),
+        });
+    }
+    cases.unwrap_or_default()
+}
+
+/// The cases of one `parametrize("a,b", [(1, 2), pytest.param(3, 4, id="x")], ids=...)`
+fn parametrization(call: &ast::ExprCall, source: &str) -> Option<Vec<TestCase>> {
+    let names: Vec<String> = match argument(call, 0, "argnames")? {
+        ast::Expr::List(list) => list.elts.iter().filter_map(string_literal).map(str::to_string).collect(),
+        ast::Expr::Tuple(tuple) => tuple.elts.iter().filter_map(string_literal).map(str::to_string).collect(),
+        names => string_literal(names)?
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect(),
+    };
+    let rows = match argument(call, 1, "argvalues")? {
+        ast::Expr::List(list) => &list.elts,
+        ast::Expr::Tuple(tuple) => &tuple.elts,
+        _ => return None,
+    };
+    let ids: Vec<Option<&str>> = match keyword(call, "ids") {
+        Some(ast::Expr::List(list)) => list.elts.iter().map(string_literal).collect(),
+        Some(ast::Expr::Tuple(tuple)) => tuple.elts.iter().map(string_literal).collect(),
+        _ => Vec::new(),
+    };
+
+    let text = |expr: &ast::Expr| {
+        let range = expr.range();
+        source.get(usize::from(range.start())..usize::from(range.end())).unwrap_or_default().to_string()
+    };
+    let cases = rows
+        .iter()
+        .enumerate()
+        .filter_map(|(index, row)| {
+            let (values, id): (&[ast::Expr], Option<&str>) = match row {
+                ast::Expr::Call(param) if dotted_name(&param.func).map_or(false, |name| name.ends_with("param")) => {
+                    (&param.args, keyword(param, "id").and_then(string_literal))
+                }
+                _ if names.len() == 1 => (std::slice::from_ref(row), None),
+                ast::Expr::Tuple(tuple) => (&tuple.elts, None),
+                ast::Expr::List(list) => (&list.elts, None),
+                _ => return None,
+            };
+            if values.len() != names.len() {
+                return None;
+            }
+            Some(TestCase {
+                id: id.or_else(|| ids.get(index).copied().flatten()).map(str::to_string),
+                args: names.iter().cloned().zip(values.iter().map(text)).collect(),
+            })
+        })
+        .take(MAX_CASES)
+        .collect();
+    Some(cases)
+}
+
+/// Fill `tested_by` on production entities from the `exercises` of tests.
+///
+/// A name matches the entity with that qualified name, or else the only
+/// entity whose qualified name ends with it, which covers tests importing a
+/// package by a shorter path than its module path. Test files are told
+/// apart by `is_test_file` under `root`.
+pub fn link(entities: &mut [CodeEntity], root: Option<&Path>) {
+    let mut by_qualified: HashMap<&str, usize> = HashMap::new();
+    let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, entity) in entities.iter().enumerate() {
+        if !is_test_file(&entity.file_path, root) {
+            by_qualified.insert(entity.qualified_name.as_str(), index);
+            by_name.entry(entity.name.as_str()).or_default().push(index);
+        }
+    }
+    let resolve = |name: &str| -> Option<usize> {
+        if let Some(&index) = by_qualified.get(name) {
+            return Some(index);
+        }
+        let last = name.rsplit('.').next().unwrap_or(name);
+        let suffix = format!(".{}", name);
+        let mut matches = by_name.get(last)?.iter().filter(|&&index| entities[index].qualified_name.ends_with(&suffix));
+        match (matches.next(), matches.next()) {
+            (Some(&index), None) => Some(index),
+            _ => None,
+        }
+    };
+
+    let mut links: Vec<(usize, String)> = Vec::new();
+    let mut examples: Vec<(usize, UsageExample)> = Vec::new();
+    for test in entities.iter().filter(|e| !e.exercises.is_empty()) {
+        for name in &test.exercises {
+            if let Some(index) = resolve(name) {
+                links.push((index, test.entity_id.clone()));
+            }
+        }
+        for example in &test.harvested_examples {
+            if let Some(index) = resolve(&example.target) {
+                let example = UsageExample {
+                    test_id: test.entity_id.clone(),
+                    ..example.clone()
+                };
+                examples.push((index, example));
+            }
+        }
+    }
+
+    for entity in entities.iter_mut() {
+        entity.tested_by.clear();
+        entity.examples.clear();
+    }
+    for (index, test) in links {
+        if !entities[index].tested_by.contains(&test) {
+            entities[index].tested_by.push(test);
+        }
+    }
+    for (index, example) in examples {
+        if !entities[index].examples.iter().any(|known| known.code == example.code) {
+            entities[index].examples.push(example);
+        }
+    }
+    for entity in entities.iter_mut().filter(|e| !e.examples.is_empty()) {
+        entity.examples.sort_by_key(|example| example.code.len());
+        entity.examples.truncate(MAX_EXAMPLES);
+    }
+}
+
+/// Entities with tests, in entity order
+pub fn mappings(entities: &[CodeEntity]) -> Vec<TestMapping> {
+    entities
+        .iter()
+        .filter(|entity| !entity.tested_by.is_empty())
+        .map(|entity| TestMapping {
+            entity_id: entity.entity_id.clone(),
+            qualified_name: entity.qualified_name.clone(),
+            tests: entity.tested_by.clone(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::RustAnalyzer;
+
+    #[test]
+    fn test_parametrize_cases() {
+        let source = "import pytest\n\n@pytest.mark.parametrize(\"a, b\", [(1, 2), pytest.param(\"x\", None, id=\"text\")])\n@pytest.mark.parametrize(\"mode\", [\"fast\", \"safe\"], ids=[\"f\", \"s\"])\ndef test_add(a, b, mode):\n    pass\n";
+        let entities = crate::parser::PythonParser::new().parse_source(source, Path::new("test_add.py")).unwrap();
+        let cases = &entities[0].test_cases;
+
+        assert_eq!(cases.len(), 4);
+        assert_eq!(cases[0].id, None);
+        assert_eq!(cases[0].args, vec![("a".to_string(), "1".to_string()), ("b".to_string(), "2".to_string()), ("mode".to_string(), "\"fast\"".to_string())]);
+        assert_eq!(cases[3].id.as_deref(), Some("text-s"));
+        assert_eq!(cases[3].args[1], ("b".to_string(), "None".to_string()));
+    }
+
+    #[test]
+    fn test_map_tests_to_code() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("app")).unwrap();
+        std::fs::create_dir_all(dir.path().join("tests")).unwrap();
+        std::fs::write(
+            dir.path().join("app/billing.py"),
+            "def total(items):\n    return sum(items)\n\nclass Invoice:\n    def send(self):\n        pass\n\ndef untested():\n    pass\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("tests/test_billing.py"),
+            "from app.billing import total\nfrom app import billing\n\ndef test_total():\n    assert total([1, 2]) == 3\n\ndef test_send():\n    invoice = billing.Invoice()\n    invoice.send()\n",
+        )
+        .unwrap();
+
+        let entities = RustAnalyzer::new().analyze_directory(dir.path()).unwrap();
+        let find = |name: &str| entities.iter().find(|e| e.name == name).unwrap();
+        assert_eq!(find("test_send").exercises, vec!["app.billing.Invoice", "app.billing.Invoice.send"]);
+        assert_eq!(find("total").tested_by, vec![find("test_total").entity_id.clone()]);
+        assert_eq!(find("send").tested_by, vec![find("test_send").entity_id.clone()]);
+        assert_eq!(find("send").examples[0].code, "from app import billing\n\ninvoice = billing.Invoice()\ninvoice.send()");
+        assert_eq!(find("send").examples[0].test_id, find("test_send").entity_id);
+        assert_eq!(find("total").examples[0].code, "from app.billing import total\n\nassert total([1, 2]) == 3");
+        assert!(find("untested").tested_by.is_empty());
+
+        let mapping = mappings(&entities);
+        assert_eq!(mapping.len(), 3);
+        assert!(mapping.iter().any(|m| m.entity_id == find("Invoice").entity_id && m.tests.len() == 1));
+    }
+
+    #[test]
+    fn test_only_directories_below_the_root_mark_tests() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("tests/project");
+        std::fs::create_dir_all(root.join("checks")).unwrap();
+        std::fs::create_dir_all(root.join("test")).unwrap();
+        std::fs::write(root.join("app.py"), "def total(items):\n    return sum(items)\n").unwrap();
+        std::fs::write(root.join("checks/totals.py"), "from app import total\n\ndef test_sum():\n    total([1])\n").unwrap();
+        std::fs::write(root.join("test/totals.py"), "from app import total\n\ndef test_total():\n    total([1])\n").unwrap();
+
+        let entities = RustAnalyzer::new().analyze_directory(&root).unwrap();
+        let find = |name: &str| entities.iter().find(|e| e.name == name).unwrap();
+        assert!(find("test_sum").exercises.is_empty());
+        assert_eq!(find("total").tested_by, vec![find("test_total").entity_id.clone()]);
+    }
+}
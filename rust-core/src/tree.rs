@@ -0,0 +1,240 @@
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::entity::CodeEntity;
+use crate::ident::normalize_identifier;
+
+/// Level of a node in the module tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    Package,
+    Module,
+    Entity,
+}
+
+impl NodeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NodeKind::Package => "package",
+            NodeKind::Module => "module",
+            NodeKind::Entity => "entity",
+        }
+    }
+}
+
+/// One node of the package → module → class → member hierarchy
+#[derive(Debug, Clone, Serialize)]
+pub struct TreeNode {
+    pub name: String,
+    pub kind: NodeKind,
+    /// Dotted path from the root, e.g. `pkg.api.Client.get`; empty for the root
+    pub path: String,
+    /// Position of the entity in the source slice, for entity nodes
+    pub entity: Option<usize>,
+    pub children: Vec<TreeNode>,
+}
+
+impl TreeNode {
+    fn new(name: &str, kind: NodeKind, parent: &str, entity: Option<usize>) -> Self {
+        let name = normalize_identifier(name);
+        TreeNode {
+            name: name.to_string(),
+            kind,
+            path: if parent.is_empty() { name.to_string() } else { format!("{}.{}", parent, name) },
+            entity,
+            children: Vec::new(),
+        }
+    }
+
+    /// The node at a dotted path below this one, compared after NFKC normalization
+    pub fn find(&self, path: &str) -> Option<&TreeNode> {
+        let path = normalize_identifier(path);
+        path.split('.')
+            .filter(|part| !part.is_empty())
+            .try_fold(self, |node, part| node.children.iter().find(|child| child.name == part))
+    }
+
+    /// This node and all of its descendants, depth first
+    pub fn walk(&self) -> Vec<&TreeNode> {
+        let mut nodes = vec![self];
+        for child in &self.children {
+            nodes.extend(child.walk());
+        }
+        nodes
+    }
+
+    /// The child `name` of `kind`, created if missing; the last one when
+    /// a scope defines a name twice, as that definition is the one in effect
+    fn child(&mut self, name: &str, kind: NodeKind) -> &mut TreeNode {
+        let name = normalize_identifier(name);
+        let position = match self.children.iter().rposition(|c| c.name == name && c.kind == kind) {
+            Some(position) => position,
+            None => {
+                self.children.push(TreeNode::new(&name, kind, &self.path, None));
+                self.children.len() - 1
+            }
+        };
+        &mut self.children[position]
+    }
+
+    /// Attach entity `index` as child `name`, filling in the node its
+    /// members created if they came first
+    fn attach(&mut self, name: &str, index: usize) {
+        let name = normalize_identifier(name);
+        let placeholder = self
+            .children
+            .iter_mut()
+            .find(|c| c.name == name && c.kind == NodeKind::Entity && c.entity.is_none());
+        match placeholder {
+            Some(node) => node.entity = Some(index),
+            None => self.children.push(TreeNode::new(&name, NodeKind::Entity, &self.path, Some(index))),
+        }
+    }
+}
+
+/// Arrange entities into a package → module → class → member tree.
+///
+/// Modules are placed by `module_path`, or by the file path relative to
+/// `root` (the common ancestor directory of all files when `None`) for
+/// entities analysis gave none; a package's own entities sit in an
+/// `__init__` module. Members nest by `qualified_name` below their module,
+/// in source order. Packages sort before modules, both by name.
+pub fn build_tree(entities: &[CodeEntity], root: Option<&Path>) -> TreeNode {
+    let root = root.map(Path::to_path_buf).unwrap_or_else(|| common_root(entities));
+
+    // Enclosing definitions first when two start on the same line
+    let mut order: Vec<usize> = (0..entities.len()).collect();
+    order.sort_by_key(|&i| (entities[i].file_path.as_path(), entities[i].line_number, Reverse(entities[i].end_line)));
+
+    let mut tree = TreeNode::new("", NodeKind::Package, "", None);
+    for index in order {
+        let entity = &entities[index];
+        let (module, parts) = module_of(entity, &root);
+        let mut node = &mut tree;
+        if let Some((last, packages)) = parts.split_last() {
+            for package in packages {
+                node = node.child(package, NodeKind::Package);
+            }
+            node = node.child(last, NodeKind::Module);
+        }
+
+        let qualified = match entity.qualified_name.as_str() {
+            "" => entity.name.as_str(),
+            qualified => qualified,
+        };
+        let local = qualified
+            .strip_prefix(module.as_str())
+            .and_then(|rest| rest.strip_prefix('.'))
+            .unwrap_or(qualified);
+        let scope = local.strip_suffix(entity.name.as_str()).unwrap_or_default().trim_end_matches('.');
+        for part in scope.split('.').filter(|part| !part.is_empty()) {
+            node = node.child(part, NodeKind::Entity);
+        }
+        node.attach(&entity.name, index);
+    }
+
+    sort_packages(&mut tree);
+    tree
+}
+
+/// The entity's dotted module path and the tree nodes it lives under:
+/// `module_path`, or the file path relative to `root` when analysis didn't
+/// resolve one. Package `__init__` files get a module of that name.
+fn module_of(entity: &CodeEntity, root: &Path) -> (String, Vec<String>) {
+    let is_init = entity.file_path.file_stem().map_or(false, |stem| stem == "__init__");
+    if !entity.module_path.is_empty() {
+        let mut parts: Vec<String> = entity.module_path.split('.').map(str::to_string).collect();
+        if is_init {
+            parts.push("__init__".to_string());
+        }
+        return (entity.module_path.clone(), parts);
+    }
+
+    let relative = entity.file_path.strip_prefix(&root).unwrap_or(entity.file_path.as_path());
+    let mut parts: Vec<String> = relative
+        .parent()
+        .into_iter()
+        .flat_map(Path::components)
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    parts.push(relative.file_stem().map_or_else(|| "(unknown)".to_string(), |stem| stem.to_string_lossy().into_owned()));
+    let module = match is_init {
+        true => parts[..parts.len() - 1].join("."),
+        false => parts.join("."),
+    };
+    (module, parts)
+}
+
+/// Sort package and module children by kind, then name; entity order is kept
+fn sort_packages(node: &mut TreeNode) {
+    if node.kind == NodeKind::Package {
+        node.children.sort_by(|a, b| {
+            let rank = |n: &TreeNode| n.kind != NodeKind::Package;
+            rank(a).cmp(&rank(b)).then_with(|| a.name.cmp(&b.name))
+        });
+        node.children.iter_mut().for_each(sort_packages);
+    }
+}
+
+/// Deepest directory containing every entity's file
+fn common_root(entities: &[CodeEntity]) -> PathBuf {
+    let mut dirs = entities.iter().map(|e| e.file_path.parent().unwrap_or(Path::new("")));
+    let Some(first) = dirs.next() else { return PathBuf::new() };
+    dirs.fold(first.to_path_buf(), |common, dir| {
+        common
+            .components()
+            .zip(dir.components())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a)
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_tree() {
+        let entities = vec![
+            CodeEntity::stub("function", "main", "/repo/cli.py").lines(1, 3),
+            CodeEntity::stub("method", "Client.get", "/repo/pkg/api.py").lines(12, 14),
+            CodeEntity::stub("class", "Client", "/repo/pkg/api.py").lines(10, 20),
+            CodeEntity::stub("function", "helper", "/repo/pkg/api.py").lines(22, 23),
+            CodeEntity::stub("class", "Model", "/repo/pkg/db/models.py").lines(1, 5),
+        ];
+        let tree = build_tree(&entities, None);
+
+        let names: Vec<&str> = tree.children.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, vec!["pkg", "cli"]);
+
+        let client = tree.find("pkg.api.Client").unwrap();
+        assert_eq!(client.entity, Some(2));
+        assert_eq!(client.children[0].path, "pkg.api.Client.get");
+        assert_eq!(tree.find("pkg.api.helper").unwrap().kind, NodeKind::Entity);
+        assert_eq!(tree.find("pkg.db").unwrap().kind, NodeKind::Package);
+        assert!(tree.find("pkg.api.get").is_none());
+        assert_eq!(tree.walk().iter().filter(|n| n.entity.is_some()).count(), 5);
+    }
+
+    #[test]
+    fn test_tree_follows_module_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/shop")).unwrap();
+        std::fs::write(dir.path().join("src/shop/__init__.py"), "def setup():\n    pass\n").unwrap();
+        std::fs::write(
+            dir.path().join("src/shop/cart.py"),
+            "class Cart:\n    def total(self):\n        def add(a, b):\n            return a + b\n        return 0\n",
+        )
+        .unwrap();
+
+        let entities = crate::analyzer::RustAnalyzer::new().analyze_directory(dir.path()).unwrap();
+        let tree = build_tree(&entities, None);
+        assert!(tree.find("src").is_none());
+        assert_eq!(tree.find("shop.__init__.setup").unwrap().kind, NodeKind::Entity);
+        assert_eq!(tree.find("shop.cart.Cart.total.add").unwrap().path, "shop.cart.Cart.total.add");
+    }
+}
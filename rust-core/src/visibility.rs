@@ -0,0 +1,346 @@
+use anyhow::{bail, Result};
+use regex::Regex;
+use std::collections::HashSet;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use crate::config::AnalyzerConfig;
+use crate::entity::CodeEntity;
+use crate::hierarchy;
+use crate::intern::Symbol;
+
+/// Evidence on whether a Python entity is internal. The analyzer consults
+/// the signals in the configured order and the first with a verdict wins;
+/// when none has one the entity is public.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VisibilitySignal {
+    /// The module, or a package directory above it, has a leading
+    /// underscore: everything in it is internal
+    PrivateModule,
+    /// The module defines `__all__`: top-level names listed there are
+    /// public, others (and their members) are internal
+    DunderAll,
+    /// The entity or a class enclosing it has a leading underscore
+    Underscore,
+}
+
+/// Private modules first, then `__all__` (so listing `_helper` exports
+/// it), then underscores
+pub const DEFAULT_PRECEDENCE: [VisibilitySignal; 3] = [
+    VisibilitySignal::PrivateModule,
+    VisibilitySignal::DunderAll,
+    VisibilitySignal::Underscore,
+];
+
+impl VisibilitySignal {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VisibilitySignal::PrivateModule => "private-module",
+            VisibilitySignal::DunderAll => "__all__",
+            VisibilitySignal::Underscore => "underscore",
+        }
+    }
+}
+
+impl FromStr for VisibilitySignal {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "private-module" => Ok(VisibilitySignal::PrivateModule),
+            "__all__" | "all" => Ok(VisibilitySignal::DunderAll),
+            "underscore" => Ok(VisibilitySignal::Underscore),
+            _ => bail!("Unknown visibility signal '{}' (expected private-module, __all__ or underscore)", s),
+        }
+    }
+}
+
+/// How the Python parser decides `is_internal`.
+///
+/// Explicit markers win over every signal: a decorator named in
+/// `public_decorators`/`private_decorators`, or a `#: public-api` /
+/// `#: private-api` comment on the definition line or the comment and
+/// decorator lines just above it. Members of a class marked private are
+/// internal; members of a class marked public only look at their own name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VisibilityRules {
+    pub precedence: Vec<VisibilitySignal>,
+    /// Decorators declaring an entity public, matched on the name before
+    /// any arguments, either in full or by its last dotted segment
+    pub public_decorators: Vec<String>,
+    /// Decorators declaring an entity internal, matched the same way
+    pub private_decorators: Vec<String>,
+}
+
+impl Default for VisibilityRules {
+    fn default() -> Self {
+        VisibilityRules {
+            precedence: DEFAULT_PRECEDENCE.to_vec(),
+            public_decorators: vec!["public".to_string()],
+            private_decorators: vec!["private".to_string()],
+        }
+    }
+}
+
+impl VisibilityRules {
+    /// Rules from `visibility_precedence` and the marker decorator options,
+    /// skipping unknown signal names with a warning
+    pub fn from_config(config: &AnalyzerConfig) -> Self {
+        let precedence = config
+            .visibility_precedence
+            .iter()
+            .filter_map(|name| match name.parse() {
+                Ok(signal) => Some(signal),
+                Err(e) => {
+                    log::warn!("{}", e);
+                    None
+                }
+            })
+            .collect();
+        VisibilityRules {
+            precedence,
+            public_decorators: config.public_decorators.clone(),
+            private_decorators: config.private_decorators.clone(),
+        }
+    }
+
+    /// `Some(true)` for a private decorator, `Some(false)` for a public one
+    fn decorator_marker(&self, decorators: &[Symbol]) -> Option<bool> {
+        decorators.iter().find_map(|decorator| {
+            let name = decorator.split('(').next().unwrap_or_default().trim();
+            let last = name.rsplit('.').next().unwrap_or(name);
+            let matches = |names: &[String]| names.iter().any(|n| n == name || n == last);
+            if matches(&self.private_decorators) {
+                Some(true)
+            } else if matches(&self.public_decorators) {
+                Some(false)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// A class whose body is being visited
+pub struct Enclosing {
+    pub name: String,
+    /// Explicit marker on the class: `Some(true)` when declared internal
+    pub marker: Option<bool>,
+}
+
+/// What one module says about the visibility of its entities
+pub struct ModuleVisibility<'a> {
+    rules: &'a VisibilityRules,
+    /// Names in `__all__`, when the module defines it
+    exports: Option<HashSet<String>>,
+    private_module: bool,
+    /// Source lines, kept only when the module has marker comments
+    lines: Vec<&'a str>,
+}
+
+impl<'a> ModuleVisibility<'a> {
+    pub fn new(rules: &'a VisibilityRules, path: &Path, source: &'a str, exports: Option<HashSet<String>>) -> Self {
+        let lines = if marker_regex().is_match(source) { source.lines().collect() } else { Vec::new() };
+        ModuleVisibility {
+            rules,
+            exports,
+            private_module: is_private_module(path),
+            lines,
+        }
+    }
+
+    /// Explicit marker for a definition starting at 1-based `line`:
+    /// `Some(true)` when it is declared internal
+    pub fn marker(&self, decorators: &[Symbol], line: usize) -> Option<bool> {
+        self.rules.decorator_marker(decorators).or_else(|| self.comment_marker(line))
+    }
+
+    /// Whether `name`, nested in `enclosing` (outermost first) and carrying
+    /// `marker`, is internal
+    pub fn is_internal(&self, enclosing: &[Enclosing], name: &str, marker: Option<bool>) -> bool {
+        if let Some(internal) = marker {
+            return internal;
+        }
+        match enclosing.iter().rev().find_map(|class| class.marker) {
+            Some(true) => true,
+            Some(false) => {
+                self.rules.precedence.contains(&VisibilitySignal::Underscore) && is_private_name(name)
+            }
+            None => self
+                .rules
+                .precedence
+                .iter()
+                .find_map(|signal| self.verdict(*signal, enclosing, name))
+                .unwrap_or(false),
+        }
+    }
+
+    fn verdict(&self, signal: VisibilitySignal, enclosing: &[Enclosing], name: &str) -> Option<bool> {
+        match signal {
+            VisibilitySignal::PrivateModule => self.private_module.then_some(true),
+            VisibilitySignal::DunderAll => {
+                let exports = self.exports.as_ref()?;
+                let top_level = enclosing.first().map_or(name, |class| class.name.as_str());
+                match (exports.contains(top_level), enclosing.is_empty()) {
+                    (false, _) => Some(true),
+                    (true, true) => Some(false),
+                    // Members of exported classes are left to later signals
+                    (true, false) => None,
+                }
+            }
+            VisibilitySignal::Underscore => enclosing
+                .iter()
+                .map(|class| class.name.as_str())
+                .chain(std::iter::once(name))
+                .any(is_private_name)
+                .then_some(true),
+        }
+    }
+
+    /// A marker comment on the definition's decorator and `def` lines, or
+    /// on the comment and decorator lines directly above
+    fn comment_marker(&self, line: usize) -> Option<bool> {
+        let start = line.checked_sub(1).filter(|&start| start < self.lines.len())?;
+        let header_end = self.lines[start..]
+            .iter()
+            .position(|text| !text.trim_start().starts_with('@'))
+            .map_or(self.lines.len(), |def_line| start + def_line + 1);
+        let above = self.lines[..start]
+            .iter()
+            .rev()
+            .take_while(|text| matches!(text.trim_start().chars().next(), Some('#' | '@')));
+        self.lines[start..header_end].iter().chain(above).find_map(|text| {
+            let captures = marker_regex().captures(text)?;
+            Some(&captures[1] == "private")
+        })
+    }
+}
+
+/// Drop the entities `include_private`, `include_dunder` and `public_only`
+/// leave out. `__init__` counts as documenting its class and is kept
+/// whatever `include_dunder` says.
+pub fn retain_visible(entities: &mut Vec<CodeEntity>, config: &AnalyzerConfig) {
+    if config.include_private && config.include_dunder && !config.public_only {
+        return;
+    }
+    entities.retain(|entity| {
+        let dunder = entity.name.starts_with("__") && entity.name.ends_with("__") && entity.name != "__init__";
+        (config.include_private || !entity.is_internal)
+            && (config.include_dunder || !dunder)
+            && (!config.public_only || entity.is_public())
+    });
+    hierarchy::prune(entities);
+}
+
+fn marker_regex() -> &'static Regex {
+    static MARKER: OnceLock<Regex> = OnceLock::new();
+    MARKER.get_or_init(|| Regex::new(r"#:\s*(public|private)-api\b").unwrap())
+}
+
+/// `_name` but not `__dunder__`
+pub fn is_private_name(name: &str) -> bool {
+    name.starts_with('_') && !(name.starts_with("__") && name.ends_with("__"))
+}
+
+/// The file stem, or a package directory above it (one with an
+/// `__init__.py`), is private. Other directories are ignored so that where
+/// a checkout happens to live doesn't matter.
+fn is_private_module(path: &Path) -> bool {
+    if path.file_stem().map_or(false, |stem| is_private_name(&stem.to_string_lossy())) {
+        return true;
+    }
+    path.ancestors()
+        .skip(1)
+        .take_while(|dir| dir.join("__init__.py").is_file())
+        .any(|dir| dir.file_name().map_or(false, |name| is_private_name(&name.to_string_lossy())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class(name: &str) -> Enclosing {
+        Enclosing {
+            name: name.to_string(),
+            marker: None,
+        }
+    }
+
+    #[test]
+    fn test_precedence() {
+        let exports: HashSet<String> = ["Client", "_helper"].iter().map(|s| s.to_string()).collect();
+        let rules = VisibilityRules::default();
+        let module = ModuleVisibility::new(&rules, Path::new("pkg/api.py"), "", Some(exports.clone()));
+        assert!(!module.is_internal(&[], "_helper", None));
+        assert!(module.is_internal(&[], "unlisted", None));
+        assert!(!module.is_internal(&[class("Client")], "get", None));
+        assert!(module.is_internal(&[class("Client")], "_retry", None));
+        assert!(!module.is_internal(&[class("Client")], "__init__", None));
+
+        // Underscores ahead of `__all__`: an exported `_helper` stays internal
+        let strict = VisibilityRules {
+            precedence: vec![VisibilitySignal::Underscore, VisibilitySignal::DunderAll],
+            ..VisibilityRules::default()
+        };
+        let module = ModuleVisibility::new(&strict, Path::new("pkg/api.py"), "", Some(exports));
+        assert!(module.is_internal(&[], "_helper", None));
+
+        let module = ModuleVisibility::new(&rules, Path::new("pkg/_impl.py"), "", None);
+        assert!(module.is_internal(&[], "run", None));
+
+        let config = AnalyzerConfig {
+            visibility_precedence: vec!["all".to_string(), "bogus".to_string()],
+            ..AnalyzerConfig::default()
+        };
+        assert_eq!(VisibilityRules::from_config(&config).precedence, vec![VisibilitySignal::DunderAll]);
+    }
+
+    #[test]
+    fn test_explicit_markers() {
+        let rules = VisibilityRules {
+            public_decorators: vec!["public".to_string(), "api.export".to_string()],
+            ..VisibilityRules::default()
+        };
+        let source = "#: public-api\n@cache\ndef _fast():\n    pass\n\ndef hidden():  #: private-api\n    pass\n";
+        let module = ModuleVisibility::new(&rules, Path::new("pkg/_impl.py"), source, None);
+
+        assert_eq!(module.marker(&[], 2), Some(false));
+        assert_eq!(module.marker(&[], 6), Some(true));
+        assert_eq!(module.marker(&["atpublic.public".into()], 9), Some(false));
+        assert_eq!(module.marker(&["api.export(since='2.0')".into()], 9), Some(false));
+        assert_eq!(module.marker(&["private".into()], 9), Some(true));
+        assert_eq!(module.marker(&["cache".into()], 9), None);
+
+        assert!(!module.is_internal(&[], "_fast", Some(false)));
+        let public_class = Enclosing {
+            name: "_Impl".to_string(),
+            marker: Some(false),
+        };
+        assert!(!module.is_internal(&[public_class], "run", None));
+    }
+
+    #[test]
+    fn test_retain_visible() {
+        let source = "class Client:\n    def __init__(self):\n        pass\n\n    def __repr__(self):\n        pass\n\n    def _retry(self):\n        pass\n\n@public\ndef _exported():\n    pass\n";
+        let entities = crate::parser::PythonParser::new().parse_source(source, Path::new("api.py")).unwrap();
+        let names = |config: AnalyzerConfig| {
+            let mut entities = entities.clone();
+            retain_visible(&mut entities, &config);
+            entities.into_iter().map(|e| e.name).collect::<Vec<_>>()
+        };
+
+        assert_eq!(names(AnalyzerConfig::default()).len(), 5);
+        let no_private = AnalyzerConfig {
+            include_private: false,
+            include_dunder: false,
+            ..AnalyzerConfig::default()
+        };
+        assert_eq!(names(no_private), vec!["Client", "__init__", "_exported"]);
+        let public_only = AnalyzerConfig {
+            public_only: true,
+            ..AnalyzerConfig::default()
+        };
+        assert_eq!(names(public_only), vec!["Client", "__init__", "__repr__"]);
+    }
+}
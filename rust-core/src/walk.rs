@@ -0,0 +1,288 @@
+use rustpython_parser::ast;
+
+/// Callbacks for `walk_body`, called in source order with parents before
+/// their children
+pub trait BodyVisitor<'a> {
+    fn stmt(&mut self, _stmt: &'a ast::Stmt) {}
+    fn expr(&mut self, _expr: &'a ast::Expr) {}
+}
+
+/// Visit the statements and expressions of a function body without
+/// entering nested `def`s or classes, which are entities of their own
+pub fn walk_body<'a, V: BodyVisitor<'a>>(body: &'a [ast::Stmt], visitor: &mut V) {
+    for stmt in body {
+        walk_stmt(stmt, visitor);
+    }
+}
+
+/// Every call in `body`, in source order
+pub fn for_each_call<'a>(body: &'a [ast::Stmt], f: impl FnMut(&'a ast::ExprCall)) {
+    struct Calls<F>(F);
+    impl<'a, F: FnMut(&'a ast::ExprCall)> BodyVisitor<'a> for Calls<F> {
+        fn expr(&mut self, expr: &'a ast::Expr) {
+            if let ast::Expr::Call(call) = expr {
+                (self.0)(call);
+            }
+        }
+    }
+    walk_body(body, &mut Calls(f));
+}
+
+/// The dotted name an expression spells, e.g. `os.environ.get`; `None` for
+/// anything but a name or attribute chain
+pub fn dotted_name(expr: &ast::Expr) -> Option<String> {
+    match expr {
+        ast::Expr::Name(name) => Some(name.id.as_str().to_string()),
+        ast::Expr::Attribute(attr) => Some(format!("{}.{}", dotted_name(&attr.value)?, attr.attr.as_str())),
+        _ => None,
+    }
+}
+
+/// The text of a string literal
+pub fn string_literal(expr: &ast::Expr) -> Option<&str> {
+    match expr {
+        ast::Expr::Constant(constant) => match &constant.value {
+            ast::Constant::Str(s) => Some(s.as_str()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// A string literal, or an f-string with its fields as `{...}`
+pub fn template(expr: &ast::Expr) -> Option<String> {
+    match expr {
+        ast::Expr::JoinedStr(joined) => Some(
+            joined
+                .values
+                .iter()
+                .map(|part| string_literal(part).unwrap_or("{...}"))
+                .collect(),
+        ),
+        _ => string_literal(expr).map(str::to_string),
+    }
+}
+
+/// A literal as plain text: strings unquoted, numbers as written by Python,
+/// `True`/`False`/`None`; `None` for anything computed
+pub fn literal(expr: &ast::Expr) -> Option<String> {
+    match expr {
+        ast::Expr::Constant(constant) => match &constant.value {
+            ast::Constant::Str(s) => Some(s.to_string()),
+            ast::Constant::Int(i) => Some(i.to_string()),
+            ast::Constant::Float(f) => Some(f.to_string()),
+            ast::Constant::Bool(b) => Some(if *b { "True" } else { "False" }.to_string()),
+            ast::Constant::None => Some("None".to_string()),
+            _ => None,
+        },
+        ast::Expr::UnaryOp(op) if matches!(op.op, ast::UnaryOp::USub) => literal(&op.operand).map(|value| format!("-{}", value)),
+        _ => None,
+    }
+}
+
+/// Value of the keyword argument `name` in `call`
+pub fn keyword<'a>(call: &'a ast::ExprCall, name: &str) -> Option<&'a ast::Expr> {
+    call.keywords
+        .iter()
+        .find(|k| k.arg.as_ref().map_or(false, |arg| arg.as_str() == name))
+        .map(|k| &k.value)
+}
+
+/// Positional argument `index` of `call`, or else its keyword argument `name`
+pub fn argument<'a>(call: &'a ast::ExprCall, index: usize, name: &str) -> Option<&'a ast::Expr> {
+    call.args.get(index).or_else(|| keyword(call, name))
+}
+
+/// `walk_body` for a single expression
+pub fn walk_expr<'a, V: BodyVisitor<'a>>(expr: &'a ast::Expr, visitor: &mut V) {
+    use ast::Expr;
+
+    visitor.expr(expr);
+    match expr {
+        Expr::BoolOp(e) => walk_exprs(&e.values, visitor),
+        Expr::NamedExpr(e) => {
+            walk_expr(&e.target, visitor);
+            walk_expr(&e.value, visitor);
+        }
+        Expr::BinOp(e) => {
+            walk_expr(&e.left, visitor);
+            walk_expr(&e.right, visitor);
+        }
+        Expr::UnaryOp(e) => walk_expr(&e.operand, visitor),
+        Expr::IfExp(e) => {
+            walk_expr(&e.test, visitor);
+            walk_expr(&e.body, visitor);
+            walk_expr(&e.orelse, visitor);
+        }
+        Expr::Dict(e) => {
+            for (key, value) in e.keys.iter().zip(&e.values) {
+                walk_optional(key.as_ref(), visitor);
+                walk_expr(value, visitor);
+            }
+        }
+        Expr::Set(e) => walk_exprs(&e.elts, visitor),
+        Expr::List(e) => walk_exprs(&e.elts, visitor),
+        Expr::Tuple(e) => walk_exprs(&e.elts, visitor),
+        Expr::ListComp(e) => {
+            walk_expr(&e.elt, visitor);
+            walk_comprehensions(&e.generators, visitor);
+        }
+        Expr::SetComp(e) => {
+            walk_expr(&e.elt, visitor);
+            walk_comprehensions(&e.generators, visitor);
+        }
+        Expr::GeneratorExp(e) => {
+            walk_expr(&e.elt, visitor);
+            walk_comprehensions(&e.generators, visitor);
+        }
+        Expr::DictComp(e) => {
+            walk_expr(&e.key, visitor);
+            walk_expr(&e.value, visitor);
+            walk_comprehensions(&e.generators, visitor);
+        }
+        Expr::Lambda(e) => walk_expr(&e.body, visitor),
+        Expr::Await(e) => walk_expr(&e.value, visitor),
+        Expr::Yield(e) => walk_optional(e.value.as_deref(), visitor),
+        Expr::YieldFrom(e) => walk_expr(&e.value, visitor),
+        Expr::Compare(e) => {
+            walk_expr(&e.left, visitor);
+            walk_exprs(&e.comparators, visitor);
+        }
+        Expr::Call(e) => {
+            walk_expr(&e.func, visitor);
+            walk_exprs(&e.args, visitor);
+            for keyword in &e.keywords {
+                walk_expr(&keyword.value, visitor);
+            }
+        }
+        Expr::FormattedValue(e) => {
+            walk_expr(&e.value, visitor);
+            walk_optional(e.format_spec.as_deref(), visitor);
+        }
+        Expr::JoinedStr(e) => walk_exprs(&e.values, visitor),
+        Expr::Attribute(e) => walk_expr(&e.value, visitor),
+        Expr::Subscript(e) => {
+            walk_expr(&e.value, visitor);
+            walk_expr(&e.slice, visitor);
+        }
+        Expr::Starred(e) => walk_expr(&e.value, visitor),
+        Expr::Slice(e) => {
+            walk_optional(e.lower.as_deref(), visitor);
+            walk_optional(e.upper.as_deref(), visitor);
+            walk_optional(e.step.as_deref(), visitor);
+        }
+        _ => {}
+    }
+}
+
+fn walk_exprs<'a, V: BodyVisitor<'a>>(exprs: &'a [ast::Expr], visitor: &mut V) {
+    for expr in exprs {
+        walk_expr(expr, visitor);
+    }
+}
+
+fn walk_optional<'a, V: BodyVisitor<'a>>(expr: Option<&'a ast::Expr>, visitor: &mut V) {
+    if let Some(expr) = expr {
+        walk_expr(expr, visitor);
+    }
+}
+
+fn walk_comprehensions<'a, V: BodyVisitor<'a>>(generators: &'a [ast::Comprehension], visitor: &mut V) {
+    for generator in generators {
+        walk_expr(&generator.target, visitor);
+        walk_expr(&generator.iter, visitor);
+        walk_exprs(&generator.ifs, visitor);
+    }
+}
+
+fn walk_stmt<'a, V: BodyVisitor<'a>>(stmt: &'a ast::Stmt, visitor: &mut V) {
+    use ast::Stmt;
+
+    visitor.stmt(stmt);
+    match stmt {
+        Stmt::Return(s) => walk_optional(s.value.as_deref(), visitor),
+        Stmt::Delete(s) => walk_exprs(&s.targets, visitor),
+        Stmt::Assign(s) => {
+            walk_exprs(&s.targets, visitor);
+            walk_expr(&s.value, visitor);
+        }
+        Stmt::AugAssign(s) => {
+            walk_expr(&s.target, visitor);
+            walk_expr(&s.value, visitor);
+        }
+        Stmt::AnnAssign(s) => {
+            walk_expr(&s.target, visitor);
+            walk_optional(s.value.as_deref(), visitor);
+        }
+        Stmt::For(s) => {
+            walk_expr(&s.target, visitor);
+            walk_expr(&s.iter, visitor);
+            walk_body(&s.body, visitor);
+            walk_body(&s.orelse, visitor);
+        }
+        Stmt::AsyncFor(s) => {
+            walk_expr(&s.target, visitor);
+            walk_expr(&s.iter, visitor);
+            walk_body(&s.body, visitor);
+            walk_body(&s.orelse, visitor);
+        }
+        Stmt::While(s) => {
+            walk_expr(&s.test, visitor);
+            walk_body(&s.body, visitor);
+            walk_body(&s.orelse, visitor);
+        }
+        Stmt::If(s) => {
+            walk_expr(&s.test, visitor);
+            walk_body(&s.body, visitor);
+            walk_body(&s.orelse, visitor);
+        }
+        Stmt::With(s) => {
+            for item in &s.items {
+                walk_expr(&item.context_expr, visitor);
+            }
+            walk_body(&s.body, visitor);
+        }
+        Stmt::AsyncWith(s) => {
+            for item in &s.items {
+                walk_expr(&item.context_expr, visitor);
+            }
+            walk_body(&s.body, visitor);
+        }
+        Stmt::Match(s) => {
+            walk_expr(&s.subject, visitor);
+            for case in &s.cases {
+                walk_optional(case.guard.as_deref(), visitor);
+                walk_body(&case.body, visitor);
+            }
+        }
+        Stmt::Raise(s) => {
+            walk_optional(s.exc.as_deref(), visitor);
+            walk_optional(s.cause.as_deref(), visitor);
+        }
+        Stmt::Try(s) => {
+            walk_body(&s.body, visitor);
+            walk_handlers(&s.handlers, visitor);
+            walk_body(&s.orelse, visitor);
+            walk_body(&s.finalbody, visitor);
+        }
+        Stmt::TryStar(s) => {
+            walk_body(&s.body, visitor);
+            walk_handlers(&s.handlers, visitor);
+            walk_body(&s.orelse, visitor);
+            walk_body(&s.finalbody, visitor);
+        }
+        Stmt::Assert(s) => {
+            walk_expr(&s.test, visitor);
+            walk_optional(s.msg.as_deref(), visitor);
+        }
+        Stmt::Expr(s) => walk_expr(&s.value, visitor),
+        _ => {}
+    }
+}
+
+fn walk_handlers<'a, V: BodyVisitor<'a>>(handlers: &'a [ast::ExceptHandler], visitor: &mut V) {
+    for ast::ExceptHandler::ExceptHandler(handler) in handlers {
+        walk_optional(handler.type_.as_deref(), visitor);
+        walk_body(&handler.body, visitor);
+    }
+}
@@ -0,0 +1,38 @@
+// JavaScript bindings for wasm32 builds (`wasm` feature)
+
+use serde::Serialize;
+use std::path::Path;
+use wasm_bindgen::prelude::*;
+
+use crate::analyzer::RustAnalyzer;
+use crate::config::AnalyzerConfig;
+
+/// Analyze in-memory source and return its entities as plain objects.
+///
+/// `path` picks the language backend by extension and defaults to Python;
+/// `config` is an optional object with `AnalyzerConfig` fields. Only
+/// in-memory analysis is offered: there is no file system to walk.
+#[wasm_bindgen(js_name = analyzeSource)]
+pub fn analyze_source(source: &str, path: Option<String>, config: JsValue) -> Result<JsValue, JsError> {
+    let config: AnalyzerConfig = if config.is_undefined() || config.is_null() {
+        AnalyzerConfig::default()
+    } else {
+        serde_wasm_bindgen::from_value(config).map_err(|e| JsError::new(&format!("Invalid config: {}", e)))?
+    };
+    let path = path.unwrap_or_else(|| "<string>".to_string());
+    let entities = RustAnalyzer::with_config(config)
+        .analyze_source(source, Path::new(&path))
+        .map_err(|e| JsError::new(&format!("{:#}", e)))?;
+
+    // Plain objects rather than `Map`s for metadata, so results can go
+    // straight to `JSON.stringify`
+    entities
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Version of the core the module was built from
+#[wasm_bindgen]
+pub fn version() -> String {
+    crate::VERSION.to_string()
+}
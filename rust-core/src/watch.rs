@@ -0,0 +1,99 @@
+use anyhow::{Context, Result};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+/// Recursive file watcher that batches bursts of source file changes
+pub struct FileWatcher {
+    // Dropping the watcher stops event delivery, so it lives as long as the receiver
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    debounce: Duration,
+}
+
+impl FileWatcher {
+    /// Start watching `root`; changes arriving within `debounce` of each other are batched
+    pub fn new(root: &Path, debounce: Duration) -> Result<Self> {
+        let (tx, events) = channel();
+        let mut watcher = notify::recommended_watcher(tx)
+            .context("Failed to create file watcher")?;
+        watcher.watch(root, RecursiveMode::Recursive)
+            .with_context(|| format!("Failed to watch {:?}", root))?;
+
+        Ok(FileWatcher {
+            _watcher: watcher,
+            events,
+            debounce,
+        })
+    }
+
+    /// Block until files accepted by `relevant` change and return the changed paths
+    pub fn next_batch(&self, relevant: impl Fn(&Path) -> bool) -> Result<Vec<PathBuf>> {
+        loop {
+            let mut changed = BTreeSet::new();
+
+            let first = self.events.recv().context("File watcher disconnected")?;
+            collect_paths(first, &relevant, &mut changed);
+
+            loop {
+                match self.events.recv_timeout(self.debounce) {
+                    Ok(event) => collect_paths(event, &relevant, &mut changed),
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => {
+                        return Err(anyhow::anyhow!("File watcher disconnected"));
+                    }
+                }
+            }
+
+            if !changed.is_empty() {
+                return Ok(changed.into_iter().collect());
+            }
+        }
+    }
+}
+
+fn collect_paths(event: notify::Result<Event>, relevant: &impl Fn(&Path) -> bool, changed: &mut BTreeSet<PathBuf>) {
+    let event = match event {
+        Ok(event) => event,
+        Err(e) => {
+            log::warn!("File watcher error: {}", e);
+            return;
+        }
+    };
+
+    if matches!(event.kind, EventKind::Access(_)) {
+        return;
+    }
+
+    changed.extend(
+        event
+            .paths
+            .into_iter()
+            .filter(|p| relevant(p)),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind};
+
+    #[test]
+    fn test_collect_paths_filters_events() {
+        let mut changed = BTreeSet::new();
+        let is_python = |path: &Path| path.extension().map_or(false, |ext| ext == "py");
+
+        let create = Event::new(EventKind::Create(CreateKind::File))
+            .add_path(PathBuf::from("pkg/mod.py"))
+            .add_path(PathBuf::from("README.md"));
+        collect_paths(Ok(create), &is_python, &mut changed);
+
+        let access = Event::new(EventKind::Access(AccessKind::Any))
+            .add_path(PathBuf::from("pkg/other.py"));
+        collect_paths(Ok(access), &is_python, &mut changed);
+
+        assert_eq!(changed.into_iter().collect::<Vec<_>>(), vec![PathBuf::from("pkg/mod.py")]);
+    }
+}
@@ -271,5 +271,64 @@ class Class_{i}:
         assert speedup > 3.0  # At least 3x faster
 
 
+def test_rust_analyzer_directory_async():
+    """Test awaiting directory analysis from an event loop."""
+    import asyncio
+
+    with tempfile.TemporaryDirectory() as tmpdir:
+        (Path(tmpdir) / "module.py").write_text("def func(): pass\n")
+
+        analyzer = autodoc_core.RustAnalyzer()
+
+        async def run():
+            return await analyzer.analyze_directory_async(tmpdir)
+
+        entities = asyncio.run(run())
+
+        assert [e.name for e in entities] == ["func"]
+
+
+def test_rust_analyzer_hooks():
+    """Test per-entity hooks can annotate and drop entities."""
+    with tempfile.TemporaryDirectory() as tmpdir:
+        (Path(tmpdir) / "module.py").write_text("def keep(): pass\ndef test_drop(): pass\n")
+
+        def hook(entity):
+            if entity.name.startswith("test_"):
+                return False
+            entity.metadata = {"owner": "docs"}
+
+        analyzer = autodoc_core.RustAnalyzer()
+        analyzer.add_hook(hook)
+        entities = analyzer.analyze_directory(tmpdir)
+
+        assert [e.name for e in entities] == ["keep"]
+        assert entities[0].metadata == {"owner": "docs"}
+
+
+def test_rust_analyzer_collection_protocol():
+    """Test results support len, iteration, indexing and slicing."""
+    with tempfile.TemporaryDirectory() as tmpdir:
+        (Path(tmpdir) / "module.py").write_text("def a(): pass\ndef b(): pass\ndef c(): pass\n")
+
+        entities = autodoc_core.RustAnalyzer().analyze_directory(tmpdir)
+
+        assert len(entities) == 3
+        assert [e.name for e in entities] == ["a", "b", "c"]
+        assert entities[-1].name == "c"
+        assert [e.name for e in entities[1:]] == ["b", "c"]
+        with pytest.raises(IndexError):
+            entities[3]
+
+
+def test_rust_analyze_source():
+    """Test analyzing in-memory source without a temp file."""
+    entities = autodoc_core.analyze_source_rust("def generated(x):\n    pass\n", "gen/module.py")
+
+    assert len(entities) == 1
+    assert entities[0].name == "generated"
+    assert entities[0].file_path == "gen/module.py"
+
+
 if __name__ == "__main__":
     pytest.main([__file__, "-v"])